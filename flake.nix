@@ -9,5 +9,10 @@
     devShells = forEachSystem (system: {
       default = pkgsForEach.${system}.callPackage ./nix/shell.nix {};
     });
+
+    # Reference output for `cargo test --test render_compat -- --ignored`.
+    packages = forEachSystem (system: {
+      goldens = pkgsForEach.${system}.callPackage ./nix/goldens.nix {inherit nixpkgs;};
+    });
   };
 }
@@ -0,0 +1,32 @@
+# Reference output for the compat renderer's tests in tests/render_compat.rs,
+# built with the nixpkgs revision the flake pins:
+#
+# - `upstream/<name>.md` is what the original nixdoc writes for each file of
+#   src/tests/fixtures, and `upstream/VERSION` is that nixdoc's version.
+# - `nixpkgs/strings.md` is the nixpkgs manual's reference for
+#   `lib/strings.nix`, from the manual's own build, next to the file it
+#   documents. `nixpkgs/REVISION` is the nixpkgs revision.
+{
+  runCommand,
+  callPackage,
+  nixdoc,
+  nixpkgs,
+}: let
+  libDocs = callPackage "${nixpkgs}/doc/doc-support/lib-function-docs.nix" {
+    nixpkgs = {inherit (nixpkgs) rev;};
+  };
+in
+  runCommand "nixdoc-goldens" {nativeBuildInputs = [nixdoc];} ''
+    mkdir -p $out/upstream
+    echo ${nixdoc.version} > $out/upstream/VERSION
+    for file in ${../src/tests/fixtures}/*.nix; do
+      name=$(basename "$file" .nix)
+      nixdoc --category "$name" --description "$name" --file "$file" \
+        > "$out/upstream/$name.md" || rm "$out/upstream/$name.md"
+    done
+
+    mkdir -p $out/nixpkgs
+    echo ${nixpkgs.rev} > $out/nixpkgs/REVISION
+    cp ${nixpkgs}/lib/strings.nix $out/nixpkgs/strings.nix
+    cp "$(find ${libDocs} -name strings.md | head -n 1)" $out/nixpkgs/strings.md
+  ''
@@ -0,0 +1,134 @@
+//! Unique anchors for the items and sections of a [`DocSet`].
+//!
+//! Renderers link to each documented binding by its name and to each
+//! section by the binding's name and the section's
+//! [`anchor`](crate::Section::anchor), such as `concatStrings-examples`. In
+//! a large set these collide: two files define `map`, or one comment has two
+//! `# Note` sections. [`Anchors::build`] gives every item and section an
+//! anchor that is unique across the set, adding `-2`, `-3`, and so on to
+//! the later ones in set order, and lists what it changed.
+//!
+//! The first item or section to claim an anchor keeps it, so adding items
+//! after existing ones never moves a published link.
+
+use std::collections::BTreeSet;
+
+use crate::DocSet;
+
+/// The anchors of the items of a [`DocSet`], and of their sections.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+///
+/// let mut set = DocSet::new();
+/// set.add_source("/** Lists. */\nmap = f: xs: xs;", Some("lists.nix".as_ref()));
+/// set.add_source("/** Attribute sets. */\nmap = f: s: s;", Some("attrsets.nix".as_ref()));
+///
+/// let anchors = set.anchors();
+/// assert_eq!(anchors.item(0), Some("map"));
+/// assert_eq!(anchors.item(1), Some("map-2"));
+/// assert_eq!(anchors.remaps()[0].to_string(), "`map` is now `map-2`");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Anchors {
+    items: Vec<ItemAnchors>,
+    remaps: Vec<AnchorRemap>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ItemAnchors {
+    item: Option<String>,
+    sections: Vec<String>,
+}
+
+/// An anchor that was changed to keep anchors unique.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorRemap {
+    /// The index of the item in the set.
+    pub item: usize,
+    /// The index of the section in the item's comment, or `None` for the
+    /// item's own anchor.
+    pub section: Option<usize>,
+    /// The anchor the item or section would have had.
+    pub original: String,
+    /// The anchor it has instead.
+    pub anchor: String,
+}
+
+impl std::fmt::Display for AnchorRemap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` is now `{}`", self.original, self.anchor)
+    }
+}
+
+impl Anchors {
+    /// Assign the anchors of `set`.
+    ///
+    /// An item's anchor is its name; items without one have none. A
+    /// section's anchor is the item's anchor and the section's own, joined
+    /// by `-`, or the section's own for unnamed items.
+    pub fn build(set: &DocSet) -> Self {
+        let mut taken = BTreeSet::new();
+        let mut anchors = Self::default();
+        for (index, item) in set.iter().enumerate() {
+            let mut claim = |original: String, section: Option<usize>| {
+                let mut anchor = original.clone();
+                let mut n = 2;
+                while !taken.insert(anchor.clone()) {
+                    anchor = format!("{original}-{n}");
+                    n += 1;
+                }
+                if anchor != original {
+                    anchors.remaps.push(AnchorRemap {
+                        item: index,
+                        section,
+                        original,
+                        anchor: anchor.clone(),
+                    });
+                }
+                anchor
+            };
+
+            let own = item.name.clone().map(|name| claim(name, None));
+            let sections = item
+                .doc
+                .sections
+                .iter()
+                .enumerate()
+                .map(|(i, section)| {
+                    let anchor = match &own {
+                        Some(own) => format!("{own}-{}", section.anchor()),
+                        None => section.anchor(),
+                    };
+                    claim(anchor, Some(i))
+                })
+                .collect();
+            anchors.items.push(ItemAnchors {
+                item: own,
+                sections,
+            });
+        }
+        anchors
+    }
+
+    /// Returns the anchor of the item at `index`, if it has one.
+    pub fn item(&self, index: usize) -> Option<&str> {
+        self.items.get(index)?.item.as_deref()
+    }
+
+    /// Returns the anchor of section `section` of the item at `item`.
+    pub fn section(&self, item: usize, section: usize) -> Option<&str> {
+        self.items
+            .get(item)?
+            .sections
+            .get(section)
+            .map(String::as_str)
+    }
+
+    /// Returns the anchors that were changed, in set order.
+    pub fn remaps(&self) -> &[AnchorRemap] {
+        &self.remaps
+    }
+}
@@ -0,0 +1,510 @@
+//! The `nixdoc` command-line interface.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use nixdoc::diff::Impact;
+use nixdoc::doctest::{Doctest, Evaluator, Outcome};
+use nixdoc::jsonl::JsonLinesWriter;
+use nixdoc::linkcheck::{self, LinkChecker};
+use nixdoc::lint::{Linter, Rule};
+use nixdoc::render::inventory::Inventory;
+use nixdoc::render::serve::DocServer;
+use nixdoc::render::site::{Site, SiteOptions};
+use nixdoc::{DocSet, Severity, SpecProfile, extract};
+
+#[derive(Parser)]
+#[command(
+    name = "nixdoc",
+    version,
+    about = "Work with Nixdoc documentation comments"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Evaluate documented Nix examples and compare them with their `=>` results.
+    Doctest {
+        /// Nix files or directories to search for doc comments.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// The Nix evaluator to use.
+        #[arg(long, value_enum, default_value_t = EvaluatorArg::Nix)]
+        evaluator: EvaluatorArg,
+
+        /// An expression prefix placed before every example,
+        /// e.g. `with import <nixpkgs/lib>;`.
+        #[arg(long)]
+        prelude: Option<String>,
+    },
+
+    /// Check the HTTP(S) links in doc comments.
+    Linkcheck {
+        /// Nix files or directories to search for doc comments.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Only check links for malformed or insecure URLs, without fetching them.
+        #[arg(long)]
+        offline: bool,
+
+        /// The maximum number of requests in flight.
+        #[arg(long, default_value_t = 8)]
+        concurrency: usize,
+    },
+
+    /// Check doc comments against documentation quality rules such as
+    /// `ND001` (missing type).
+    Lint {
+        /// Nix files or directories to search for doc comments.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// A rule to skip, by code or name; may be repeated.
+        #[arg(long, value_name = "RULE")]
+        allow: Vec<String>,
+
+        /// The spec profile deciding which sections are unknown.
+        #[arg(long, default_value = "nixpkgs")]
+        profile: String,
+
+        /// Fail on warnings as well as errors.
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// List documentation changes between two versions, grouped by impact
+    /// for changelogs.
+    Diff {
+        /// The old Nix file or directory.
+        old: PathBuf,
+
+        /// The new Nix file or directory.
+        new: PathBuf,
+
+        /// Only list changes with at least this impact.
+        #[arg(long, value_enum, default_value_t = ImpactArg::Cosmetic)]
+        min_impact: ImpactArg,
+    },
+
+    /// Print the `# Laws` of doc comments as a Nix expression that checks
+    /// them.
+    Laws {
+        /// Nix files or directories to search for doc comments.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// An expression prefix that brings the documented functions into
+        /// scope, e.g. `with import <nixpkgs/lib>;`.
+        #[arg(long)]
+        prelude: Option<String>,
+    },
+
+    /// Print every documented item, as JSON with one object per line or as
+    /// text.
+    Parse {
+        /// Nix files or directories to search for doc comments.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// The output format.
+        #[arg(long, value_enum, default_value = "json")]
+        format: FormatArg,
+    },
+
+    /// Print a field of every doc comment, selected by a path such as
+    /// `sections[kind=arguments].items[*].name`.
+    Query {
+        /// The path of the field to print.
+        path: String,
+
+        /// Nix files or directories to search for doc comments.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+
+    /// Serve the documentation site, a search API and a symbol API on
+    /// localhost.
+    Serve {
+        /// Nix files or directories to search for doc comments.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// The port to listen on.
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+
+        /// The site title.
+        #[arg(long, default_value = "Documentation")]
+        title: String,
+    },
+
+    /// Write a static HTML documentation site with search and a Sphinx
+    /// `objects.inv` inventory.
+    Site {
+        /// Nix files or directories to search for doc comments.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// The directory to write the site to.
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// The site title.
+        #[arg(long, default_value = "Documentation")]
+        title: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EvaluatorArg {
+    Nix,
+    Tvix,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FormatArg {
+    Json,
+    Text,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImpactArg {
+    Cosmetic,
+    Semantic,
+    Lifecycle,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Doctest {
+            paths,
+            evaluator,
+            prelude,
+        } => doctest(&paths, evaluator, prelude),
+        Command::Linkcheck {
+            paths,
+            offline,
+            concurrency,
+        } => check_links(&paths, offline, concurrency),
+        Command::Lint {
+            paths,
+            allow,
+            profile,
+            strict,
+        } => lint(&paths, &allow, &profile, strict),
+        Command::Diff {
+            old,
+            new,
+            min_impact,
+        } => diff(&old, &new, min_impact),
+        Command::Laws { paths, prelude } => laws(&paths, prelude),
+        Command::Parse { paths, format } => parse(&paths, format),
+        Command::Query { path, paths } => query(&path, &paths),
+        Command::Serve { paths, port, title } => serve(&paths, port, title),
+        Command::Site {
+            paths,
+            output,
+            title,
+        } => site(&paths, &output, title),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("nixdoc: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn files(paths: &[PathBuf]) -> std::io::Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    for path in paths {
+        out.extend(extract::nix_files(path)?);
+    }
+    Ok(out)
+}
+
+fn warn(warning: &extract::FileWarning) {
+    eprintln!(
+        "nixdoc: warning: {}: {}",
+        warning.file.display(),
+        warning.message
+    );
+}
+
+fn doctest(
+    paths: &[PathBuf],
+    evaluator: EvaluatorArg,
+    prelude: Option<String>,
+) -> std::io::Result<ExitCode> {
+    let evaluator = match evaluator {
+        EvaluatorArg::Nix => Evaluator::Nix,
+        EvaluatorArg::Tvix => Evaluator::Tvix,
+    };
+    let runner = Doctest::new(evaluator).with_prelude(prelude.unwrap_or_default());
+
+    let (mut passed, mut failed, mut skipped) = (0usize, 0usize, 0usize);
+    for file in files(paths)? {
+        let (src, warning) = extract::read_lossy(&file)?;
+        warning.iter().for_each(warn);
+        for result in runner.run_source(&src, Some(&file)) {
+            let label = match &result.title {
+                Some(title) => format!("{} example {} ({title})", result.location, result.index),
+                None => format!("{} example {}", result.location, result.index),
+            };
+            match &result.outcome {
+                Outcome::Passed => {
+                    passed += 1;
+                    println!("PASS {label}");
+                }
+                Outcome::Skipped => skipped += 1,
+                Outcome::Failed { expected, actual } => {
+                    failed += 1;
+                    println!("FAIL {label}\n  expected: {expected}\n  actual:   {actual}");
+                }
+                Outcome::Error(e) => {
+                    failed += 1;
+                    println!("ERROR {label}\n  {}", e.replace('\n', "\n  "));
+                }
+            }
+        }
+    }
+
+    println!("\n{passed} passed, {failed} failed, {skipped} skipped");
+    Ok(if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn check_links(paths: &[PathBuf], offline: bool, concurrency: usize) -> std::io::Result<ExitCode> {
+    let set = DocSet::from_paths_lossy(paths)?;
+    set.file_warnings().iter().for_each(warn);
+    let diagnostics = if offline {
+        set.iter()
+            .flat_map(|item| {
+                linkcheck::check_doc(&item.doc)
+                    .into_iter()
+                    .map(move |diagnostic| (item, diagnostic.with_provenance(item.provenance())))
+            })
+            .collect()
+    } else {
+        LinkChecker::new()
+            .with_concurrency(concurrency)
+            .check_set(&set)
+    };
+
+    let mut errors = 0usize;
+    for (item, diagnostic) in &diagnostics {
+        if diagnostic.severity == Severity::Error {
+            errors += 1;
+        }
+        let provenance = diagnostic
+            .provenance
+            .clone()
+            .unwrap_or_else(|| item.provenance());
+        println!("{provenance}: {diagnostic}");
+    }
+
+    println!("\n{} problem(s), {errors} error(s)", diagnostics.len());
+    Ok(if errors == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn lint(
+    paths: &[PathBuf],
+    allow: &[String],
+    profile: &str,
+    strict: bool,
+) -> std::io::Result<ExitCode> {
+    let invalid =
+        |what: &str, name: &str| std::io::Error::other(format!("unknown {what} '{name}'"));
+    let profile = SpecProfile::parse_name(profile).ok_or_else(|| invalid("profile", profile))?;
+    let mut linter = Linter::new().with_profile(profile);
+    for name in allow {
+        linter = linter.without_rule(Rule::from_name(name).ok_or_else(|| invalid("rule", name))?);
+    }
+
+    let set = DocSet::from_paths_lossy(paths)?;
+    set.file_warnings().iter().for_each(warn);
+    let diagnostics = linter.check_set(&set);
+    let mut failures = 0usize;
+    for (item, diagnostic) in &diagnostics {
+        let fails = match diagnostic.severity {
+            Severity::Error => true,
+            Severity::Warning => strict,
+            Severity::Info => false,
+        };
+        failures += usize::from(fails);
+        let provenance = diagnostic
+            .provenance
+            .clone()
+            .unwrap_or_else(|| item.provenance());
+        println!("{provenance}: {diagnostic}");
+    }
+
+    println!("\n{} problem(s), {failures} failing", diagnostics.len());
+    Ok(if failures == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn diff(old: &Path, new: &Path, min_impact: ImpactArg) -> std::io::Result<ExitCode> {
+    let min_impact = match min_impact {
+        ImpactArg::Cosmetic => Impact::Cosmetic,
+        ImpactArg::Semantic => Impact::Semantic,
+        ImpactArg::Lifecycle => Impact::Lifecycle,
+    };
+    let old = DocSet::from_paths_lossy(&[old])?;
+    let new = DocSet::from_paths_lossy(&[new])?;
+    old.file_warnings().iter().for_each(warn);
+    new.file_warnings().iter().for_each(warn);
+
+    let diff = DocSet::diff(&old, &new);
+    for (impact, title) in [
+        (Impact::Lifecycle, "Lifecycle"),
+        (Impact::Semantic, "Semantic"),
+        (Impact::Cosmetic, "Cosmetic"),
+    ] {
+        let changes = diff.with_impact(impact);
+        if impact < min_impact || changes.is_empty() {
+            continue;
+        }
+        println!("## {title}\n\n{changes}");
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse(paths: &[PathBuf], format: FormatArg) -> std::io::Result<ExitCode> {
+    let mut writer = JsonLinesWriter::new(std::io::BufWriter::new(std::io::stdout().lock()));
+    // One file at a time, so output starts before the whole corpus is read.
+    for file in files(paths)? {
+        let (src, warning) = extract::read_lossy(&file)?;
+        warning.iter().for_each(warn);
+        for result in extract::parse_source(&src, Some(&file)) {
+            match result {
+                Ok(item) => match format {
+                    FormatArg::Json => writer.write(&item)?,
+                    FormatArg::Text => write_text(writer.get_mut(), &item)?,
+                },
+                Err(failure) => {
+                    eprintln!(
+                        "nixdoc: warning: {}:{}: {}",
+                        file.display(),
+                        failure.line,
+                        failure.error
+                    );
+                }
+            }
+        }
+    }
+    writer.into_inner().flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Write `item` for reading in a terminal: its position and name, then its
+/// description and sections, indented.
+fn write_text(out: &mut impl Write, item: &extract::DocumentedItem) -> std::io::Result<()> {
+    let file = item.file.as_deref().unwrap_or("<input>".as_ref());
+    match &item.name {
+        Some(name) => writeln!(out, "{}:{}: {name}", file.display(), item.line)?,
+        None => writeln!(out, "{}:{}", file.display(), item.line)?,
+    }
+    let indent = |text: &str| {
+        text.lines()
+            .map(|line| {
+                if line.is_empty() {
+                    String::new()
+                } else {
+                    format!("    {line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    if !item.doc.description.is_empty() {
+        writeln!(out, "\n{}", indent(&item.doc.description))?;
+    }
+    for section in &item.doc.sections {
+        writeln!(out, "\n  # {}", section.heading)?;
+        if !section.content.is_empty() {
+            writeln!(out, "\n{}", indent(&section.content))?;
+        }
+    }
+    for warning in &item.doc.warnings {
+        writeln!(out, "\n  warning: {}", warning.message)?;
+    }
+    writeln!(out)
+}
+
+fn laws(paths: &[PathBuf], prelude: Option<String>) -> std::io::Result<ExitCode> {
+    let set = DocSet::from_paths_lossy(paths)?;
+    set.file_warnings().iter().for_each(warn);
+    print!(
+        "{}",
+        nixdoc::laws::assertions(&set, prelude.as_deref().unwrap_or_default())
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn query(path: &str, paths: &[PathBuf]) -> std::io::Result<ExitCode> {
+    let set = DocSet::from_paths_lossy(paths)?;
+    set.file_warnings().iter().for_each(warn);
+    for item in &set {
+        let file = item.file.as_deref().unwrap_or("<input>".as_ref());
+        for value in item.doc.query(path).map_err(std::io::Error::other)? {
+            println!("{}:{}: {value}", file.display(), item.line);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn site_options(paths: &[PathBuf], title: String) -> SiteOptions {
+    let options = SiteOptions::new().with_title(title);
+    // Pages of a single directory are named relative to it.
+    match paths {
+        [dir] if dir.is_dir() => options.with_root(dir),
+        _ => options,
+    }
+}
+
+fn serve(paths: &[PathBuf], port: u16, title: String) -> std::io::Result<ExitCode> {
+    let set = DocSet::from_paths_lossy(paths)?;
+    set.file_warnings().iter().for_each(warn);
+    let server = DocServer::new(set, &site_options(paths, title));
+    let addr = format!("127.0.0.1:{port}");
+    println!("serving {} item(s) on http://{addr}/", server.set().len());
+    server.serve(&addr)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn site(paths: &[PathBuf], output: &Path, title: String) -> std::io::Result<ExitCode> {
+    let set = DocSet::from_paths_lossy(paths)?;
+    set.file_warnings().iter().for_each(warn);
+    let options = site_options(paths, title);
+    let site = Site::build(&set, &options);
+    site.write(output)?;
+    let inventory = Inventory::from_site(&set, &site, &options.title, "");
+    std::fs::write(output.join("objects.inv"), inventory.to_bytes())?;
+    println!(
+        "wrote {} file(s) documenting {} item(s) to {}",
+        site.files.len(),
+        set.len(),
+        output.display()
+    );
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,148 @@
+//! Generate Nix flake checks from documented examples.
+//!
+//! [`flake_checks`] turns every doctest in a [`DocSet`] into a `lib.runTests`
+//! case and wraps them in a derivation, so documented examples are evaluated
+//! by `nix flake check`. The generated file is a function of `pkgs`:
+//!
+//! ```nix
+//! {
+//!   checks = forAllSystems (system: {
+//!     docExamples = import ./doc-examples.nix {
+//!       pkgs = nixpkgs.legacyPackages.${system};
+//!     };
+//!   });
+//! }
+//! ```
+
+use std::fmt::Write;
+
+use crate::DocSet;
+
+/// Options for [`flake_checks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckOptions {
+    /// The name of the generated derivation.
+    pub name: String,
+    /// An expression prefix placed before every example, such as `with lib;`.
+    pub prelude: String,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        Self {
+            name: "doc-examples".to_string(),
+            prelude: "with lib;".to_string(),
+        }
+    }
+}
+
+/// Render a Nix expression checking every doctest in `set`.
+///
+/// Examples are included if they are tagged `nix`, state a `=>` result, and
+/// are not flagged `no-run` or `expect-error`. Setup bindings are carried
+/// over. Evaluating the result throws with the list of failing examples;
+/// otherwise it is a trivial derivation, suitable for `checks.<system>`.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+/// use nixdoc::checks::{CheckOptions, flake_checks};
+///
+/// let mut set = DocSet::new();
+/// set.add_source(
+///     "{\n  /**\n    Identity.\n\n    # Example\n\n    ```nix\n    id 1\n    => 1\n    ```\n  */\n  id = x: x;\n}",
+///     Some("lib/trivial.nix".as_ref()),
+/// );
+/// let nix = flake_checks(&set, &CheckOptions::default());
+/// assert!(nix.contains(r#""test lib/trivial.nix:2 id example 1" = {"#));
+/// assert!(nix.contains("lib.runTests"));
+/// ```
+pub fn flake_checks(set: &DocSet, options: &CheckOptions) -> String {
+    let mut tests = String::new();
+
+    for item in set.items() {
+        let location = match &item.file {
+            Some(file) => format!("{}:{}", file.display(), item.line),
+            None => format!("<input>:{}", item.line),
+        };
+        let identifier = item.name.as_deref().unwrap_or("<anonymous>");
+
+        for (i, example) in item.doc.examples().iter().enumerate() {
+            let Some(expected) = &example.expected else {
+                continue;
+            };
+            if !example.is_nix()
+                || example.has_attribute("no-run")
+                || example.has_attribute("expect-error")
+            {
+                continue;
+            }
+
+            let name = format!("test {location} {identifier} example {}", i + 1);
+            let _ = writeln!(tests, "    {} = {{", nix_string(&name));
+            let _ = writeln!(
+                tests,
+                "      expr = {};",
+                expression(&options.prelude, &example.input_with_setup())
+            );
+            let _ = writeln!(
+                tests,
+                "      expected = {};",
+                expression(&options.prelude, &example.with_setup(expected))
+            );
+            tests.push_str("    };\n");
+        }
+    }
+
+    format!(
+        "# Generated by nixdoc from documented examples. Do not edit.\n\
+         {{ pkgs, lib ? pkgs.lib }}:\n\
+         let\n  \
+           failures = lib.runTests {{\n\
+         {tests}  }};\n\
+         in\n\
+         if failures == [ ] then\n  \
+           pkgs.runCommand {} {{ }} \"touch $out\"\n\
+         else\n  \
+           throw \"documentation examples failed:\\n${{lib.generators.toPretty {{ }} failures}}\"\n",
+        nix_string(&options.name),
+    )
+}
+
+/// Render `prelude` and `code` as one parenthesised expression, indented to
+/// sit inside a test case.
+fn expression(prelude: &str, code: &str) -> String {
+    let mut out = String::from("(\n");
+    for line in prelude.lines().chain(code.lines()) {
+        if line.is_empty() {
+            out.push('\n');
+        } else {
+            let _ = writeln!(out, "        {line}");
+        }
+    }
+    out.push_str("      )");
+    out
+}
+
+/// Quote `s` as a Nix string literal.
+fn nix_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '$' if chars.peek() == Some(&'{') => out.push_str("\\$"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+#[path = "tests/checks.rs"]
+mod tests;
@@ -0,0 +1,213 @@
+//! Splitting a [`DocSet`] into passages for embedding models.
+//!
+//! Retrieval tools, such as an assistant answering questions from the
+//! nixpkgs manual, index documentation as short overlapping passages, each
+//! with enough context to be understood and linked on its own.
+//! [`DocSet::chunks`] produces these: the description and every section of
+//! each item become plain text, split into [`Chunk`]s of at most
+//! [`ChunkOptions::max_chars`] characters that carry the item's name, the
+//! section, the file and the anchor to link to.
+
+use std::path::PathBuf;
+
+use crate::parser::{LineRole, fence_lines};
+use crate::summary::plain_text;
+use crate::{DocSet, SectionKind};
+
+/// Sizes for [`DocSet::chunks`].
+///
+/// Sizes are in characters; for English text, a token of most embedding
+/// models is about four characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkOptions {
+    /// The longest a chunk may be, header included. Defaults to 1000.
+    pub max_chars: usize,
+    /// How much of the end of a chunk is repeated at the start of the next
+    /// one of the same section, so a passage cut in two is still found
+    /// whole. Defaults to 100.
+    pub overlap: usize,
+    /// Start each chunk with a line naming its item and section. Defaults
+    /// to `true`.
+    pub header: bool,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            max_chars: 1000,
+            overlap: 100,
+            header: true,
+        }
+    }
+}
+
+impl ChunkOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the longest a chunk may be.
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+
+    /// Set how much consecutive chunks overlap.
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Set whether chunks start with a header line.
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+/// A passage of documentation, see [`DocSet::chunks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chunk {
+    /// The name of the documented binding, if known.
+    pub identifier: Option<String>,
+    /// The kind of the section the text is from, or `None` for the
+    /// description.
+    pub section: Option<SectionKind>,
+    /// The heading of the section, as written.
+    pub heading: Option<String>,
+    /// The file containing the comment, if known.
+    pub file: Option<PathBuf>,
+    /// The 1-based line on which the comment starts.
+    pub line: usize,
+    /// The anchor of the item or section, as given by
+    /// [`DocSet::anchors`].
+    pub anchor: Option<String>,
+    /// The plain text of the chunk.
+    pub text: String,
+}
+
+/// Split every item of `set` into chunks, see [`DocSet::chunks`].
+pub(crate) fn chunks(set: &DocSet, options: &ChunkOptions) -> Vec<Chunk> {
+    let anchors = set.anchors();
+    let mut chunks = Vec::new();
+    for (index, item) in set.iter().enumerate() {
+        let parts = std::iter::once((None, None, item.doc.description(), anchors.item(index)))
+            .chain(item.doc.sections.iter().enumerate().map(|(i, section)| {
+                (
+                    Some(section.kind()),
+                    Some(section.heading.as_str()),
+                    section.content.as_str(),
+                    anchors.section(index, i),
+                )
+            }));
+
+        for (section, heading, content, anchor) in parts {
+            let text = to_plain_text(content);
+            if text.is_empty() {
+                continue;
+            }
+            let header = match (&item.name, heading) {
+                _ if !options.header => String::new(),
+                (Some(name), Some(heading)) => format!("{name}: {heading}\n\n"),
+                (Some(name), None) => format!("{name}\n\n"),
+                (None, Some(heading)) => format!("{heading}\n\n"),
+                (None, None) => String::new(),
+            };
+            let max_chars = options
+                .max_chars
+                .saturating_sub(header.chars().count())
+                .max(1);
+            for window in windows(&text, max_chars, options.overlap) {
+                chunks.push(Chunk {
+                    identifier: item.name.clone(),
+                    section: section.clone(),
+                    heading: heading.map(str::to_string),
+                    file: item.file.clone(),
+                    line: item.line,
+                    anchor: anchor.map(str::to_string),
+                    text: format!("{header}{window}"),
+                });
+            }
+        }
+    }
+    chunks
+}
+
+/// Markdown as plain text: paragraphs without their inline Markdown, and
+/// code blocks without their fences, separated by blank lines.
+fn to_plain_text(markdown: &str) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            blocks.push(plain_text(&paragraph.join("\n")).to_string());
+            paragraph.clear();
+        }
+    };
+
+    for (_, line, role) in fence_lines(markdown) {
+        match role {
+            LineRole::OpeningFence => {
+                flush(&mut paragraph, &mut blocks);
+                code = Some(Vec::new());
+            }
+            LineRole::Code => code.get_or_insert_default().push(line),
+            LineRole::ClosingFence => {
+                blocks.push(code.take().unwrap_or_default().join("\n"));
+            }
+            LineRole::Prose if line.trim().is_empty() => flush(&mut paragraph, &mut blocks),
+            LineRole::Prose => paragraph.push(line.trim()),
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    if let Some(lines) = code {
+        blocks.push(lines.join("\n"));
+    }
+    blocks.retain(|block| !block.trim().is_empty());
+    blocks.join("\n\n")
+}
+
+/// Split `text` at whitespace into windows of at most `max_chars`
+/// characters, each starting with up to `overlap` characters of the end of
+/// the previous one. A word longer than `max_chars` is a window of its own.
+fn windows(text: &str, max_chars: usize, overlap: usize) -> Vec<&str> {
+    // Byte offsets of the start and end of every word.
+    let mut words: Vec<(usize, usize)> = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                words.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    let chars = |from: usize, to: usize| text[from..to].chars().count();
+
+    let mut windows = Vec::new();
+    let mut first = 0;
+    while first < words.len() {
+        let begin = words[first].0;
+        let mut last = first;
+        while last + 1 < words.len() && chars(begin, words[last + 1].1) <= max_chars {
+            last += 1;
+        }
+        let end = words[last].1;
+        windows.push(&text[begin..end]);
+        if last + 1 == words.len() {
+            break;
+        }
+        // Back up over the words that fit in the overlap, always moving on.
+        let mut next = last + 1;
+        while next > first + 1 && chars(words[next - 1].0, end) <= overlap {
+            next -= 1;
+        }
+        first = next;
+    }
+    windows
+}
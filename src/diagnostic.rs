@@ -0,0 +1,92 @@
+//! Problems found in doc comments by checks beyond parsing.
+//!
+//! Parsing itself only reports [`ParseWarning`](crate::ParseWarning)s. Tools
+//! that inspect the content of a comment, such as the link checker, report
+//! [`Diagnostic`]s instead: each has a stable code, a severity, and the span
+//! it concerns.
+
+use core::fmt;
+
+#[cfg(feature = "std")]
+use crate::Provenance;
+use crate::Span;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// Something is wrong and should be fixed.
+    Error,
+    /// Something is likely wrong.
+    Warning,
+    /// A suggestion.
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        })
+    }
+}
+
+/// A problem found in a doc comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    /// A short, stable identifier for the kind of problem, such as
+    /// `insecure-url`.
+    pub code: String,
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// A human-readable description.
+    pub message: String,
+    /// Where the problem is, relative to
+    /// [`DocComment::raw_content`](crate::DocComment::raw_content).
+    pub span: Span,
+    /// The comment the problem is in, when the check knew it.
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub provenance: Option<Provenance>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic.
+    pub fn new(
+        code: impl Into<String>,
+        severity: Severity,
+        message: impl Into<String>,
+        span: Span,
+    ) -> Self {
+        Self {
+            code: code.into(),
+            severity,
+            message: message.into(),
+            span,
+            #[cfg(feature = "std")]
+            provenance: None,
+        }
+    }
+
+    /// Set the comment the problem is in.
+    #[cfg(feature = "std")]
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+}
+
+/// Formats as `severity[code]: message`.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
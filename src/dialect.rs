@@ -0,0 +1,237 @@
+//! Doc comments in languages other than Nix.
+//!
+//! RFC 145's style, Markdown with `# Section` headings, has been copied by
+//! other configuration languages, with their own comment markers and their
+//! own names for some sections. A [`Dialect`] describes those differences,
+//! and [`ParserOptions::with_dialect`] parses such comments into the same
+//! [`DocComment`] as Nixdoc ones, so one parser serves a family of tools.
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, ParseError, ParserOptions, Section, SectionKind};
+
+/// How a dialect writes the items of an `# Arguments` section.
+///
+/// Whatever the syntax, the section is read into the Nixdoc form, so
+/// [`DocComment::arguments`] works for every dialect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArgumentSyntax {
+    /// `- [name] description`, a definition list, or a table, as in Nixdoc.
+    #[default]
+    Nixdoc,
+    /// `- name: description` list items.
+    Colon,
+    /// `@param name description` lines, as in JSDoc.
+    Param,
+}
+
+/// A language's variant of the doc comment format.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::dialect::{ArgumentSyntax, Dialect};
+/// use nixdoc::{DocComment, ParserOptions, SectionKind};
+///
+/// let dialect = Dialect::new("example")
+///     .with_delimiters("(**", "*)")
+///     .with_section("Parameters", SectionKind::Arguments)
+///     .with_arguments(ArgumentSyntax::Colon);
+/// let options = ParserOptions::new().with_dialect(dialect);
+///
+/// let doc = DocComment::parse_with("(**\n  Add.\n\n  # Parameters\n\n  - a: First\n*)", &options).unwrap();
+/// assert_eq!(doc.sections[0].heading, "Arguments");
+/// assert_eq!(doc.arguments()[0].name, "a");
+/// assert_eq!(doc.arguments()[0].description, "First");
+/// assert!(doc.warnings.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dialect {
+    /// The name of the language, such as `nickel`.
+    pub name: String,
+    /// The opening and closing comment markers, as in
+    /// [`ParserOptions::delimiters`].
+    pub delimiters: Option<(String, String)>,
+    /// Headings of the dialect that mean a Nixdoc section, such as
+    /// `Parameters` for [`SectionKind::Arguments`]. Matched
+    /// case-insensitively.
+    pub sections: Vec<(String, SectionKind)>,
+    /// How arguments are written.
+    pub arguments: ArgumentSyntax,
+}
+
+impl Dialect {
+    /// Create a dialect with `/** … */` comments and no differences from
+    /// Nixdoc.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            delimiters: Some(("/**".to_string(), "*/".to_string())),
+            sections: Vec::new(),
+            arguments: ArgumentSyntax::default(),
+        }
+    }
+
+    /// Nixdoc itself.
+    pub fn nix() -> Self {
+        Self::new("nix")
+    }
+
+    /// [Nickel](https://nickel-lang.org) documentation, the Markdown of a
+    /// `| doc m%" … "%` annotation, with `- name: description` arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::dialect::Dialect;
+    /// use nixdoc::{DocComment, ParserOptions};
+    ///
+    /// let options = ParserOptions::new().with_dialect(Dialect::nickel());
+    /// let input = "m%\"\n  Adds two numbers.\n\n  # Parameters\n\n  - a: First\n  - b: Second\n\n  # Examples\n\n  ```nickel\n  add 1 2\n  ```\n\"%";
+    /// let doc = DocComment::parse_with(input, &options).unwrap();
+    /// assert_eq!(doc.arguments().len(), 2);
+    /// assert_eq!(doc.examples()[0].language.as_deref(), Some("nickel"));
+    /// ```
+    pub fn nickel() -> Self {
+        Self::new("nickel")
+            .with_delimiters("m%\"", "\"%")
+            .with_section("Parameters", SectionKind::Arguments)
+            .with_section("Params", SectionKind::Arguments)
+            .with_section("Returns", SectionKind::Output)
+            .with_arguments(ArgumentSyntax::Colon)
+    }
+
+    /// Set the opening and closing comment markers.
+    pub fn with_delimiters(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.delimiters = Some((open.into(), close.into()));
+        self
+    }
+
+    /// Read sections headed `heading` as sections of `kind`.
+    pub fn with_section(mut self, heading: impl Into<String>, kind: SectionKind) -> Self {
+        self.sections.push((heading.into(), kind));
+        self
+    }
+
+    /// Set how arguments are written.
+    pub fn with_arguments(mut self, syntax: ArgumentSyntax) -> Self {
+        self.arguments = syntax;
+        self
+    }
+
+    /// Returns the kind of sections headed `heading` in this dialect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::SectionKind;
+    /// use nixdoc::dialect::Dialect;
+    ///
+    /// assert_eq!(Dialect::nickel().section_kind("parameters"), SectionKind::Arguments);
+    /// assert_eq!(Dialect::nickel().section_kind("Type"), SectionKind::Type);
+    /// ```
+    pub fn section_kind(&self, heading: &str) -> SectionKind {
+        self.sections
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(heading))
+            .map_or_else(
+                || SectionKind::from_heading(heading),
+                |(_, kind)| kind.clone(),
+            )
+    }
+
+    /// Parse `input` as a doc comment of this dialect.
+    pub fn parse(&self, input: &str) -> Result<DocComment, ParseError> {
+        DocComment::parse_with(input, &ParserOptions::new().with_dialect(self.clone()))
+    }
+
+    /// Rewrite `section` in Nixdoc terms: an aliased heading becomes the
+    /// Nixdoc heading, and arguments are written as `- [name] description`.
+    pub(crate) fn normalize(&self, section: &mut Section) {
+        let kind = self.section_kind(&section.heading);
+        if let Some(heading) = canonical_heading(&kind)
+            && SectionKind::from_heading(&section.heading) != kind
+        {
+            section.heading = heading.to_string();
+        }
+        if matches!(kind, SectionKind::Arguments | SectionKind::Inputs) {
+            section.content = self.rewrite_arguments(&section.content);
+        }
+    }
+
+    fn rewrite_arguments(&self, content: &str) -> String {
+        if self.arguments == ArgumentSyntax::Nixdoc {
+            return content.to_string();
+        }
+        let lines: Vec<String> = content
+            .lines()
+            .map(|line| {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                let item = match self.arguments {
+                    ArgumentSyntax::Nixdoc => None,
+                    ArgumentSyntax::Colon => line
+                        .trim_start()
+                        .strip_prefix("- ")
+                        .and_then(|item| item.split_once(':'))
+                        .filter(|(name, _)| is_name(name.trim())),
+                    ArgumentSyntax::Param => line
+                        .trim_start()
+                        .strip_prefix("@param ")
+                        .map(|item| item.trim_start().split_once(' ').unwrap_or((item, ""))),
+                };
+                match item {
+                    Some((name, description)) => {
+                        let name = name.trim().trim_matches('`');
+                        format!("{indent}- [{name}] {}", description.trim())
+                            .trim_end()
+                            .to_string()
+                    }
+                    None => line.to_string(),
+                }
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self::nix()
+    }
+}
+
+/// The heading Nixdoc writes for sections of `kind`.
+fn canonical_heading(kind: &SectionKind) -> Option<&'static str> {
+    Some(match kind {
+        SectionKind::Type => "Type",
+        SectionKind::Arguments => "Arguments",
+        SectionKind::Example => "Example",
+        SectionKind::Examples => "Examples",
+        SectionKind::Note => "Note",
+        SectionKind::Notes => "Notes",
+        SectionKind::Warning => "Warning",
+        SectionKind::Deprecated => "Deprecated",
+        SectionKind::Meta => "Meta",
+        SectionKind::Since => "Since",
+        SectionKind::Maintainers => "Maintainers",
+        SectionKind::Authors => "Authors",
+        SectionKind::Tags => "Tags",
+        SectionKind::Stability => "Stability",
+        SectionKind::Summary => "Summary",
+        SectionKind::Inputs => "Inputs",
+        SectionKind::Output => "Output",
+        SectionKind::Laws => "Laws",
+        SectionKind::Unknown(_) => return None,
+    })
+}
+
+/// Returns `true` if `name` can be an argument name, possibly in backticks.
+fn is_name(name: &str) -> bool {
+    let name = name.trim_matches('`');
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '\'' | '.'))
+}
@@ -0,0 +1,591 @@
+//! Comparing documentation between two versions of a comment or a corpus.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::extract::DocumentedItem;
+use crate::{Argument, DocComment, DocSet, SectionKind};
+
+/// How much a documentation change matters to users, for sorting changelog
+/// entries.
+///
+/// Impacts are ordered from least to most important.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Impact {
+    /// Wording or formatting, such as an edited description or example.
+    Cosmetic,
+    /// The interface, such as a type signature or the arguments.
+    Semantic,
+    /// The item's life cycle: added, removed, deprecated, undeprecated, or a
+    /// change of stability.
+    Lifecycle,
+}
+
+impl fmt::Display for Impact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Cosmetic => "cosmetic",
+            Self::Semantic => "semantic",
+            Self::Lifecycle => "lifecycle",
+        })
+    }
+}
+
+/// How one aspect of an item's documentation changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChangeDetail {
+    /// The description text was edited.
+    DescriptionEdited,
+    /// The type signature was added, removed, or changed.
+    TypeSignatureChanged {
+        /// The previous signature.
+        old: Option<String>,
+        /// The new signature.
+        new: Option<String>,
+    },
+    /// The item gained a `# Deprecated` section.
+    NewlyDeprecated,
+    /// The item's `# Deprecated` section was removed.
+    NoLongerDeprecated,
+    /// The documented arguments changed.
+    ArgumentsChanged,
+    /// The examples changed.
+    ExamplesChanged,
+    /// Any other section was added, removed, or edited.
+    OtherSectionsChanged,
+}
+
+impl ChangeDetail {
+    /// Returns how much this change matters.
+    pub fn impact(&self) -> Impact {
+        match self {
+            Self::DescriptionEdited | Self::ExamplesChanged | Self::OtherSectionsChanged => {
+                Impact::Cosmetic
+            }
+            Self::TypeSignatureChanged { .. } | Self::ArgumentsChanged => Impact::Semantic,
+            Self::NewlyDeprecated | Self::NoLongerDeprecated => Impact::Lifecycle,
+        }
+    }
+}
+
+impl fmt::Display for ChangeDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DescriptionEdited => f.write_str("description edited"),
+            Self::TypeSignatureChanged { .. } => f.write_str("type signature changed"),
+            Self::NewlyDeprecated => f.write_str("newly deprecated"),
+            Self::NoLongerDeprecated => f.write_str("no longer deprecated"),
+            Self::ArgumentsChanged => f.write_str("arguments changed"),
+            Self::ExamplesChanged => f.write_str("examples changed"),
+            Self::OtherSectionsChanged => f.write_str("other sections changed"),
+        }
+    }
+}
+
+/// What happened to one identifier between two document sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ItemStatus {
+    /// The identifier is only documented in the new set.
+    Added,
+    /// The identifier is only documented in the old set.
+    Removed,
+    /// The identifier is documented in both sets, differently.
+    Changed(Vec<ChangeDetail>),
+}
+
+/// The change to one identifier, see [`DocSetDiff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ItemDiff {
+    /// The documented binding's name.
+    pub identifier: String,
+    /// What happened to it.
+    pub status: ItemStatus,
+}
+
+impl ItemDiff {
+    /// Returns how much the change matters: [`Impact::Lifecycle`] for added
+    /// and removed items, otherwise the highest impact of the details.
+    pub fn impact(&self) -> Impact {
+        match &self.status {
+            ItemStatus::Added | ItemStatus::Removed => Impact::Lifecycle,
+            ItemStatus::Changed(details) => details
+                .iter()
+                .map(ChangeDetail::impact)
+                .max()
+                .unwrap_or(Impact::Cosmetic),
+        }
+    }
+}
+
+/// The differences between two [`DocSet`]s, computed by [`DocSet::diff`].
+///
+/// Its [`Display`](fmt::Display) output is a Markdown list suitable for a
+/// "documentation changes" section in release notes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocSetDiff {
+    /// One entry per added, removed, or changed identifier, sorted by name.
+    pub items: Vec<ItemDiff>,
+}
+
+impl DocSetDiff {
+    /// Returns `true` if the two sets document the same things identically.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the identifiers only present in the new set.
+    pub fn added(&self) -> impl Iterator<Item = &str> {
+        self.with_status(|status| matches!(status, ItemStatus::Added))
+    }
+
+    /// Returns the identifiers only present in the old set.
+    pub fn removed(&self) -> impl Iterator<Item = &str> {
+        self.with_status(|status| matches!(status, ItemStatus::Removed))
+    }
+
+    /// Returns the identifiers whose documentation changed.
+    pub fn changed(&self) -> impl Iterator<Item = &str> {
+        self.with_status(|status| matches!(status, ItemStatus::Changed(_)))
+    }
+
+    /// Returns the changes with the given impact, see [`ItemDiff::impact`].
+    pub fn with_impact(&self, impact: Impact) -> DocSetDiff {
+        DocSetDiff {
+            items: self
+                .items
+                .iter()
+                .filter(|item| item.impact() == impact)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn with_status(&self, pred: impl Fn(&ItemStatus) -> bool) -> impl Iterator<Item = &str> {
+        self.items
+            .iter()
+            .filter(move |item| pred(&item.status))
+            .map(|item| item.identifier.as_str())
+    }
+}
+
+impl fmt::Display for DocSetDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in &self.items {
+            match &item.status {
+                ItemStatus::Added => writeln!(f, "- Added `{}`", item.identifier)?,
+                ItemStatus::Removed => writeln!(f, "- Removed `{}`", item.identifier)?,
+                ItemStatus::Changed(details) => {
+                    let details: Vec<String> = details.iter().map(ToString::to_string).collect();
+                    writeln!(f, "- Changed `{}`: {}", item.identifier, details.join(", "))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A run of words in a word-level diff, see [`DocDiff::description`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hunk {
+    /// Words present in both texts.
+    Equal(String),
+    /// Words only present in the old text.
+    Removed(String),
+    /// Words only present in the new text.
+    Inserted(String),
+}
+
+/// What happened to one section, see [`DocDiff::sections`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SectionChange {
+    /// A section with this heading was added.
+    Added(String),
+    /// A section with this heading was removed.
+    Removed(String),
+    /// The content of a section with this heading was edited.
+    Changed {
+        /// The section heading.
+        heading: String,
+        /// The word-level edits to its content.
+        hunks: Vec<Hunk>,
+    },
+}
+
+impl SectionChange {
+    /// Returns how much this change matters.
+    ///
+    /// Changes to `# Type`, `# Arguments`, `# Inputs`, `# Output`, and
+    /// `# Laws` sections are semantic. Adding
+    /// or removing a `# Deprecated` section, and any change to a
+    /// `# Stability` section, are lifecycle changes. Everything else is
+    /// cosmetic.
+    pub fn impact(&self) -> Impact {
+        let (heading, edited) = match self {
+            Self::Added(heading) | Self::Removed(heading) => (heading, false),
+            Self::Changed { heading, .. } => (heading, true),
+        };
+        match SectionKind::from_heading(heading) {
+            SectionKind::Type
+            | SectionKind::Arguments
+            | SectionKind::Inputs
+            | SectionKind::Output
+            | SectionKind::Laws => Impact::Semantic,
+            SectionKind::Deprecated if !edited => Impact::Lifecycle,
+            SectionKind::Stability => Impact::Lifecycle,
+            _ => Impact::Cosmetic,
+        }
+    }
+}
+
+/// What happened to one documented argument, see [`DocDiff::arguments`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArgumentChange {
+    /// The argument was added.
+    Added(Argument),
+    /// The argument was removed.
+    Removed(Argument),
+    /// The argument's description was edited.
+    Changed {
+        /// The argument name.
+        name: String,
+        /// The previous description.
+        old: String,
+        /// The new description.
+        new: String,
+    },
+}
+
+/// The differences between two [`DocComment`]s, computed by
+/// [`DocComment::diff`].
+///
+/// Its [`Display`](fmt::Display) output marks word edits the way
+/// `git diff --word-diff` does, with `[-removed-]` and `{+inserted+}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocDiff {
+    /// Word-level hunks of the description, empty if it is unchanged.
+    pub description: Vec<Hunk>,
+    /// Added, removed and edited sections, in document order.
+    pub sections: Vec<SectionChange>,
+    /// Added, removed and edited arguments, in document order.
+    pub arguments: Vec<ArgumentChange>,
+}
+
+impl DocDiff {
+    /// Returns `true` if the two comments document the same things
+    /// identically, ignoring whitespace.
+    pub fn is_empty(&self) -> bool {
+        self.description.is_empty() && self.sections.is_empty() && self.arguments.is_empty()
+    }
+
+    /// Returns the highest impact of the changes, or `None` if there are
+    /// none.
+    ///
+    /// Description edits are cosmetic and argument changes are semantic;
+    /// see [`SectionChange::impact`] for sections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    /// use nixdoc::diff::Impact;
+    ///
+    /// let old = DocComment::parse("/**\n  Sort.\n*/").unwrap();
+    /// let reworded = DocComment::parse("/**\n  Sort a list.\n*/").unwrap();
+    /// let deprecated = DocComment::parse("/**\n  Sort.\n\n  # Deprecated\n\n  Use sortOn.\n*/").unwrap();
+    ///
+    /// assert_eq!(old.diff(&old).impact(), None);
+    /// assert_eq!(old.diff(&reworded).impact(), Some(Impact::Cosmetic));
+    /// assert_eq!(old.diff(&deprecated).impact(), Some(Impact::Lifecycle));
+    /// ```
+    pub fn impact(&self) -> Option<Impact> {
+        let description = (!self.description.is_empty()).then_some(Impact::Cosmetic);
+        let arguments = (!self.arguments.is_empty()).then_some(Impact::Semantic);
+        let sections = self.sections.iter().map(SectionChange::impact).max();
+        description.max(arguments).max(sections)
+    }
+}
+
+impl fmt::Display for DocDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.description.is_empty() {
+            writeln!(f, "Description: {}", WordDiff(&self.description))?;
+        }
+        for change in &self.sections {
+            match change {
+                SectionChange::Added(heading) => writeln!(f, "Added section `{heading}`")?,
+                SectionChange::Removed(heading) => writeln!(f, "Removed section `{heading}`")?,
+                SectionChange::Changed { heading, hunks } => {
+                    writeln!(f, "Changed section `{heading}`: {}", WordDiff(hunks))?
+                }
+            }
+        }
+        for change in &self.arguments {
+            match change {
+                ArgumentChange::Added(argument) => {
+                    writeln!(f, "Added argument `{}`", argument.name)?
+                }
+                ArgumentChange::Removed(argument) => {
+                    writeln!(f, "Removed argument `{}`", argument.name)?
+                }
+                ArgumentChange::Changed { name, old, new } => writeln!(
+                    f,
+                    "Changed argument `{name}`: {}",
+                    WordDiff(&diff_words(old, new))
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+struct WordDiff<'a>(&'a [Hunk]);
+
+impl fmt::Display for WordDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, hunk) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            match hunk {
+                Hunk::Equal(text) => f.write_str(text)?,
+                Hunk::Removed(text) => write!(f, "[-{text}-]")?,
+                Hunk::Inserted(text) => write!(f, "{{+{text}+}}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare two comments section by section, see [`DocComment::diff`].
+///
+/// Sections are matched by heading, ignoring case; repeated headings are
+/// paired in order. Arguments are matched by name.
+pub(crate) fn diff_comments(old: &DocComment, new: &DocComment) -> DocDiff {
+    let description = if words(&old.description).eq(words(&new.description)) {
+        Vec::new()
+    } else {
+        diff_words(&old.description, &new.description)
+    };
+
+    let key = |heading: &str| heading.trim().to_lowercase();
+    let mut sections = Vec::new();
+    let mut unmatched: Vec<_> = new.sections.iter().map(Some).collect();
+    for section in &old.sections {
+        let found = unmatched
+            .iter_mut()
+            .find(|candidate| candidate.is_some_and(|c| key(&c.heading) == key(&section.heading)));
+        match found.and_then(Option::take) {
+            Some(other) if words(&section.content).eq(words(&other.content)) => {}
+            Some(other) => sections.push(SectionChange::Changed {
+                heading: other.heading.clone(),
+                hunks: diff_words(&section.content, &other.content),
+            }),
+            None => sections.push(SectionChange::Removed(section.heading.clone())),
+        }
+    }
+    sections.extend(
+        unmatched
+            .into_iter()
+            .flatten()
+            .map(|s| SectionChange::Added(s.heading.clone())),
+    );
+
+    let old_args = old.arguments();
+    let new_args = new.arguments();
+    let mut arguments = Vec::new();
+    for argument in &old_args {
+        match new_args.iter().find(|a| a.name == argument.name) {
+            Some(other) if words(&argument.description).eq(words(&other.description)) => {}
+            Some(other) => arguments.push(ArgumentChange::Changed {
+                name: argument.name.clone(),
+                old: argument.description.clone(),
+                new: other.description.clone(),
+            }),
+            None => arguments.push(ArgumentChange::Removed(argument.clone())),
+        }
+    }
+    arguments.extend(
+        new_args
+            .iter()
+            .filter(|a| !old_args.iter().any(|o| o.name == a.name))
+            .cloned()
+            .map(ArgumentChange::Added),
+    );
+
+    DocDiff {
+        description,
+        sections,
+        arguments,
+    }
+}
+
+fn words(text: &str) -> std::str::SplitWhitespace<'_> {
+    text.split_whitespace()
+}
+
+/// Diff two texts word by word, using a longest common subsequence.
+///
+/// Whitespace is not preserved: each hunk joins its words with one space.
+fn diff_words(old: &str, new: &str) -> Vec<Hunk> {
+    let old: Vec<&str> = words(old).collect();
+    let new: Vec<&str> = words(new).collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of
+    // old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            push_word(&mut hunks, Hunk::Equal(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push_word(&mut hunks, Hunk::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            push_word(&mut hunks, Hunk::Inserted(new[j].to_string()));
+            j += 1;
+        }
+    }
+    hunks
+}
+
+/// Append a one-word hunk, merging it into the last hunk if it is of the
+/// same kind.
+fn push_word(hunks: &mut Vec<Hunk>, hunk: Hunk) {
+    match (hunks.last_mut(), &hunk) {
+        (Some(Hunk::Equal(text)), Hunk::Equal(word))
+        | (Some(Hunk::Removed(text)), Hunk::Removed(word))
+        | (Some(Hunk::Inserted(text)), Hunk::Inserted(word)) => {
+            text.push(' ');
+            text.push_str(word);
+        }
+        _ => hunks.push(hunk),
+    }
+}
+
+/// Compare two document sets by identifier.
+///
+/// Items are matched by [`DocumentedItem::name`]; unnamed items are ignored.
+/// If a name is documented more than once in a set, occurrences are paired
+/// in order.
+pub(crate) fn diff_sets(old: &DocSet, new: &DocSet) -> DocSetDiff {
+    let old = by_name(old);
+    let new = by_name(new);
+    let mut items = Vec::new();
+
+    let mut names: Vec<&str> = old.keys().chain(new.keys()).copied().collect();
+    names.sort_unstable();
+    names.dedup();
+
+    for name in names {
+        let old_items = old.get(name).map(Vec::as_slice).unwrap_or_default();
+        let new_items = new.get(name).map(Vec::as_slice).unwrap_or_default();
+
+        for i in 0..old_items.len().max(new_items.len()) {
+            let status = match (old_items.get(i), new_items.get(i)) {
+                (Some(_), None) => ItemStatus::Removed,
+                (None, Some(_)) => ItemStatus::Added,
+                (Some(a), Some(b)) => {
+                    let details = compare(&a.doc, &b.doc);
+                    if details.is_empty() {
+                        continue;
+                    }
+                    ItemStatus::Changed(details)
+                }
+                (None, None) => unreachable!("index is below one of the lengths"),
+            };
+            items.push(ItemDiff {
+                identifier: name.to_string(),
+                status,
+            });
+        }
+    }
+
+    DocSetDiff { items }
+}
+
+fn by_name(set: &DocSet) -> BTreeMap<&str, Vec<&DocumentedItem>> {
+    let mut map: BTreeMap<&str, Vec<&DocumentedItem>> = BTreeMap::new();
+    for item in set {
+        if let Some(name) = &item.name {
+            map.entry(name).or_default().push(item);
+        }
+    }
+    map
+}
+
+/// List the ways in which `new` differs from `old`.
+fn compare(old: &DocComment, new: &DocComment) -> Vec<ChangeDetail> {
+    let mut details = Vec::new();
+
+    if old.description() != new.description() {
+        details.push(ChangeDetail::DescriptionEdited);
+    }
+
+    let old_sig = old.type_sig().map(|s| s.trim().to_string());
+    let new_sig = new.type_sig().map(|s| s.trim().to_string());
+    if old_sig != new_sig {
+        details.push(ChangeDetail::TypeSignatureChanged {
+            old: old_sig,
+            new: new_sig,
+        });
+    }
+
+    let deprecation_changed = old.is_deprecated() != new.is_deprecated();
+    if deprecation_changed {
+        details.push(if new.is_deprecated() {
+            ChangeDetail::NewlyDeprecated
+        } else {
+            ChangeDetail::NoLongerDeprecated
+        });
+    }
+
+    if old.arguments() != new.arguments() {
+        details.push(ChangeDetail::ArgumentsChanged);
+    }
+    if old.examples() != new.examples() {
+        details.push(ChangeDetail::ExamplesChanged);
+    }
+
+    // Everything not covered above, compared as trimmed section text.
+    let others = |doc: &DocComment| -> Vec<(SectionKind, String)> {
+        doc.sections
+            .iter()
+            .map(|s| (s.kind(), s.content.trim().to_string()))
+            .filter(|(kind, _)| match kind {
+                SectionKind::Type
+                | SectionKind::Arguments
+                | SectionKind::Example
+                | SectionKind::Examples => false,
+                SectionKind::Deprecated => !deprecation_changed,
+                _ => true,
+            })
+            .collect()
+    };
+    if others(old) != others(new) {
+        details.push(ChangeDetail::OtherSectionsChanged);
+    }
+
+    details
+}
@@ -0,0 +1,553 @@
+//! Working with the doc comments of many files at once.
+//!
+//! A [`DocSet`] gathers the [`DocumentedItem`]s of a corpus, such as a whole
+//! library, and answers questions about it as a whole: how many comments
+//! there are, which failed to parse, which files produce the most warnings,
+//! and how often each section kind is used.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::anchor::Anchors;
+use crate::chunk::{self, Chunk, ChunkOptions};
+use crate::diff::{self, DocSetDiff};
+use crate::extract::{self, DocumentedItem, FileWarning, ParseFailure};
+use crate::search::{FieldBoosts, SearchIndex};
+#[cfg(feature = "sqlite")]
+use crate::store::{self, StoreError};
+use crate::xref::{self, XrefGraph};
+use crate::{ParserOptions, SectionKind, WarningKind};
+
+/// A collection of documented items gathered from one or more Nix files.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+///
+/// let mut set = DocSet::new();
+/// set.add_source("/** The identity. */\nid = x: x;\n/** */", None);
+/// assert_eq!(set.len(), 1);
+/// assert_eq!(set.failures().len(), 1);
+///
+/// let stats = set.stats();
+/// assert_eq!(stats.comments, 2);
+/// assert_eq!(stats.parsed, 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocSet {
+    pub(crate) items: Vec<DocumentedItem>,
+    pub(crate) failures: Vec<ParseFailure>,
+    pub(crate) files: Vec<PathBuf>,
+    pub(crate) file_warnings: Vec<FileWarning>,
+    options: ParserOptions,
+}
+
+impl DocSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the [`ParserOptions`] used for sources added afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocSet, ParserOptions};
+    ///
+    /// let options = ParserOptions::new().with_legacy_comments(true);
+    /// let mut set = DocSet::new().with_options(options);
+    /// set.add_source("{\n  /* Old style. */\n  old = 1;\n}", None);
+    /// assert_eq!(set.len(), 1);
+    /// assert!(set.items()[0].doc.is_legacy());
+    /// ```
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Read every `.nix` file at the given paths into a new set.
+    ///
+    /// Directories are searched recursively, see [`extract::nix_files`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first I/O error encountered.
+    pub fn from_paths<P: AsRef<Path>>(paths: &[P]) -> io::Result<Self> {
+        let mut set = Self::new();
+        for path in paths {
+            for file in extract::nix_files(path.as_ref())? {
+                set.add_file(&file)?;
+            }
+        }
+        Ok(set)
+    }
+
+    /// Read every `.nix` file at the given paths into a new set, decoding
+    /// files that are not valid UTF-8 with [`extract::read_lossy`].
+    ///
+    /// Files that needed decoding are reported in [`Self::file_warnings`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first I/O error encountered.
+    pub fn from_paths_lossy<P: AsRef<Path>>(paths: &[P]) -> io::Result<Self> {
+        let mut set = Self::new();
+        for path in paths {
+            for file in extract::nix_files(path.as_ref())? {
+                set.add_file_lossy(&file)?;
+            }
+        }
+        Ok(set)
+    }
+
+    /// Read a Nix file and add its documented items.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn add_file(&mut self, path: &Path) -> io::Result<()> {
+        let src = fs::read_to_string(path)?;
+        self.add_source(&src, Some(path));
+        Ok(())
+    }
+
+    /// Read a Nix file with [`extract::read_lossy`] and add its documented
+    /// items, recording a [`FileWarning`] if it was not valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn add_file_lossy(&mut self, path: &Path) -> io::Result<()> {
+        let (src, warning) = extract::read_lossy(path)?;
+        self.file_warnings.extend(warning);
+        self.add_source(&src, Some(path));
+        Ok(())
+    }
+
+    /// Add the documented items of a Nix source string.
+    ///
+    /// `file` is recorded on each item and counted in [`Self::files`], but
+    /// not read. Comments that fail to parse are kept in [`Self::failures`].
+    pub fn add_source(&mut self, src: &str, file: Option<&Path>) {
+        if let Some(file) = file {
+            self.files.push(file.to_path_buf());
+        }
+        for result in extract::parse_source_with(src, file, &self.options) {
+            match result {
+                Ok(item) => self.items.push(item),
+                Err(failure) => self.failures.push(failure),
+            }
+        }
+    }
+
+    /// Add a single, already extracted item.
+    pub fn push(&mut self, item: DocumentedItem) {
+        self.items.push(item);
+    }
+
+    /// Returns the documented items in the order they were added.
+    pub fn items(&self) -> &[DocumentedItem] {
+        &self.items
+    }
+
+    /// Returns the documented items, for changes that keep their order.
+    pub(crate) fn items_mut(&mut self) -> &mut [DocumentedItem] {
+        &mut self.items
+    }
+
+    /// Returns an iterator over the documented items.
+    pub fn iter(&self) -> std::slice::Iter<'_, DocumentedItem> {
+        self.items.iter()
+    }
+
+    /// Returns the number of successfully parsed items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the set contains no parsed items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the comments that could not be parsed.
+    pub fn failures(&self) -> &[ParseFailure] {
+        &self.failures
+    }
+
+    /// Returns the problems with whole files, such as invalid UTF-8, that
+    /// were worked around while reading them.
+    pub fn file_warnings(&self) -> &[FileWarning] {
+        &self.file_warnings
+    }
+
+    /// Returns the files added to the set, including those without comments.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Returns the items whose binding is named `name`.
+    pub fn find(&self, name: &str) -> Vec<&DocumentedItem> {
+        self.items
+            .iter()
+            .filter(|item| item.name.as_deref() == Some(name))
+            .collect()
+    }
+
+    /// Returns the items tagged `tag`, compared case-insensitively, see
+    /// [`DocComment::tags`](crate::DocComment::tags).
+    pub fn with_tag(&self, tag: &str) -> Vec<&DocumentedItem> {
+        self.items
+            .iter()
+            .filter(|item| item.doc.tags().iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            .collect()
+    }
+
+    /// Returns the items grouped by tag, in set order within each tag.
+    ///
+    /// An item with several tags appears in each group; untagged items are
+    /// omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocSet;
+    ///
+    /// let mut set = DocSet::new();
+    /// set.add_source(
+    ///     "{\n  /**\n    A.\n\n    # Tags\n\n    lists, strings\n  */\n  a = 1;\n  /**\n    B.\n\n    # Tags\n\n    lists\n  */\n  b = 2;\n}",
+    ///     None,
+    /// );
+    /// let groups = set.by_tag();
+    /// assert_eq!(groups["lists"].len(), 2);
+    /// assert_eq!(groups["strings"][0].name.as_deref(), Some("a"));
+    /// assert_eq!(set.with_tag("Strings").len(), 1);
+    /// ```
+    pub fn by_tag(&self) -> BTreeMap<String, Vec<&DocumentedItem>> {
+        let mut groups: BTreeMap<String, Vec<&DocumentedItem>> = BTreeMap::new();
+        for item in &self.items {
+            for tag in item.doc.tags() {
+                groups.entry(tag).or_default().push(item);
+            }
+        }
+        groups
+    }
+
+    /// Returns the number of parse warnings per file.
+    ///
+    /// Files without warnings are omitted; items without a file are counted
+    /// under `None`.
+    pub fn warning_counts(&self) -> BTreeMap<Option<&Path>, usize> {
+        let mut counts = BTreeMap::new();
+        for item in &self.items {
+            if !item.doc.warnings.is_empty() {
+                *counts.entry(item.file.as_deref()).or_default() += item.doc.warnings.len();
+            }
+        }
+        counts
+    }
+
+    /// Returns how many sections of each kind the set contains.
+    pub fn section_histogram(&self) -> BTreeMap<SectionKind, usize> {
+        let mut histogram = BTreeMap::new();
+        for section in self.items.iter().flat_map(|item| &item.doc.sections) {
+            *histogram.entry(section.kind()).or_default() += 1;
+        }
+        histogram
+    }
+
+    /// Compute corpus-wide totals.
+    pub fn stats(&self) -> DocSetStats {
+        let mut warnings = BTreeMap::new();
+        for warning in self.items.iter().flat_map(|item| &item.doc.warnings) {
+            *warnings.entry(warning.kind.clone()).or_default() += 1;
+        }
+
+        DocSetStats {
+            files: self.files.len(),
+            comments: self.items.len() + self.failures.len(),
+            parsed: self.items.len(),
+            failed: self.failures.len(),
+            sections: self.items.iter().map(|item| item.doc.sections.len()).sum(),
+            warnings,
+        }
+    }
+
+    /// Compare two sets by identifier.
+    ///
+    /// Each documented name is classified as added, removed, or changed;
+    /// changed names carry details such as an edited description, a new type
+    /// signature, or a new deprecation. Unchanged names are omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocSet;
+    ///
+    /// let mut old = DocSet::new();
+    /// old.add_source("/** Old. */\nf = 1;\n/** Gone. */\ng = 2;", None);
+    /// let mut new = DocSet::new();
+    /// new.add_source("/** New. */\nf = 1;\n/** Fresh. */\nh = 3;", None);
+    ///
+    /// let diff = DocSet::diff(&old, &new);
+    /// assert_eq!(
+    ///     diff.to_string(),
+    ///     "- Changed `f`: description edited\n- Removed `g`\n- Added `h`\n",
+    /// );
+    /// ```
+    pub fn diff(old: &DocSet, new: &DocSet) -> DocSetDiff {
+        diff::diff_sets(old, new)
+    }
+
+    /// Build a full-text search index of the set with the default field boosts.
+    ///
+    /// See [`SearchIndex::build`] to choose the boosts.
+    pub fn search_index(&self) -> SearchIndex {
+        SearchIndex::build(self, &FieldBoosts::default())
+    }
+
+    /// Build the graph of references between the set's documented items.
+    ///
+    /// See [`XrefGraph::build`] for how mentions are resolved.
+    pub fn xref_graph(&self) -> XrefGraph {
+        XrefGraph::build(self)
+    }
+
+    /// Render the graph of references between the set's documented items
+    /// in Graphviz DOT.
+    ///
+    /// Items are clustered by file. Items that neither refer to nor are
+    /// referred to by another item are dashed, so isolated functions stand
+    /// out; render with `dot -Tsvg`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocSet;
+    ///
+    /// let mut set = DocSet::new();
+    /// set.add_source("{\n  /** Like [`map`]. */\n  imap = f: xs: xs;\n  /** Map. */\n  map = f: xs: xs;\n}", Some("lists.nix".as_ref()));
+    /// let dot = set.to_dot();
+    /// assert!(dot.contains("label=\"lists.nix\";"));
+    /// assert!(dot.contains("\"imap\" -> \"map\";"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        xref::to_dot(self)
+    }
+
+    /// Assign every item and section an anchor that is unique across the
+    /// set.
+    ///
+    /// See [`Anchors::build`] for how collisions are resolved.
+    pub fn anchors(&self) -> Anchors {
+        Anchors::build(self)
+    }
+
+    /// Split the set into overlapping plain-text passages for embedding
+    /// models, with the metadata to cite and link each.
+    ///
+    /// Every item gives chunks for its description and for each section in
+    /// turn; a passage longer than [`ChunkOptions::max_chars`] is split at
+    /// word boundaries, with [`ChunkOptions::overlap`] characters repeated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::chunk::ChunkOptions;
+    /// use nixdoc::{DocSet, SectionKind};
+    ///
+    /// let mut set = DocSet::new();
+    /// set.add_source(
+    ///     "{\n  /**\n    Apply `f` to each element.\n\n    # Example\n\n    ```nix\n    map (x: x + 1) [ 1 2 ]\n    ```\n  */\n  map = f: xs: xs;\n}",
+    ///     Some("lists.nix".as_ref()),
+    /// );
+    /// let chunks = set.chunks(&ChunkOptions::new());
+    /// assert_eq!(chunks.len(), 2);
+    /// assert_eq!(chunks[0].text, "map\n\nApply f to each element.");
+    /// assert_eq!(chunks[1].section, Some(SectionKind::Example));
+    /// assert_eq!(chunks[1].anchor.as_deref(), Some("map-example"));
+    /// assert_eq!(chunks[1].text, "map: Example\n\nmap (x: x + 1) [ 1 2 ]");
+    /// ```
+    pub fn chunks(&self, options: &ChunkOptions) -> Vec<Chunk> {
+        chunk::chunks(self, options)
+    }
+
+    /// Save the set to an SQLite database at `path`, replacing any file
+    /// there, so that it can be [opened](Self::open) without parsing again.
+    ///
+    /// See [`store`](crate::store) for the layout of the database. Requires
+    /// the `sqlite` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be replaced or written.
+    #[cfg(feature = "sqlite")]
+    pub fn save(&self, path: &Path) -> Result<(), StoreError> {
+        store::save(self, path)
+    }
+
+    /// Open a set saved with [`save`](Self::save).
+    ///
+    /// The set has default [`ParserOptions`]; they are not saved. Requires
+    /// the `sqlite` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` is not a database saved by this version.
+    #[cfg(feature = "sqlite")]
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        store::open(path)
+    }
+
+    /// Build an index of every example in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocSet;
+    ///
+    /// let mut set = DocSet::new();
+    /// set.add_source(
+    ///     "{\n  /**\n    f.\n\n    # Example\n\n    ```nix\n    filterAttrs (n: v: v) x\n    ```\n  */\n  f = 1;\n}",
+    ///     None,
+    /// );
+    /// let index = set.examples_index();
+    /// let hits = index.search("filterAttrs");
+    /// assert_eq!(hits.len(), 1);
+    /// assert_eq!(hits[0].identifier.as_deref(), Some("f"));
+    /// ```
+    pub fn examples_index(&self) -> ExampleIndex {
+        let entries = self
+            .items
+            .iter()
+            .flat_map(|item| {
+                item.doc.examples().into_iter().map(|example| ExampleEntry {
+                    identifier: item.name.clone(),
+                    file: item.file.clone(),
+                    line: item.line,
+                    language: example.language,
+                    title: example.title,
+                    input: example.input,
+                    expected: example.expected,
+                })
+            })
+            .collect();
+        ExampleIndex { entries }
+    }
+}
+
+impl<'a> IntoIterator for &'a DocSet {
+    type Item = &'a DocumentedItem;
+    type IntoIter = std::slice::Iter<'a, DocumentedItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl Extend<DocumentedItem> for DocSet {
+    fn extend<T: IntoIterator<Item = DocumentedItem>>(&mut self, iter: T) {
+        self.items.extend(iter);
+    }
+}
+
+impl FromIterator<DocumentedItem> for DocSet {
+    fn from_iter<T: IntoIterator<Item = DocumentedItem>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Corpus-wide totals computed by [`DocSet::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocSetStats {
+    /// Number of files added to the set.
+    pub files: usize,
+    /// Number of doc comments found, parsed or not.
+    pub comments: usize,
+    /// Number of comments that parsed successfully.
+    pub parsed: usize,
+    /// Number of comments that failed to parse.
+    pub failed: usize,
+    /// Total number of sections across all parsed comments.
+    pub sections: usize,
+    /// Number of parse warnings of each kind.
+    pub warnings: BTreeMap<WarningKind, usize>,
+}
+
+/// One example in an [`ExampleIndex`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExampleEntry {
+    /// The name of the documented binding, if known.
+    pub identifier: Option<String>,
+    /// The file containing the doc comment, if known.
+    pub file: Option<PathBuf>,
+    /// The 1-based line on which the doc comment starts.
+    pub line: usize,
+    /// The language of the example's code block.
+    pub language: Option<String>,
+    /// The example's title.
+    pub title: Option<String>,
+    /// The example code, without any `=>` result.
+    pub input: String,
+    /// The expected result, if the example states one.
+    pub expected: Option<String>,
+}
+
+/// A searchable list of examples, built by [`DocSet::examples_index`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExampleIndex {
+    /// Every example, in document order.
+    pub entries: Vec<ExampleEntry>,
+}
+
+impl ExampleIndex {
+    /// Returns the examples mentioning `term` in their code, title, or
+    /// identifier, compared case-insensitively.
+    pub fn search(&self, term: &str) -> Vec<&ExampleEntry> {
+        let term = term.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|entry| {
+                [
+                    Some(entry.input.as_str()),
+                    entry.expected.as_deref(),
+                    entry.title.as_deref(),
+                    entry.identifier.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .any(|text| text.to_lowercase().contains(&term))
+            })
+            .collect()
+    }
+
+    /// Returns the examples written in `language`, compared case-insensitively.
+    pub fn by_language(&self, language: &str) -> Vec<&ExampleEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry
+                    .language
+                    .as_deref()
+                    .is_some_and(|lang| lang.eq_ignore_ascii_case(language))
+            })
+            .collect()
+    }
+
+    /// Serialize the index as a pretty-printed JSON array of entries.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.entries).expect("example entries serialize to JSON")
+    }
+}
@@ -0,0 +1,307 @@
+//! Evaluate documented Nix examples and check them against their results.
+//!
+//! Enabled by the `doctest` feature.
+//!
+//! Every example tagged with the `nix` language that follows the `=>`
+//! convention is a doctest: the input expression and the expected result are
+//! both evaluated, and the example passes if their JSON encodings match.
+//!
+//! ````text
+//! # Example
+//!
+//! ```nix
+//! lib.id 1
+//! => 1
+//! ```
+//! ````
+//!
+//! Evaluation is delegated to an external program, see [`Evaluator`].
+//! Examples usually refer to library functions, so a [`Doctest::with_prelude`]
+//! expression can bring them into scope.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::parser::raw_examples;
+use crate::{DocComment, Example, Span, extract};
+
+/// The program used to evaluate Nix expressions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Evaluator {
+    /// `nix eval --raw --expr`, with the `nix-command` experimental feature
+    /// enabled for the invocation.
+    #[default]
+    Nix,
+
+    /// `tvix --raw -E`.
+    Tvix,
+
+    /// A custom program. The expression is passed as the final argument and
+    /// the program must print the evaluated string verbatim on stdout.
+    Command {
+        /// The program to run.
+        program: String,
+        /// Arguments placed before the expression.
+        args: Vec<String>,
+    },
+}
+
+impl Evaluator {
+    fn command(&self, expr: &str) -> Command {
+        let mut cmd = match self {
+            Self::Nix => {
+                let mut cmd = Command::new("nix");
+                cmd.args([
+                    "--extra-experimental-features",
+                    "nix-command",
+                    "eval",
+                    "--raw",
+                    "--expr",
+                ]);
+                cmd
+            }
+            Self::Tvix => {
+                let mut cmd = Command::new("tvix");
+                cmd.args(["--raw", "-E"]);
+                cmd
+            }
+            Self::Command { program, args } => {
+                let mut cmd = Command::new(program);
+                cmd.args(args);
+                cmd
+            }
+        };
+        cmd.arg(expr);
+        cmd
+    }
+
+    /// Evaluate `expr` and return its JSON encoding.
+    ///
+    /// The expression is wrapped in `builtins.toJSON`, so the output of every
+    /// evaluator is directly comparable.
+    ///
+    /// # Errors
+    ///
+    /// Returns the evaluator's error output if it could not be started or
+    /// exited unsuccessfully.
+    pub fn eval_json(&self, expr: &str) -> Result<String, String> {
+        let wrapped = format!("builtins.toJSON (\n{expr}\n)");
+        let output = self
+            .command(&wrapped)
+            .output()
+            .map_err(|e| format!("failed to run evaluator: {e}"))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+/// Where a doctest comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// The source file, if the comment was read from one.
+    pub file: Option<PathBuf>,
+    /// The 1-based line on which the example's code block starts.
+    pub line: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}", file.display(), self.line),
+            None => write!(f, "<input>:{}", self.line),
+        }
+    }
+}
+
+/// The outcome of running one doctest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The input evaluated to the expected result.
+    Passed,
+    /// The input evaluated to something other than the expected result.
+    Failed {
+        /// JSON encoding of the expected result.
+        expected: String,
+        /// JSON encoding of the actual result.
+        actual: String,
+    },
+    /// The example has no `=>` expectation, so there was nothing to check.
+    Skipped,
+    /// The input or the expectation failed to evaluate.
+    Error(String),
+}
+
+impl Outcome {
+    /// Returns `true` unless the doctest failed or could not be evaluated.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Passed | Self::Skipped)
+    }
+}
+
+/// The result of running one doctest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctestResult {
+    /// Where the example is.
+    pub location: Location,
+    /// The 1-based position of the example among the comment's doctests,
+    /// not counting examples in other languages.
+    pub index: usize,
+    /// The example's caption, if any.
+    pub title: Option<String>,
+    /// The evaluated input expression.
+    pub input: String,
+    /// What happened.
+    pub outcome: Outcome,
+}
+
+/// A doctest runner.
+///
+/// # Examples
+///
+/// ```no_run
+/// use nixdoc::doctest::{Doctest, Evaluator};
+///
+/// let runner = Doctest::new(Evaluator::Nix).with_prelude("with import <nixpkgs/lib>;");
+/// for result in runner.run_file("lib/trivial.nix".as_ref()).unwrap() {
+///     println!("{}: {:?}", result.location, result.outcome);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Doctest {
+    evaluator: Evaluator,
+    prelude: String,
+}
+
+impl Doctest {
+    /// Create a runner using the given evaluator and no prelude.
+    pub fn new(evaluator: Evaluator) -> Self {
+        Self {
+            evaluator,
+            prelude: String::new(),
+        }
+    }
+
+    /// Set an expression prefix placed before every example.
+    ///
+    /// The prelude is pasted in front of the example input and expectation,
+    /// so it must be something that takes an expression after it, like
+    /// `with import <nixpkgs/lib>;` or `let lib = import ./lib; in`.
+    pub fn with_prelude(mut self, prelude: impl Into<String>) -> Self {
+        self.prelude = prelude.into();
+        self
+    }
+
+    /// Returns `true` if `example` is a Nix example this runner evaluates.
+    pub fn is_doctest(example: &Example) -> bool {
+        example.is_nix()
+    }
+
+    /// Run a single example.
+    ///
+    /// Two fence flags change how an example is run: `no-run` skips it, and
+    /// `expect-error` makes it pass only if the input fails to evaluate.
+    /// Bindings from the example's `setup` blocks are in scope for both the
+    /// input and the expectation.
+    pub fn run_example(&self, example: &Example) -> Outcome {
+        if example.has_attribute("no-run") {
+            return Outcome::Skipped;
+        }
+
+        let eval = |expr: &str| {
+            self.evaluator
+                .eval_json(&format!("{}\n{}", self.prelude, example.with_setup(expr)))
+        };
+
+        if example.has_attribute("expect-error") {
+            return match eval(&example.input) {
+                Ok(actual) => Outcome::Failed {
+                    expected: "an evaluation error".to_string(),
+                    actual,
+                },
+                Err(_) => Outcome::Passed,
+            };
+        }
+
+        let Some(expected) = &example.expected else {
+            return Outcome::Skipped;
+        };
+        let actual = match eval(&example.input) {
+            Ok(value) => value,
+            Err(e) => return Outcome::Error(e),
+        };
+        let expected = match eval(expected) {
+            Ok(value) => value,
+            Err(e) => return Outcome::Error(format!("cannot evaluate expectation: {e}")),
+        };
+
+        if actual == expected {
+            Outcome::Passed
+        } else {
+            Outcome::Failed { expected, actual }
+        }
+    }
+
+    /// Run every Nix example of a parsed comment.
+    ///
+    /// `location` is where the first line of the comment's
+    /// [`raw_content`](DocComment::raw_content) is; each result's line
+    /// counts on from it to the example's code block.
+    pub fn run_doc(&self, doc: &DocComment, location: &Location) -> Vec<DoctestResult> {
+        self.run_examples(doc, |span| Location {
+            file: location.file.clone(),
+            line: location.line + doc.raw_content[..span.start].matches('\n').count(),
+        })
+    }
+
+    /// Run the doctests of every doc comment in a Nix source file.
+    ///
+    /// `file` is only used to label the results. Comments that fail to parse
+    /// are skipped.
+    pub fn run_source(&self, src: &str, file: Option<&Path>) -> Vec<DoctestResult> {
+        extract::documented_items(src, file)
+            .iter()
+            .flat_map(|item| {
+                self.run_examples(&item.doc, |span| Location {
+                    file: file.map(Path::to_path_buf),
+                    line: item.source_span(src, span).line_col(src).0,
+                })
+            })
+            .collect()
+    }
+
+    fn run_examples(
+        &self,
+        doc: &DocComment,
+        locate: impl Fn(Span) -> Location,
+    ) -> Vec<DoctestResult> {
+        raw_examples(&doc.raw_content)
+            .into_iter()
+            .filter(|(_, example)| Self::is_doctest(example))
+            .enumerate()
+            .map(|(i, (span, example))| DoctestResult {
+                location: locate(span),
+                index: i + 1,
+                title: example.title.clone(),
+                outcome: self.run_example(&example),
+                input: example.input,
+            })
+            .collect()
+    }
+
+    /// Read a Nix file and run its doctests.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn run_file(&self, path: &Path) -> io::Result<Vec<DoctestResult>> {
+        let src = fs::read_to_string(path)?;
+        Ok(self.run_source(&src, Some(path)))
+    }
+}
@@ -1,8 +1,16 @@
 use thiserror::Error;
 
+use crate::Span;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
 /// Errors that can occur while parsing a Nixdoc comment.
+///
+/// More errors may be added without a major release, so matches need a
+/// wildcard arm.
 #[derive(Debug, Error, PartialEq, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum ParseError {
     /// The input is not a doc comment; it does not start with `/**`.
     #[error("not a doc comment: input must start with '/**'")]
@@ -15,13 +23,29 @@ pub enum ParseError {
     /// The doc comment has no content after stripping delimiters and normalizing.
     #[error("empty doc comment")]
     EmptyComment,
+
+    /// The comment parsed with a warning, which
+    /// [`ParserOptions::deny_warnings`](crate::ParserOptions::deny_warnings)
+    /// turns into an error. Holds the first warning.
+    #[error("{} warning denied: {}", .0.kind.name(), .0.message)]
+    DeniedWarning(ParseWarning),
+}
+
+/// A [`ParseError`] together with the part of the input it concerns.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpannedError {
+    /// The error.
+    pub error: ParseError,
+    /// Where the error is, relative to the parsed input.
+    pub span: Span,
 }
 
 /// A non-fatal warning produced during parsing.
 ///
 /// Warnings indicate structurally valid but potentially problematic content
 /// (e.g. an empty section, or an unrecognized section heading).
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParseWarning {
     /// The category of this warning.
@@ -31,11 +55,87 @@ pub struct ParseWarning {
 }
 
 /// The category of a [`ParseWarning`].
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WarningKind {
     /// A section heading was found but the section has no body content.
     EmptySection,
     /// The section heading is not one of the recognized Nixdoc section names.
     UnknownSection,
+    /// The comment has no closing `*/`; the end of the input was used instead.
+    ///
+    /// Only reported by [`DocComment::parse_lossy`](crate::DocComment::parse_lossy).
+    UnclosedComment,
+    /// Lines start with a javadoc-style `*` gutter, which was stripped.
+    JavadocGutter,
+    /// A byte order mark, zero-width character or non-ASCII space was
+    /// removed from indentation or a heading.
+    ///
+    /// Only reported with [`UnicodePolicy::Warn`](crate::UnicodePolicy::Warn).
+    UnusualWhitespace,
+    /// A `*/` inside a code block ended the comment, although the block
+    /// continues after it.
+    ///
+    /// Only reported when extracting comments from a source file, see
+    /// [`extract::parse_source`](crate::extract::parse_source).
+    AmbiguousCommentEnd,
+    /// The comment mixes legacy and RFC 145 styles, such as an inline
+    /// `name :: type` line next to a `# Type` section or directly in one,
+    /// without a code block, or an `Example:` label next to Markdown
+    /// headings.
+    MixedStyle,
+    /// A heading ends with a closing `#` sequence or a colon, as in
+    /// `# Examples #` or `# Example:`, which were removed.
+    HeadingStyle,
+    /// The comment is a plain `/* … */` comment rather than an RFC 145 doc
+    /// comment.
+    ///
+    /// Only reported with
+    /// [`ParserOptions::legacy_comments`](crate::ParserOptions::legacy_comments).
+    LegacyComment,
+}
+
+impl WarningKind {
+    /// Every kind of warning.
+    pub const ALL: [Self; 9] = [
+        Self::EmptySection,
+        Self::UnknownSection,
+        Self::UnclosedComment,
+        Self::JavadocGutter,
+        Self::UnusualWhitespace,
+        Self::AmbiguousCommentEnd,
+        Self::MixedStyle,
+        Self::HeadingStyle,
+        Self::LegacyComment,
+    ];
+
+    /// Returns the name of the kind in kebab case, such as
+    /// `unknown-section`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::EmptySection => "empty-section",
+            Self::UnknownSection => "unknown-section",
+            Self::UnclosedComment => "unclosed-comment",
+            Self::JavadocGutter => "javadoc-gutter",
+            Self::UnusualWhitespace => "unusual-whitespace",
+            Self::AmbiguousCommentEnd => "ambiguous-comment-end",
+            Self::MixedStyle => "mixed-style",
+            Self::HeadingStyle => "heading-style",
+            Self::LegacyComment => "legacy-comment",
+        }
+    }
+
+    /// Look up a kind by its [`name`](Self::name).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::WarningKind;
+    ///
+    /// assert_eq!(WarningKind::from_name("mixed-style"), Some(WarningKind::MixedStyle));
+    /// assert_eq!(WarningKind::from_name("MixedStyle"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
 }
@@ -0,0 +1,846 @@
+//! Locating doc comments in Nix source files.
+//!
+//! The parser in this crate works on a single `/** … */` comment. The helpers
+//! here find those comments in whole `.nix` files so that callers can parse
+//! each one and report positions back against the original source.
+
+#[cfg(feature = "rnix")]
+mod ast;
+#[cfg(feature = "ts")]
+pub mod ts;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "rnix")]
+pub use ast::attach;
+
+use crate::{
+    DocComment, DocView, ParseError, ParseWarning, ParserOptions, Provenance, Span, WarningKind,
+    parser,
+};
+
+/// Find every `/** … */` comment in a Nix source file.
+///
+/// Returns the span of each comment (including its delimiters) together with
+/// the comment text, in source order. A comment missing its closing `*/` is
+/// not returned; see [`extract_doc_comments_lossy`].
+///
+/// A `*/` inside a code block fenced with four or more backticks or tildes
+/// does not end the comment when the block closes later, so examples can
+/// show doc comments themselves. [`parse_source`] reports a
+/// [`WarningKind::AmbiguousCommentEnd`] warning where a `*/` in a code
+/// block had to end the comment.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::extract_doc_comments;
+///
+/// let src = "{\n  /** The identity. */\n  id = x: x;\n}";
+/// let comments = extract_doc_comments(src);
+/// assert_eq!(comments.len(), 1);
+/// assert_eq!(comments[0].1, "/** The identity. */");
+/// assert_eq!(&src[comments[0].0.start..comments[0].0.end], comments[0].1);
+/// ```
+pub fn extract_doc_comments(src: &str) -> Vec<(Span, String)> {
+    extracted_pairs(scan(src, false, false))
+}
+
+/// Find every `/** … */` comment in a Nix source file, including a final
+/// comment missing its closing `*/`.
+///
+/// An unclosed comment runs to the end of `src`. This is meant for source
+/// that is still being edited; parse the result with
+/// [`DocComment::parse_lossy`].
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::extract_doc_comments_lossy;
+///
+/// let src = "{\n  /** The identity. */\n  id = x: x;\n  /** Half-typed";
+/// let comments = extract_doc_comments_lossy(src);
+/// assert_eq!(comments.len(), 2);
+/// assert_eq!(comments[1].1, "/** Half-typed");
+/// assert_eq!(comments[1].0.end, src.len());
+/// ```
+pub fn extract_doc_comments_lossy(src: &str) -> Vec<(Span, String)> {
+    extracted_pairs(scan(src, true, false))
+}
+
+/// A comment found by [`scan`].
+struct Extracted {
+    span: Span,
+    text: String,
+    /// The end was cut at a `*/` inside a code block although the block
+    /// continues after it.
+    ambiguous: bool,
+}
+
+/// Find the comments of `src`: `/** … */` ones, or every block comment if
+/// `legacy` is set.
+fn scan(src: &str, keep_unclosed: bool, legacy: bool) -> Vec<Extracted> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    let mut lexer = Lexer::new();
+
+    while let Some(start) = lexer.next_comment(src, pos, legacy) {
+        let from = if src[start..].starts_with("/**") && !src[start..].starts_with("/**/") {
+            start + 3
+        } else {
+            start + 2
+        };
+        match comment_end(src, from) {
+            Some((end, ambiguous)) => {
+                out.push(Extracted {
+                    span: Span::new(start, end),
+                    text: src[start..end].to_string(),
+                    ambiguous,
+                });
+                pos = end;
+            }
+            None => {
+                if keep_unclosed {
+                    out.push(Extracted {
+                        span: Span::new(start, src.len()),
+                        text: src[start..].to_string(),
+                        ambiguous: false,
+                    });
+                }
+                break;
+            }
+        }
+    }
+
+    out
+}
+
+/// What the text at the current position of a [`Lexer`] is part of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    /// Nix code, with the number of `{` not yet closed.
+    Code { braces: usize },
+    /// A `"…"` string.
+    String,
+    /// An `''…''` indented string.
+    IndentedString,
+}
+
+/// Just enough of the Nix lexer to tell comments from text that looks like
+/// one: `/**` inside a string, after `#`, or inside a plain `/* … */`
+/// comment does not start a doc comment. Interpolations nest, so the lexer
+/// keeps a stack of contexts.
+struct Lexer {
+    stack: Vec<Context>,
+}
+
+impl Lexer {
+    fn new() -> Self {
+        Self {
+            stack: vec![Context::Code { braces: 0 }],
+        }
+    }
+
+    /// Returns the offset of the next comment start at or after `pos`: a
+    /// `/**` doc comment, or with `legacy` any `/*` comment. Other comments
+    /// are skipped.
+    fn next_comment(&mut self, src: &str, pos: usize, legacy: bool) -> Option<usize> {
+        let bytes = src.as_bytes();
+        let mut i = pos;
+        while i < bytes.len() {
+            let rest = &bytes[i..];
+            let context = self
+                .stack
+                .last()
+                .copied()
+                .unwrap_or(Context::Code { braces: 0 });
+            i += match (context, bytes[i]) {
+                (Context::Code { .. }, b'#') => {
+                    rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len())
+                }
+                (Context::Code { .. }, b'/') if rest.starts_with(b"/*") => {
+                    let is_doc = rest.starts_with(b"/**") && !rest.starts_with(b"/**/");
+                    if is_doc || legacy {
+                        return Some(i);
+                    }
+                    src[i + 2..].find("*/").map_or(rest.len(), |end| end + 4)
+                }
+                (Context::Code { .. }, b'"') => {
+                    self.stack.push(Context::String);
+                    1
+                }
+                (Context::Code { .. }, b'\'')
+                    if rest.starts_with(b"''") && !ends_identifier(&bytes[..i]) =>
+                {
+                    self.stack.push(Context::IndentedString);
+                    2
+                }
+                (Context::Code { braces }, b'{') => {
+                    self.set_braces(braces + 1);
+                    1
+                }
+                (Context::Code { braces: 0 }, b'}') if self.stack.len() > 1 => {
+                    // The end of an interpolation.
+                    self.stack.pop();
+                    1
+                }
+                (Context::Code { braces }, b'}') => {
+                    self.set_braces(braces.saturating_sub(1));
+                    1
+                }
+                (Context::String, b'\\') => 2,
+                (Context::String, b'"') => {
+                    self.stack.pop();
+                    1
+                }
+                (Context::IndentedString, b'\'') if rest.starts_with(b"''") => match rest.get(2) {
+                    Some(b'\'' | b'$') => 3,
+                    Some(b'\\') => 4,
+                    _ => {
+                        self.stack.pop();
+                        2
+                    }
+                },
+                (Context::String | Context::IndentedString, b'$') if rest.starts_with(b"$$") => 2,
+                (Context::String | Context::IndentedString, b'$') if rest.starts_with(b"${") => {
+                    self.stack.push(Context::Code { braces: 0 });
+                    2
+                }
+                _ => 1,
+            };
+        }
+        None
+    }
+
+    fn set_braces(&mut self, braces: usize) {
+        if let Some(top) = self.stack.last_mut() {
+            *top = Context::Code { braces };
+        }
+    }
+}
+
+/// Returns `true` if `before` ends with a character that can end a Nix
+/// identifier, so that a `''` after it is part of a name such as `f''`.
+fn ends_identifier(before: &[u8]) -> bool {
+    before
+        .last()
+        .is_some_and(|&b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'\'' | b'-'))
+}
+
+fn extracted_pairs(comments: Vec<Extracted>) -> Vec<(Span, String)> {
+    comments.into_iter().map(|c| (c.span, c.text)).collect()
+}
+
+/// Returns the offset just past the `*/` closing a comment whose content
+/// starts at `from`, and whether the cut was ambiguous.
+///
+/// A `*/` inside a code block fenced with four or more backticks or tildes
+/// is taken as part of the example when the block closes later and a `*/`
+/// follows, since such fences are how Markdown escapes inner fences. Any
+/// other `*/` ends the comment, as it does for Nix; it is ambiguous if it
+/// is inside a code block that closes later.
+fn comment_end(src: &str, from: usize) -> Option<(usize, bool)> {
+    let mut fence: Option<(char, usize)> = None;
+    let mut offset = from;
+
+    for (index, line) in src[from..].split_inclusive('\n').enumerate() {
+        let line_start = offset;
+        offset += line.len();
+        // The rest of the line with `/**` cannot open or close a block.
+        if index > 0 {
+            let content = without_gutter(line);
+            fence = match (fence, fence_run(content)) {
+                (Some(open), Some(run)) if closes(open, run, content) => None,
+                (None, Some(run)) => Some(run),
+                (fence, _) => fence,
+            };
+        }
+
+        let mut search = 0;
+        while let Some(found) = line[search..].find("*/") {
+            let end = line_start + search + found + 2;
+            let Some(open) = fence else {
+                return Some((end, false));
+            };
+            let continues = block_closes_after(src, offset, open);
+            if open.1 < 4 || !continues {
+                return Some((end, continues || open.1 >= 4));
+            }
+            search += found + 2;
+        }
+    }
+    None
+}
+
+/// Returns `true` if a line after `from` closes the code block `open`, and
+/// a `*/` follows it.
+fn block_closes_after(src: &str, from: usize, open: (char, usize)) -> bool {
+    let mut offset = from;
+    for line in src[from..].split_inclusive('\n') {
+        offset += line.len();
+        let content = without_gutter(line);
+        if fence_run(content).is_some_and(|run| closes(open, run, content)) {
+            return src[offset - line.len()..].contains("*/");
+        }
+    }
+    false
+}
+
+/// A line without its indentation and any javadoc-style `*` gutter.
+fn without_gutter(line: &str) -> &str {
+    let line = line.trim_start();
+    match line.strip_prefix('*') {
+        Some(rest) if !rest.starts_with('/') => rest.trim_start(),
+        _ => line,
+    }
+}
+
+/// The fence character and length at the start of `line`, if it opens or
+/// closes a code block.
+fn fence_run(line: &str) -> Option<(char, usize)> {
+    let c = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = line.len() - line.trim_start_matches(c).len();
+    (len >= 3).then_some((c, len))
+}
+
+/// Returns `true` if `line`, starting with the fence `run`, closes the block
+/// opened by `open`. The comment may end on the same line.
+fn closes(open: (char, usize), run: (char, usize), line: &str) -> bool {
+    let rest = line[run.1..].trim();
+    run.0 == open.0 && run.1 >= open.1 && (rest.is_empty() || rest.starts_with("*/"))
+}
+
+/// Collect the `.nix` files at `path`.
+///
+/// A file path is returned as-is regardless of its extension. Directories are
+/// walked recursively and every `.nix` file below them is returned, sorted by
+/// path so that output is deterministic.
+///
+/// # Errors
+///
+/// Returns any I/O error raised while reading `path` or its subdirectories.
+pub fn nix_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        // Surface a missing path as an error rather than an empty result.
+        fs::metadata(path)?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<_>>()?;
+    entries.sort();
+
+    let mut out = Vec::new();
+    for entry in entries {
+        if entry.is_dir() {
+            out.extend(nix_files(&entry)?);
+        } else if entry.extension().and_then(|e| e.to_str()) == Some("nix") {
+            out.push(entry);
+        }
+    }
+    Ok(out)
+}
+
+/// A problem with a whole source file that did not stop it being read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileWarning {
+    /// The file concerned.
+    pub file: PathBuf,
+    /// A human-readable description.
+    pub message: String,
+}
+
+/// Read a source file, decoding bytes that are not valid UTF-8 as
+/// Windows-1252.
+///
+/// Valid UTF-8 is kept as it is, so a mostly UTF-8 file with a few legacy
+/// bytes decodes sensibly, as does a Latin-1 file. Bytes that Windows-1252
+/// leaves undefined become U+FFFD. If any byte needed decoding, a
+/// [`FileWarning`] is returned alongside the text.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn read_lossy(path: &Path) -> io::Result<(String, Option<FileWarning>)> {
+    let bytes = fs::read(path)?;
+    let mut text = String::with_capacity(bytes.len());
+    let mut decoded = 0;
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        for &byte in chunk.invalid() {
+            text.push(windows_1252(byte));
+            decoded += 1;
+        }
+    }
+
+    let warning = (decoded > 0).then(|| FileWarning {
+        file: path.to_path_buf(),
+        message: format!("not valid UTF-8; decoded {decoded} byte(s) as Windows-1252"),
+    });
+    Ok((text, warning))
+}
+
+/// Decode a single byte that is not part of valid UTF-8 as Windows-1252.
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{fffd}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{fffd}', 'Ž',
+        '\u{fffd}', '\u{fffd}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ',
+        '\u{fffd}', 'ž', 'Ÿ',
+    ];
+    match byte {
+        0x80..=0x9f => HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+/// A doc comment together with what it documents and where it was found.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocumentedItem {
+    /// The attribute path of the binding following the comment, as written
+    /// (e.g. `concatStrings` or `strings.concatStrings`), if there is one.
+    pub name: Option<String>,
+    /// The file the comment was read from, if any.
+    pub file: Option<PathBuf>,
+    /// The span of the comment, delimiters included, within its source.
+    pub span: Span,
+    /// The 1-based line on which the comment starts.
+    pub line: usize,
+    /// The 1-based column, in characters, at which the comment starts.
+    pub column: usize,
+    /// The formal arguments of the function the comment documents, in the
+    /// order [`formals_after`] gives them.
+    ///
+    /// Only [`attach`] reads these from the syntax tree; the textual
+    /// extraction leaves them empty.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub formals: Vec<String>,
+    /// The parsed comment.
+    pub doc: DocComment,
+}
+
+impl DocumentedItem {
+    /// Returns where the comment came from.
+    pub fn provenance(&self) -> Provenance {
+        Provenance {
+            file: self.file.clone(),
+            byte_range: self.span,
+            line: self.line,
+            attr_path: self.name.clone(),
+        }
+    }
+
+    /// Compute the [view](DocComment::view) of the comment, with its
+    /// [provenance](Self::provenance).
+    pub fn view(&self) -> DocView {
+        DocView {
+            provenance: Some(self.provenance()),
+            ..self.doc.view()
+        }
+    }
+
+    /// Map a span of [`DocComment::raw_content`] to a span of `src`, the
+    /// source the item was extracted from.
+    ///
+    /// Spans reported against the parsed comment, such as those of
+    /// [`Diagnostic`](crate::Diagnostic)s, do not count the indentation
+    /// and delimiters that parsing removed. This finds each line of the raw
+    /// content in the source comment again. Text that cannot be found, for
+    /// instance because parsing removed invisible characters, maps to the
+    /// start of the comment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::Span;
+    /// use nixdoc::extract::documented_items;
+    ///
+    /// let src = "{\n  /**\n    Sort.\n\n    See <https://x.org>.\n  */\n  sort = x: x;\n}";
+    /// let item = &documented_items(src, None)[0];
+    /// let raw = item.doc.raw_content.find("https").unwrap();
+    /// let span = item.source_span(src, Span::new(raw, raw + 5));
+    /// assert_eq!(&src[span.start..span.end], "https");
+    /// ```
+    pub fn source_span(&self, src: &str, span: Span) -> Span {
+        Span::new(
+            self.source_offset(src, span.start),
+            self.source_offset(src, span.end),
+        )
+    }
+
+    fn source_offset(&self, src: &str, offset: usize) -> usize {
+        let raw = &self.doc.raw_content;
+        let Some(comment) = src.get(self.span.start..self.span.end) else {
+            return self.span.start;
+        };
+        let offset = offset.min(raw.len());
+        let target_line = raw[..offset].matches('\n').count();
+        let column = offset - raw[..offset].rfind('\n').map_or(0, |i| i + 1);
+
+        // Raw lines follow source lines one to one, starting with the first
+        // source line that contains the first raw line.
+        let mut source_lines = comment.split_inclusive('\n').scan(0, |pos, line| {
+            let start = *pos;
+            *pos += line.len();
+            Some((start, line.trim_end_matches(['\n', '\r'])))
+        });
+        for (i, raw_line) in raw.split('\n').enumerate() {
+            let locate = |(start, line): (usize, &str)| {
+                let line = line.trim_end().trim_end_matches("*/").trim_end();
+                let column = if line.ends_with(raw_line) {
+                    line.len() - raw_line.len()
+                } else {
+                    line.find(raw_line)?
+                };
+                Some(start + column)
+            };
+            let found = if i == 0 {
+                source_lines.find_map(locate)
+            } else {
+                source_lines.next().and_then(locate)
+            };
+            let Some(line_start) = found else {
+                return self.span.start;
+            };
+            if i == target_line {
+                return self.span.start + line_start + column.min(raw_line.len());
+            }
+        }
+        self.span.start
+    }
+}
+
+/// A doc comment in a source file that could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseFailure {
+    /// The file the comment was read from, if any.
+    pub file: Option<PathBuf>,
+    /// The span of the comment, delimiters included, within its source.
+    pub span: Span,
+    /// The 1-based line on which the comment starts.
+    pub line: usize,
+    /// Why the comment was rejected.
+    pub error: ParseError,
+    /// The part of the comment the error concerns, within its source, see
+    /// [`DocComment::parse_spanned`].
+    pub error_span: Span,
+}
+
+/// Find and parse every doc comment in a Nix source file.
+///
+/// Each comment is paired with the name of the binding directly after it,
+/// see [`binding_name_after`]. Comments that fail to parse are skipped; use
+/// [`parse_source`] to get them too.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::documented_items;
+///
+/// let src = "{\n  /** The identity. */\n  id = x: x;\n}";
+/// let items = documented_items(src, None);
+/// assert_eq!(items[0].name.as_deref(), Some("id"));
+/// assert_eq!(items[0].line, 2);
+/// ```
+pub fn documented_items(src: &str, file: Option<&Path>) -> Vec<DocumentedItem> {
+    parse_source(src, file)
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect()
+}
+
+/// Find and parse every doc comment in a Nix source file, keeping failures.
+///
+/// Returns one result per comment, in source order.
+pub fn parse_source(src: &str, file: Option<&Path>) -> Vec<Result<DocumentedItem, ParseFailure>> {
+    parse_source_with(src, file, &ParserOptions::default())
+}
+
+/// Find and parse every doc comment in a Nix source file with custom
+/// [`ParserOptions`], keeping failures.
+///
+/// With [`ParserOptions::legacy_comments`], plain `/* … */` comments are
+/// found and parsed as well, each carrying a
+/// [`WarningKind::LegacyComment`] warning.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::ParserOptions;
+/// use nixdoc::extract::parse_source_with;
+///
+/// let src = "{\n  /* Old style. */\n  old = 1;\n  /** New style. */\n  new = 2;\n}";
+/// let options = ParserOptions::new().with_legacy_comments(true);
+/// let items: Vec<_> = parse_source_with(src, None, &options)
+///     .into_iter()
+///     .map(|item| item.unwrap())
+///     .collect();
+/// assert_eq!(items.len(), 2);
+/// assert!(items[0].doc.is_legacy());
+/// assert!(!items[1].doc.is_legacy());
+/// ```
+pub fn parse_source_with(
+    src: &str,
+    file: Option<&Path>,
+    options: &ParserOptions,
+) -> Vec<Result<DocumentedItem, ParseFailure>> {
+    let comments = scan(src, false, options.legacy_open().is_some());
+    parse_comments(src, file, comments, options, |text| {
+        DocComment::parse_with(text, options)
+    })
+}
+
+/// Find and parse every doc comment in a Nix source file, recovering from a
+/// final comment missing its closing `*/`.
+///
+/// Like [`parse_source`], but uses [`extract_doc_comments_lossy`] and
+/// [`DocComment::parse_lossy`], so a half-typed comment at the end of the
+/// file still yields an item, carrying a
+/// [`WarningKind::UnclosedComment`](crate::WarningKind::UnclosedComment)
+/// warning.
+pub fn parse_source_lossy(
+    src: &str,
+    file: Option<&Path>,
+) -> Vec<Result<DocumentedItem, ParseFailure>> {
+    parse_comments(
+        src,
+        file,
+        scan(src, true, false),
+        &ParserOptions::default(),
+        DocComment::parse_lossy,
+    )
+}
+
+fn parse_comments(
+    src: &str,
+    file: Option<&Path>,
+    comments: Vec<Extracted>,
+    options: &ParserOptions,
+    parse: impl Fn(&str) -> Result<DocComment, ParseError>,
+) -> Vec<Result<DocumentedItem, ParseFailure>> {
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!("extract", file = ?file, comments = comments.len()).entered();
+    comments
+        .into_iter()
+        .map(|comment| {
+            let span = comment.span;
+            let (line, column) = span.line_col(src);
+            #[cfg(feature = "trace")]
+            let _span = tracing::trace_span!("comment", line, column).entered();
+            match parse(&comment.text) {
+                Ok(mut doc) => {
+                    if comment.ambiguous {
+                        doc.warnings.push(ParseWarning {
+                            kind: WarningKind::AmbiguousCommentEnd,
+                            message: "the comment ends at a `*/` inside a code block; \
+                                      use a longer fence to keep it in the example"
+                                .to_string(),
+                        });
+                    }
+                    Ok(DocumentedItem {
+                        name: binding_name_after(src, span.end),
+                        file: file.map(Path::to_path_buf),
+                        span,
+                        line,
+                        column,
+                        formals: Vec::new(),
+                        doc,
+                    })
+                }
+                Err(error) => {
+                    #[cfg(feature = "trace")]
+                    tracing::debug!(%error, "comment failed to parse");
+                    let at = parser::error_span(&comment.text, &error, options);
+                    Err(ParseFailure {
+                        file: file.map(Path::to_path_buf),
+                        span,
+                        line,
+                        error_span: Span::new(span.start + at.start, span.start + at.end),
+                        error,
+                    })
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns the attribute path bound directly after byte offset `offset`.
+///
+/// This is a lightweight textual heuristic: after skipping whitespace and
+/// `#` line comments, it accepts an attribute path (identifiers joined by
+/// `.`) followed by `=`. Anything else, such as a file-level comment before a
+/// function header, yields `None`.
+pub fn binding_name_after(src: &str, offset: usize) -> Option<String> {
+    let rest = skip_trivia(src.get(offset..)?);
+    let path_len = rest
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '\'' | '.')))
+        .unwrap_or(rest.len());
+    let path = &rest[..path_len];
+    let after = rest[path_len..].trim_start();
+
+    let is_binding = after.starts_with('=') && !after.starts_with("==");
+    let is_valid_path = !path.is_empty()
+        && path
+            .split('.')
+            .all(|part| part.starts_with(|c: char| c.is_alphabetic() || c == '_'));
+
+    (is_binding && is_valid_path).then(|| path.to_string())
+}
+
+/// Returns the formal arguments of the function bound directly after byte
+/// offset `offset`.
+///
+/// Like [`binding_name_after`], this is a textual heuristic. It reads the
+/// lambda heads after the binding's `=`: plain arguments such as `x:` and
+/// the attributes of attribute set patterns such as `{ a, b ? 1, ... }:`,
+/// in order. A binding that is not a function yields no arguments.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::formals_after;
+///
+/// let src = "/** f */\nf = sep: { list, strict ? false, ... }@args: list;";
+/// assert_eq!(formals_after(src, 8), ["sep", "list", "strict"]);
+/// assert!(formals_after("/** x */ x = 1;", 8).is_empty());
+/// ```
+pub fn formals_after(src: &str, offset: usize) -> Vec<String> {
+    let mut formals = Vec::new();
+    if binding_name_after(src, offset).is_none() {
+        return formals;
+    }
+    let rest = skip_trivia(&src[offset..]);
+    let Some((_, mut rest)) = rest.split_once('=') else {
+        return formals;
+    };
+
+    loop {
+        rest = skip_trivia(rest);
+        // An optional `name@` before a pattern.
+        let (name, after_name) = split_identifier(rest);
+        if let Some(pattern) = skip_trivia(after_name).strip_prefix('@') {
+            rest = skip_trivia(pattern);
+        }
+
+        if let Some(pattern) = rest.strip_prefix('{') {
+            let Some(end) = closing_brace(pattern) else {
+                break;
+            };
+            let mut after = skip_trivia(&pattern[end + 1..]);
+            // An optional `@name` after a pattern.
+            if let Some(alias) = after.strip_prefix('@') {
+                after = skip_trivia(split_identifier(skip_trivia(alias)).1);
+            }
+            let Some(body) = lambda_body(after) else {
+                break;
+            };
+            formals.extend(pattern_attributes(&pattern[..end]));
+            rest = body;
+        } else if !name.is_empty() {
+            let Some(body) = lambda_body(after_name) else {
+                break;
+            };
+            formals.push(name.to_string());
+            rest = body;
+        } else {
+            break;
+        }
+    }
+    formals
+}
+
+/// Skip whitespace and `#` line comments.
+fn skip_trivia(mut rest: &str) -> &str {
+    rest = rest.trim_start();
+    while let Some(comment) = rest.strip_prefix('#') {
+        rest = comment
+            .split_once('\n')
+            .map_or("", |(_, next)| next)
+            .trim_start();
+    }
+    rest
+}
+
+/// Split a leading Nix identifier off `text`.
+fn split_identifier(text: &str) -> (&str, &str) {
+    if !text.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        return ("", text);
+    }
+    let len = text
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '\'')))
+        .unwrap_or(text.len());
+    text.split_at(len)
+}
+
+/// Returns the text after a lambda's `:`, if `rest` starts with one.
+fn lambda_body(rest: &str) -> Option<&str> {
+    let rest = skip_trivia(rest);
+    // `x:y` without a space is a URI in Nix, but `x: y` and `x:\n` are not.
+    let body = rest.strip_prefix(':')?;
+    body.starts_with(char::is_whitespace).then_some(body)
+}
+
+/// Returns the offset of the `}` closing a pattern whose `{` was just
+/// consumed, skipping nested brackets and strings in default values.
+fn closing_brace(pattern: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut chars = pattern.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '{' | '[' | '(' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' | ']' | ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The attribute names of a pattern's body, without `...` or defaults.
+fn pattern_attributes(pattern: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut expect_name = true;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut rest = pattern;
+    while !rest.is_empty() {
+        if expect_name && depth == 0 && !in_string {
+            let (name, after) = split_identifier(skip_trivia(rest));
+            if !name.is_empty() {
+                names.push(name.to_string());
+            }
+            expect_name = false;
+            rest = after;
+            continue;
+        }
+        let mut chars = rest.chars();
+        let c = chars.next().unwrap_or_default();
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => expect_name = true,
+            _ => {}
+        }
+        rest = chars.as_str();
+    }
+    names
+}
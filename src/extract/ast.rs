@@ -0,0 +1,162 @@
+//! Attaching doc comments to the bindings they document, using the rnix
+//! parser.
+//!
+//! Enabled by the `rnix` feature.
+
+use std::path::Path;
+
+use rnix::types::{Ident, KeyValue, Lambda, Pattern, TokenWrapper, TypedNode};
+use rnix::{NodeOrToken, SyntaxKind, SyntaxNode, SyntaxToken};
+
+use super::{DocumentedItem, Extracted, parse_comments};
+use crate::{DocComment, ParserOptions, Span};
+
+/// Parse the doc comments of a Nix source file and attach each one to the
+/// syntax node it documents, discarding comments that fail to parse.
+///
+/// Unlike [`documented_items`](super::documented_items), this parses `src`
+/// as Nix, so it is not misled by strings or unusual layout, and it knows
+/// the structure around each binding:
+///
+/// - an item's [`name`](DocumentedItem::name) is the full attribute path of
+///   its binding, qualified by the bindings of the attribute sets it is
+///   nested in, so `concat` inside `strings = { … };` is `strings.concat`;
+/// - an item's [`formals`](DocumentedItem::formals) are the arguments of the
+///   function it documents, whether it is bound to a name or is the
+///   function of the whole file.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::attach;
+///
+/// let src = "{\n  strings = {\n    /** Join. */\n    concat = { sep ? \"\", list }: list;\n  };\n}";
+/// let items = attach(src, None);
+/// assert_eq!(items[0].name.as_deref(), Some("strings.concat"));
+/// assert_eq!(items[0].formals, ["sep", "list"]);
+/// ```
+pub fn attach(src: &str, file: Option<&Path>) -> Vec<DocumentedItem> {
+    let root = rnix::parse(src).node();
+    let mut comments = Vec::new();
+    let mut targets = Vec::new();
+    for token in root
+        .descendants_with_tokens()
+        .filter_map(NodeOrToken::into_token)
+    {
+        let text = token.text();
+        if token.kind() != SyntaxKind::TOKEN_COMMENT || !text.starts_with("/**") || text == "/**/" {
+            continue;
+        }
+        let range = token.text_range();
+        comments.push(Extracted {
+            span: Span::new(range.start().into(), range.end().into()),
+            text: text.to_string(),
+            ambiguous: false,
+        });
+        targets.push(documented_node(&token));
+    }
+
+    parse_comments(
+        src,
+        file,
+        comments,
+        &ParserOptions::default(),
+        DocComment::parse,
+    )
+    .into_iter()
+    .zip(targets)
+    .filter_map(|(result, target)| {
+        let mut item = result.ok()?;
+        item.name = target.as_ref().and_then(attr_path);
+        item.formals = target.as_ref().map(formals).unwrap_or_default();
+        Some(item)
+    })
+    .collect()
+}
+
+/// The node following `comment`, skipping whitespace and other comments.
+fn documented_node(comment: &SyntaxToken) -> Option<SyntaxNode> {
+    let mut next = comment.next_sibling_or_token();
+    while let Some(element) = next {
+        match element {
+            NodeOrToken::Node(node) => return Some(node),
+            NodeOrToken::Token(token) if token.kind().is_trivia() => {
+                next = token.next_sibling_or_token();
+            }
+            NodeOrToken::Token(_) => return None,
+        }
+    }
+    None
+}
+
+/// The attribute path of `node` if it is a binding, qualified by the
+/// bindings of the attribute sets around it.
+fn attr_path(node: &SyntaxNode) -> Option<String> {
+    let mut binding = KeyValue::cast(node.clone())?;
+    let mut path = vec![key(&binding)?];
+    while let Some(outer) = enclosing_binding(&binding) {
+        path.push(key(&outer)?);
+        binding = outer;
+    }
+    path.reverse();
+    Some(path.join("."))
+}
+
+/// The binding whose value is the attribute set `binding` is in, looking
+/// through parentheses. Bindings of a `let` have none.
+fn enclosing_binding(binding: &KeyValue) -> Option<KeyValue> {
+    let mut value = binding
+        .node()
+        .parent()
+        .filter(|parent| parent.kind() == SyntaxKind::NODE_ATTR_SET)?;
+    loop {
+        let parent = value.parent()?;
+        if parent.kind() != SyntaxKind::NODE_PAREN {
+            return KeyValue::cast(parent);
+        }
+        value = parent;
+    }
+}
+
+/// The key of `binding`, as written, without whitespace or comments.
+fn key(binding: &KeyValue) -> Option<String> {
+    let parts: Vec<String> = binding
+        .key()?
+        .path()
+        .map(|part| part.text().to_string())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("."))
+}
+
+/// The formal arguments of the function bound by `node`, or of `node`
+/// itself if it is a function.
+fn formals(node: &SyntaxNode) -> Vec<String> {
+    let mut value = match KeyValue::cast(node.clone()) {
+        Some(binding) => binding.value(),
+        None => Some(node.clone()),
+    };
+    let mut formals = Vec::new();
+    while let Some(node) = value {
+        if node.kind() == SyntaxKind::NODE_PAREN {
+            value = node.first_child();
+            continue;
+        }
+        let Some(lambda) = Lambda::cast(node) else {
+            break;
+        };
+        if let Some(arg) = lambda.arg() {
+            if let Some(ident) = Ident::cast(arg.clone()) {
+                formals.push(ident.as_str().to_string());
+            } else if let Some(pattern) = Pattern::cast(arg) {
+                formals.extend(
+                    pattern
+                        .entries()
+                        .filter_map(|entry| entry.name())
+                        .map(|ident| ident.as_str().to_string()),
+                );
+            }
+        }
+        value = lambda.body();
+    }
+    formals
+}
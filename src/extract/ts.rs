@@ -0,0 +1,121 @@
+//! Extraction from an existing tree-sitter parse tree.
+//!
+//! Enabled by the `ts` feature.
+//!
+//! Editors that ship the tree-sitter-nix grammar already hold a parse tree
+//! of the open file. [`documented_items`] reads the doc comments from that
+//! tree instead of scanning the text again, and names each one after the
+//! `binding` that follows it, as the grammar sees it. The items are the
+//! same [`DocumentedItem`]s the textual extraction produces.
+//!
+//! This crate does not depend on a grammar: parse with whichever
+//! tree-sitter-nix version the editor uses.
+//!
+//! ```ignore
+//! let mut parser = tree_sitter::Parser::new();
+//! parser.set_language(&tree_sitter_nix::LANGUAGE.into())?;
+//! let tree = parser.parse(src, None).unwrap();
+//! let items = nixdoc::extract::ts::documented_items(&tree, src, None);
+//! ```
+
+use std::path::Path;
+
+use tree_sitter::{Node, Tree};
+
+use super::{DocumentedItem, Extracted, ParseFailure, parse_comments};
+use crate::{DocComment, ParserOptions, Span};
+
+/// Parse the doc comments of `tree`, a parse of `src`, discarding those that
+/// fail to parse.
+///
+/// See [`parse_tree`] to keep the failures.
+pub fn documented_items(tree: &Tree, src: &str, file: Option<&Path>) -> Vec<DocumentedItem> {
+    parse_tree(tree, src, file, &ParserOptions::default())
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect()
+}
+
+/// Parse the doc comments of `tree`, a parse of `src`, keeping failures.
+///
+/// Every `comment` node starting with `/**`, or with `/*` if `options`
+/// accepts [legacy comments](ParserOptions::legacy_comments), is parsed. An
+/// item's name is the `attrpath` of the `binding` node right after the
+/// comment, or of the first binding of the `binding_set` right after it, if
+/// there is one.
+pub fn parse_tree(
+    tree: &Tree,
+    src: &str,
+    file: Option<&Path>,
+    options: &ParserOptions,
+) -> Vec<Result<DocumentedItem, ParseFailure>> {
+    let open = options.legacy_open().unwrap_or("/**");
+    let mut comments = Vec::new();
+    let mut names = Vec::new();
+    let mut cursor = tree.walk();
+    'walk: loop {
+        let node = cursor.node();
+        if node.kind() == "comment"
+            && let Some(text) = src.get(node.byte_range())
+            && text.starts_with(open)
+            && text != "/**/"
+        {
+            comments.push(Extracted {
+                span: Span::new(node.start_byte(), node.end_byte()),
+                text: text.to_string(),
+                ambiguous: false,
+            });
+            names.push(binding_name(node, src));
+        }
+
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+
+    let mut results = parse_comments(src, file, comments, options, |text| {
+        DocComment::parse_with(text, options)
+    });
+    for (result, name) in results.iter_mut().zip(names) {
+        if let Ok(item) = result {
+            item.name = name;
+        }
+    }
+    results
+}
+
+/// The attribute path of the binding right after `comment`, with
+/// whitespace and comments inside it removed.
+fn binding_name(comment: Node<'_>, src: &str) -> Option<String> {
+    let mut next = comment.next_named_sibling()?;
+    while next.kind() == "comment" {
+        next = next.next_named_sibling()?;
+    }
+    // A comment before the first binding of an attribute set or `let` is
+    // outside the `binding_set` that holds the bindings.
+    if next.kind() == "binding_set" {
+        let mut cursor = next.walk();
+        next = next
+            .named_children(&mut cursor)
+            .find(|child| child.kind() != "comment")?;
+    }
+    if next.kind() != "binding" {
+        return None;
+    }
+    let attrpath = next.child_by_field_name("attrpath")?;
+    let mut cursor = attrpath.walk();
+    let parts: Vec<&str> = attrpath
+        .named_children(&mut cursor)
+        .filter(|part| part.kind() != "comment")
+        .filter_map(|part| src.get(part.byte_range()))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("."))
+}
@@ -1,3 +1,12 @@
+//! A C interface to the parser, enabled by the `ffi` feature.
+//!
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib,staticlib`
+//! builds the shared and static libraries. [cargo-c](https://github.com/lu-zero/cargo-c) also generates
+//! the `nixdoc.h` header and a pkg-config file:
+//! `cargo cinstall --release --features capi --prefix /usr`. Strings
+//! returned by the library are owned by the caller and released with
+//! [`nixdoc_free_string`] or [`nixdoc_free_string_array`].
+
 #![allow(unsafe_op_in_unsafe_fn)]
 
 use std::ffi::CString;
@@ -7,11 +16,16 @@ use std::ptr;
 use std::slice;
 
 use crate::DocComment;
+use crate::query::Value;
 
-const NIXDOC_SUCCESS: c_int = 0;
-const NIXDOC_ERROR_PARSE: c_int = 1;
-const NIXDOC_ERROR_NULL: c_int = 2;
-const NIXDOC_ERROR_PANIC: c_int = 3;
+/// The call succeeded.
+pub const NIXDOC_SUCCESS: c_int = 0;
+/// The input is not a valid doc comment.
+pub const NIXDOC_ERROR_PARSE: c_int = 1;
+/// A required pointer argument was null.
+pub const NIXDOC_ERROR_NULL: c_int = 2;
+/// The library panicked; the call had no effect.
+pub const NIXDOC_ERROR_PANIC: c_int = 3;
 
 #[repr(C)]
 pub struct NixdocDocComment {
@@ -380,6 +394,67 @@ pub unsafe extern "C" fn nixdoc_warnings(doc: *const NixdocDocComment) -> *mut N
     result.unwrap_or(ptr::null_mut())
 }
 
+/// Evaluates a query path such as `sections[kind=arguments].items[0].name`
+/// against a parsed doc comment.
+///
+/// Returns the selected values as a JSON array, or null if the path is
+/// malformed. See `DocComment::query` for the path language.
+///
+/// # Safety
+///
+/// `doc` must be a valid pointer returned by `nixdoc_parse_into`, and `path`
+/// a valid, null-terminated C string. The returned string must be freed with
+/// `nixdoc_free_string`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nixdoc_query(
+    doc: *const NixdocDocComment,
+    path: *const c_char,
+) -> *mut c_char {
+    if doc.is_null() || path.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = catch_unwind(|| {
+        let doc = &*(doc as *const DocComment);
+        let path = std::ffi::CStr::from_ptr(path).to_string_lossy();
+        doc.query(&path)
+            .map(|values| rust_string_to_cstring(&Value::Array(values).to_json()))
+            .unwrap_or(ptr::null_mut())
+    });
+
+    result.unwrap_or(ptr::null_mut())
+}
+
+/// Gets the field at a JSON Pointer (RFC 6901), such as
+/// `/sections/1/content`, into the serialized form of a parsed doc comment.
+///
+/// Returns the field as JSON, or null if the pointer selects nothing.
+///
+/// # Safety
+///
+/// `doc` must be a valid pointer returned by `nixdoc_parse_into`, and
+/// `pointer` a valid, null-terminated C string. The returned string must be
+/// freed with `nixdoc_free_string`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nixdoc_pointer(
+    doc: *const NixdocDocComment,
+    pointer: *const c_char,
+) -> *mut c_char {
+    if doc.is_null() || pointer.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = catch_unwind(|| {
+        let doc = &*(doc as *const DocComment);
+        let pointer = std::ffi::CStr::from_ptr(pointer).to_string_lossy();
+        doc.pointer(&pointer)
+            .map(|value| rust_string_to_cstring(&value.to_json()))
+            .unwrap_or(ptr::null_mut())
+    });
+
+    result.unwrap_or(ptr::null_mut())
+}
+
 /// Frees a C string returned by any string-returning function.
 ///
 /// # Safety
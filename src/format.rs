@@ -0,0 +1,287 @@
+//! Formatting doc comments in source files.
+//!
+//! [`format_comment`] rewrites one comment in a canonical layout: the
+//! delimiters on their own lines, the content indented one level deeper
+//! than the line the comment starts on, one blank line around headings, and
+//! no trailing whitespace or runs of blank lines outside code blocks.
+//! [`format_range`] applies it to the comments in part of a file and returns
+//! the edits, as an editor's "format selection" or format-on-save needs.
+
+use crate::lsp::Range;
+use crate::parser::{LineRole, fence_lines};
+use crate::{DocComment, Span, extract, wrap};
+
+/// Layout options for [`format_comment`] and [`format_range`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Style {
+    /// Spaces of indentation for the content, relative to the line the
+    /// comment starts on. Defaults to 2.
+    pub indent: usize,
+    /// Keep a comment whose content is a single line on one line, as in
+    /// `/** The identity. */`. Defaults to `true`.
+    pub keep_single_line: bool,
+    /// Reflow prose to lines of at most this many columns, indentation
+    /// included, with [`wrap`](crate::wrap::wrap). Defaults to `None`,
+    /// which keeps lines as they are.
+    pub width: Option<usize>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            keep_single_line: true,
+            width: None,
+        }
+    }
+}
+
+impl Style {
+    /// Create the default style.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the content indentation.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Set whether single-line comments stay on one line.
+    pub fn with_keep_single_line(mut self, keep: bool) -> Self {
+        self.keep_single_line = keep;
+        self
+    }
+
+    /// Set the column to reflow prose at.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+}
+
+/// A replacement of part of a source file, as in LSP `TextEdit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextEdit {
+    /// The replaced text, as a byte span.
+    pub span: Span,
+    /// The replaced text, as an LSP range.
+    pub range: Range,
+    /// The replacement.
+    pub new_text: String,
+}
+
+impl TextEdit {
+    /// Create an edit replacing `span` of `source` with `new_text`.
+    pub fn new(source: &str, span: Span, new_text: impl Into<String>) -> Self {
+        Self {
+            span,
+            range: Range::of_span(source, span),
+            new_text: new_text.into(),
+        }
+    }
+}
+
+/// Apply non-overlapping `edits` to `source`.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::format::{TextEdit, apply_edits};
+/// use nixdoc::Span;
+///
+/// let src = "a b c";
+/// let edits = [TextEdit::new(src, Span::new(4, 5), "d"), TextEdit::new(src, Span::new(0, 1), "z")];
+/// assert_eq!(apply_edits(src, &edits), "z b d");
+/// ```
+pub fn apply_edits(source: &str, edits: &[TextEdit]) -> String {
+    let mut edits: Vec<&TextEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| edit.span.start);
+    let mut out = String::with_capacity(source.len());
+    let mut pos = 0;
+    for edit in edits {
+        out.push_str(&source[pos..edit.span.start]);
+        out.push_str(&edit.new_text);
+        pos = edit.span.end;
+    }
+    out.push_str(&source[pos..]);
+    out
+}
+
+/// Format one doc comment, `/**` and `*/` included.
+///
+/// `indent` is the indentation of the line the comment starts on, used for
+/// its continuation lines. Returns `None` if `comment` does not parse.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::format::{Style, format_comment};
+///
+/// let messy = "/**   Sort a list.   \n\n\n   # Type\n   ```\n   sort :: [a] -> [a]\n   ```*/";
+/// assert_eq!(
+///     format_comment(messy, "  ", &Style::new()).unwrap(),
+///     "/**\n    Sort a list.\n\n    # Type\n\n    ```\n    sort :: [a] -> [a]\n    ```\n  */"
+/// );
+/// assert_eq!(
+///     format_comment("/**   Sort.  */", "", &Style::new()).unwrap(),
+///     "/** Sort. */"
+/// );
+/// ```
+pub fn format_comment(comment: &str, indent: &str, style: &Style) -> Option<String> {
+    let doc = DocComment::parse(comment).ok()?;
+    let content_indent = format!("{indent}{}", " ".repeat(style.indent));
+    let lines = match style.width {
+        Some(width) => {
+            let width = width.saturating_sub(content_indent.chars().count());
+            layout(&wrap::wrap(&doc.raw_content, width))
+        }
+        None => layout(&doc.raw_content),
+    };
+
+    if style.keep_single_line && lines.len() == 1 && !comment.trim().contains('\n') {
+        return Some(format!("/** {} */", lines[0]));
+    }
+
+    let mut out = String::from("/**\n");
+    for line in lines {
+        if !line.is_empty() {
+            out.push_str(&content_indent);
+            out.push_str(&line);
+        }
+        out.push('\n');
+    }
+    out.push_str(indent);
+    out.push_str("*/");
+    Some(out)
+}
+
+/// Format the doc comments in `source` on the lines `range` touches.
+///
+/// Returns one edit per comment that changes, covering only the part of the
+/// comment that differs, in source order. Comments that do not parse are
+/// left alone.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::format::{Style, apply_edits, format_range};
+/// use nixdoc::lsp::{Position, Range};
+///
+/// let src = "{\n  /**   Id.  */\n  id = x: x;\n  /**  Const.*/\n  const = x: y: x;\n}";
+/// let first_line = Range {
+///     start: Position { line: 1, character: 0 },
+///     end: Position { line: 1, character: 0 },
+/// };
+/// let edits = format_range(src, first_line, &Style::new());
+/// assert_eq!(edits.len(), 1);
+/// assert_eq!(
+///     apply_edits(src, &edits),
+///     "{\n  /** Id. */\n  id = x: x;\n  /**  Const.*/\n  const = x: y: x;\n}"
+/// );
+/// ```
+pub fn format_range(source: &str, range: Range, style: &Style) -> Vec<TextEdit> {
+    let Some(selection) = range.to_span(source) else {
+        return Vec::new();
+    };
+    // Widen the selection to whole lines.
+    let start = source[..selection.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[selection.end..]
+        .find('\n')
+        .map_or(source.len(), |i| selection.end + i);
+
+    extract::extract_doc_comments(source)
+        .into_iter()
+        .filter(|(span, _)| span.start <= end && start <= span.end)
+        .filter_map(|(span, comment)| {
+            let formatted = format_comment(&comment, indent_at(source, span), style)?;
+            minimal_edit(source, span, &comment, &formatted)
+        })
+        .collect()
+}
+
+/// An edit replacing the comment at `span` of `source` with `doc`, written
+/// in `style`, or `None` if nothing changes.
+pub(crate) fn replace_comment(
+    source: &str,
+    span: Span,
+    doc: &DocComment,
+    style: &Style,
+) -> Option<TextEdit> {
+    let old = source.get(span.start..span.end)?;
+    let comment = format!("/**\n{}\n*/", doc.raw_content);
+    let formatted = format_comment(&comment, indent_at(source, span), style)?;
+    minimal_edit(source, span, old, &formatted)
+}
+
+/// The indentation of the line on which `span` starts.
+fn indent_at(source: &str, span: Span) -> &str {
+    let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line = &source[line_start..span.start];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// The canonical lines of normalized comment content.
+fn layout(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let is_heading = |line: &str| line.starts_with("# ");
+
+    for (_, line, role) in fence_lines(content) {
+        let line = line.trim_end();
+        if matches!(role, LineRole::Code | LineRole::ClosingFence) {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let last = lines.last().map(String::as_str);
+        if line.is_empty() {
+            if last.is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+            continue;
+        }
+        // One blank line before and after every heading.
+        let needs_blank = if is_heading(line) {
+            last.is_some_and(|last| !last.is_empty())
+        } else {
+            last.is_some_and(is_heading)
+        };
+        if needs_blank {
+            lines.push(String::new());
+        }
+        lines.push(line.to_string());
+    }
+
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines
+}
+
+/// An edit turning `old`, found at `span`, into `new`, trimmed to the part
+/// that differs. Returns `None` if they are equal.
+fn minimal_edit(source: &str, span: Span, old: &str, new: &str) -> Option<TextEdit> {
+    if old == new {
+        return None;
+    }
+    let prefix = old
+        .char_indices()
+        .zip(new.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(old.len().min(new.len()), |((i, _), _)| i);
+    let suffix = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum::<usize>();
+
+    Some(TextEdit::new(
+        source,
+        Span::new(span.start + prefix, span.end - suffix),
+        &new[prefix..new.len() - suffix],
+    ))
+}
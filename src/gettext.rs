@@ -0,0 +1,429 @@
+//! Translating documentation with gettext.
+//!
+//! [`to_pot`] collects the translatable text of a [`DocSet`] into a POT
+//! template: each paragraph and subheading of the comments becomes a
+//! message, while code blocks, section headings, and sections meant for
+//! tools, such as `# Type` and `# Meta`, stay as they are. Translators fill
+//! in a PO file from the template with the usual tools, and a [`Catalog`]
+//! read from it translates comments before they are rendered:
+//!
+//! ```
+//! use nixdoc::DocSet;
+//! use nixdoc::gettext::{Catalog, to_pot};
+//!
+//! let mut set = DocSet::new();
+//! set.add_source("/** The identity. */\nid = x: x;", Some("trivial.nix".as_ref()));
+//! assert!(to_pot(&set).contains("#: trivial.nix:1\nmsgid \"The identity.\"\n"));
+//!
+//! let catalog = Catalog::parse("msgid \"The identity.\"\nmsgstr \"L'identité.\"\n").unwrap();
+//! let french = catalog.translate_set(&set);
+//! assert_eq!(french.items()[0].doc.description(), "L'identité.");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use thiserror::Error;
+
+use crate::parser::{
+    is_closing_fence, parse_fence_open, section_at, section_headings, split_explicit_id,
+    trim_heading,
+};
+use crate::{DocComment, DocSet, SectionKind, WarningKind};
+
+/// Write the POT template of `set`.
+///
+/// Messages are listed once, in order of first appearance, with a
+/// reference to every comment that contains them and the names those
+/// comments document.
+pub fn to_pot(set: &DocSet) -> String {
+    let mut order: Vec<String> = Vec::new();
+    let mut seen: BTreeMap<String, (Vec<String>, Vec<&str>)> = BTreeMap::new();
+    for item in set {
+        let content = item.doc.raw_content.as_str();
+        for range in message_ranges(content) {
+            let message = normalize(&content[range]);
+            let (references, names) = seen.entry(message.clone()).or_insert_with(|| {
+                order.push(message);
+                Default::default()
+            });
+            if let Some(file) = &item.file {
+                let reference = format!("{}:{}", file.display(), item.line);
+                if !references.contains(&reference) {
+                    references.push(reference);
+                }
+            }
+            if let Some(name) = item.name.as_deref()
+                && !names.contains(&name)
+            {
+                names.push(name);
+            }
+        }
+    }
+
+    let mut out = String::from(
+        "msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n\
+         \"Content-Transfer-Encoding: 8bit\\n\"\n",
+    );
+    for message in order {
+        let (references, names) = &seen[&message];
+        out.push('\n');
+        if !names.is_empty() {
+            let _ = writeln!(out, "#. {}", names.join(", "));
+        }
+        for reference in references {
+            let _ = writeln!(out, "#: {reference}");
+        }
+        out.push_str("msgid ");
+        write_string(&mut out, &message);
+        out.push_str("msgstr \"\"\n");
+    }
+    out
+}
+
+/// The translatable messages of `doc`, in order.
+///
+/// The lines of a paragraph are unindented, so the message does not depend
+/// on how the comment was laid out.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+/// use nixdoc::gettext::messages;
+///
+/// let doc = DocComment::parse(
+///     "/**\n  Add numbers.\n\n  # Examples\n\n  ## Small\n\n  ```nix\n  add 1 2\n  ```\n*/",
+/// )
+/// .unwrap();
+/// assert_eq!(messages(&doc), ["Add numbers.", "Small"]);
+/// ```
+pub fn messages(doc: &DocComment) -> Vec<String> {
+    let content = doc.raw_content.as_str();
+    message_ranges(content)
+        .into_iter()
+        .map(|range| normalize(&content[range]))
+        .collect()
+}
+
+/// Translations read from a PO file.
+///
+/// Entries without a translation and entries marked `fuzzy` are left out,
+/// so their messages keep the original text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    translations: BTreeMap<String, String>,
+}
+
+impl Catalog {
+    /// Create an empty catalog, which translates nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a PO file.
+    ///
+    /// Plural entries are ignored; doc comments have none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for a line that is neither a comment, a keyword
+    /// followed by a string, nor a continuation string.
+    pub fn parse(po: &str) -> Result<Self, PoError> {
+        let mut catalog = Self::new();
+        let mut entry = PoEntry::default();
+        // The field continuation strings append to.
+        let mut field: Option<&'static str> = None;
+
+        for (index, line) in po.lines().enumerate() {
+            let number = index + 1;
+            let error = |message: &str| PoError {
+                line: number,
+                message: message.to_string(),
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(flags) = line.strip_prefix("#,") {
+                // Flags come before the entry they belong to.
+                catalog.finish(std::mem::take(&mut entry));
+                field = None;
+                entry.fuzzy = flags.split(',').any(|flag| flag.trim() == "fuzzy");
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('"') {
+                let text = unquote(line).ok_or_else(|| error("malformed string"))?;
+                match field {
+                    Some("msgid") => entry.msgid.push_str(&text),
+                    Some("msgstr") => entry.msgstr.push_str(&text),
+                    Some(_) => {}
+                    None => return Err(error("string outside an entry")),
+                }
+                continue;
+            }
+
+            let (keyword, rest) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| error("expected a keyword and a string"))?;
+            let text = unquote(rest.trim()).ok_or_else(|| error("malformed string"))?;
+            field = Some(match keyword {
+                "msgctxt" => {
+                    if entry.started {
+                        catalog.finish(std::mem::take(&mut entry));
+                    }
+                    entry.started = true;
+                    "msgctxt"
+                }
+                "msgid" => {
+                    if entry.has_msgid {
+                        catalog.finish(std::mem::take(&mut entry));
+                    }
+                    entry.started = true;
+                    entry.has_msgid = true;
+                    entry.msgid = text;
+                    "msgid"
+                }
+                "msgstr" | "msgstr[0]" if entry.has_msgid => {
+                    entry.msgstr = text;
+                    "msgstr"
+                }
+                "msgid_plural" => {
+                    entry.plural = true;
+                    "msgid_plural"
+                }
+                keyword if keyword.starts_with("msgstr[") => "msgstr_plural",
+                "msgstr" => return Err(error("msgstr without msgid")),
+                _ => return Err(error("unknown keyword")),
+            });
+        }
+        catalog.finish(entry);
+        Ok(catalog)
+    }
+
+    /// Add an entry read by [`Self::parse`] if it translates a message.
+    fn finish(&mut self, entry: PoEntry) {
+        if entry.has_msgid
+            && !entry.msgid.is_empty()
+            && !entry.msgstr.is_empty()
+            && !entry.fuzzy
+            && !entry.plural
+        {
+            self.translations.insert(entry.msgid, entry.msgstr);
+        }
+    }
+
+    /// Add a translation.
+    pub fn insert(&mut self, message: impl Into<String>, translation: impl Into<String>) {
+        self.translations.insert(message.into(), translation.into());
+    }
+
+    /// Returns the translation of `message`, if there is one.
+    pub fn get(&self, message: &str) -> Option<&str> {
+        self.translations.get(message).map(String::as_str)
+    }
+
+    /// Returns the number of translated messages.
+    pub fn len(&self) -> usize {
+        self.translations.len()
+    }
+
+    /// Returns `true` if the catalog translates nothing.
+    pub fn is_empty(&self) -> bool {
+        self.translations.is_empty()
+    }
+
+    /// Translate the messages of `doc` and parse the result again.
+    ///
+    /// Messages without a translation keep their text. If the translated
+    /// comment does not parse, for instance because the translations leave
+    /// it empty, `doc` is returned unchanged.
+    pub fn translate(&self, doc: &DocComment) -> DocComment {
+        let content = &doc.raw_content;
+        let mut translated = String::with_capacity(content.len());
+        let mut last = 0;
+        for range in message_ranges(content) {
+            if let Some(translation) = self.get(&normalize(&content[range.clone()])) {
+                translated.push_str(&content[last..range.start]);
+                translated.push_str(translation);
+                last = range.end;
+            }
+        }
+        if last == 0 {
+            return doc.clone();
+        }
+        translated.push_str(&content[last..]);
+
+        let Ok(mut parsed) = DocComment::parse(&format!("/**\n{translated}\n*/")) else {
+            return doc.clone();
+        };
+        // Keep telling plain comments apart, as renderers do.
+        parsed.warnings.extend(
+            doc.warnings
+                .iter()
+                .filter(|w| w.kind == WarningKind::LegacyComment)
+                .cloned(),
+        );
+        parsed
+    }
+
+    /// Returns a copy of `set` with every comment translated by
+    /// [`Self::translate`].
+    pub fn translate_set(&self, set: &DocSet) -> DocSet {
+        let mut set = set.clone();
+        for item in set.items_mut() {
+            item.doc = self.translate(&item.doc);
+        }
+        set
+    }
+}
+
+/// A malformed PO file.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid PO file at line {line}: {message}")]
+pub struct PoError {
+    /// The 1-based line the problem was found on.
+    pub line: usize,
+    /// What is wrong.
+    pub message: String,
+}
+
+#[derive(Default)]
+struct PoEntry {
+    started: bool,
+    has_msgid: bool,
+    msgid: String,
+    msgstr: String,
+    fuzzy: bool,
+    plural: bool,
+}
+
+/// Sections whose content is for tools, or is names and versions, rather
+/// than prose to translate.
+fn is_translated(kind: &SectionKind) -> bool {
+    !matches!(
+        kind,
+        SectionKind::Type
+            | SectionKind::Meta
+            | SectionKind::Since
+            | SectionKind::Maintainers
+            | SectionKind::Authors
+            | SectionKind::Tags
+            | SectionKind::Stability
+    )
+}
+
+/// The byte ranges of the messages of `content`, a comment's raw content.
+///
+/// A message is a paragraph, or the text of a heading below the section
+/// headings. Code blocks, `:::` fences, and section headings, which name
+/// sections and their anchors, are not messages.
+fn message_ranges(content: &str) -> Vec<Range<usize>> {
+    let headings = section_headings(content);
+    let translated = |offset: usize| {
+        section_at(&headings, offset).is_none_or(|h| is_translated(&SectionKind::from_heading(h)))
+    };
+
+    let mut ranges = Vec::new();
+    let mut paragraph: Option<Range<usize>> = None;
+    let mut fence: Option<(char, usize)> = None;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let text = line.trim_end_matches(['\n', '\r']);
+        let end = start + text.len();
+        let trimmed = text.trim_start();
+
+        if let Some((fence_char, fence_len)) = fence {
+            if is_closing_fence(trimmed, fence_char, fence_len) {
+                fence = None;
+            }
+            continue;
+        }
+
+        let opens_fence = parse_fence_open(trimmed);
+        let hashes = trimmed.len() - trimmed.trim_start_matches('#').len();
+        let is_heading = (1..=6).contains(&hashes)
+            && (trimmed.len() == hashes || trimmed[hashes..].starts_with([' ', '\t']));
+        if opens_fence.is_some() || is_heading || trimmed.is_empty() || trimmed.starts_with(":::") {
+            ranges.extend(paragraph.take().filter(|r| translated(r.start)));
+            if let Some((fence_char, fence_len, _)) = opens_fence {
+                fence = Some((fence_char, fence_len));
+            }
+            if is_heading && hashes > 1 && translated(start) {
+                // Leave the marks and an explicit `{#id}` out.
+                let (heading, _) = trim_heading(trimmed[hashes..].trim());
+                let heading = split_explicit_id(heading).0.trim_end();
+                if !heading.is_empty() {
+                    let at = start + (heading.as_ptr() as usize - text.as_ptr() as usize);
+                    ranges.push(at..at + heading.len());
+                }
+            }
+            continue;
+        }
+
+        let line_start = start + (text.len() - trimmed.len());
+        match &mut paragraph {
+            Some(range) => range.end = end,
+            None => paragraph = Some(line_start..end),
+        }
+    }
+    ranges.extend(paragraph.filter(|r| translated(r.start)));
+    ranges
+}
+
+/// The message of a paragraph: its lines without indentation.
+fn normalize(paragraph: &str) -> String {
+    paragraph
+        .lines()
+        .map(str::trim_start)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Append `text` as a PO string, split after newlines as gettext does.
+fn write_string(out: &mut String, text: &str) {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    if lines.len() > 1 {
+        out.push_str("\"\"\n");
+    }
+    for line in lines {
+        out.push('"');
+        for c in line.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\t' => out.push_str("\\t"),
+                c => out.push(c),
+            }
+        }
+        out.push_str("\"\n");
+    }
+}
+
+/// Read a PO string, quotes included.
+fn unquote(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                c @ ('"' | '\\') => c,
+                _ => return None,
+            }),
+            '"' => return None,
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
@@ -0,0 +1,117 @@
+//! Semantic highlighting of doc comments.
+//!
+//! Editors highlight doc comments as generic Markdown at best. [`tokens`]
+//! classifies the parts that have a meaning in Nixdoc, such as argument
+//! names, type signatures and the `=>` of examples, so an editor can color
+//! them, for instance as LSP semantic tokens.
+
+use crate::parser::{split_explicit_id, trim_heading};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{SectionKind, Span};
+
+/// What a highlighted span of a doc comment is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TokenKind {
+    /// A section heading line, such as `# Arguments {#args}`.
+    Heading,
+    /// The name in an `# Arguments` list item, such as `xs` in `- [xs]`.
+    ArgumentName,
+    /// The backticks or tildes opening or closing a code block.
+    Fence,
+    /// The info string after an opening fence, such as `nix title="Basic"`.
+    InfoString,
+    /// A line of the type signature in a `# Type` code block.
+    TypeSignature,
+    /// The `=>` separating an example from its expected result.
+    ResultMarker,
+}
+
+/// Classify the meaningful parts of a doc comment.
+///
+/// `text` is a doc comment, or a whole file; spans are byte ranges in it,
+/// in order. Headings and list items inside code blocks are not
+/// highlighted.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::highlight::{TokenKind, tokens};
+///
+/// let text = "/**\n  Id.\n\n  # Arguments\n\n  - [x] Anything\n*/";
+/// let highlighted: Vec<_> = tokens(text)
+///     .into_iter()
+///     .map(|(span, kind)| (&text[span.start..span.end], kind))
+///     .collect();
+/// assert_eq!(
+///     highlighted,
+///     [("# Arguments", TokenKind::Heading), ("x", TokenKind::ArgumentName)]
+/// );
+/// ```
+pub fn tokens(text: &str) -> Vec<(Span, TokenKind)> {
+    let mut out = Vec::new();
+    let mut section = None;
+    let mut fence: Option<&str> = None;
+    let mut result_marker_seen = false;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end();
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = &line[indent..];
+        let at =
+            |from: usize, len: usize| Span::new(start + indent + from, start + indent + from + len);
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) && trimmed[marker.len()..].trim().is_empty() {
+                out.push((at(0, marker.len()), TokenKind::Fence));
+                fence = None;
+            } else if section == Some(SectionKind::Type) && !trimmed.is_empty() {
+                out.push((at(0, trimmed.len()), TokenKind::TypeSignature));
+            } else if matches!(section, Some(SectionKind::Example | SectionKind::Examples))
+                && !result_marker_seen
+                && trimmed.starts_with("=>")
+            {
+                out.push((at(0, 2), TokenKind::ResultMarker));
+                result_marker_seen = true;
+            }
+            continue;
+        }
+
+        if let Some(marker) = fence_marker(trimmed) {
+            out.push((at(0, marker.len()), TokenKind::Fence));
+            let info = &trimmed[marker.len()..];
+            let info_start = marker.len() + info.len() - info.trim_start().len();
+            if !info.trim().is_empty() {
+                out.push((at(info_start, info.trim().len()), TokenKind::InfoString));
+            }
+            fence = Some(marker);
+            result_marker_seen = false;
+        } else if let Some(heading) = trimmed.strip_prefix("# ") {
+            let (heading, _) = trim_heading(heading.trim());
+            let (heading, _) = split_explicit_id(heading);
+            section = Some(SectionKind::from_heading(heading.trim()));
+            out.push((at(0, trimmed.len()), TokenKind::Heading));
+        } else if section == Some(SectionKind::Arguments)
+            && let Some(rest) = trimmed.strip_prefix("- [")
+            && let Some(end) = rest.find(']')
+        {
+            let name = &rest[..end];
+            let leading = name.len() - name.trim_start().len();
+            if !name.trim().is_empty() {
+                out.push((at(3 + leading, name.trim().len()), TokenKind::ArgumentName));
+            }
+        }
+    }
+    out
+}
+
+/// Returns the backticks or tildes opening a code block on `line`.
+fn fence_marker(line: &str) -> Option<&str> {
+    let c = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = line.len() - line.trim_start_matches(c).len();
+    (len >= 3).then(|| &line[..len])
+}
@@ -0,0 +1,138 @@
+//! Finding text that breaks inside a Nix indented string.
+//!
+//! Doc comments are often copied into `'' … ''` strings, such as the
+//! `description` of a module option or a generated `lib.mdDoc` call. There,
+//! a `${` starts an interpolation and a `''` ends the string, so an example
+//! like `"${pkgs.hello}/bin/hello"` no longer means what it says. In an
+//! indented string these are written `''${` and `'''`.
+//!
+//! [`check_interpolation`] reports every such sequence in a comment, and
+//! [`escape_indented`] is the fix: it escapes them for an indented string.
+
+use crate::diagnostic::{Diagnostic, Severity};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, Span};
+
+/// A sequence that an indented string would not keep as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unescaped {
+    /// A `${`, which starts an interpolation.
+    Interpolation,
+    /// A `''`, which ends the string.
+    Quotes,
+}
+
+/// Returns the span of every `${` and `''` in `text` that an indented string
+/// would interpret, in order.
+///
+/// Sequences that are already escaped, such as `''${` and `'''`, and the
+/// literal `$${` are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::Span;
+/// use nixdoc::interpolation::{Unescaped, find_unescaped};
+///
+/// let found = find_unescaped("\"${x}\" and ''${y}, then ''");
+/// assert_eq!(
+///     found,
+///     [
+///         (Span::new(1, 3), Unescaped::Interpolation),
+///         (Span::new(24, 26), Unescaped::Quotes),
+///     ]
+/// );
+/// ```
+pub fn find_unescaped(text: &str) -> Vec<(Span, Unescaped)> {
+    let mut found = Vec::new();
+    let mut i = 0;
+    while let Some(rest) = text.get(i..).filter(|rest| !rest.is_empty()) {
+        if rest.starts_with("''$") || rest.starts_with("'''") {
+            i += 3;
+        } else if let Some(escaped) = rest.strip_prefix("''\\") {
+            // `''\n` and friends; the escaped character follows.
+            i += 3 + escaped.chars().next().map_or(0, char::len_utf8);
+        } else if rest.starts_with("''") {
+            found.push((Span::new(i, i + 2), Unescaped::Quotes));
+            i += 2;
+        } else if rest.starts_with("$${") {
+            i += 3;
+        } else if rest.starts_with("${") {
+            found.push((Span::new(i, i + 2), Unescaped::Interpolation));
+            i += 2;
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    found
+}
+
+/// Escape `text` for a Nix indented string, so that it reads as written.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::interpolation::escape_indented;
+///
+/// assert_eq!(escape_indented("\"${pkgs.hello}/bin\""), "\"''${pkgs.hello}/bin\"");
+/// assert_eq!(escape_indented("''text''"), "'''text'''");
+/// // Escaped text is left alone.
+/// assert_eq!(escape_indented("''${x}"), "''${x}");
+/// ```
+pub fn escape_indented(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (span, unescaped) in find_unescaped(text) {
+        out.push_str(&text[last..span.start]);
+        out.push_str(match unescaped {
+            Unescaped::Interpolation => "''${",
+            Unescaped::Quotes => "'''",
+        });
+        last = span.end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Report every `${` and `''` in `doc` that would break if the comment were
+/// moved into an indented string.
+///
+/// | Code | Severity | Problem |
+/// |------|----------|---------|
+/// | `unescaped-interpolation` | warning | `${` would start an interpolation |
+/// | `unescaped-quotes` | warning | `''` would end the string |
+///
+/// Spans are relative to [`DocComment::raw_content`]. Prose and examples
+/// are checked alike; [`escape_indented`] fixes them all.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+/// use nixdoc::interpolation::check_interpolation;
+///
+/// let doc = DocComment::parse("/**\n  Runs `${pkgs.hello}/bin/hello`.\n*/").unwrap();
+/// let diagnostics = check_interpolation(&doc);
+/// assert_eq!(diagnostics[0].code, "unescaped-interpolation");
+/// assert_eq!(&doc.raw_content[diagnostics[0].span.start..][..2], "${");
+/// ```
+pub fn check_interpolation(doc: &DocComment) -> Vec<Diagnostic> {
+    find_unescaped(&doc.raw_content)
+        .into_iter()
+        .map(|(span, unescaped)| match unescaped {
+            Unescaped::Interpolation => Diagnostic::new(
+                "unescaped-interpolation",
+                Severity::Warning,
+                "`${` starts an interpolation in an indented string; write `''${`",
+                span,
+            ),
+            Unescaped::Quotes => Diagnostic::new(
+                "unescaped-quotes",
+                Severity::Warning,
+                "`''` ends an indented string; write `'''`",
+                span,
+            ),
+        })
+        .collect()
+}
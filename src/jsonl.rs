@@ -0,0 +1,150 @@
+//! [JSON Lines](https://jsonlines.org) output: one documented item per line.
+//!
+//! A JSON array of a whole corpus must be read completely before any of it
+//! can be used. As JSON Lines, each [`DocumentedItem`] is a JSON object on
+//! its own line, so tools can stream it: `nixdoc parse lib | jq …` starts
+//! printing at the first item, and large corpora never have to fit in
+//! memory at once. [`JsonLinesWriter`] writes this format and
+//! [`JsonLinesReader`] reads it back.
+//!
+//! Requires the `serde` feature.
+
+use std::io::{self, BufRead, Write};
+
+use thiserror::Error;
+
+use crate::extract::DocumentedItem;
+
+/// Writes documented items as JSON Lines.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+/// use nixdoc::jsonl::{JsonLinesReader, JsonLinesWriter};
+///
+/// let mut set = DocSet::new();
+/// set.add_source("{\n  /** Id. */\n  id = x: x;\n\n  /** Const. */\n  const = x: y: x;\n}", None);
+///
+/// let mut writer = JsonLinesWriter::new(Vec::new());
+/// for item in &set {
+///     writer.write(item).unwrap();
+/// }
+/// let bytes = writer.into_inner();
+/// assert_eq!(bytes.iter().filter(|&&b| b == b'\n').count(), 2);
+///
+/// let items: Vec<_> = JsonLinesReader::new(bytes.as_slice())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(items, set.items());
+/// ```
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    /// Create a writer writing to `writer`.
+    ///
+    /// Every item is written with a single call, so wrap a file or socket
+    /// in a [`BufWriter`](std::io::BufWriter) for speed.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write `item` as one line.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by the underlying writer.
+    pub fn write(&mut self, item: &DocumentedItem) -> io::Result<()> {
+        let mut line = serde_json::to_vec(item).map_err(io::Error::other)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)
+    }
+
+    /// Write every item of `items`, then flush.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by the underlying writer.
+    pub fn write_all<'a>(
+        &mut self,
+        items: impl IntoIterator<Item = &'a DocumentedItem>,
+    ) -> io::Result<()> {
+        for item in items {
+            self.write(item)?;
+        }
+        self.writer.flush()
+    }
+
+    /// Returns a mutable reference to the underlying writer, to write
+    /// something other than an item.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// An error reading JSON Lines.
+#[derive(Debug, Error)]
+pub enum JsonLinesError {
+    /// The input could not be read.
+    #[error("could not read JSON lines: {0}")]
+    Io(#[from] io::Error),
+    /// A line is not a documented item.
+    #[error("line {line}: {source}")]
+    Json {
+        /// The 1-based number of the line.
+        line: usize,
+        /// What is wrong with it.
+        source: serde_json::Error,
+    },
+}
+
+/// Reads documented items written by a [`JsonLinesWriter`], one per line.
+///
+/// Blank lines are skipped. The reader is an iterator, so it yields each
+/// item as soon as its line has been read.
+#[derive(Debug)]
+pub struct JsonLinesReader<R: BufRead> {
+    lines: io::Lines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    /// Create a reader reading from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLinesReader<R> {
+    type Item = Result<DocumentedItem, JsonLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            self.line += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(
+                serde_json::from_str(&line).map_err(|source| JsonLinesError::Json {
+                    line: self.line,
+                    source,
+                }),
+            );
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Checking documented laws with Nix.
+//!
+//! A `# Laws` section lists equations a function satisfies, so the
+//! documentation can double as property tests:
+//!
+//! ````text
+//! # Laws
+//!
+//! - [identity] `map id [ 1 2 ] == [ 1 2 ]`
+//!
+//! ```nix
+//! # composition
+//! map (x: x + 1) (map (x: x * 2) [ 1 ]) == map (x: x * 2 + 1) [ 1 ]
+//! ```
+//! ````
+//!
+//! [`assertions`] writes the laws of a set as one Nix expression, which
+//! evaluates to `true` if every law holds and otherwise fails, naming the
+//! first law that does not:
+//!
+//! ```sh
+//! nixdoc laws lib/ --prelude 'with import ./lib;' > laws.nix
+//! nix-instantiate --eval laws.nix
+//! ```
+//!
+//! Laws are pasted in as written, so their variables must be bound by the
+//! prelude, or the law must bind them itself.
+
+use std::fmt::Write;
+
+use crate::DocSet;
+
+/// Write the laws of every item in `set` as a Nix expression that checks
+/// them, see the [module documentation](self).
+///
+/// `prelude` is placed before the expression, so it must be something that
+/// takes an expression after it, like `with import <nixpkgs/lib>;`. Each
+/// law is labelled with its item and its name, or its position among the
+/// item's laws.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+///
+/// let mut set = DocSet::new();
+/// set.add_source(
+///     "{\n  /**\n    Identity.\n\n    # Laws\n\n    - [returns] `id 1 == 1`\n  */\n  id = x: x;\n}",
+///     None,
+/// );
+/// let nix = nixdoc::laws::assertions(&set, "with import ./lib;");
+/// assert!(nix.contains("with import ./lib;\n"));
+/// assert!(nix.contains("  (law \"id: returns\" (id 1 == 1))\n"));
+/// ```
+pub fn assertions(set: &DocSet, prelude: &str) -> String {
+    let mut out = String::from(
+        "# The `# Laws` of the documentation, as generated by nixdoc.\n\
+         # Evaluates to `true` if every law holds.\n",
+    );
+    if !prelude.is_empty() {
+        out.push_str(prelude);
+        out.push('\n');
+    }
+    out.push_str(
+        "let\n  law = name: holds: holds || throw \"law does not hold: ${name}\";\n\
+         in\nbuiltins.all (holds: holds) [\n",
+    );
+    for item in set {
+        let owner = match &item.name {
+            Some(name) => name.clone(),
+            None => item.provenance().to_string(),
+        };
+        for (i, law) in item.doc.laws().iter().enumerate() {
+            let label = match &law.name {
+                Some(name) => format!("{owner}: {name}"),
+                None => format!("{owner}: law {}", i + 1),
+            };
+            let _ = writeln!(
+                out,
+                "  (law \"{}\" ({}))",
+                escape_string(&label),
+                law.expression
+            );
+        }
+    }
+    out.push_str("]\n");
+    out
+}
+
+/// Escape `text` for a double-quoted Nix string.
+fn escape_string(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("${", "\\${")
+}
@@ -42,20 +42,121 @@
 //!
 //! Recognised section headings (case-insensitive):
 //! `Type`, `Arguments`/`Args`, `Example`, `Examples`, `Note`, `Notes`,
-//! `Warning`/`Warnings`/`Caution`, `Deprecated`.
+//! `Warning`/`Warnings`/`Caution`, `Deprecated`, `Meta`, `Since`,
+//! `Maintainers`/`Maintainer`, `Authors`/`Author`, `Tags`/`Categories`,
+//! `Stability`.
+//!
+//! ## `no_std`
+//!
+//! Without the default `std` feature the crate is `no_std` and needs only
+//! `alloc`. Parsing and the accessors of [`DocComment`] are available;
+//! modules that read files or talk to the operating system, such as
+//! [`extract`] and the C interface, are not.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
+#[cfg(feature = "std")]
+pub mod anchor;
+#[cfg(feature = "std")]
+pub mod checks;
+#[cfg(feature = "std")]
+pub mod chunk;
+pub mod diagnostic;
+pub mod dialect;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod docset;
+#[cfg(feature = "doctest")]
+pub mod doctest;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod extract;
+#[cfg(feature = "ffi")]
 pub mod ffi;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+pub mod gettext;
+pub mod highlight;
+pub mod interpolation;
+#[cfg(feature = "serde")]
+pub mod jsonl;
+#[cfg(feature = "std")]
+pub mod laws;
+#[cfg(feature = "linkcheck")]
+pub mod linkcheck;
+pub mod links;
+pub mod lint;
+pub mod localize;
+#[cfg(feature = "std")]
+pub mod lsp;
+pub mod merge;
+pub mod migrate;
 pub mod parser;
+pub mod patch;
+#[cfg(feature = "serde")]
+pub mod pesto;
+pub mod profile;
+#[cfg(feature = "prost")]
+pub mod proto;
+#[cfg(feature = "std")]
+pub mod provenance;
+pub mod query;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod search;
 pub mod section;
+pub mod span;
+#[cfg(feature = "sqlite")]
+pub mod store;
+pub mod summary;
+#[cfg(feature = "rnix")]
+pub mod syntax;
+pub mod version;
+#[cfg(feature = "std")]
+pub mod view;
+pub mod visit;
+pub mod wrap;
+#[cfg(feature = "std")]
+pub mod xref;
+
+/// The `alloc` items the standard prelude provides, for `no_std` builds.
+#[cfg(not(feature = "std"))]
+mod prelude {
+    pub(crate) use alloc::string::{String, ToString};
+    pub(crate) use alloc::vec::Vec;
+    pub(crate) use alloc::{format, vec};
+}
+
+use alloc::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use prelude::*;
 
-pub use error::{ParseError, ParseWarning, WarningKind};
-pub use section::{Argument, Example, Section, SectionKind};
+pub use diagnostic::{Diagnostic, Severity};
+#[cfg(feature = "std")]
+pub use docset::DocSet;
+pub use error::{ParseError, ParseWarning, SpannedError, WarningKind};
+pub use parser::{ParseOutcome, ParserOptions, UnicodePolicy};
+pub use profile::SpecProfile;
+#[cfg(feature = "std")]
+pub use provenance::Provenance;
+pub use section::{
+    Admonition, AdmonitionLevel, Argument, Attribution, CodeBlock, Example, FenceInfo, Law, Output,
+    Section, SectionKind, Stability,
+};
+pub use span::Span;
+pub use version::Version;
+#[cfg(feature = "std")]
+pub use view::DocView;
 
 /// A fully parsed Nixdoc documentation comment.
 ///
 /// Obtain one via [`DocComment::parse`].
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DocComment {
     /// The normalized comment body with delimiters stripped and indentation removed.
@@ -67,6 +168,11 @@ pub struct DocComment {
     /// Sections in document order.
     pub sections: Vec<Section>,
 
+    /// Link reference definitions (`[label]: url`) from anywhere in the
+    /// comment, in document order. They are removed from the description
+    /// and section content.
+    pub link_definitions: Vec<links::LinkDefinition>,
+
     /// Non-fatal warnings produced during parsing.
     pub warnings: Vec<ParseWarning>,
 }
@@ -97,7 +203,89 @@ impl DocComment {
     /// assert_eq!(DocComment::parse("/** */"), Err(ParseError::EmptyComment));
     /// ```
     pub fn parse(input: &str) -> Result<Self, ParseError> {
-        parser::parse(input)
+        parser::parse(input, &ParserOptions::default())
+    }
+
+    /// Parse a doc comment with custom [`ParserOptions`].
+    ///
+    /// This lets text from other pipelines, with different comment markers
+    /// or none at all, go through the same section, argument and example
+    /// parsing as `/** … */` comments.
+    ///
+    /// # Errors
+    ///
+    /// As for [`Self::parse`], with the markers of `options` in place of
+    /// `/**` and `*/`. Without markers, only [`ParseError::EmptyComment`] is
+    /// possible. With [`ParserOptions::recover_unclosed`],
+    /// [`ParseError::UnclosedComment`] is not returned, and with
+    /// [`ParserOptions::deny_warnings`], a comment with warnings fails with
+    /// [`ParseError::DeniedWarning`].
+    pub fn parse_with(input: &str, options: &ParserOptions) -> Result<Self, ParseError> {
+        parser::parse(input, options)
+    }
+
+    /// Parse a doc comment with custom [`ParserOptions`], locating the error
+    /// on failure.
+    ///
+    /// The span of a [`SpannedError`] is relative to `input`: the text where
+    /// the opening marker was expected, the opening marker of an unclosed
+    /// comment, or the space between the markers of an empty one.
+    ///
+    /// # Errors
+    ///
+    /// As for [`Self::parse_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, ParseError, ParserOptions, Span};
+    ///
+    /// let error = DocComment::parse_spanned("  /** unclosed", &ParserOptions::new()).unwrap_err();
+    /// assert_eq!(error.error, ParseError::UnclosedComment);
+    /// assert_eq!(error.span, Span::new(2, 5));
+    ///
+    /// let error = DocComment::parse_spanned("/**   */", &ParserOptions::new()).unwrap_err();
+    /// assert_eq!(error.span, Span::new(3, 6));
+    /// ```
+    pub fn parse_spanned(input: &str, options: &ParserOptions) -> Result<Self, SpannedError> {
+        parser::parse_spanned(input, options)
+    }
+
+    /// Parse a doc comment, collecting everything found into a
+    /// [`ParseOutcome`] instead of stopping at the first error.
+    ///
+    /// A comment missing its closing marker is parsed as with
+    /// [`Self::parse_lossy`] and reported as an
+    /// [`ParseError::UnclosedComment`] error alongside the comment. Other
+    /// errors leave [`ParseOutcome::doc`] empty.
+    pub fn parse_outcome(input: &str, options: &ParserOptions) -> ParseOutcome {
+        parser::parse_outcome(input, options)
+    }
+
+    /// Parse a doc comment that may be missing its closing `*/`.
+    ///
+    /// Behaves like [`Self::parse`], except that an unclosed comment extends
+    /// to the end of the input and carries a [`WarningKind::UnclosedComment`]
+    /// warning instead of failing. This suits editors, where the comment
+    /// under the cursor is often still being typed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::NotDocComment`] or [`ParseError::EmptyComment`]
+    /// under the same conditions as [`Self::parse`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, WarningKind};
+    ///
+    /// let doc = DocComment::parse_lossy("/**\n  Half-typed.\n\n  # Arguments\n\n  - [x] The input").unwrap();
+    /// assert_eq!(doc.title(), Some("Half-typed."));
+    /// assert_eq!(doc.arguments().len(), 1);
+    /// assert_eq!(doc.warnings[0].kind, WarningKind::UnclosedComment);
+    /// ```
+    pub fn parse_lossy(input: &str) -> Result<Self, ParseError> {
+        parser::parse_lossy(input, &ParserOptions::default())
     }
 
     /// Returns `true` if the given string looks like a Nixdoc doc comment.
@@ -139,6 +327,60 @@ impl DocComment {
         if title.is_empty() { None } else { Some(title) }
     }
 
+    /// Returns a one-line summary: the content of the `# Summary` section if
+    /// there is one, or else the first paragraph of the description.
+    ///
+    /// Unlike [`Self::title`], a first sentence wrapped over several lines is
+    /// kept whole, with its lines joined by spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  Concatenate a list of strings\n  with a separator.\n\n  More.\n*/").unwrap();
+    /// assert_eq!(doc.title(), Some("Concatenate a list of strings"));
+    /// assert_eq!(doc.summary().as_deref(), Some("Concatenate a list of strings with a separator."));
+    ///
+    /// let doc = DocComment::parse("/**\n  Long prose.\n\n  # Summary\n\n  Short.\n*/").unwrap();
+    /// assert_eq!(doc.summary().as_deref(), Some("Short."));
+    /// ```
+    pub fn summary(&self) -> Option<String> {
+        let text = match self.section_by_kind(SectionKind::Summary) {
+            Some(section) => &section.content,
+            None => &self.description,
+        };
+        let lines: Vec<&str> = text
+            .trim()
+            .lines()
+            .map(str::trim)
+            .take_while(|line| !line.is_empty())
+            .collect();
+        (!lines.is_empty()).then(|| lines.join(" "))
+    }
+
+    /// Returns the [`summary`](Self::summary) as plain text of at most
+    /// `max_chars` characters, for search results, hover text and lists.
+    ///
+    /// Markdown is removed, and a summary that is too long is shortened to
+    /// its first sentence or cut at a word boundary with an ellipsis, never
+    /// inside inline code. See [`summary::truncate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  Concatenate **strings** with `sep`. Empty lists give `\"\"`.\n*/").unwrap();
+    /// assert_eq!(doc.short_summary(80).as_deref(), Some("Concatenate strings with sep. Empty lists give \"\"."));
+    /// assert_eq!(doc.short_summary(40).as_deref(), Some("Concatenate strings with sep."));
+    /// assert_eq!(doc.short_summary(20).as_deref(), Some("Concatenate strings…"));
+    /// ```
+    pub fn short_summary(&self, max_chars: usize) -> Option<String> {
+        let text = summary::plain_text(&self.summary()?);
+        (!text.is_empty()).then(|| summary::truncate(&text, max_chars))
+    }
+
     /// Returns the full description. Description is the content before the first section heading.
     ///
     /// The description is trimmed of leading and trailing whitespace but
@@ -171,34 +413,245 @@ impl DocComment {
             .find(|s| s.heading.to_lowercase() == name_lower)
     }
 
+    /// Returns the sections for which `predicate` returns `true`, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  Desc.\n\n  # Note\n\n  A.\n\n  # See Also\n\n  B.\n*/").unwrap();
+    /// let unknown: Vec<_> = doc.sections_where(|s| !s.kind().is_known()).collect();
+    /// assert_eq!(unknown[0].heading, "See Also");
+    /// ```
+    pub fn sections_where<'a>(
+        &'a self,
+        mut predicate: impl FnMut(&Section) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a Section> + 'a {
+        self.sections.iter().filter(move |s| predicate(s))
+    }
+
+    /// Returns the sections whose kind is one of `kinds`, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, SectionKind};
+    ///
+    /// let doc = DocComment::parse("/**\n  Desc.\n\n  # Note\n\n  A.\n\n  # Notes\n\n  B.\n*/").unwrap();
+    /// let notes = doc.sections_of(&[SectionKind::Note, SectionKind::Notes]);
+    /// assert_eq!(notes.count(), 2);
+    /// ```
+    pub fn sections_of<'a>(
+        &'a self,
+        kinds: &'a [SectionKind],
+    ) -> impl Iterator<Item = &'a Section> + 'a {
+        self.sections_where(move |s| kinds.contains(&s.kind()))
+    }
+
+    /// Returns the first section of the given kind.
+    ///
+    /// Unlike [`Self::section`], this matches every heading of the kind, so
+    /// `# Args` is found as [`SectionKind::Arguments`] and `# Caution` as
+    /// [`SectionKind::Warning`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, SectionKind};
+    ///
+    /// let doc = DocComment::parse("/**\n  f.\n\n  # Args\n\n  - [x] Input\n*/").unwrap();
+    /// assert_eq!(doc.section_by_kind(SectionKind::Arguments).unwrap().heading, "Args");
+    /// assert!(doc.section("Arguments").is_none());
+    /// ```
+    pub fn section_by_kind(&self, kind: SectionKind) -> Option<&Section> {
+        self.sections.iter().find(|s| s.kind() == kind)
+    }
+
+    /// Returns the sections of the given kind, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, SectionKind};
+    ///
+    /// let doc = DocComment::parse("/**\n  f.\n\n  # Warning\n\n  A.\n\n  # Caution\n\n  B.\n*/").unwrap();
+    /// assert_eq!(doc.sections_by_kind(SectionKind::Warning).count(), 2);
+    /// ```
+    pub fn sections_by_kind(&self, kind: SectionKind) -> impl Iterator<Item = &Section> {
+        self.sections.iter().filter(move |s| s.kind() == kind)
+    }
+
+    /// Returns the first non-`None` result of `f` over the sections, in
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  Desc.\n\n  # Note\n\n  A.\n\n  # Type\n\n  `int`\n*/").unwrap();
+    /// let id = doc.find_map_sections(|s| (s.heading == "Type").then(|| s.anchor()));
+    /// assert_eq!(id.as_deref(), Some("type"));
+    /// ```
+    pub fn find_map_sections<'a, T>(
+        &'a self,
+        f: impl FnMut(&'a Section) -> Option<T>,
+    ) -> Option<T> {
+        self.sections.iter().find_map(f)
+    }
+
+    /// Returns the link reference definition for `label`, matched
+    /// case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  Implements [the RFC][rfc].\n\n  # Note\n\n  Details.\n\n  [rfc]: https://github.com/NixOS/rfcs/pull/145\n*/",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.sections[0].content, "Details.");
+    /// assert_eq!(
+    ///     doc.link_definition("RFC").unwrap().url,
+    ///     "https://github.com/NixOS/rfcs/pull/145",
+    /// );
+    /// ```
+    pub fn link_definition(&self, label: &str) -> Option<&links::LinkDefinition> {
+        self.link_definitions.iter().find(|def| def.matches(label))
+    }
+
+    /// Returns every HTTP(S) URL in the comment, in order of appearance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  Docs.\n\n  # Note\n\n  See <https://nixos.org/manual>.\n*/",
+    /// )
+    /// .unwrap();
+    /// let urls = doc.urls();
+    /// assert_eq!(urls[0].url, "https://nixos.org/manual");
+    /// assert_eq!(urls[0].section.as_deref(), Some("Note"));
+    /// ```
+    pub fn urls(&self) -> Vec<links::Link> {
+        links::urls(self)
+    }
+
+    /// Returns every mention of another identifier, in order of appearance.
+    ///
+    /// Mentions are written `` [`lib.attrsets.mapAttrs`] `` anywhere in the
+    /// comment; in a `# See Also` section, any code span holding an
+    /// identifier counts. Spans are relative to [`Self::raw_content`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  Like [`map`].\n\n  # See Also\n\n  - `lib.imap0`\n*/",
+    /// )
+    /// .unwrap();
+    /// let refs: Vec<_> = doc
+    ///     .references()
+    ///     .into_iter()
+    ///     .map(|r| (r.text, r.section))
+    ///     .collect();
+    /// assert_eq!(
+    ///     refs,
+    ///     [
+    ///         ("map".to_string(), None),
+    ///         ("lib.imap0".to_string(), Some("See Also".to_string())),
+    ///     ],
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn references(&self) -> Vec<xref::Reference> {
+        xref::references(self)
+    }
+
+    /// Resolve the [`references`](Self::references) of this comment
+    /// against `symbols`.
+    ///
+    /// Spans are relative to [`Self::raw_content`] and cover the brackets,
+    /// so renderers can replace each mention with a link.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    /// use nixdoc::xref::SymbolTable;
+    ///
+    /// let doc = DocComment::parse("/** Like [`lib.map`], not [`lib.nope`]. */").unwrap();
+    /// let symbols: SymbolTable = ["map"].into_iter().collect();
+    /// let refs = doc.resolve_references(&symbols);
+    ///
+    /// assert_eq!(refs.resolved[0].target, "map");
+    /// let span = refs.resolved[0].reference.span;
+    /// assert_eq!(&doc.raw_content[span.start..span.end], "[`lib.map`]");
+    /// assert_eq!(refs.unresolved[0].text, "lib.nope");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn resolve_references(&self, symbols: &xref::SymbolTable) -> xref::ReferenceResolution {
+        xref::resolve_references(self, symbols)
+    }
+
     /// Returns the type signature, if one can be found.
     ///
     /// Two formats are recognised, in order of priority:
     ///
     /// 1. **Modern format** (RFC145): the first fenced code block inside a
-    ///    `# Type` section.
+    ///    `# Type` section, or failing that an `identifier :: type` line in
+    ///    it, which carries a [`WarningKind::MixedStyle`] warning.
     /// 2. **Legacy format**: an `identifier :: type` annotation embedded
     ///    directly in the description text, without a `# Type` section.
     ///
     /// # Examples
     ///
     /// ```
-    /// use nixdoc::DocComment;
+    /// use nixdoc::{DocComment, WarningKind};
     ///
     /// let input = "/**\n  f.\n\n  # Type\n\n  ```\n  f :: Int -> Int\n  ```\n*/";
     /// let doc = DocComment::parse(input).unwrap();
     /// assert_eq!(doc.type_sig(), Some("f :: Int -> Int\n".to_string()));
+    ///
+    /// let bare = DocComment::parse("/**\n  f.\n\n  # Type\n\n  f :: Int -> Int\n*/").unwrap();
+    /// assert_eq!(bare.type_sig(), Some("f :: Int -> Int".to_string()));
+    /// assert_eq!(bare.warnings[0].kind, WarningKind::MixedStyle);
     /// ```
     pub fn type_sig(&self) -> Option<String> {
         // Modern format: first fenced code block inside a `# Type` section.
-        if let Some(section) = self.section("Type") {
-            return parser::extract_first_code_block(&section.content);
+        if let Some(section) = self.section_by_kind(SectionKind::Type) {
+            return parser::extract_first_code_block(&section.content)
+                .or_else(|| parser::extract_inline_type_sig(&section.content));
         }
         // Legacy format: inline `identifier :: type` in the description.
         parser::extract_inline_type_sig(&self.description)
     }
 
-    /// Returns the parsed arguments from the `# Arguments` (or `# Args`) section.
+    /// Returns the info string of the code block in the `# Type` section.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let input = "/**\n  f.\n\n  # Type\n\n  ```haskell since=24.05\n  f :: Int -> Int\n  ```\n*/";
+    /// let info = DocComment::parse(input).unwrap().type_info().unwrap();
+    /// assert_eq!(info.language.as_deref(), Some("haskell"));
+    /// assert_eq!(info.attributes["since"], "24.05");
+    /// ```
+    pub fn type_info(&self) -> Option<FenceInfo> {
+        parser::first_code_block_info(&self.section_by_kind(SectionKind::Type)?.content)
+    }
+
+    /// Returns the parsed arguments from the first `# Arguments` (or
+    /// `# Args`) section.
     ///
     /// Each `- [name] description` line in the section becomes an [`Argument`].
     /// Returns an empty vector if there is no arguments section.
@@ -216,12 +669,101 @@ impl DocComment {
     /// assert_eq!(args[0].description, "First");
     /// ```
     pub fn arguments(&self) -> Vec<Argument> {
-        match self.section("Arguments").or_else(|| self.section("Args")) {
+        match self.section_by_kind(SectionKind::Arguments) {
             Some(s) => parser::parse_arguments(&s.content),
             None => Vec::new(),
         }
     }
 
+    /// Returns the attributes documented in the `# Inputs` section of a
+    /// builder function, parsed like [`Self::arguments`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let input = "/**\n  Build a package.\n\n  # Inputs\n\n  `pname`\n  : The package name.\n\n  `src`\n  : The source.\n*/";
+    /// let doc = DocComment::parse(input).unwrap();
+    /// let inputs = doc.inputs();
+    /// assert_eq!(inputs.len(), 2);
+    /// assert_eq!(inputs[0].name, "pname");
+    /// assert_eq!(inputs[1].description, "The source.");
+    /// ```
+    pub fn inputs(&self) -> Vec<Argument> {
+        self.sections_by_kind(SectionKind::Inputs)
+            .flat_map(|s| parser::parse_arguments(&s.content))
+            .collect()
+    }
+
+    /// Returns every fenced code block of the comment, in the description
+    /// and in every section, in order of appearance.
+    ///
+    /// Unlike [`Self::examples`], blocks are returned as they are, without
+    /// splitting off `=>` results or `nix repl` prompts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, SectionKind};
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  Like:\n\n  ```sh\n  ls\n  ```\n\n  # Type\n\n  ```\n  f :: a\n  ```\n*/",
+    /// )
+    /// .unwrap();
+    /// let blocks = doc.code_blocks();
+    /// assert_eq!(blocks.len(), 2);
+    /// assert_eq!(blocks[0].section_kind, None);
+    /// assert_eq!(blocks[0].language.as_deref(), Some("sh"));
+    /// assert_eq!(blocks[0].code, "ls\n");
+    /// assert_eq!(blocks[1].section_kind, Some(SectionKind::Type));
+    /// assert!(doc.raw_content[blocks[1].span.start..].starts_with("```\nf :: a"));
+    /// ```
+    pub fn code_blocks(&self) -> Vec<CodeBlock> {
+        parser::code_blocks(&self.raw_content)
+    }
+
+    /// Returns the laws of the `# Laws` section, equations such as
+    /// `map id xs == xs` that the function satisfies.
+    ///
+    /// See [`laws`](crate::laws) for exporting them as Nix assertions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let input = "/**\n  Map over a list.\n\n  # Laws\n\n  - [identity] `map id xs == xs`\n  - `map f [ ] == [ ]`\n*/";
+    /// let laws = DocComment::parse(input).unwrap().laws();
+    /// assert_eq!(laws[0].name.as_deref(), Some("identity"));
+    /// assert_eq!(laws[0].expression, "map id xs == xs");
+    /// assert_eq!(laws[1].name, None);
+    /// ```
+    pub fn laws(&self) -> Vec<Law> {
+        self.sections_by_kind(SectionKind::Laws)
+            .flat_map(|section| parser::parse_laws(&section.content))
+            .collect()
+    }
+
+    /// Returns the `# Output` section of a builder function: its
+    /// description and the attributes it lists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let input = "/**\n  Build a package.\n\n  # Output\n\n  A derivation.\n\n  - [out] The program.\n  - [doc] The manual.\n*/";
+    /// let doc = DocComment::parse(input).unwrap();
+    /// let output = doc.output().unwrap();
+    /// assert_eq!(output.description, "A derivation.");
+    /// assert_eq!(output.attributes[1].name, "doc");
+    /// ```
+    pub fn output(&self) -> Option<Output> {
+        let section = self.section_by_kind(SectionKind::Output)?;
+        Some(parser::parse_output(&section.content))
+    }
+
     /// Returns all code examples from `# Example` and `# Examples` sections.
     ///
     /// Multiple examples within a single section (multiple code blocks) are
@@ -239,38 +781,58 @@ impl DocComment {
     /// assert_eq!(examples[0].language, Some("nix".to_string()));
     /// ```
     pub fn examples(&self) -> Vec<Example> {
-        self.sections
-            .iter()
-            .filter(|s| {
-                let h = s.heading.to_lowercase();
-                h == "example" || h == "examples"
-            })
+        self.sections_of(&[SectionKind::Example, SectionKind::Examples])
             .flat_map(|s| parser::parse_examples(&s.content))
             .collect()
     }
 
     /// Returns the trimmed content of all `# Note` and `# Notes` sections.
     pub fn notes(&self) -> Vec<&str> {
-        self.sections
-            .iter()
-            .filter(|s| {
-                let h = s.heading.to_lowercase();
-                h == "note" || h == "notes"
-            })
+        self.sections_of(&[SectionKind::Note, SectionKind::Notes])
             .map(|s| s.content.trim())
             .collect()
     }
 
     /// Returns the trimmed content of all warning sections
     /// (`# Warning`, `# Warnings`, `# Caution`).
+    ///
+    /// These are not the parser's [`Self::warnings`]; see
+    /// [`Self::admonitions`] for all callouts with their level.
     pub fn warnings_content(&self) -> Vec<&str> {
+        self.sections_by_kind(SectionKind::Warning)
+            .map(|s| s.content.trim())
+            .collect()
+    }
+
+    /// Returns the notes, warnings, cautions and deprecation notices, in
+    /// document order.
+    ///
+    /// Renderers can show every callout the same way, styled by its
+    /// [`AdmonitionLevel`]. This covers the sections of [`Self::notes`],
+    /// [`Self::warnings_content`] and [`Self::deprecation_notice`]; the
+    /// parser's [`Self::warnings`] are unrelated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{AdmonitionLevel, DocComment};
+    ///
+    /// let input = "/**\n  Old fn.\n\n  # Caution\n\n  Deletes files.\n\n  # Note\n\n  Slow.\n*/";
+    /// let admonitions = DocComment::parse(input).unwrap().admonitions();
+    /// assert_eq!(admonitions[0].level, AdmonitionLevel::Danger);
+    /// assert_eq!(admonitions[0].heading, "Caution");
+    /// assert_eq!(admonitions[1].content, "Slow.");
+    /// ```
+    pub fn admonitions(&self) -> Vec<Admonition> {
         self.sections
             .iter()
-            .filter(|s| {
-                let h = s.heading.to_lowercase();
-                h == "warning" || h == "warnings" || h == "caution"
+            .filter_map(|section| {
+                Some(Admonition {
+                    level: section.admonition_level()?,
+                    heading: section.heading.clone(),
+                    content: section.content.trim().to_string(),
+                })
             })
-            .map(|s| s.content.trim())
             .collect()
     }
 
@@ -286,11 +848,538 @@ impl DocComment {
     /// assert!(doc.is_deprecated());
     /// ```
     pub fn is_deprecated(&self) -> bool {
-        self.section("Deprecated").is_some()
+        self.section_by_kind(SectionKind::Deprecated).is_some()
+    }
+
+    /// Returns `true` if this is a plain `/* … */` comment, accepted with
+    /// [`ParserOptions::legacy_comments`], rather than an RFC 145 doc comment.
+    pub fn is_legacy(&self) -> bool {
+        self.warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::LegacyComment)
     }
 
     /// Returns the trimmed content of the `# Deprecated` section, if present.
     pub fn deprecation_notice(&self) -> Option<&str> {
-        self.section("Deprecated").map(|s| s.content.trim())
+        self.section_by_kind(SectionKind::Deprecated)
+            .map(|s| s.content.trim())
+    }
+
+    /// Returns the `key: value` attributes of the `# Meta` sections.
+    ///
+    /// Lines may be written as list items (`- key: value`). Lines without a
+    /// `:` are ignored, and a repeated key keeps its last value. A block of
+    /// such lines between `---` lines at the very start of the comment,
+    /// frontmatter-style, is read as a `Meta` section as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let input = "/**\n  Trim a string.\n\n  # Meta\n\n  - category: strings\n  - stability: stable\n*/";
+    /// let meta = DocComment::parse(input).unwrap().metadata();
+    /// assert_eq!(meta["category"], "strings");
+    /// assert_eq!(meta["stability"], "stable");
+    ///
+    /// let input = "/**\n  ---\n  category: lists\n  ---\n  Reverse a list.\n*/";
+    /// let doc = DocComment::parse(input).unwrap();
+    /// assert_eq!(doc.title(), Some("Reverse a list."));
+    /// assert_eq!(doc.metadata()["category"], "lists");
+    /// ```
+    pub fn metadata(&self) -> BTreeMap<String, String> {
+        self.sections_by_kind(SectionKind::Meta)
+            .flat_map(|s| parser::parse_metadata(&s.content))
+            .collect()
+    }
+
+    /// Returns the tags of the `# Tags` sections, in order and without
+    /// duplicates.
+    ///
+    /// Tags are separated by commas or written as list items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let input = "/**\n  Title.\n\n  # Tags\n\n  strings, `text`\n  - strings\n  - unicode\n*/";
+    /// let doc = DocComment::parse(input).unwrap();
+    /// assert_eq!(doc.tags(), ["strings", "text", "unicode"]);
+    /// ```
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self
+            .sections_by_kind(SectionKind::Tags)
+            .flat_map(|s| parser::parse_tags(&s.content))
+        {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// Returns how settled the item's interface is.
+    ///
+    /// The stability is taken from the first word of a `# Stability`
+    /// section, then from an `@stable`, `@experimental` or `@internal`
+    /// marker in the description, then from a `stability` entry of
+    /// [`Self::metadata`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, Stability};
+    ///
+    /// let doc = DocComment::parse("/**\n  Helper.\n\n  # Stability\n\n  Experimental, see RFC 42.\n*/").unwrap();
+    /// assert_eq!(doc.stability(), Some(Stability::Experimental));
+    ///
+    /// let doc = DocComment::parse("/** @internal Used by `mkDerivation`. */").unwrap();
+    /// assert_eq!(doc.stability(), Some(Stability::Internal));
+    /// ```
+    pub fn stability(&self) -> Option<Stability> {
+        let first_word = |text: &str| {
+            let word = text.split_whitespace().next()?;
+            Stability::parse(word.trim_matches(|c: char| !c.is_alphanumeric()))
+        };
+
+        self.sections_by_kind(SectionKind::Stability)
+            .find_map(|s| first_word(&s.content))
+            .or_else(|| {
+                self.description
+                    .split_whitespace()
+                    .filter_map(|word| word.strip_prefix('@'))
+                    .find_map(|marker| {
+                        Stability::parse(marker.trim_end_matches(|c: char| !c.is_alphanumeric()))
+                    })
+            })
+            .or_else(|| first_word(self.metadata().get("stability")?))
+    }
+
+    /// Visit the description, sections, arguments, examples, link
+    /// definitions and warnings of the comment, in that order.
+    ///
+    /// See [`visit::DocVisitor`].
+    pub fn walk(&self, visitor: &mut (impl visit::DocVisitor + ?Sized)) {
+        visit::walk(self, visitor);
+    }
+
+    /// Returns the salient terms of the comment, for search indexes and tag
+    /// clouds.
+    ///
+    /// Identifiers come first, as written: names in the type signature, the
+    /// arguments, the [`references`](Self::references), and inline code in
+    /// the description. Then come the lowercased words of the description,
+    /// most frequent first, leaving out short words, numbers and
+    /// [`search::STOPWORDS`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  Filter an attribute set with `pred`. The attribute\n  names are kept.\n\n  # Type\n\n  ```\n  filterAttrs :: (String -> Any -> Bool) -> AttrSet -> AttrSet\n  ```\n*/").unwrap();
+    /// assert_eq!(
+    ///     doc.keywords(),
+    ///     ["filterAttrs", "String", "Any", "Bool", "AttrSet", "pred", "attribute", "filter", "set", "names", "kept"]
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn keywords(&self) -> Vec<String> {
+        search::keywords(self)
+    }
+
+    /// Find the occurrences of `query` in the comment, ignoring case.
+    ///
+    /// Each [`search::Match`] names the section it is in and carries a span
+    /// into [`Self::raw_content`] for highlighting. Use [`Self::find_with`]
+    /// for case-sensitive or whole-word matching.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  Map a list.\n\n  # Note\n\n  Lists are lazy; see `map`.\n*/").unwrap();
+    /// let matches = doc.find("list");
+    /// assert_eq!(matches.len(), 2);
+    /// assert_eq!(matches[1].section.as_deref(), Some("Note"));
+    /// assert_eq!(&doc.raw_content[matches[1].span.start..matches[1].span.end], "List");
+    /// assert_eq!(matches[1].snippet, "Lists are lazy; see `map`.");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn find(&self, query: &str) -> Vec<search::Match> {
+        search::find(self, query, &search::FindOptions::default())
+    }
+
+    /// Find the occurrences of `query` in the comment, see [`Self::find`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    /// use nixdoc::search::FindOptions;
+    ///
+    /// let doc = DocComment::parse("/** Map a list; Lists are lazy. */").unwrap();
+    /// let options = FindOptions::new().with_whole_word(true);
+    /// assert_eq!(doc.find_with("list", &options).len(), 1);
+    /// let options = options.with_case_sensitive(true);
+    /// assert_eq!(doc.find_with("List", &options).len(), 0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn find_with(&self, query: &str, options: &search::FindOptions) -> Vec<search::Match> {
+        search::find(self, query, options)
+    }
+
+    /// Select fields with a path such as `sections[kind=arguments].items[0].name`.
+    ///
+    /// See the [`query`] module for the path language. Fields that do not
+    /// exist select nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`query::QueryError`] if the path is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let input = "/**\n  Add.\n\n  # Arguments\n\n  - [a] First\n  - [b] Second\n*/";
+    /// let doc = DocComment::parse(input).unwrap();
+    /// let names = doc.query("sections[kind=arguments].items[*].name").unwrap();
+    /// assert_eq!(names, ["a".into(), "b".into()]);
+    /// assert_eq!(doc.query("arguments[-1].description").unwrap()[0].as_str(), Some("Second"));
+    /// assert!(doc.query("sections[").is_err());
+    /// ```
+    pub fn query(&self, path: &str) -> Result<Vec<query::Value>, query::QueryError> {
+        query::query(self, path)
+    }
+
+    /// Returns the field at a JSON Pointer (RFC 6901) into the serialized
+    /// form of the comment, such as `/sections/1/content`.
+    ///
+    /// The serialized form is the one produced with the `serde` feature, but
+    /// this method is always available. Returns `None` if the pointer is
+    /// malformed or selects nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  Desc.\n\n  # Note\n\n  Careful.\n*/").unwrap();
+    /// let content = doc.pointer("/sections/0/content").unwrap();
+    /// assert_eq!(content.as_str(), Some("Careful."));
+    /// assert_eq!(doc.pointer("/sections/0/explicit_id").unwrap().to_json(), "null");
+    /// assert!(doc.pointer("/sections/1").is_none());
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<query::Value> {
+        query::serialized_value(self).pointer(pointer).cloned()
+    }
+
+    /// Returns the people listed in the `# Maintainers` sections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let input = "/**\n  Title.\n\n  # Maintainers\n\n  - Jane Doe <jane@example.org>\n  - @jdoe\n*/";
+    /// let maintainers = DocComment::parse(input).unwrap().maintainers();
+    /// assert_eq!(maintainers[0].email.as_deref(), Some("jane@example.org"));
+    /// assert_eq!(maintainers[1].handle.as_deref(), Some("jdoe"));
+    /// ```
+    pub fn maintainers(&self) -> Vec<Attribution> {
+        self.attributions(SectionKind::Maintainers)
+    }
+
+    /// Returns the people listed in the `# Authors` sections.
+    pub fn authors(&self) -> Vec<Attribution> {
+        self.attributions(SectionKind::Authors)
+    }
+
+    fn attributions(&self, kind: SectionKind) -> Vec<Attribution> {
+        self.sections_by_kind(kind)
+            .flat_map(|s| parser::parse_attributions(&s.content))
+            .collect()
+    }
+
+    /// Returns the version that introduced the item.
+    ///
+    /// The version is the first one found in a `# Since` section, then on a
+    /// `Since:` line in the description or a prose section such as
+    /// `# Note`, then in a `since` entry of [`Self::metadata`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  Sort a list.\n\n  # Since\n\n  NixOS 24.05\n*/").unwrap();
+    /// assert_eq!(doc.since().unwrap().to_string(), "24.05");
+    ///
+    /// let doc = DocComment::parse("/**\n  Sort a list.\n\n  **Since:** v1.2\n*/").unwrap();
+    /// assert_eq!(doc.since().unwrap().minor, 2);
+    /// ```
+    pub fn since(&self) -> Option<Version> {
+        let section = self
+            .sections_by_kind(SectionKind::Since)
+            .find_map(|s| version::find(&s.content));
+
+        let prose = || {
+            let sections = self.sections_where(|s| {
+                !matches!(
+                    s.kind(),
+                    SectionKind::Type
+                        | SectionKind::Arguments
+                        | SectionKind::Example
+                        | SectionKind::Examples
+                        | SectionKind::Meta
+                )
+            });
+            core::iter::once(self.description.as_str())
+                .chain(sections.map(|s| s.content.as_str()))
+                .flat_map(str::lines)
+                .find_map(|line| {
+                    let line = line.trim().trim_start_matches(['*', '_']);
+                    let rest = line
+                        .get(..6)?
+                        .eq_ignore_ascii_case("since:")
+                        .then(|| &line[6..])?;
+                    version::find(rest)
+                })
+        };
+
+        section
+            .or_else(prose)
+            .or_else(|| version::find(self.metadata().get("since")?))
+    }
+
+    /// Compares this comment with a newer version of it.
+    ///
+    /// The result lists added, removed and edited sections and arguments,
+    /// and word-level edits to the description. Changes in whitespace alone
+    /// are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    /// use nixdoc::diff::{Hunk, SectionChange};
+    ///
+    /// let old = DocComment::parse("/**\n  Sort a list.\n\n  # Note\n\n  Stable.\n*/").unwrap();
+    /// let new = DocComment::parse("/**\n  Sort a list quickly.\n*/").unwrap();
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(
+    ///     diff.description,
+    ///     [
+    ///         Hunk::Equal("Sort a".into()),
+    ///         Hunk::Removed("list.".into()),
+    ///         Hunk::Inserted("list quickly.".into()),
+    ///     ]
+    /// );
+    /// assert_eq!(diff.sections, [SectionChange::Removed("Note".into())]);
+    /// assert!(old.diff(&old).is_empty());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn diff(&self, other: &DocComment) -> diff::DocDiff {
+        diff::diff_comments(self, other)
+    }
+
+    /// Render the comment as normalized Markdown: the description, then the
+    /// sections in a canonical order, with notes and warnings as admonition
+    /// blocks. See [`render::markdown`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse("/**\n  # Type\n\n  `f :: a`\n\n  F.\n*/").unwrap();
+    /// assert!(doc.to_markdown().starts_with("# Type\n"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_markdown(&self) -> String {
+        render::markdown::render(self)
+    }
+
+    /// Render the comment as an HTML fragment, with anchored section
+    /// headings and highlighted `nix` code blocks. See [`render::html`].
+    ///
+    /// Requires the `html` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    /// use nixdoc::render::html::HtmlOptions;
+    ///
+    /// let doc = DocComment::parse("/**\n  F.\n\n  # Note\n\n  Slow.\n*/").unwrap();
+    /// let html = doc.to_html(&HtmlOptions::new());
+    /// assert!(html.contains("<div class=\"admonition note\">"));
+    /// ```
+    #[cfg(feature = "html")]
+    pub fn to_html(&self, options: &render::html::HtmlOptions) -> String {
+        render::html::render(self, options)
+    }
+
+    /// Merges two edited versions of a comment against their common base.
+    ///
+    /// The description, each section and each link definition are merged as
+    /// a unit: a unit changed on one side only takes that side's version,
+    /// and a unit changed differently on both sides is written with conflict
+    /// markers. Sections keep our order; sections only they added follow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let base = DocComment::parse("/**\n  Sort.\n\n  # Note\n\n  Stable.\n*/").unwrap();
+    /// let ours = DocComment::parse("/**\n  Sort a list.\n\n  # Note\n\n  Stable.\n*/").unwrap();
+    /// let theirs = DocComment::parse("/**\n  Sort.\n\n  # Note\n\n  Not stable.\n*/").unwrap();
+    ///
+    /// let merge = DocComment::merge3(&base, &ours, &theirs);
+    /// assert!(merge.is_clean());
+    /// assert_eq!(merge.text, "Sort a list.\n\n# Note\n\nNot stable.");
+    /// assert_eq!(merge.to_doc().unwrap().notes(), ["Not stable."]);
+    /// ```
+    pub fn merge3(base: &DocComment, ours: &DocComment, theirs: &DocComment) -> merge::Merge {
+        merge::merge3(base, ours, theirs)
+    }
+
+    /// Applies a [`patch::DocPatch`], returning the edited comment.
+    ///
+    /// The edited comment is written back as Markdown and parsed again, so
+    /// its [`Self::raw_content`] has no comment markers or indentation.
+    /// Fails without applying anything if an edit names a missing section or
+    /// argument.
+    pub fn apply(&self, patch: &patch::DocPatch) -> Result<DocComment, patch::PatchError> {
+        patch::apply(self, patch)
+    }
+
+    /// Returns the languages the comment has translations into, in order of
+    /// appearance, see [`localize`].
+    pub fn languages(&self) -> Vec<&str> {
+        localize::languages(self)
+    }
+
+    /// Returns the comment as read in `lang`: its translations into `lang`
+    /// replace the English originals, which remain where there is no
+    /// translation. Translations into other languages are left out, so
+    /// `localized("en")` is the original alone. See [`localize`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  Sort a list.\n\n  # Description (fr)\n\n  Trie une liste.\n\n  # Note\n\n  Stable.\n\n  # Note (de)\n\n  Stabil.\n*/",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.languages(), ["fr", "de"]);
+    ///
+    /// let fr = doc.localized("fr");
+    /// assert_eq!(fr.description(), "Trie une liste.");
+    /// assert_eq!(fr.notes(), ["Stable."]);
+    ///
+    /// let de = doc.localized("de");
+    /// assert_eq!(de.description(), "Sort a list.");
+    /// assert_eq!(de.notes(), ["Stabil."]);
+    /// assert_eq!(doc.localized("en").sections.len(), 1);
+    /// ```
+    pub fn localized(&self, lang: &str) -> DocComment {
+        localize::localized(self, lang)
+    }
+
+    /// Computes the title, summary, type, arguments, examples and the other
+    /// commonly shown fields at once, see [`DocView`].
+    #[cfg(feature = "std")]
+    pub fn view(&self) -> DocView {
+        DocView::new(self)
+    }
+
+    /// Returns `true` if the two comments say the same thing, differing at
+    /// most in whitespace and in the casing of headings.
+    ///
+    /// Runs of whitespace, including line breaks and indentation, count as a
+    /// single space. Warnings and [`Self::raw_content`] are not compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let a = DocComment::parse("/**\n  Sort a\n  list.\n\n  # Note\n\n  Stable.\n*/").unwrap();
+    /// let b = DocComment::parse("/**\n  Sort a list.\n\n  # NOTE\n\n    Stable.\n*/").unwrap();
+    /// let c = DocComment::parse("/**\n  Sort a list.\n\n  # Note\n\n  Unstable.\n*/").unwrap();
+    /// assert_ne!(a, b);
+    /// assert!(a.semantic_eq(&b));
+    /// assert!(!a.semantic_eq(&c));
+    /// ```
+    pub fn semantic_eq(&self, other: &DocComment) -> bool {
+        self.semantic_form() == other.semantic_form()
+    }
+
+    /// Returns a hash of the comment that is equal for comments that are
+    /// [`semantic_eq`](Self::semantic_eq).
+    ///
+    /// The hash is 64-bit FNV-1a of a normalized form of the comment, so it
+    /// is the same on every platform and with every build, and can be
+    /// stored in caches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let a = DocComment::parse("/** Sort a\n    list. */").unwrap();
+    /// let b = DocComment::parse("/** Sort a list. */").unwrap();
+    /// assert_eq!(a.semantic_hash(), b.semantic_hash());
+    /// ```
+    pub fn semantic_hash(&self) -> u64 {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+        self.semantic_form().bytes().fold(OFFSET, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+    }
+
+    /// The text [`Self::semantic_eq`] and [`Self::semantic_hash`] compare:
+    /// every part with whitespace collapsed, separated by control
+    /// characters.
+    fn semantic_form(&self) -> String {
+        fn push_words(out: &mut String, text: &str) {
+            for (i, word) in text.split_whitespace().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                out.push_str(word);
+            }
+        }
+
+        let mut out = String::new();
+        push_words(&mut out, &self.description);
+        for section in &self.sections {
+            out.push('\u{1e}');
+            push_words(&mut out, &section.heading.to_lowercase());
+            out.push('\u{1f}');
+            out.push_str(section.explicit_id.as_deref().unwrap_or_default());
+            out.push('\u{1f}');
+            push_words(&mut out, &section.content);
+        }
+        for link in &self.link_definitions {
+            out.push('\u{1d}');
+            push_words(&mut out, &link.label.to_lowercase());
+            out.push('\u{1f}');
+            out.push_str(&link.url);
+            out.push('\u{1f}');
+            push_words(&mut out, link.title.as_deref().unwrap_or_default());
+        }
+        out
     }
 }
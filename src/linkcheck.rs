@@ -0,0 +1,308 @@
+//! Checking the external links in doc comments.
+//!
+//! Enabled by the `linkcheck` feature.
+//!
+//! [`check_doc`] looks at the HTTP(S) URLs of a comment without touching the
+//! network and flags those that are malformed, insecure, or point at the
+//! local machine. A [`LinkChecker`] additionally requests every URL, a few at
+//! a time, and reports those that are broken or unreachable.
+//!
+//! Requests are made by an external program, `curl` by default, see
+//! [`Fetcher`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::extract::DocumentedItem;
+pub use crate::links::{Link, extract_links};
+use crate::{DocComment, DocSet, Span};
+
+/// Check the links of `doc` without accessing the network.
+///
+/// | Code | Severity | Problem |
+/// |------|----------|---------|
+/// | `invalid-url` | error | the URL has no valid host |
+/// | `local-url` | warning | the URL points at `localhost` or a loopback address |
+/// | `insecure-url` | info | the URL uses `http` rather than `https` |
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+/// use nixdoc::linkcheck::check_doc;
+///
+/// let doc = DocComment::parse("/** See http://nixos.org. */").unwrap();
+/// let diagnostics = check_doc(&doc);
+/// assert_eq!(diagnostics[0].code, "insecure-url");
+/// ```
+pub fn check_doc(doc: &DocComment) -> Vec<Diagnostic> {
+    doc.urls().iter().filter_map(check_link).collect()
+}
+
+fn check_link(link: &Link) -> Option<Diagnostic> {
+    let (scheme, rest) = link.url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = match host_port.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+
+    let diagnostic =
+        |code, severity, message: String| Some(Diagnostic::new(code, severity, message, link.span));
+
+    let valid_host = !host.is_empty()
+        && (host.contains(':')
+            || host.split('.').all(|label| {
+                !label.is_empty()
+                    && label
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            }));
+    if !valid_host {
+        return diagnostic(
+            "invalid-url",
+            Severity::Error,
+            format!("'{}' has no valid host", link.url),
+        );
+    }
+
+    let is_local = host.eq_ignore_ascii_case("localhost")
+        || host.starts_with("127.")
+        || host == "::1"
+        || host == "0.0.0.0";
+    if is_local {
+        return diagnostic(
+            "local-url",
+            Severity::Warning,
+            format!("'{}' points at the local machine", link.url),
+        );
+    }
+
+    if scheme == "http" {
+        return diagnostic(
+            "insecure-url",
+            Severity::Info,
+            format!("'{}' uses http; prefer https", link.url),
+        );
+    }
+    None
+}
+
+/// The program used to request URLs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Fetcher {
+    /// `curl`, following redirects and making `HEAD` requests.
+    #[default]
+    Curl,
+
+    /// A custom program. The URL is passed as the final argument and the
+    /// program must print the HTTP status code on stdout.
+    Command {
+        /// The program to run.
+        program: String,
+        /// Arguments placed before the URL.
+        args: Vec<String>,
+    },
+}
+
+impl Fetcher {
+    /// Request `url` and return its HTTP status code.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the failure if the program could not be run,
+    /// exited unsuccessfully, or printed no status code.
+    pub fn status(&self, url: &str, timeout: Duration) -> Result<u16, String> {
+        let mut cmd = match self {
+            Self::Curl => {
+                let mut cmd = Command::new("curl");
+                cmd.args(["--silent", "--show-error", "--head", "--location"])
+                    .args(["--output", "/dev/null", "--write-out", "%{http_code}"])
+                    .arg("--max-time")
+                    .arg(timeout.as_secs().max(1).to_string());
+                cmd
+            }
+            Self::Command { program, args } => {
+                let mut cmd = Command::new(program);
+                cmd.args(args);
+                cmd
+            }
+        };
+        let output = cmd
+            .arg(url)
+            .output()
+            .map_err(|e| format!("failed to run fetcher: {e}"))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match stdout.trim().parse::<u16>() {
+            Ok(status) if output.status.success() && status != 0 => Ok(status),
+            _ => {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                Err(if stderr.is_empty() {
+                    "no HTTP status received".to_string()
+                } else {
+                    stderr
+                })
+            }
+        }
+    }
+}
+
+/// Checks links offline and by requesting them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use nixdoc::DocSet;
+/// use nixdoc::linkcheck::LinkChecker;
+///
+/// let set = DocSet::from_paths(&["lib"]).unwrap();
+/// for (item, diagnostic) in LinkChecker::new().check_set(&set) {
+///     println!("{}:{}: {diagnostic}", item.file.as_ref().unwrap().display(), item.line);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LinkChecker {
+    fetcher: Fetcher,
+    concurrency: usize,
+    timeout: Duration,
+}
+
+impl Default for LinkChecker {
+    fn default() -> Self {
+        Self {
+            fetcher: Fetcher::default(),
+            concurrency: 8,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl LinkChecker {
+    /// Create a checker using `curl`, eight requests at a time, with a
+    /// ten-second timeout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the program used to request URLs.
+    pub fn with_fetcher(mut self, fetcher: Fetcher) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// Set the maximum number of requests in flight. Zero is treated as one.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the timeout of each request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Check the links of one comment.
+    pub fn check_doc(&self, doc: &DocComment) -> Vec<Diagnostic> {
+        let links = doc.urls();
+        let statuses = self.fetch(links.iter().map(|link| link.url.as_str()));
+        self.diagnose(&links, &statuses)
+    }
+
+    /// Check the links of every item in `set`.
+    ///
+    /// Each distinct URL is requested once, however often it appears. The
+    /// diagnostics carry the [provenance](DocumentedItem::provenance) of
+    /// their item.
+    pub fn check_set<'a>(&self, set: &'a DocSet) -> Vec<(&'a DocumentedItem, Diagnostic)> {
+        let links: Vec<(&DocumentedItem, Vec<Link>)> =
+            set.iter().map(|item| (item, item.doc.urls())).collect();
+        let statuses = self.fetch(
+            links
+                .iter()
+                .flat_map(|(_, links)| links.iter().map(|link| link.url.as_str())),
+        );
+
+        links
+            .iter()
+            .flat_map(|(item, links)| {
+                self.diagnose(links, &statuses)
+                    .into_iter()
+                    .map(move |diagnostic| (*item, diagnostic.with_provenance(item.provenance())))
+            })
+            .collect()
+    }
+
+    fn diagnose(
+        &self,
+        links: &[Link],
+        statuses: &BTreeMap<String, Result<u16, String>>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for link in links {
+            if let Some(diagnostic) = check_link(link) {
+                let is_error = diagnostic.severity == Severity::Error;
+                diagnostics.push(diagnostic);
+                if is_error {
+                    continue;
+                }
+            }
+            match statuses.get(&link.url) {
+                Some(Ok(status)) if *status >= 400 => diagnostics.push(Diagnostic::new(
+                    "broken-url",
+                    Severity::Error,
+                    format!("'{}' returned HTTP {status}", link.url),
+                    link.span,
+                )),
+                Some(Err(error)) => diagnostics.push(Diagnostic::new(
+                    "unreachable-url",
+                    Severity::Warning,
+                    format!("'{}' could not be fetched: {error}", link.url),
+                    link.span,
+                )),
+                _ => {}
+            }
+        }
+        diagnostics
+    }
+
+    /// Request every distinct, well-formed URL, `concurrency` at a time.
+    fn fetch<'a>(
+        &self,
+        urls: impl Iterator<Item = &'a str>,
+    ) -> BTreeMap<String, Result<u16, String>> {
+        let urls: BTreeSet<&str> = urls
+            .filter(|url| {
+                let link = Link {
+                    url: url.to_string(),
+                    section: None,
+                    span: Span::default(),
+                };
+                check_link(&link).is_none_or(|d| d.severity != Severity::Error)
+            })
+            .collect();
+        let queue = Mutex::new(urls.into_iter());
+        let results = Mutex::new(BTreeMap::new());
+
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency {
+                scope.spawn(|| {
+                    loop {
+                        let Some(url) = queue.lock().unwrap().next() else {
+                            break;
+                        };
+                        let status = self.fetcher.status(url, self.timeout);
+                        results.lock().unwrap().insert(url.to_string(), status);
+                    }
+                });
+            }
+        });
+        results.into_inner().unwrap()
+    }
+}
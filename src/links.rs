@@ -0,0 +1,188 @@
+//! External links in doc comments.
+//!
+//! See [`DocComment::urls`] and, for references to other identifiers,
+//! [`DocComment::references`]. Reference-style link definitions are
+//! collected into [`DocComment::link_definitions`].
+
+use core::fmt;
+
+use crate::parser::{section_at, section_headings};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, Span};
+
+/// A Markdown link reference definition, such as
+/// `[rfc145]: https://github.com/NixOS/rfcs/pull/145 "RFC 145"`.
+///
+/// Definitions apply to the whole comment, so the parser removes them from
+/// the description and sections they were written in and collects them in
+/// [`DocComment::link_definitions`]. Renderers that output sections on
+/// their own append them wherever they are needed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkDefinition {
+    /// The label, as written between the brackets.
+    pub label: String,
+    /// The link destination.
+    pub url: String,
+    /// The optional link title.
+    pub title: Option<String>,
+}
+
+impl LinkDefinition {
+    /// Returns `true` if `label` refers to this definition.
+    ///
+    /// Labels match case-insensitively, with runs of whitespace treated as a
+    /// single space.
+    pub fn matches(&self, label: &str) -> bool {
+        normalize_label(&self.label) == normalize_label(label)
+    }
+
+    /// Parse a line holding a link reference definition.
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        if indent > 3 {
+            return None;
+        }
+        let rest = line.trim().strip_prefix('[')?;
+        let (label, rest) = rest.split_once("]:")?;
+        if label.trim().is_empty() || label.starts_with('^') || label.contains(['[', ']']) {
+            return None;
+        }
+
+        let rest = rest.trim();
+        let (url, title) = match rest.split_once(char::is_whitespace) {
+            Some((url, title)) => (url, Some(title.trim())),
+            None => (rest, None),
+        };
+        let url = url
+            .strip_prefix('<')
+            .and_then(|u| u.strip_suffix('>'))
+            .unwrap_or(url);
+        if url.is_empty() {
+            return None;
+        }
+        let title = match title {
+            Some(title) => Some(unquote_title(title)?),
+            None => None,
+        };
+
+        Some(Self {
+            label: label.to_string(),
+            url: url.to_string(),
+            title,
+        })
+    }
+}
+
+/// Formats as the Markdown definition.
+impl fmt::Display for LinkDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]: {}", self.label, self.url)?;
+        if let Some(title) = &self.title {
+            write!(f, " {title:?}")?;
+        }
+        Ok(())
+    }
+}
+
+fn unquote_title(title: &str) -> Option<String> {
+    [('"', '"'), ('\'', '\''), ('(', ')')]
+        .into_iter()
+        .find_map(|(open, close)| title.strip_prefix(open)?.strip_suffix(close))
+        .filter(|inner| !inner.is_empty() || title.len() == 2)
+        .map(str::to_string)
+}
+
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// An external link in a doc comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Link {
+    /// The URL, without surrounding Markdown syntax.
+    pub url: String,
+    /// The heading of the section containing the link, or `None` in the
+    /// description.
+    pub section: Option<String>,
+    /// The span of the URL within [`DocComment::raw_content`].
+    pub span: Span,
+}
+
+/// Find the HTTP(S) URLs in `text`.
+///
+/// Links found this way have no section; see [`DocComment::urls`] for links
+/// labelled with their section.
+///
+/// URLs are recognized wherever they start, whether bare, in an autolink
+/// (`<https://…>`) or as a Markdown link destination. Trailing punctuation
+/// and unbalanced closing parentheses are not part of the URL.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::links::extract_links;
+///
+/// let links = extract_links("See [Union](https://en.wikipedia.org/wiki/Union_(set_theory)).");
+/// assert_eq!(links[0].url, "https://en.wikipedia.org/wiki/Union_(set_theory)");
+/// ```
+pub fn extract_links(text: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = find_scheme(&text[pos..]) {
+        let start = pos + found;
+        let len = url_len(&text[start..]);
+        links.push(Link {
+            url: text[start..start + len].to_string(),
+            section: None,
+            span: Span::new(start, start + len),
+        });
+        pos = start + len.max(1);
+    }
+    links
+}
+
+fn find_scheme(text: &str) -> Option<usize> {
+    let http = text.find("http://");
+    let https = text.find("https://");
+    match (http, https) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// The length of the URL at the start of `text`.
+fn url_len(text: &str) -> usize {
+    let mut depth = 0usize;
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            c if c.is_whitespace() => break,
+            '<' | '>' | '"' | '`' | '[' | ']' | '{' | '}' | '|' | '\\' => break,
+            '(' => depth += 1,
+            ')' if depth == 0 => break,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        end = i + c.len_utf8();
+    }
+    text[..end]
+        .trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '*', '_'])
+        .len()
+}
+
+/// Find the URLs of `doc`, labelled with their section.
+pub(crate) fn urls(doc: &DocComment) -> Vec<Link> {
+    let headings = section_headings(&doc.raw_content);
+    let mut links = extract_links(&doc.raw_content);
+    for link in &mut links {
+        link.section = section_at(&headings, link.span.start).map(str::to_string);
+    }
+    links
+}
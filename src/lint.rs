@@ -0,0 +1,342 @@
+//! Doc quality rules with stable codes, for CI checks.
+//!
+//! Parse warnings say whether a comment is well-formed. A [`Linter`] says
+//! whether it is good documentation, by the [`Rule`]s it is configured
+//! with, and reports each problem as a [`Diagnostic`] whose code, such as
+//! `ND001`, stays the same across releases:
+//!
+//! | Code | Name | Default severity |
+//! |------|------|------------------|
+//! | `ND001` | `missing-type` | warning |
+//! | `ND002` | `unlabeled-example` | warning |
+//! | `ND003` | `empty-description` | error |
+//! | `ND004` | `unknown-section` | info |
+//! | `ND005` | `empty-section` | warning |
+//! | `ND006` | `mixed-style` | warning |
+//! | `ND007` | `legacy-comment` | warning |
+//! | `ND008` | `javadoc-gutter` | info |
+//! | `ND009` | `unusual-whitespace` | info |
+//! | `ND010` | `heading-style` | info |
+//!
+//! Rules from `ND005` on report the parse warning of the same name, so a
+//! [`SpecProfile`] can name the warnings it forbids as rules, see
+//! [`SpecProfile::lints`].
+//!
+//! Codes are never reused; new rules get new codes.
+
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::localize::split_language;
+use crate::parser::section_headings;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, SectionKind, Span, SpecProfile, WarningKind};
+#[cfg(feature = "std")]
+use crate::{DocSet, extract::DocumentedItem};
+
+/// A check a [`Linter`] can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rule {
+    /// `ND001`: the comment has no type signature.
+    MissingType,
+    /// `ND002`: a code block in an example section has no language.
+    UnlabeledExample,
+    /// `ND003`: the comment has no description.
+    EmptyDescription,
+    /// `ND004`: a section heading is not one the profile recognizes.
+    UnknownSection,
+    /// `ND005`: a section has a heading but no content.
+    EmptySection,
+    /// `ND006`: the comment mixes legacy and RFC 145 styles.
+    MixedStyle,
+    /// `ND007`: the comment is a plain `/* … */` comment.
+    LegacyComment,
+    /// `ND008`: lines start with a javadoc-style `*` gutter.
+    JavadocGutter,
+    /// `ND009`: indentation or a heading had unusual Unicode whitespace.
+    UnusualWhitespace,
+    /// `ND010`: a heading ends with a closing `#` sequence or a colon.
+    HeadingStyle,
+}
+
+impl Rule {
+    /// Every rule, in code order.
+    pub const ALL: [Self; 10] = [
+        Self::MissingType,
+        Self::UnlabeledExample,
+        Self::EmptyDescription,
+        Self::UnknownSection,
+        Self::EmptySection,
+        Self::MixedStyle,
+        Self::LegacyComment,
+        Self::JavadocGutter,
+        Self::UnusualWhitespace,
+        Self::HeadingStyle,
+    ];
+
+    /// The stable code of the rule, such as `ND001`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::MissingType => "ND001",
+            Self::UnlabeledExample => "ND002",
+            Self::EmptyDescription => "ND003",
+            Self::UnknownSection => "ND004",
+            Self::EmptySection => "ND005",
+            Self::MixedStyle => "ND006",
+            Self::LegacyComment => "ND007",
+            Self::JavadocGutter => "ND008",
+            Self::UnusualWhitespace => "ND009",
+            Self::HeadingStyle => "ND010",
+        }
+    }
+
+    /// The readable name of the rule, such as `missing-type`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MissingType => "missing-type",
+            Self::UnlabeledExample => "unlabeled-example",
+            Self::EmptyDescription => "empty-description",
+            Self::UnknownSection => "unknown-section",
+            Self::EmptySection => "empty-section",
+            Self::MixedStyle => "mixed-style",
+            Self::LegacyComment => "legacy-comment",
+            Self::JavadocGutter => "javadoc-gutter",
+            Self::UnusualWhitespace => "unusual-whitespace",
+            Self::HeadingStyle => "heading-style",
+        }
+    }
+
+    /// The kind of parse warning the rule reports, for rules from `ND005`
+    /// on.
+    pub fn warning_kind(self) -> Option<WarningKind> {
+        Some(match self {
+            Self::MissingType
+            | Self::UnlabeledExample
+            | Self::EmptyDescription
+            | Self::UnknownSection => return None,
+            Self::EmptySection => WarningKind::EmptySection,
+            Self::MixedStyle => WarningKind::MixedStyle,
+            Self::LegacyComment => WarningKind::LegacyComment,
+            Self::JavadocGutter => WarningKind::JavadocGutter,
+            Self::UnusualWhitespace => WarningKind::UnusualWhitespace,
+            Self::HeadingStyle => WarningKind::HeadingStyle,
+        })
+    }
+
+    /// Look up a rule by its [code](Self::code) or [name](Self::name),
+    /// case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::lint::Rule;
+    ///
+    /// assert_eq!(Rule::from_name("nd002"), Some(Rule::UnlabeledExample));
+    /// assert_eq!(Rule::from_name("missing-type"), Some(Rule::MissingType));
+    /// assert_eq!(Rule::from_name("ND999"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|rule| {
+            rule.code().eq_ignore_ascii_case(name) || rule.name().eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// The severity of the rule's diagnostics unless configured otherwise.
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Self::MissingType
+            | Self::UnlabeledExample
+            | Self::EmptySection
+            | Self::MixedStyle
+            | Self::LegacyComment => Severity::Warning,
+            Self::EmptyDescription => Severity::Error,
+            Self::UnknownSection
+            | Self::JavadocGutter
+            | Self::UnusualWhitespace
+            | Self::HeadingStyle => Severity::Info,
+        }
+    }
+}
+
+impl core::fmt::Display for Rule {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Runs a configurable set of [`Rule`]s over doc comments.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+/// use nixdoc::lint::{Linter, Rule};
+///
+/// let doc = DocComment::parse("/**\n  Id.\n\n  # Example\n\n  ```\n  id 1\n  ```\n*/").unwrap();
+/// let diagnostics = Linter::new().check(&doc);
+/// let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+/// assert_eq!(codes, ["ND001", "ND002"]);
+///
+/// let diagnostics = Linter::new().without_rule(Rule::MissingType).check(&doc);
+/// assert_eq!(diagnostics.len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linter {
+    rules: Vec<(Rule, Severity)>,
+    profile: SpecProfile,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self {
+            rules: Rule::ALL
+                .into_iter()
+                .map(|rule| (rule, rule.default_severity()))
+                .collect(),
+            profile: SpecProfile::default(),
+        }
+    }
+}
+
+impl Linter {
+    /// Create a linter running every rule at its default severity, judging
+    /// sections by [`SpecProfile::NixpkgsCurrent`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `rule`, reporting its problems with `severity`.
+    pub fn with_rule(mut self, rule: Rule, severity: Severity) -> Self {
+        match self.rules.iter_mut().find(|(r, _)| *r == rule) {
+            Some(entry) => entry.1 = severity,
+            None => {
+                self.rules.push((rule, severity));
+                self.rules.sort();
+            }
+        }
+        self
+    }
+
+    /// Do not run `rule`.
+    pub fn without_rule(mut self, rule: Rule) -> Self {
+        self.rules.retain(|(r, _)| *r != rule);
+        self
+    }
+
+    /// Create a linter running the [rules of `profile`](SpecProfile::lints)
+    /// at their default severities, judging sections by `profile`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::lint::{Linter, Rule};
+    /// use nixdoc::SpecProfile;
+    ///
+    /// let linter = Linter::for_profile(SpecProfile::NixpkgsCurrent);
+    /// let rules: Vec<Rule> = linter.rules().iter().map(|(rule, _)| *rule).collect();
+    /// assert_eq!(rules, [Rule::EmptySection, Rule::MixedStyle, Rule::LegacyComment]);
+    /// ```
+    pub fn for_profile(profile: SpecProfile) -> Self {
+        let mut rules: Vec<(Rule, Severity)> = profile
+            .lints()
+            .iter()
+            .map(|&rule| (rule, rule.default_severity()))
+            .collect();
+        rules.sort();
+        Self { rules, profile }
+    }
+
+    /// Judge which sections are unknown by `profile`.
+    pub fn with_profile(mut self, profile: SpecProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// The rules the linter runs, with their severities, in code order.
+    pub fn rules(&self) -> &[(Rule, Severity)] {
+        &self.rules
+    }
+
+    /// Check `doc`, returning its problems in rule order.
+    ///
+    /// Spans are relative to [`DocComment::raw_content`]. Problems with the
+    /// comment as a whole point at its first line.
+    pub fn check(&self, doc: &DocComment) -> Vec<Diagnostic> {
+        let raw = &doc.raw_content;
+        let first_line = Span::new(0, raw.find('\n').unwrap_or(raw.len()));
+        let mut diagnostics = Vec::new();
+        for &(rule, severity) in &self.rules {
+            let mut report = |message: String, span: Span| {
+                diagnostics.push(Diagnostic::new(rule.code(), severity, message, span));
+            };
+            match rule {
+                Rule::MissingType => {
+                    if doc.type_sig().is_none() {
+                        report(
+                            "no type signature; add a `# Type` section".into(),
+                            first_line,
+                        );
+                    }
+                }
+                Rule::UnlabeledExample => {
+                    for block in doc.code_blocks() {
+                        let in_example = matches!(
+                            block.section_kind,
+                            Some(SectionKind::Example | SectionKind::Examples)
+                        );
+                        if in_example && block.language.is_none() {
+                            report(
+                                "example code block has no language; label it `nix`".into(),
+                                block.span,
+                            );
+                        }
+                    }
+                }
+                Rule::EmptyDescription => {
+                    if doc.description.trim().is_empty() {
+                        report("the comment has no description".into(), first_line);
+                    }
+                }
+                Rule::EmptySection
+                | Rule::MixedStyle
+                | Rule::LegacyComment
+                | Rule::JavadocGutter
+                | Rule::UnusualWhitespace
+                | Rule::HeadingStyle => {
+                    for warning in &doc.warnings {
+                        if rule.warning_kind().as_ref() == Some(&warning.kind) {
+                            report(warning.message.clone(), first_line);
+                        }
+                    }
+                }
+                Rule::UnknownSection => {
+                    for (start, heading) in section_headings(raw) {
+                        let (text, _) = split_language(&heading);
+                        if self.profile.recognizes(&SectionKind::from_heading(text)) {
+                            continue;
+                        }
+                        let end = raw[start..].find('\n').map_or(raw.len(), |n| start + n);
+                        report(
+                            format!("unknown section '{heading}'"),
+                            Span::new(start, end),
+                        );
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// Check every item of `set`, attaching each item's
+    /// [provenance](DocumentedItem::provenance) to its diagnostics.
+    #[cfg(feature = "std")]
+    pub fn check_set<'a>(&self, set: &'a DocSet) -> Vec<(&'a DocumentedItem, Diagnostic)> {
+        set.iter()
+            .flat_map(|item| {
+                self.check(&item.doc)
+                    .into_iter()
+                    .map(move |diagnostic| (item, diagnostic.with_provenance(item.provenance())))
+            })
+            .collect()
+    }
+}
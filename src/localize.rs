@@ -0,0 +1,158 @@
+//! Translations kept next to the original text, in one comment.
+//!
+//! A section whose heading ends in a language tag, such as
+//! `# Description (fr)` or `# Examples (de)`, is a translation. The rest of
+//! the comment is the English original:
+//!
+//! ```nix
+//! /**
+//!   Sort a list.
+//!
+//!   # Description (de)
+//!
+//!   Sortiert eine Liste.
+//!
+//!   # Examples
+//!
+//!   …
+//!
+//!   # Examples (de)
+//!
+//!   …
+//! */
+//! ```
+//!
+//! [`DocComment::localized`] picks one language. A `Description`
+//! translation replaces the description; any other translation replaces the
+//! original section of the same [kind](crate::SectionKind), so headings
+//! should stay in English to be matched. A translation without an original
+//! is kept where it is, and an original without a translation is kept as
+//! the fallback.
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, Section};
+
+/// Split a heading into its text and its language tag, if it ends in one.
+///
+/// A tag is a two-letter language code with optional subtags, in
+/// parentheses: `(fr)`, `(pt-BR)` or `(zh-Hans)`.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::localize::split_language;
+///
+/// assert_eq!(split_language("Examples (pt-BR)"), ("Examples", Some("pt-BR")));
+/// assert_eq!(split_language("Examples"), ("Examples", None));
+/// assert_eq!(split_language("Arguments (optional)"), ("Arguments (optional)", None));
+/// ```
+pub fn split_language(heading: &str) -> (&str, Option<&str>) {
+    let parsed = heading
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" ("))
+        .filter(|(_, tag)| is_language_tag(tag));
+    match parsed {
+        Some((text, tag)) => (text.trim_end(), Some(tag)),
+        None => (heading, None),
+    }
+}
+
+fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    primary.len() == 2
+        && primary.bytes().all(|b| b.is_ascii_alphabetic())
+        && subtags.all(|subtag| {
+            (2..=8).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+        })
+}
+
+/// The distinct language tags of `doc`'s sections, in order of appearance.
+pub(crate) fn languages(doc: &DocComment) -> Vec<&str> {
+    let mut languages: Vec<&str> = Vec::new();
+    for section in &doc.sections {
+        if let Some(tag) = section.language()
+            && !languages.iter().any(|l| l.eq_ignore_ascii_case(tag))
+        {
+            languages.push(tag);
+        }
+    }
+    languages
+}
+
+/// The view of `doc` in `lang`, see the [module documentation](self).
+pub(crate) fn localized(doc: &DocComment, lang: &str) -> DocComment {
+    let is_lang = |section: &Section| {
+        section
+            .language()
+            .is_some_and(|tag| tag.eq_ignore_ascii_case(lang))
+    };
+    let translations: Vec<(usize, Section)> = doc
+        .sections
+        .iter()
+        .enumerate()
+        .filter(|(_, section)| is_lang(section))
+        .map(|(i, section)| {
+            let section = Section {
+                heading: split_language(&section.heading).0.to_string(),
+                ..section.clone()
+            };
+            (i, section)
+        })
+        .collect();
+
+    let mut description = doc.description.clone();
+    let mut used = vec![false; doc.sections.len()];
+    if let Some((i, section)) = translations
+        .iter()
+        .find(|(_, section)| section.heading.eq_ignore_ascii_case("description"))
+    {
+        description = section.content.clone();
+        used[*i] = true;
+    }
+
+    // Pair each original with the first unused translation of its kind.
+    let mut replacements = vec![None; doc.sections.len()];
+    for (i, section) in doc.sections.iter().enumerate() {
+        if section.language().is_some() {
+            continue;
+        }
+        let kind = section.kind();
+        if let Some((j, translation)) = translations
+            .iter()
+            .find(|(j, translation)| !used[*j] && translation.kind() == kind)
+        {
+            used[*j] = true;
+            replacements[i] = Some(Section {
+                explicit_id: translation
+                    .explicit_id
+                    .clone()
+                    .or_else(|| section.explicit_id.clone()),
+                ..translation.clone()
+            });
+        }
+    }
+
+    let mut sections = Vec::new();
+    let mut translations = translations.into_iter().peekable();
+    for (i, section) in doc.sections.iter().enumerate() {
+        if let Some(replacement) = replacements[i].take() {
+            sections.push(replacement);
+        } else if section.language().is_none() {
+            sections.push(section.clone());
+        } else if let Some((_, translation)) = translations.next_if(|(j, _)| *j == i)
+            && !used[i]
+        {
+            sections.push(translation);
+        }
+    }
+
+    DocComment {
+        raw_content: doc.raw_content.clone(),
+        description,
+        sections,
+        link_definitions: doc.link_definitions.clone(),
+        warnings: doc.warnings.clone(),
+    }
+}
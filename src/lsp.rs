@@ -0,0 +1,697 @@
+//! Building blocks for language servers.
+//!
+//! Language servers such as nil and nixd can use nixdoc as their
+//! documentation backend: [`hover_markup`] renders the hover text for a
+//! documented binding, [`completions_at`] suggests section headings and
+//! argument names while a comment is typed, [`symbols`] outlines a comment,
+//! [`code_actions`] offers fixes for missing structure, and [`Position`] and
+//! [`Range`]
+//! convert between byte offsets and the line and UTF-16 column positions of
+//! the Language Server Protocol. The types serialize, with the `serde`
+//! feature, to the JSON the protocol expects.
+//!
+//! With the `lsp-types` feature, [`Position`] and [`Range`] convert to their
+//! [`lsp_types`] counterparts, and [`to_lsp_diagnostics`] turns parse
+//! warnings and [`Diagnostic`](crate::Diagnostic)s into the values a server
+//! publishes.
+
+use crate::extract::{self, DocumentedItem};
+use crate::format::{self, Style, TextEdit};
+use crate::parser::{LineRole, example_blocks, fence_lines, section_headings};
+use crate::patch::{DocPatch, PatchOp};
+use crate::{SectionKind, Span};
+
+/// The format of [`MarkupContent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MarkupKind {
+    /// Plain text.
+    PlainText,
+    /// Markdown.
+    Markdown,
+}
+
+/// Text for an editor to display, as in LSP `MarkupContent`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarkupContent {
+    /// The format of [`Self::value`].
+    pub kind: MarkupKind,
+    /// The text.
+    pub value: String,
+}
+
+/// A position in a text document, as in LSP `Position`.
+///
+/// Both fields are zero-based, and `character` counts UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    /// The line.
+    pub line: u32,
+    /// The UTF-16 offset within the line.
+    pub character: u32,
+}
+
+impl Position {
+    /// Returns the position of a byte offset in `source`.
+    ///
+    /// Offsets past the end of `source` map to its end, and offsets inside a
+    /// character to the start of that character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::lsp::Position;
+    ///
+    /// let src = "{\n  /** π */\n}";
+    /// let position = Position::of_offset(src, 10);
+    /// assert_eq!((position.line, position.character), (1, 7));
+    /// assert_eq!(position.to_offset(src), Some(10));
+    /// ```
+    pub fn of_offset(source: &str, offset: usize) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() as u32,
+            character: before[line_start..].encode_utf16().count() as u32,
+        }
+    }
+
+    /// Returns the byte offset of this position in `source`, or `None` if
+    /// the line does not exist.
+    ///
+    /// A character past the end of the line maps to the end of the line, as
+    /// the protocol requires.
+    pub fn to_offset(self, source: &str) -> Option<usize> {
+        let line_start = if self.line == 0 {
+            0
+        } else {
+            source
+                .match_indices('\n')
+                .nth(self.line as usize - 1)
+                .map(|(i, _)| i + 1)?
+        };
+        let line = &source[line_start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+
+        let mut units = 0;
+        for (i, c) in line.char_indices() {
+            if units >= self.character as usize {
+                return Some(line_start + i);
+            }
+            units += c.len_utf16();
+        }
+        Some(line_start + line.len())
+    }
+}
+
+/// A range in a text document, as in LSP `Range`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Range {
+    /// The first position in the range.
+    pub start: Position,
+    /// The position just past the range.
+    pub end: Position,
+}
+
+impl Range {
+    /// Returns the range of a byte span in `source`.
+    pub fn of_span(source: &str, span: Span) -> Self {
+        Self {
+            start: Position::of_offset(source, span.start),
+            end: Position::of_offset(source, span.end),
+        }
+    }
+
+    /// Returns the byte span of this range in `source`, or `None` if a line
+    /// does not exist.
+    pub fn to_span(self, source: &str) -> Option<Span> {
+        Some(Span::new(
+            self.start.to_offset(source)?,
+            self.end.to_offset(source)?,
+        ))
+    }
+}
+
+/// Render the hover text for a documented binding.
+///
+/// The Markdown has the binding's name, the first paragraph of the
+/// description, the type signature, and the first example, each when
+/// present. Further paragraphs and sections are left out to keep the hover
+/// short.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+/// use nixdoc::lsp::{MarkupKind, hover_markup};
+///
+/// let mut set = DocSet::new();
+/// set.add_source("{\n  /**\n    The identity.\n\n    More.\n  */\n  id = x: x;\n}", None);
+///
+/// let hover = hover_markup(&set.items()[0]);
+/// assert_eq!(hover.kind, MarkupKind::Markdown);
+/// assert_eq!(hover.value, "`id`\n\nThe identity.");
+/// ```
+pub fn hover_markup(item: &DocumentedItem) -> MarkupContent {
+    let doc = &item.doc;
+    let mut blocks = Vec::new();
+
+    if let Some(name) = &item.name {
+        blocks.push(format!("`{name}`"));
+    }
+    if let Some(summary) = doc.description().split("\n\n").next()
+        && !summary.is_empty()
+    {
+        blocks.push(summary.to_string());
+    }
+    if let Some(sig) = doc.type_sig() {
+        blocks.push(format!("```nix\n{}\n```", sig.trim_end()));
+    }
+    if let Some(example) = doc.examples().into_iter().next() {
+        let language = example.language.as_deref().unwrap_or("nix");
+        let heading = match &example.title {
+            Some(title) => format!("**Example:** {title}"),
+            None => "**Example**".to_string(),
+        };
+        blocks.push(format!(
+            "{heading}\n\n```{language}\n{}\n```",
+            example.code.trim_end()
+        ));
+    }
+
+    MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: blocks.join("\n\n"),
+    }
+}
+
+/// What a [`Symbol`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SymbolKind {
+    /// The documented binding, usually a function.
+    Binding,
+    /// A section.
+    Section,
+    /// An argument in an `# Arguments` section.
+    Argument,
+    /// A code block in an `# Example` or `# Examples` section.
+    Example,
+}
+
+/// An entry in the outline of a doc comment, see [`symbols`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbol {
+    /// The name to show.
+    pub name: String,
+    /// What the symbol is.
+    pub kind: SymbolKind,
+    /// Where the symbol is, relative to
+    /// [`DocComment::raw_content`](crate::DocComment::raw_content). Use
+    /// [`DocumentedItem::source_span`] to find it in the source file.
+    pub span: Span,
+    /// The symbols nested in this one, in document order.
+    pub children: Vec<Symbol>,
+}
+
+/// Outline a doc comment, for an editor's outline and breadcrumb views.
+///
+/// The root symbol is the binding, named after it, or after the comment's
+/// title if it has no name. Its children are the sections, whose children
+/// are the arguments of `# Arguments` sections and the code blocks of
+/// example sections. Examples are named after their title, or numbered.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::documented_items;
+/// use nixdoc::lsp::{SymbolKind, symbols};
+///
+/// let src = "/**\n  Id.\n\n  # Arguments\n\n  - [x] Anything\n*/\nid = x: x;";
+/// let outline = symbols(&documented_items(src, None)[0]);
+/// assert_eq!(outline.name, "id");
+/// assert_eq!(outline.children[0].name, "Arguments");
+/// assert_eq!(outline.children[0].children[0].name, "x");
+/// assert_eq!(outline.children[0].children[0].kind, SymbolKind::Argument);
+/// ```
+pub fn symbols(item: &DocumentedItem) -> Symbol {
+    let raw = &item.doc.raw_content;
+    let headings = section_headings(raw);
+
+    let mut sections = Vec::new();
+    for (i, (start, heading)) in headings.iter().enumerate() {
+        let end = headings.get(i + 1).map_or(raw.len(), |(next, _)| *next);
+        let end = start + raw[*start..end].trim_end().len();
+        let body_start = raw[*start..end].find('\n').map_or(end, |n| start + n + 1);
+        let body = &raw[body_start..end];
+
+        let children = match SectionKind::from_heading(heading) {
+            SectionKind::Arguments => argument_symbols(body, body_start),
+            SectionKind::Example | SectionKind::Examples => example_blocks(body)
+                .into_iter()
+                .enumerate()
+                .map(|(n, (span, title))| Symbol {
+                    name: title.unwrap_or_else(|| format!("Example {}", n + 1)),
+                    kind: SymbolKind::Example,
+                    span: Span::new(body_start + span.start, body_start + span.end),
+                    children: Vec::new(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        sections.push(Symbol {
+            name: heading.clone(),
+            kind: SymbolKind::Section,
+            span: Span::new(*start, end),
+            children,
+        });
+    }
+
+    let name = item
+        .name
+        .as_deref()
+        .or(item.doc.title())
+        .unwrap_or_default()
+        .to_string();
+    Symbol {
+        name,
+        kind: SymbolKind::Binding,
+        span: Span::new(0, raw.len()),
+        children: sections,
+    }
+}
+
+/// The `- [name]` items of an `# Arguments` section body starting at
+/// `offset`.
+fn argument_symbols(body: &str, offset: usize) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    let mut pos = offset;
+    for line in body.split_inclusive('\n') {
+        let line_start = pos;
+        pos += line.len();
+        let trimmed = line.trim();
+        if let Some((name, _)) = trimmed
+            .strip_prefix("- [")
+            .and_then(|rest| rest.split_once(']'))
+            && !name.trim().is_empty()
+        {
+            let start = line_start + (line.len() - line.trim_start().len());
+            symbols.push(Symbol {
+                name: name.trim().to_string(),
+                kind: SymbolKind::Argument,
+                span: Span::new(start, start + trimmed.len()),
+                children: Vec::new(),
+            });
+        }
+    }
+    symbols
+}
+
+/// A fix offered for a doc comment, see [`code_actions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodeAction {
+    /// The title to show, such as "Document missing argument `y`".
+    pub title: String,
+    /// The change to the source file.
+    pub edit: TextEdit,
+}
+
+/// Returns fixes for missing documentation structure in `item`, found in
+/// `src`:
+///
+/// - "Add `# Type` section", with a placeholder signature, if there is none;
+/// - "Convert inline type to `# Type`", for a legacy `Type: f :: a -> a`
+///   line in the description;
+/// - "Document missing argument `y`", for each formal argument of the
+///   function after the comment that the `# Arguments` sections do not list.
+///
+/// Each fix is applied with a [`DocPatch`] and written back in the default
+/// [`Style`].
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::documented_items;
+/// use nixdoc::format::apply_edits;
+/// use nixdoc::lsp::code_actions;
+///
+/// let src = "/**\n  Pick one.\n\n  # Type\n\n  ```\n  pick :: a -> a -> a\n  ```\n*/\npick = x: y: x;";
+/// let actions = code_actions(&documented_items(src, None)[0], src);
+/// let titles: Vec<_> = actions.iter().map(|a| a.title.as_str()).collect();
+/// assert_eq!(titles, ["Document missing argument `x`", "Document missing argument `y`"]);
+/// assert!(apply_edits(src, &[actions[0].edit.clone()]).contains("# Arguments\n\n  - [x]\n"));
+/// ```
+pub fn code_actions(item: &DocumentedItem, src: &str) -> Vec<CodeAction> {
+    let doc = &item.doc;
+    let mut actions = Vec::new();
+    let mut offer = |title: String, patch: DocPatch| {
+        let edit = doc
+            .apply(&patch)
+            .ok()
+            .and_then(|patched| format::replace_comment(src, item.span, &patched, &Style::new()));
+        if let Some(edit) = edit {
+            actions.push(CodeAction { title, edit });
+        }
+    };
+
+    if doc.type_sig().is_none() {
+        let inline = doc.description().lines().enumerate().find_map(|(i, line)| {
+            let sig = line.trim().strip_prefix("Type:")?.trim();
+            (!sig.is_empty()).then_some((i, sig))
+        });
+        match inline {
+            Some((index, sig)) => {
+                let description: Vec<&str> = doc
+                    .description()
+                    .lines()
+                    .enumerate()
+                    .filter(|(i, _)| *i != index)
+                    .map(|(_, line)| line)
+                    .collect();
+                offer(
+                    "Convert inline type to `# Type`".to_string(),
+                    DocPatch::new()
+                        .with(PatchOp::SetDescription(description.join("\n")))
+                        .with(type_section(sig.to_string())),
+                );
+            }
+            None => {
+                let name = item.name.as_deref().unwrap_or("f");
+                offer(
+                    "Add `# Type` section".to_string(),
+                    DocPatch::new().with(type_section(format!("{name} :: "))),
+                );
+            }
+        }
+    }
+
+    let documented = doc.arguments();
+    let arguments = doc
+        .sections
+        .iter()
+        .find(|s| s.kind() == SectionKind::Arguments);
+    for formal in extract::formals_after(src, item.span.end) {
+        if documented.iter().any(|a| a.name == formal) {
+            continue;
+        }
+        let (heading, content) = match arguments {
+            Some(section) => (
+                section.heading.clone(),
+                format!("{}\n- [{formal}]", section.content.trim_end()),
+            ),
+            None => ("Arguments".to_string(), format!("- [{formal}]")),
+        };
+        offer(
+            format!("Document missing argument `{formal}`"),
+            DocPatch::new().with(PatchOp::SetSection { heading, content }),
+        );
+    }
+
+    actions
+}
+
+fn type_section(sig: String) -> PatchOp {
+    PatchOp::SetSection {
+        heading: "Type".to_string(),
+        content: format!("```\n{sig}\n```"),
+    }
+}
+
+/// The headings suggested by [`completions_at`], in the order of the
+/// specification.
+const HEADINGS: &[&str] = &[
+    "Summary",
+    "Type",
+    "Arguments",
+    "Examples",
+    "Example",
+    "Note",
+    "Notes",
+    "Warning",
+    "Deprecated",
+    "Since",
+    "Stability",
+    "Tags",
+    "Maintainers",
+    "Authors",
+];
+
+/// What a [`Completion`] inserts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompletionKind {
+    /// A section heading, after `# `.
+    SectionHeading,
+    /// An argument name, after `- [` in an `# Arguments` section.
+    Argument,
+}
+
+/// A completion candidate, see [`completions_at`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Completion {
+    /// The text to insert.
+    pub label: String,
+    /// What the text is.
+    pub kind: CompletionKind,
+    /// The partly typed word that the label replaces, in the source.
+    pub replace: Span,
+}
+
+/// Options for [`completions_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompletionOptions {
+    /// Headings to suggest after the known ones, for projects that use
+    /// their own sections.
+    pub custom_headings: Vec<String>,
+}
+
+impl CompletionOptions {
+    /// Create options that suggest only the known headings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also suggest `heading`.
+    pub fn with_custom_heading(mut self, heading: impl Into<String>) -> Self {
+        self.custom_headings.push(heading.into());
+        self
+    }
+}
+
+/// Returns completion candidates at byte offset `offset`, with the default
+/// [`CompletionOptions`].
+///
+/// `source` is a whole Nix file or the text of a single comment. After `# `
+/// the candidates are section headings starting with what has been typed.
+/// After `- [` in an `# Arguments` section, they are the not yet documented
+/// formal arguments of the function after the comment, which are only known
+/// when `source` is a whole file. Anywhere else, including inside code
+/// blocks, there are none.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::lsp::completions_at;
+///
+/// let src = "/**\n  Map.\n\n  # Arguments\n\n  - [f] The function\n  - [\n*/\nmap = f: list: [ ];";
+/// let offset = src.find("- [\n").unwrap() + 3;
+/// let labels: Vec<_> = completions_at(src, offset).into_iter().map(|c| c.label).collect();
+/// assert_eq!(labels, ["list"]);
+///
+/// let labels: Vec<_> = completions_at("/**\n  # Ex", 10).into_iter().map(|c| c.label).collect();
+/// assert_eq!(labels, ["Examples", "Example"]);
+/// ```
+pub fn completions_at(source: &str, offset: usize) -> Vec<Completion> {
+    completions_with(source, offset, &CompletionOptions::default())
+}
+
+/// Returns completion candidates at byte offset `offset`, see
+/// [`completions_at`].
+pub fn completions_with(
+    source: &str,
+    offset: usize,
+    options: &CompletionOptions,
+) -> Vec<Completion> {
+    let Some(prefix) = source.get(..offset) else {
+        return Vec::new();
+    };
+    let comment = extract::extract_doc_comments_lossy(source)
+        .into_iter()
+        .map(|(span, _)| span)
+        .find(|span| span.start < offset && offset <= span.end);
+    let start = comment.map_or(0, |span| span.start);
+    let line_start = prefix.rfind('\n').map_or(0, |i| i + 1).max(start);
+    let line = prefix[line_start..].trim_start();
+
+    // Find the enclosing section, skipping headings in code blocks.
+    let mut in_fence = false;
+    let mut section = None;
+    let mut section_start = line_start;
+    for (pos, text, role) in fence_lines(&source[start..line_start]) {
+        in_fence = matches!(role, LineRole::OpeningFence | LineRole::Code);
+        if role == LineRole::Prose
+            && let Some(heading) = text.trim().strip_prefix("# ")
+        {
+            section = Some(SectionKind::from_heading(heading.trim()));
+            section_start = start + pos;
+        }
+    }
+    if in_fence {
+        return Vec::new();
+    }
+
+    let completion = |label: &str, kind, partial: &str| Completion {
+        label: label.to_string(),
+        kind,
+        replace: Span::new(offset - partial.len(), offset),
+    };
+    let starts_with = |label: &str, partial: &str| {
+        label
+            .to_lowercase()
+            .starts_with(&partial.trim_start().to_lowercase())
+    };
+
+    if let Some(partial) = line.strip_prefix("# ")
+        && !partial.contains('{')
+    {
+        return HEADINGS
+            .iter()
+            .copied()
+            .chain(options.custom_headings.iter().map(String::as_str))
+            .filter(|heading| starts_with(heading, partial))
+            .map(|heading| completion(heading, CompletionKind::SectionHeading, partial))
+            .collect();
+    }
+
+    if let Some(partial) = line.strip_prefix("- [")
+        && !partial.contains(']')
+        && section == Some(SectionKind::Arguments)
+        && let Some(comment) = comment
+    {
+        // Arguments already documented anywhere in this section.
+        let section_text = &source[section_start..comment.end];
+        let documented: Vec<&str> = section_text
+            .lines()
+            .skip(1)
+            .take_while(|text| !text.trim_start().starts_with("# "))
+            .filter_map(|text| {
+                let (name, _) = text.trim_start().strip_prefix("- [")?.split_once(']')?;
+                Some(name.trim())
+            })
+            .collect();
+        return extract::formals_after(source, comment.end)
+            .iter()
+            .filter(|name| !documented.contains(&name.as_str()) && starts_with(name, partial))
+            .map(|name| completion(name, CompletionKind::Argument, partial))
+            .collect();
+    }
+
+    Vec::new()
+}
+
+#[cfg(feature = "lsp-types")]
+impl From<Position> for lsp_types::Position {
+    fn from(position: Position) -> Self {
+        Self::new(position.line, position.character)
+    }
+}
+
+#[cfg(feature = "lsp-types")]
+impl From<lsp_types::Position> for Position {
+    fn from(position: lsp_types::Position) -> Self {
+        Self {
+            line: position.line,
+            character: position.character,
+        }
+    }
+}
+
+#[cfg(feature = "lsp-types")]
+impl From<Range> for lsp_types::Range {
+    fn from(range: Range) -> Self {
+        Self::new(range.start.into(), range.end.into())
+    }
+}
+
+#[cfg(feature = "lsp-types")]
+impl From<lsp_types::Range> for Range {
+    fn from(range: lsp_types::Range) -> Self {
+        Self {
+            start: range.start.into(),
+            end: range.end.into(),
+        }
+    }
+}
+
+/// Convert the parse warnings of `item` and `diagnostics` found in it into
+/// LSP diagnostics for `src`, the source `item` was extracted from.
+///
+/// Diagnostic spans are mapped to the source with
+/// [`DocumentedItem::source_span`]. Parse warnings have no span and cover
+/// the whole comment. Each diagnostic has the source `nixdoc` and its code:
+/// a [`Diagnostic::code`](crate::Diagnostic::code), or the warning kind's
+/// [`name`](crate::WarningKind::name), such as `unknown-section`.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::documented_items;
+/// use nixdoc::lsp::to_lsp_diagnostics;
+///
+/// let src = "{\n  /**\n    Sort.\n\n    # Bogus\n\n    x\n  */\n  sort = x: x;\n}";
+/// let item = &documented_items(src, None)[0];
+/// let diagnostics = to_lsp_diagnostics(item, src, &[]);
+/// assert_eq!(diagnostics[0].range.start.line, 1);
+/// assert_eq!(diagnostics[0].source.as_deref(), Some("nixdoc"));
+/// ```
+#[cfg(feature = "lsp-types")]
+pub fn to_lsp_diagnostics(
+    item: &DocumentedItem,
+    src: &str,
+    diagnostics: &[crate::Diagnostic],
+) -> Vec<lsp_types::Diagnostic> {
+    use lsp_types::{DiagnosticSeverity, NumberOrString};
+
+    let lsp_diagnostic = |span, severity, code: String, message: &str| lsp_types::Diagnostic {
+        range: Range::of_span(src, span).into(),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code)),
+        source: Some("nixdoc".to_string()),
+        message: message.to_string(),
+        ..Default::default()
+    };
+
+    let warnings = item.doc.warnings.iter().map(|warning| {
+        lsp_diagnostic(
+            item.span,
+            DiagnosticSeverity::WARNING,
+            warning.kind.name().to_string(),
+            &warning.message,
+        )
+    });
+    let diagnostics = diagnostics.iter().map(|diagnostic| {
+        let severity = match diagnostic.severity {
+            crate::Severity::Error => DiagnosticSeverity::ERROR,
+            crate::Severity::Warning => DiagnosticSeverity::WARNING,
+            crate::Severity::Info => DiagnosticSeverity::INFORMATION,
+        };
+        lsp_diagnostic(
+            item.source_span(src, diagnostic.span),
+            severity,
+            diagnostic.code.clone(),
+            &diagnostic.message,
+        )
+    });
+    warnings.chain(diagnostics).collect()
+}
@@ -0,0 +1,204 @@
+//! Three-way merging of doc comments.
+//!
+//! When documentation is partly generated and partly edited by hand, a new
+//! generated version has to be rebased onto the edited one.
+//! [`DocComment::merge3`] does this a region at a time: the description,
+//! each section, and each link definition. A region changed on only one side
+//! takes that side's version; a region changed differently on both sides is
+//! a conflict, written with Git-style markers.
+
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, ParserOptions};
+
+/// A part of a comment that is merged as a unit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Region {
+    /// The text before the first section.
+    Description,
+    /// The section with this heading.
+    Section(String),
+    /// The link definition with this label.
+    LinkDefinition(String),
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Description => f.write_str("description"),
+            Self::Section(heading) => write!(f, "section `{heading}`"),
+            Self::LinkDefinition(label) => write!(f, "link definition `[{label}]`"),
+        }
+    }
+}
+
+/// A region both sides changed differently, see [`Merge::conflicts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conflict {
+    /// Where the conflict is.
+    pub region: Region,
+    /// Our version of the region, `None` if we removed it.
+    pub ours: Option<String>,
+    /// Their version of the region, `None` if they removed it.
+    pub theirs: Option<String>,
+}
+
+/// The result of [`DocComment::merge3`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Merge {
+    /// The merged comment content, without comment markers. Conflicting
+    /// regions are written between `<<<<<<< ours`, `=======` and
+    /// `>>>>>>> theirs` lines.
+    pub text: String,
+    /// The conflicting regions, in the order they appear in [`Self::text`].
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Returns `true` if the merge has no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Parses the merged text, if the merge is clean.
+    pub fn to_doc(&self) -> Option<DocComment> {
+        if !self.is_clean() {
+            return None;
+        }
+        DocComment::parse_with(&self.text, &ParserOptions::new().without_delimiters()).ok()
+    }
+}
+
+/// A region of one comment and its Markdown text.
+struct Unit {
+    /// The region's lowercase name and its occurrence among regions of the
+    /// same name, so repeated headings are paired in order.
+    key: (String, usize),
+    region: Region,
+    text: String,
+}
+
+/// Merge `ours` and `theirs` against `base`, see [`DocComment::merge3`].
+pub(crate) fn merge3(base: &DocComment, ours: &DocComment, theirs: &DocComment) -> Merge {
+    let mut conflicts = Vec::new();
+    let sections = merge_units(
+        &sections(base),
+        &sections(ours),
+        &sections(theirs),
+        &mut conflicts,
+    );
+    let definitions = merge_units(
+        &link_definitions(base),
+        &link_definitions(ours),
+        &link_definitions(theirs),
+        &mut conflicts,
+    );
+
+    // Sections are separated by blank lines, link definitions are not.
+    let blocks = [sections.join("\n\n"), definitions.join("\n")];
+    Merge {
+        text: blocks
+            .into_iter()
+            .filter(|block| !block.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        conflicts,
+    }
+}
+
+/// Merge regions in our order, followed by regions only they added.
+fn merge_units(
+    base: &[Unit],
+    ours: &[Unit],
+    theirs: &[Unit],
+    conflicts: &mut Vec<Conflict>,
+) -> Vec<String> {
+    let mut order: Vec<&Unit> = ours.iter().collect();
+    order.extend(
+        theirs
+            .iter()
+            .filter(|t| !ours.iter().any(|o| o.key == t.key)),
+    );
+
+    let mut merged = Vec::new();
+    for unit in order {
+        let find = |units: &[Unit]| {
+            units
+                .iter()
+                .find(|u| u.key == unit.key)
+                .map(|u| u.text.clone())
+        };
+        let (b, o, t) = (find(base), find(ours), find(theirs));
+        if o == t || t == b {
+            merged.extend(o);
+        } else if o == b {
+            merged.extend(t);
+        } else {
+            merged.push(format!(
+                "<<<<<<< ours\n{}=======\n{}>>>>>>> theirs",
+                with_newline(o.as_deref()),
+                with_newline(t.as_deref()),
+            ));
+            conflicts.push(Conflict {
+                region: unit.region.clone(),
+                ours: o,
+                theirs: t,
+            });
+        }
+    }
+    merged
+}
+
+fn with_newline(text: Option<&str>) -> String {
+    text.map(|text| format!("{text}\n")).unwrap_or_default()
+}
+
+/// The description and sections of a comment.
+fn sections(doc: &DocComment) -> Vec<Unit> {
+    let mut units = Vec::new();
+    let description = doc.description.trim();
+    if !description.is_empty() {
+        push(
+            &mut units,
+            String::new(),
+            Region::Description,
+            description.to_string(),
+        );
+    }
+    for section in &doc.sections {
+        push(
+            &mut units,
+            format!("# {}", section.heading.to_lowercase()),
+            Region::Section(section.heading.clone()),
+            section.to_markdown(),
+        );
+    }
+    units
+}
+
+fn link_definitions(doc: &DocComment) -> Vec<Unit> {
+    let mut units = Vec::new();
+    for definition in &doc.link_definitions {
+        push(
+            &mut units,
+            definition.label.to_lowercase(),
+            Region::LinkDefinition(definition.label.clone()),
+            definition.to_string(),
+        );
+    }
+    units
+}
+
+fn push(units: &mut Vec<Unit>, name: String, region: Region, text: String) {
+    let occurrence = units.iter().filter(|u| u.key.0 == name).count();
+    units.push(Unit {
+        key: (name, occurrence),
+        region,
+        text,
+    });
+}
@@ -0,0 +1,167 @@
+//! Converting old-style comments to RFC 145.
+//!
+//! Before RFC 145, nixpkgs documented functions in plain `/* … */` comments
+//! with `Type:` and `Example:` labels followed by an indented block.
+//! [`to_rfc145`] turns such a comment into a [`DocComment`] with `# Type`
+//! and `# Examples` sections, and [`emit`] writes it back as a `/** … */`
+//! comment, so a bulk conversion can be generated and reviewed as a diff.
+
+use crate::parser::{self, LEGACY_LABELS, LineRole};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, ParseError, ParserOptions};
+
+/// Convert an old-style comment to an RFC 145 doc comment.
+///
+/// `comment` is a `/* … */` or `/** … */` comment. Every `Type:` block
+/// becomes the `# Type` section and every `Example:` or `Examples:` block a
+/// `nix` code block in the `# Examples` section; the rest is kept as the
+/// description. Without a `Type:` label, an inline `name :: type` line of
+/// the description becomes the `# Type` section. Labels inside code blocks
+/// are left alone.
+///
+/// # Errors
+///
+/// Returns [`ParseError::NotDocComment`] if `comment` does not start with
+/// `/*`, [`ParseError::UnclosedComment`] if it does not end with `*/`, and
+/// [`ParseError::EmptyComment`] if it has no content.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::migrate::to_rfc145;
+///
+/// let old = "/* Return the first element of a list.\n\n   Example:\n     head [ 1 2 3 ]\n     => 1\n\n   Type:\n     head :: [a] -> a\n*/";
+/// let doc = to_rfc145(old).unwrap();
+/// assert_eq!(doc.description(), "Return the first element of a list.");
+/// assert_eq!(doc.type_sig().as_deref(), Some("head :: [a] -> a\n"));
+/// assert_eq!(doc.examples()[0].code, "head [ 1 2 3 ]\n=> 1\n");
+/// ```
+pub fn to_rfc145(comment: &str) -> Result<DocComment, ParseError> {
+    let trimmed = comment.trim();
+    let rest = trimmed
+        .strip_prefix("/**")
+        .or_else(|| trimmed.strip_prefix("/*"))
+        .ok_or(ParseError::NotDocComment)?;
+    let inner = rest.strip_suffix("*/").ok_or(ParseError::UnclosedComment)?;
+
+    let content = parser::dedent_after_first_line(inner);
+    let (mut description, type_sig, examples) = split_labels(&content);
+
+    let type_sig = type_sig.or_else(|| {
+        let sig = parser::extract_inline_type_sig(&description.join("\n"))?;
+        let index = description.iter().position(|line| line.trim() == sig)?;
+        description.remove(index);
+        Some(sig)
+    });
+
+    // Removed blocks leave runs of blank lines behind.
+    description.dedup_by(|a, b| a.trim().is_empty() && b.trim().is_empty());
+    let mut blocks = vec![description.join("\n").trim().to_string()];
+    if let Some(sig) = type_sig {
+        blocks.push(format!("# Type\n\n```\n{sig}\n```"));
+    }
+    if !examples.is_empty() {
+        let examples: Vec<String> = examples
+            .iter()
+            .map(|code| format!("```nix\n{code}\n```"))
+            .collect();
+        blocks.push(format!("# Examples\n\n{}", examples.join("\n\n")));
+    }
+    blocks.retain(|block| !block.is_empty());
+
+    DocComment::parse_with(
+        &blocks.join("\n\n"),
+        &ParserOptions::new().without_delimiters(),
+    )
+}
+
+/// Write `doc` as a `/** … */` comment.
+///
+/// `indent` is the indentation of the line the comment starts on; the
+/// content is indented two spaces deeper and the closing `*/` is aligned
+/// with the opening `/**`.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::migrate::{emit, to_rfc145};
+///
+/// let doc = to_rfc145("/* Identity.\n\n   Type: id :: a -> a\n*/").unwrap();
+/// assert_eq!(
+///     emit(&doc, "  "),
+///     "/**\n    Identity.\n\n    # Type\n\n    ```\n    id :: a -> a\n    ```\n  */"
+/// );
+/// ```
+pub fn emit(doc: &DocComment, indent: &str) -> String {
+    let mut out = String::from("/**\n");
+    for line in doc.raw_content.lines() {
+        if !line.trim().is_empty() {
+            out.push_str(indent);
+            out.push_str("  ");
+            out.push_str(line.trim_end());
+        }
+        out.push('\n');
+    }
+    out.push_str(indent);
+    out.push_str("*/");
+    out
+}
+
+/// Split dedented content into its description lines, the `Type:` block and
+/// the `Example:` blocks.
+fn split_labels(content: &str) -> (Vec<&str>, Option<String>, Vec<String>) {
+    let lines: Vec<(&str, LineRole)> = parser::fence_lines(content)
+        .map(|(_, line, role)| (line, role))
+        .collect();
+    let mut description = Vec::new();
+    let mut type_sig: Option<String> = None;
+    let mut examples = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let (line, role) = lines[i];
+        let trimmed = line.trim_start();
+        i += 1;
+
+        let label = LEGACY_LABELS
+            .iter()
+            .find(|label| trimmed.starts_with(*label));
+        let Some(label) = label.filter(|_| role == LineRole::Prose) else {
+            description.push(line);
+            continue;
+        };
+
+        // The block is the rest of the label line and the lines indented
+        // deeper than the label.
+        let indent = line.len() - trimmed.len();
+        let mut block: Vec<&str> = Vec::new();
+        let same_line = trimmed[label.len()..].trim();
+        while i < lines.len() {
+            let (next, _) = lines[i];
+            if !next.trim().is_empty() && next.len() - next.trim_start().len() <= indent {
+                break;
+            }
+            block.push(next);
+            i += 1;
+        }
+        let body = parser::normalize(&block.join("\n"));
+        let body = match (same_line, body.as_str()) {
+            (first, "") => first.to_string(),
+            ("", body) => body.to_string(),
+            (first, body) => format!("{first}\n{body}"),
+        };
+        if body.is_empty() {
+            continue;
+        }
+        if *label == "Type:" {
+            type_sig = Some(match type_sig {
+                Some(sig) => format!("{sig}\n{body}"),
+                None => body,
+            });
+        } else {
+            examples.push(body);
+        }
+    }
+    (description, type_sig, examples)
+}
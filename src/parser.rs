@@ -1,48 +1,1004 @@
-use crate::DocComment;
-use crate::error::{ParseError, ParseWarning, WarningKind};
-use crate::section::{Argument, Example, Section};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+
+use crate::dialect::Dialect;
+use crate::error::{ParseError, ParseWarning, SpannedError, WarningKind};
+use crate::links::LinkDefinition;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::profile::SpecProfile;
+use crate::section::{
+    Argument, Attribution, CodeBlock, Example, FenceInfo, Law, Output, Section, SectionKind,
+};
+use crate::{DocComment, Span};
+
+mod events;
+
+pub use events::{Events, ParseEvent, Spanned, events, events_with};
+
+/// Options controlling how doc comments are parsed.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::{DocComment, ParserOptions};
+///
+/// let rust_style = ParserOptions::new().with_delimiters("/*!", "*/");
+/// let doc = DocComment::parse_with("/*! Inner docs. */", &rust_style).unwrap();
+/// assert_eq!(doc.title(), Some("Inner docs."));
+///
+/// let stripped = ParserOptions::new().without_delimiters();
+/// let doc = DocComment::parse_with("Already stripped.\n\n# Type\n\n```\nint\n```", &stripped).unwrap();
+/// assert_eq!(doc.type_sig().as_deref(), Some("int\n"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// The opening and closing comment markers, `/**` and `*/` by default.
+    /// `None` parses the whole input as comment content, for text whose
+    /// markers were removed beforehand.
+    pub delimiters: Option<(String, String)>,
+    /// What to do with byte order marks, zero-width characters and
+    /// non-ASCII spaces in indentation and headings.
+    pub unicode: UnicodePolicy,
+    /// Treat blocks indented by four spaces as code, as CommonMark does.
+    /// They are rewritten as fenced blocks in the description and section
+    /// content, so `# Type` and `# Example` sections written in that style
+    /// still yield code. Off by default.
+    pub indented_code: bool,
+    /// How many spaces a section heading may be indented by. Defaults to 3,
+    /// as in CommonMark; 0 requires headings to start their line.
+    pub max_heading_indent: usize,
+    /// Also accept plain `/* … */` comments when the opening marker is
+    /// `/**`, as older code documents functions with them. Such comments
+    /// are parsed as usual and carry a [`WarningKind::LegacyComment`]
+    /// warning. Off by default.
+    pub legacy_comments: bool,
+    /// The revision of the format whose sections are recognized; others
+    /// carry a [`WarningKind::UnknownSection`] warning. Use
+    /// [`SpecProfile::options`] to take the profile's other defaults too.
+    pub profile: SpecProfile,
+    /// The language variant of the format, if not Nixdoc. Its section
+    /// headings and arguments are rewritten in Nixdoc terms, see
+    /// [`Dialect`].
+    pub dialect: Option<Dialect>,
+    /// Fail with [`ParseError::DeniedWarning`] instead of returning a comment
+    /// that has warnings. Off by default.
+    pub deny_warnings: bool,
+    /// Parse a comment that has no closing marker up to the end of the
+    /// input, as [`DocComment::parse_lossy`] does, instead of failing with
+    /// [`ParseError::UnclosedComment`]. Off by default.
+    pub recover_unclosed: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            delimiters: Some(("/**".to_string(), "*/".to_string())),
+            unicode: UnicodePolicy::default(),
+            indented_code: false,
+            max_heading_indent: DEFAULT_HEADING_INDENT,
+            legacy_comments: false,
+            profile: SpecProfile::default(),
+            dialect: None,
+            deny_warnings: false,
+            recover_unclosed: false,
+        }
+    }
+}
+
+/// How [`ParserOptions`] treats invisible or unusual Unicode characters.
+///
+/// Text copied from web pages often carries a leading byte order mark,
+/// zero-width characters, or non-breaking spaces. In indentation they throw
+/// off dedenting, and in headings they stop sections from being recognised.
+/// Characters elsewhere, such as inside paragraphs, are never touched.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::{DocComment, ParserOptions, UnicodePolicy, WarningKind};
+///
+/// let input = "\u{feff}/**\n  Title.\n\n\u{200b}  # Example\u{a0}\n\n  ```nix\n  1\n  ```\n*/";
+/// let doc = DocComment::parse(input).unwrap();
+/// assert_eq!(doc.examples().len(), 1);
+///
+/// let warn = ParserOptions::new().with_unicode(UnicodePolicy::Warn);
+/// let doc = DocComment::parse_with(input, &warn).unwrap();
+/// assert_eq!(doc.warnings[0].kind, WarningKind::UnusualWhitespace);
+///
+/// let preserve = ParserOptions::new().with_unicode(UnicodePolicy::Preserve);
+/// assert!(DocComment::parse_with(input, &preserve).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnicodePolicy {
+    /// Remove a leading byte order mark and zero-width characters, and
+    /// replace non-ASCII spaces with ASCII ones.
+    #[default]
+    Strip,
+    /// Leave the input as it is.
+    Preserve,
+    /// Strip as with [`Self::Strip`], and report a
+    /// [`WarningKind::UnusualWhitespace`] warning if anything was changed.
+    Warn,
+}
+
+impl ParserOptions {
+    /// Create options for standard `/** … */` comments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the opening and closing comment markers.
+    pub fn with_delimiters(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.delimiters = Some((open.into(), close.into()));
+        self
+    }
+
+    /// Parse input that has no comment markers.
+    pub fn without_delimiters(mut self) -> Self {
+        self.delimiters = None;
+        self
+    }
+
+    /// Set how unusual Unicode whitespace is treated.
+    pub fn with_unicode(mut self, policy: UnicodePolicy) -> Self {
+        self.unicode = policy;
+        self
+    }
+
+    /// Set whether indented code blocks are recognised.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, ParserOptions};
+    ///
+    /// let input = "/**\n  Id.\n\n  # Type\n\n      id :: a -> a\n*/";
+    /// // Without the option, the line is only a bare signature.
+    /// let doc = DocComment::parse(input).unwrap();
+    /// assert_eq!(doc.type_sig().as_deref(), Some("id :: a -> a"));
+    ///
+    /// let options = ParserOptions::new().with_indented_code(true);
+    /// let doc = DocComment::parse_with(input, &options).unwrap();
+    /// assert_eq!(doc.type_sig().as_deref(), Some("id :: a -> a\n"));
+    /// ```
+    pub fn with_indented_code(mut self, indented_code: bool) -> Self {
+        self.indented_code = indented_code;
+        self
+    }
+
+    /// Set how many spaces a section heading may be indented by.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, ParserOptions};
+    ///
+    /// let input = "/**\n  Id.\n\n   # Since\n\n  1.0\n*/";
+    /// assert!(DocComment::parse(input).unwrap().since().is_some());
+    ///
+    /// let strict = ParserOptions::new().with_max_heading_indent(0);
+    /// assert!(DocComment::parse_with(input, &strict).unwrap().sections.is_empty());
+    /// ```
+    pub fn with_max_heading_indent(mut self, spaces: usize) -> Self {
+        self.max_heading_indent = spaces;
+        self
+    }
+
+    /// Set whether plain `/* … */` comments are accepted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, ParserOptions, WarningKind};
+    ///
+    /// let input = "/*\n  Id.\n\n  # Type\n\n  ```\n  id :: a -> a\n  ```\n*/";
+    /// assert!(DocComment::parse(input).is_err());
+    ///
+    /// let options = ParserOptions::new().with_legacy_comments(true);
+    /// let doc = DocComment::parse_with(input, &options).unwrap();
+    /// assert_eq!(doc.type_sig().as_deref(), Some("id :: a -> a\n"));
+    /// assert!(doc.is_legacy());
+    /// assert_eq!(doc.warnings[0].kind, WarningKind::LegacyComment);
+    /// ```
+    pub fn with_legacy_comments(mut self, legacy: bool) -> Self {
+        self.legacy_comments = legacy;
+        self
+    }
+
+    /// Set the revision of the format whose sections are recognized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, ParserOptions, SpecProfile, WarningKind};
+    ///
+    /// let input = "/**\n  Id.\n\n  # Stability\n\n  stable\n*/";
+    /// assert!(DocComment::parse(input).unwrap().warnings.is_empty());
+    ///
+    /// let options = ParserOptions::new().with_profile(SpecProfile::Rfc145_2023);
+    /// let doc = DocComment::parse_with(input, &options).unwrap();
+    /// assert_eq!(doc.warnings[0].kind, WarningKind::UnknownSection);
+    /// ```
+    pub fn with_profile(mut self, profile: SpecProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Parse comments of another language's variant of the format, with
+    /// its delimiters, see [`Dialect`].
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.delimiters = dialect.delimiters.clone();
+        self.dialect = Some(dialect);
+        self
+    }
+
+    /// Set whether a comment with warnings fails to parse.
+    pub fn with_deny_warnings(mut self, deny: bool) -> Self {
+        self.deny_warnings = deny;
+        self
+    }
+
+    /// Set whether a comment without a closing marker is parsed up to the
+    /// end of the input.
+    pub fn with_recover_unclosed(mut self, recover: bool) -> Self {
+        self.recover_unclosed = recover;
+        self
+    }
+
+    /// Options for checking that comments follow RFC 145 to the letter:
+    /// those of [`SpecProfile::Rfc145_2023`], with warnings denied, so
+    /// sections the RFC does not define, decorated headings such as
+    /// `# Example:` and stray Unicode whitespace are errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, ParseError, ParserOptions, WarningKind};
+    ///
+    /// let input = "/**\n  Id.\n\n  # Stability\n\n  stable\n*/";
+    /// assert!(DocComment::parse(input).is_ok());
+    ///
+    /// let Err(ParseError::DeniedWarning(warning)) = DocComment::parse_with(input, &ParserOptions::strict()) else {
+    ///     panic!("expected a denied warning");
+    /// };
+    /// assert_eq!(warning.kind, WarningKind::UnknownSection);
+    /// ```
+    pub fn strict() -> Self {
+        SpecProfile::Rfc145_2023.options().with_deny_warnings(true)
+    }
+
+    /// Options for getting what can be had out of sloppy comments: those of
+    /// [`SpecProfile::Permissive`], which accept plain `/* … */` comments,
+    /// with unclosed comments recovered and headings recognized however
+    /// deeply they are indented. Indented code is off, as it would take
+    /// such headings for code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::{DocComment, ParserOptions, WarningKind};
+    ///
+    /// let input = "/*\n  Id.\n\n        # Since\n\n  1.0\n";
+    /// assert!(DocComment::parse(input).is_err());
+    ///
+    /// let doc = DocComment::parse_with(input, &ParserOptions::lenient()).unwrap();
+    /// assert_eq!(doc.since().unwrap().to_string(), "1.0");
+    /// assert!(doc.warnings.iter().any(|w| w.kind == WarningKind::UnclosedComment));
+    /// ```
+    pub fn lenient() -> Self {
+        SpecProfile::Permissive
+            .options()
+            .with_indented_code(false)
+            .with_recover_unclosed(true)
+            .with_max_heading_indent(usize::MAX)
+    }
+
+    /// The opening marker of legacy comments, if they are accepted.
+    pub(crate) fn legacy_open(&self) -> Option<&str> {
+        let (open, _) = self.delimiters.as_ref()?;
+        (self.legacy_comments && open == "/**").then_some("/*")
+    }
+}
+
+/// The default [`ParserOptions::max_heading_indent`].
+const DEFAULT_HEADING_INDENT: usize = 3;
+
+/// Everything a single parse found: the best-effort comment, errors and
+/// warnings.
+///
+/// Returned by [`DocComment::parse_outcome`]. Unlike a [`Result`], an
+/// outcome can hold a usable comment and errors at the same time, such as
+/// the sections of a comment that is missing its closing marker.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::{DocComment, ParseError, ParserOptions};
+///
+/// let outcome = DocComment::parse_outcome("/**\n  Half-typed.\n\n  # Note\n\n  Careful.", &ParserOptions::new());
+/// assert_eq!(outcome.doc.as_ref().unwrap().notes(), ["Careful."]);
+/// assert_eq!(outcome.errors[0].error, ParseError::UnclosedComment);
+/// assert!(outcome.warnings.is_empty());
+/// assert_eq!(outcome.into_result(), Err(ParseError::UnclosedComment));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOutcome {
+    /// The comment as far as it could be parsed, or `None` if nothing usable
+    /// was found.
+    pub doc: Option<DocComment>,
+    /// The errors, with spans relative to the input.
+    pub errors: Vec<SpannedError>,
+    /// The warnings of `doc` that are not already reported in `errors`.
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl ParseOutcome {
+    /// Returns `true` if there are no errors.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Convert to the all-or-nothing result of [`DocComment::parse_with`],
+    /// failing with the first error, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of the outcome.
+    pub fn into_result(self) -> Result<DocComment, ParseError> {
+        match (self.errors.into_iter().next(), self.doc) {
+            (Some(spanned), _) => Err(spanned.error),
+            (None, Some(doc)) => Ok(doc),
+            (None, None) => Err(ParseError::EmptyComment),
+        }
+    }
+}
 
 /// Parse a raw input string as a Nixdoc doc comment.
 ///
-/// This is the entry point called by [`DocComment::parse`].
-pub(crate) fn parse(input: &str) -> Result<DocComment, ParseError> {
-    let trimmed = input.trim();
+/// This is the entry point called by [`DocComment::parse`] and
+/// [`DocComment::parse_with`].
+pub(crate) fn parse(input: &str, options: &ParserOptions) -> Result<DocComment, ParseError> {
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!("parse", len = input.len()).entered();
+    if options.recover_unclosed {
+        return parse_lossy(input, options);
+    }
+    let mut warnings = Vec::new();
+    let input = clean_unicode(input, options.unicode, &mut warnings);
+    let legacy = legacy_warning(&input, options, &mut warnings);
 
     // Strip delimiters, propagating appropriate errors.
-    let inner = trimmed
-        .strip_prefix("/**")
-        .ok_or(ParseError::NotDocComment)?
-        .strip_suffix("*/")
-        .ok_or(ParseError::UnclosedComment)?;
+    let inner = match strip_delimiters(&input, options)? {
+        (inner, true) => inner,
+        (_, false) => return Err(ParseError::UnclosedComment),
+    };
+
+    parse_inner(&legacy_dedent(inner, legacy), options, warnings)
+        .and_then(|doc| deny_warnings(doc, options))
+}
+
+/// Parse a raw input string as a Nixdoc doc comment, treating the end of the
+/// input as the terminator if the closing marker is missing.
+///
+/// This is the entry point called by [`DocComment::parse_lossy`].
+pub(crate) fn parse_lossy(input: &str, options: &ParserOptions) -> Result<DocComment, ParseError> {
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!("parse_lossy", len = input.len()).entered();
+    parse_recovering(input, options).and_then(|doc| deny_warnings(doc, options))
+}
+
+/// Parse as [`parse_lossy`] does, keeping warnings even if they are denied.
+fn parse_recovering(input: &str, options: &ParserOptions) -> Result<DocComment, ParseError> {
+    let mut warnings = Vec::new();
+    let input = clean_unicode(input, options.unicode, &mut warnings);
+    let legacy = legacy_warning(&input, options, &mut warnings);
+
+    let (inner, closed) = strip_delimiters(&input, options)?;
+    if !closed {
+        let close = options.delimiters.as_ref().map_or("", |(_, c)| c);
+        warnings.push(ParseWarning {
+            kind: WarningKind::UnclosedComment,
+            message: format!("comment is not closed with '{close}'"),
+        });
+    }
+    parse_inner(&legacy_dedent(inner, legacy), options, warnings)
+}
+
+/// Fail with the first warning of `doc` if `options` deny warnings.
+fn deny_warnings(doc: DocComment, options: &ParserOptions) -> Result<DocComment, ParseError> {
+    match doc.warnings.first() {
+        Some(warning) if options.deny_warnings => Err(ParseError::DeniedWarning(warning.clone())),
+        _ => Ok(doc),
+    }
+}
+
+/// Parse a raw input string, locating the error on failure.
+///
+/// This is the entry point called by [`DocComment::parse_spanned`].
+pub(crate) fn parse_spanned(
+    input: &str,
+    options: &ParserOptions,
+) -> Result<DocComment, SpannedError> {
+    parse(input, options).map_err(|error| SpannedError {
+        span: error_span(input, &error, options),
+        error,
+    })
+}
+
+/// The part of `input` that `error`, returned for it, concerns.
+///
+/// For [`ParseError::NotDocComment`] this is where the opening marker was
+/// expected, for [`ParseError::UnclosedComment`] the opening marker of the
+/// comment that is not closed, for [`ParseError::EmptyComment`] the space
+/// between the markers where content was expected, and for
+/// [`ParseError::DeniedWarning`] the whole comment.
+pub(crate) fn error_span(input: &str, error: &ParseError, options: &ParserOptions) -> Span {
+    let start = input.len()
+        - input
+            .trim_start_matches(|c: char| c.is_whitespace() || is_zero_width(c))
+            .len();
+    let end = start.max(input.trim_end().len());
+    let (open, close) = options
+        .delimiters
+        .as_ref()
+        .map_or(("", ""), |(open, close)| (open.as_str(), close.as_str()));
+    let rest = &input[start..end];
+
+    match error {
+        ParseError::NotDocComment => {
+            let len = rest
+                .char_indices()
+                .take_while(|&(_, c)| c != '\n')
+                .nth(open.chars().count())
+                .map_or_else(|| rest.lines().next().unwrap_or("").len(), |(i, _)| i);
+            Span::new(start, start + len)
+        }
+        ParseError::UnclosedComment => Span::new(start, start + open.len().min(rest.len())),
+        ParseError::EmptyComment => {
+            let content_start = start + open.len().min(rest.len());
+            let content_end = if rest.len() >= open.len() + close.len() && rest.ends_with(close) {
+                end - close.len()
+            } else {
+                end
+            };
+            Span::new(content_start, content_start.max(content_end))
+        }
+        ParseError::DeniedWarning(_) => Span::new(start, end),
+    }
+}
+
+/// Parse a raw input string into a [`ParseOutcome`].
+///
+/// This is the entry point called by [`DocComment::parse_outcome`].
+pub(crate) fn parse_outcome(input: &str, options: &ParserOptions) -> ParseOutcome {
+    match parse_recovering(input, options) {
+        Ok(doc) => {
+            let (unclosed, warnings): (Vec<_>, Vec<_>) =
+                doc.warnings.iter().cloned().partition(|w| {
+                    w.kind == WarningKind::UnclosedComment && !options.recover_unclosed
+                });
+            let (denied, warnings) = if options.deny_warnings {
+                (warnings, Vec::new())
+            } else {
+                (Vec::new(), warnings)
+            };
+            let errors = unclosed
+                .into_iter()
+                .map(|_| ParseError::UnclosedComment)
+                .chain(denied.into_iter().map(ParseError::DeniedWarning))
+                .map(|error| SpannedError {
+                    span: error_span(input, &error, options),
+                    error,
+                })
+                .collect();
+            ParseOutcome {
+                doc: Some(doc),
+                errors,
+                warnings,
+            }
+        }
+        Err(error) => ParseOutcome {
+            doc: None,
+            errors: vec![SpannedError {
+                span: error_span(input, &error, options),
+                error,
+            }],
+            warnings: Vec::new(),
+        },
+    }
+}
+
+/// Returns `true` for characters that take no space when displayed.
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}'
+    )
+}
+
+/// Apply `policy` to a leading byte order mark, and to zero-width characters
+/// and non-ASCII spaces in the indentation of every line and in headings
+/// outside code blocks.
+fn clean_unicode<'a>(
+    input: &'a str,
+    policy: UnicodePolicy,
+    warnings: &mut Vec<ParseWarning>,
+) -> Cow<'a, str> {
+    let is_unusual = |c: char| is_zero_width(c) || (c.is_whitespace() && !c.is_ascii());
+    if policy == UnicodePolicy::Preserve || !input.contains(is_unusual) {
+        return Cow::Borrowed(input);
+    }
+
+    let mut removed = 0;
+    let mut clean = |text: &str, out: &mut String| {
+        for c in text.chars() {
+            if is_zero_width(c) {
+                removed += 1;
+            } else if c.is_whitespace() && !c.is_ascii() {
+                removed += 1;
+                out.push(' ');
+            } else {
+                out.push(c);
+            }
+        }
+    };
+
+    let mut out = String::with_capacity(input.len());
+    let mut fence: Option<(char, usize)> = None;
+    for (i, line) in input.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let indent_len = line
+            .find(|c: char| !(c.is_whitespace() || is_zero_width(c)))
+            .unwrap_or(line.len());
+        let (indent, text) = line.split_at(indent_len);
+        clean(indent, &mut out);
+
+        let is_heading = fence.is_none() && text.starts_with('#');
+        match fence {
+            None => fence = parse_fence_open(text).map(|(fc, fl, _)| (fc, fl)),
+            Some((fc, fl)) if is_closing_fence(text, fc, fl) => fence = None,
+            Some(_) => {}
+        }
+        if is_heading {
+            clean(text, &mut out);
+        } else {
+            out.push_str(text);
+        }
+    }
+
+    if removed > 0 && policy == UnicodePolicy::Warn {
+        warnings.push(ParseWarning {
+            kind: WarningKind::UnusualWhitespace,
+            message: format!(
+                "replaced {removed} byte order mark, zero-width or non-breaking space character(s)"
+            ),
+        });
+    }
+    Cow::Owned(out)
+}
+
+/// Returns the content between the comment markers of `input`, and whether
+/// the closing marker was present. Without it, the content runs to the end
+/// of the input.
+fn strip_delimiters<'a>(
+    input: &'a str,
+    options: &ParserOptions,
+) -> Result<(&'a str, bool), ParseError> {
+    let trimmed = input.trim();
+    let Some((open, close)) = &options.delimiters else {
+        return Ok((trimmed, true));
+    };
+
+    let rest = trimmed
+        .strip_prefix(open.as_str())
+        .or_else(|| trimmed.strip_prefix(options.legacy_open()?))
+        .ok_or(ParseError::NotDocComment)?;
+    let stripped = match rest.strip_suffix(close.as_str()) {
+        Some(inner) => (inner, true),
+        None => (rest, false),
+    };
+    #[cfg(feature = "trace")]
+    tracing::trace!(
+        closed = stripped.1,
+        len = stripped.0.len(),
+        "stripped delimiters"
+    );
+    Ok(stripped)
+}
+
+/// Warn if `input` is a plain `/* … */` comment accepted by
+/// [`ParserOptions::legacy_comments`], and return whether it is.
+fn legacy_warning(input: &str, options: &ParserOptions, warnings: &mut Vec<ParseWarning>) -> bool {
+    let trimmed = input.trim_start();
+    let is_legacy = options.legacy_open().is_some_and(|legacy| {
+        trimmed.starts_with(legacy)
+            && !options
+                .delimiters
+                .as_ref()
+                .is_some_and(|(open, _)| trimmed.starts_with(open.as_str()))
+    });
+    if is_legacy {
+        warnings.push(ParseWarning {
+            kind: WarningKind::LegacyComment,
+            message: "plain '/*' comment rather than an RFC 145 '/**' doc comment".to_string(),
+        });
+    }
+    is_legacy
+}
+
+/// Dedent the content of a legacy comment with [`dedent_after_first_line`],
+/// as such comments usually start their text on the line of `/*`.
+fn legacy_dedent(inner: &str, legacy: bool) -> Cow<'_, str> {
+    if legacy {
+        Cow::Owned(dedent_after_first_line(inner))
+    } else {
+        Cow::Borrowed(inner)
+    }
+}
+
+/// Remove the indentation of comment content whose text starts on the line
+/// of the opening marker. That line is not indented like the lines after
+/// it, so it is left out when finding the common indentation.
+pub(crate) fn dedent_after_first_line(inner: &str) -> String {
+    let (first, rest) = inner.split_once('\n').unwrap_or((inner, ""));
+    let rest = normalize(&format!("\n{rest}"));
+    match first.trim() {
+        "" => rest,
+        first => format!("{first}\n{rest}"),
+    }
+}
+
+/// Parse the text between the comment delimiters, appending to `warnings`
+/// found so far.
+fn parse_inner(
+    inner: &str,
+    options: &ParserOptions,
+    mut warnings: Vec<ParseWarning>,
+) -> Result<DocComment, ParseError> {
+    let stripped = strip_gutter(inner);
+    if stripped.is_some() {
+        warnings.push(ParseWarning {
+            kind: WarningKind::JavadocGutter,
+            message: "lines start with a '*' gutter, which RFC 145 comments do not use".to_string(),
+        });
+    }
 
     // Normalize indentation and trim surrounding blank lines.
-    let content = normalize(inner);
+    let content = normalize(stripped.as_deref().unwrap_or(inner));
 
     if content.trim().is_empty() {
+        #[cfg(feature = "trace")]
+        tracing::debug!("nothing left after normalization");
         return Err(ParseError::EmptyComment);
     }
 
-    let mut warnings = Vec::new();
-    let (description, sections) = parse_sections(&content, &mut warnings);
+    let (body, link_definitions) = take_link_definitions(&content);
+    let (frontmatter, body) = take_frontmatter(&body);
+    #[cfg(feature = "trace")]
+    tracing::trace!(
+        gutter = stripped.is_some(),
+        link_definitions = link_definitions.len(),
+        frontmatter = frontmatter.is_some(),
+        "prepared body"
+    );
+    let body = if options.indented_code {
+        Cow::Owned(fence_indented_code(body))
+    } else {
+        Cow::Borrowed(body)
+    };
+    let (description, mut sections) =
+        parse_sections(&body, options.max_heading_indent, &mut warnings);
+    if let Some(dialect) = &options.dialect {
+        sections
+            .iter_mut()
+            .for_each(|section| dialect.normalize(section));
+    }
+    if let Some(frontmatter) = frontmatter {
+        sections.insert(
+            0,
+            Section {
+                heading: "Meta".to_string(),
+                content: frontmatter,
+                explicit_id: None,
+            },
+        );
+    }
 
     // Warn about any unrecognized section headings.
     for section in &sections {
-        if !section.kind().is_known() {
+        // Translated headings need not be in English, see `localize`.
+        if section.language().is_none() && !options.profile.recognizes(&section.kind()) {
             warnings.push(ParseWarning {
                 kind: WarningKind::UnknownSection,
                 message: format!("unrecognized section heading: '{}'", section.heading),
             });
         }
     }
+    mixed_style_warnings(&description, &sections, &mut warnings);
 
     Ok(DocComment {
         raw_content: content,
         description,
         sections,
+        link_definitions,
         warnings,
     })
 }
 
+/// Labels that introduced parts of a comment before RFC 145 headings.
+pub(crate) const LEGACY_LABELS: [&str; 3] = ["Type:", "Example:", "Examples:"];
+
+/// Warn about comments that mix the legacy and RFC 145 styles: an inline
+/// `name :: type` line next to a `# Type` section or in one without a code
+/// block, or a legacy label such as `Example:` next to Markdown headings.
+fn mixed_style_warnings(description: &str, sections: &[Section], warnings: &mut Vec<ParseWarning>) {
+    if sections.is_empty() {
+        return;
+    }
+    let has_type_section = sections.iter().any(|s| s.kind() == SectionKind::Type);
+    if has_type_section && let Some(sig) = extract_inline_type_sig(description) {
+        warnings.push(ParseWarning {
+            kind: WarningKind::MixedStyle,
+            message: format!("inline type '{sig}' next to a '# Type' section"),
+        });
+    }
+    let bare_type = sections
+        .iter()
+        .filter(|s| s.kind() == SectionKind::Type)
+        .filter(|s| extract_first_code_block(&s.content).is_none())
+        .find_map(|s| extract_inline_type_sig(&s.content));
+    if let Some(sig) = bare_type {
+        warnings.push(ParseWarning {
+            kind: WarningKind::MixedStyle,
+            message: format!("type '{sig}' in the '# Type' section is not in a code block"),
+        });
+    }
+
+    let texts = core::iter::once(description).chain(sections.iter().map(|s| s.content.as_str()));
+    let label = texts.flat_map(prose_lines).find_map(|line| {
+        LEGACY_LABELS
+            .into_iter()
+            .find(|label| line.trim_start().starts_with(label))
+    });
+    if let Some(label) = label {
+        warnings.push(ParseWarning {
+            kind: WarningKind::MixedStyle,
+            message: format!("legacy '{label}' label next to Markdown section headings"),
+        });
+    }
+}
+
+/// The lines of `content` outside fenced code blocks.
+fn prose_lines(content: &str) -> impl Iterator<Item = &str> {
+    fence_lines(content)
+        .filter(|(_, _, role)| *role == LineRole::Prose)
+        .map(|(_, line, _)| line)
+}
+
+/// What a line is in Markdown, see [`fence_lines`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LineRole {
+    /// Text outside fenced code blocks.
+    Prose,
+    /// The fence opening a code block.
+    OpeningFence,
+    /// A line inside a code block.
+    Code,
+    /// The fence closing a code block.
+    ClosingFence,
+}
+
+/// The lines of `content`, without line endings, with their byte offsets
+/// and roles.
+///
+/// Fences are recognized as the parser recognizes them: a block is closed
+/// only by a fence of the same character that is at least as long as the
+/// opening one, so a ```` ```` ```` block can hold ```` ``` ```` lines. A
+/// block that is never closed runs to the end of `content`.
+pub(crate) fn fence_lines(content: &str) -> impl Iterator<Item = (usize, &str, LineRole)> {
+    let mut fence: Option<(char, usize)> = None;
+    let mut offset = 0;
+    content.split_inclusive('\n').map(move |line| {
+        let start = offset;
+        offset += line.len();
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let trimmed = line.trim_start();
+        let role = match fence {
+            Some((fc, fl)) if is_closing_fence(trimmed, fc, fl) => {
+                fence = None;
+                LineRole::ClosingFence
+            }
+            Some(_) => LineRole::Code,
+            None => match fence_open(trimmed) {
+                Some((fc, fl, _)) => {
+                    fence = Some((fc, fl));
+                    LineRole::OpeningFence
+                }
+                None => LineRole::Prose,
+            },
+        };
+        (start, line, role)
+    })
+}
+
+/// Remove a javadoc-style `*` gutter from the inner content of a comment.
+///
+/// A gutter is detected when every non-blank line after the first starts,
+/// after indentation, with a `*` followed by a space or the end of the line.
+/// The `*` and one following space are removed from those lines; text on the
+/// first line, directly after the opening marker, is kept as it is.
+///
+/// Returns `None` if the content has no gutter.
+fn strip_gutter(inner: &str) -> Option<String> {
+    let mut lines = inner.lines();
+    let first = lines.next().unwrap_or_default();
+    let rest: Vec<&str> = lines.filter(|line| !line.trim().is_empty()).collect();
+
+    let has_gutter = !rest.is_empty()
+        && rest.iter().all(|line| {
+            line.trim_start()
+                .strip_prefix('*')
+                .is_some_and(|after| after.is_empty() || after.starts_with(char::is_whitespace))
+        });
+    if !has_gutter {
+        return None;
+    }
+
+    let mut out = first.trim().to_string();
+    for line in inner.lines().skip(1) {
+        out.push('\n');
+        if let Some(after) = line.trim_start().strip_prefix('*') {
+            out.push_str(after.strip_prefix(' ').unwrap_or(after));
+        }
+    }
+    Some(out)
+}
+
+/// Split a frontmatter block off the start of `content`.
+///
+/// Frontmatter is a `---` line, one or more `key: value` lines, and a closing
+/// `---` line. Anything else, such as a Markdown thematic break, is left in
+/// place.
+fn take_frontmatter(content: &str) -> (Option<String>, &str) {
+    let Some(rest) = content.strip_prefix("---\n") else {
+        return (None, content);
+    };
+    let Some((block, body)) = rest
+        .split_once("\n---\n")
+        .or_else(|| rest.strip_suffix("\n---").map(|block| (block, "")))
+    else {
+        return (None, content);
+    };
+
+    let is_metadata = block.lines().any(|line| !line.trim().is_empty())
+        && block
+            .lines()
+            .all(|line| line.trim().is_empty() || metadata_entry(line).is_some());
+    if is_metadata {
+        (Some(block.to_string()), body)
+    } else {
+        (None, content)
+    }
+}
+
+/// Parse the tags of a `# Tags` section: list items or comma-separated
+/// words, with surrounding backticks removed.
+pub(crate) fn parse_tags(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .flat_map(|line| {
+            let line = line.trim();
+            let line = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .unwrap_or(line);
+            line.split(',')
+        })
+        .map(|tag| tag.trim().trim_matches('`').trim())
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse the entries of a `# Maintainers` or `# Authors` section.
+///
+/// Each list item is one person. Lines outside list items count only when
+/// they start with an `@handle`.
+pub(crate) fn parse_attributions(content: &str) -> Vec<Attribution> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let entry = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .or_else(|| line.starts_with('@').then_some(line))?;
+            parse_attribution(entry)
+        })
+        .collect()
+}
+
+fn parse_attribution(entry: &str) -> Option<Attribution> {
+    let mut rest = entry.to_string();
+
+    let email = entry.find('<').and_then(|open| {
+        let close = open + entry[open..].find('>')?;
+        rest.replace_range(open..=close, " ");
+        Some(entry[open + 1..close].trim().to_string())
+    });
+
+    let mut handle = None;
+    let mut name_words = Vec::new();
+    for word in rest.split_whitespace() {
+        let bare = word.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';'));
+        match bare.strip_prefix('@') {
+            Some(h) if handle.is_none() && !h.is_empty() => handle = Some(h.to_string()),
+            _ if bare.is_empty() => {}
+            _ => name_words.push(word),
+        }
+    }
+    let name = name_words.join(" ");
+    let name = name.trim_matches(|c: char| c == ',' || c.is_whitespace());
+
+    let attribution = Attribution {
+        name: (!name.is_empty()).then(|| name.to_string()),
+        email: email.filter(|e| !e.is_empty()),
+        handle,
+    };
+    (attribution.name.is_some() || attribution.email.is_some() || attribution.handle.is_some())
+        .then_some(attribution)
+}
+
+/// Parse the `key: value` lines of a `# Meta` section, in order.
+pub(crate) fn parse_metadata(content: &str) -> Vec<(String, String)> {
+    content.lines().filter_map(metadata_entry).collect()
+}
+
+fn metadata_entry(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    let line = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .unwrap_or(line);
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim();
+    (!key.is_empty()).then(|| (key.to_string(), value.trim().to_string()))
+}
+
+/// Remove the link reference definitions from `content`, outside fenced code
+/// blocks, returning the remaining lines and the definitions in order.
+fn take_link_definitions(content: &str) -> (String, Vec<LinkDefinition>) {
+    let mut lines = Vec::new();
+    let mut definitions = Vec::new();
+    let mut in_code_block = false;
+    let mut fence_char: char = '`';
+    let mut fence_len: usize = 3;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if !in_code_block {
+            if let Some((fc, fl, _)) = parse_fence_open(trimmed) {
+                in_code_block = true;
+                fence_char = fc;
+                fence_len = fl;
+            } else if let Some(definition) = LinkDefinition::parse(line) {
+                definitions.push(definition);
+                continue;
+            }
+        } else if is_closing_fence(trimmed, fence_char, fence_len) {
+            in_code_block = false;
+        }
+        lines.push(line);
+    }
+
+    (lines.join("\n"), definitions)
+}
+
 /// Normalize the raw inner content of a doc comment by stripping consistent
 /// leading whitespace and trimming surrounding blank lines.
 ///
@@ -76,6 +1032,9 @@ pub fn normalize(content: &str) -> String {
         .min()
         .unwrap_or(0);
 
+    #[cfg(feature = "trace")]
+    tracing::trace!(lines = lines.len(), min_indent, "normalizing indentation");
+
     // Strip `min_indent` leading characters from each line.
     // Only-whitespace lines become empty strings.
     let dedented: Vec<String> = lines
@@ -95,11 +1054,9 @@ pub fn normalize(content: &str) -> String {
 }
 
 /// If `trimmed` (a line with leading whitespace already stripped) starts an
-/// opening code fence, return `(fence_char, fence_len, language)`.
-///
-/// Per CommonMark, a fence is 3+ identical backticks or tildes. The opening
-/// line may be followed by an optional language info string.
-fn parse_fence_open(trimmed: &str) -> Option<(char, usize, Option<String>)> {
+/// opening code fence, return `(fence_char, fence_len, info)` with the info
+/// string trimmed, without reading it as [`parse_fence_open`] does.
+pub(crate) fn fence_open(trimmed: &str) -> Option<(char, usize, &str)> {
     let fence_char = if trimmed.starts_with("```") {
         '`'
     } else if trimmed.starts_with("~~~") {
@@ -107,25 +1064,95 @@ fn parse_fence_open(trimmed: &str) -> Option<(char, usize, Option<String>)> {
     } else {
         return None;
     };
-
     let fence_len = trimmed.chars().take_while(|&c| c == fence_char).count();
+    // Everything after the fence chars is the info string.
+    Some((fence_char, fence_len, trimmed[fence_len..].trim()))
+}
 
-    // Everything after the fence chars is the info string (language).
-    // CommonMark: backtick info strings may not contain backticks.
-    let after = trimmed[fence_len..].trim();
+/// If `trimmed` (a line with leading whitespace already stripped) starts an
+/// opening code fence, return `(fence_char, fence_len, info)`.
+///
+/// Per CommonMark, a fence is 3+ identical backticks or tildes. The opening
+/// line may be followed by an info string: the language, then attributes.
+pub(crate) fn parse_fence_open(trimmed: &str) -> Option<(char, usize, FenceInfo)> {
+    let (fence_char, fence_len, after) = fence_open(trimmed)?;
     let language = if after.is_empty() {
         None
     } else {
         // Take only the first whitespace-delimited token as the language.
+        // A leading `key=value` attribute means no language was given.
         let lang = after.split_whitespace().next().unwrap_or("");
-        if lang.is_empty() {
+        if lang.is_empty() || lang.contains('=') {
             None
         } else {
             Some(lang.to_string())
         }
     };
 
-    Some((fence_char, fence_len, language))
+    let info = FenceInfo {
+        language,
+        info: after.to_string(),
+        attributes: parse_info_attributes(after),
+    };
+    Some((fence_char, fence_len, info))
+}
+
+/// Parse the attributes following the language in a fence info string.
+///
+/// `info` is the whole info string after the fence characters, e.g.
+/// `nix title="Basic usage" expect-error`. The first token is the language
+/// unless it is itself an attribute. Every other token is either
+/// `key=value`, where the value may be wrapped in double or single quotes to
+/// include spaces, or a bare flag such as `no-run`, which maps to an empty
+/// string.
+fn parse_info_attributes(info: &str) -> BTreeMap<String, String> {
+    let mut attributes = BTreeMap::new();
+    let mut chars = info.trim().chars().peekable();
+    let mut first = true;
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            key.push(c);
+        }
+
+        let value = if chars.next_if_eq(&'=').is_some() {
+            let mut value = String::new();
+            match chars.next_if(|c| *c == '"' || *c == '\'') {
+                Some(quote) => {
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\\' => value.extend(chars.next()),
+                            c if c == quote => break,
+                            c => value.push(c),
+                        }
+                    }
+                }
+                None => {
+                    while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                        value.push(c);
+                    }
+                }
+            }
+            Some(value)
+        } else {
+            None
+        };
+
+        // The leading bare token is the language, not a flag.
+        let is_language = first && value.is_none();
+        first = false;
+        if !is_language && !key.is_empty() {
+            attributes.insert(key, value.unwrap_or_default());
+        }
+    }
+
+    attributes
 }
 
 /// Returns `true` if `trimmed` is a valid closing fence for a code block that
@@ -134,7 +1161,7 @@ fn parse_fence_open(trimmed: &str) -> Option<(char, usize, Option<String>)> {
 /// Per CommonMark: the closing fence must consist of at least `fence_len`
 /// occurrences of `fence_char`, optionally followed by spaces, with nothing
 /// else on the line.
-fn is_closing_fence(trimmed: &str, fence_char: char, fence_len: usize) -> bool {
+pub(crate) fn is_closing_fence(trimmed: &str, fence_char: char, fence_len: usize) -> bool {
     // All-ASCII fence characters, so char count == byte count here.
     let count = trimmed.chars().take_while(|&c| c == fence_char).count();
     if count < fence_len {
@@ -144,14 +1171,123 @@ fn is_closing_fence(trimmed: &str, fence_char: char, fence_len: usize) -> bool {
     trimmed[count..].chars().all(|c| c == ' ')
 }
 
+/// Rewrite the indented code blocks of normalized content as fenced blocks.
+///
+/// As in CommonMark, an indented code block is a run of lines indented by
+/// at least four spaces that follows a blank line. Indented lines belonging
+/// to a list item, and lines inside fenced blocks, are left alone.
+fn fence_indented_code(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    let mut in_list = false;
+    let mut after_blank = true;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        i += 1;
+
+        if let Some((fc, fl)) = fence {
+            if is_closing_fence(trimmed, fc, fl) {
+                fence = None;
+            }
+            out.push(line.to_string());
+            continue;
+        }
+        if trimmed.is_empty() {
+            after_blank = true;
+            out.push(String::new());
+            continue;
+        }
+
+        if after_blank && !in_list && indent_width(line) >= 4 {
+            // Take the block, up to its last indented line.
+            let start = i - 1;
+            let mut end = i;
+            while i < lines.len() && (lines[i].trim().is_empty() || indent_width(lines[i]) >= 4) {
+                i += 1;
+                if !lines[i - 1].trim().is_empty() {
+                    end = i;
+                }
+            }
+            i = end;
+            out.push("```".to_string());
+            for code in &lines[start..end] {
+                out.push(strip_indent(code, 4).to_string());
+            }
+            out.push("```".to_string());
+        } else {
+            if let Some((fc, fl, _)) = parse_fence_open(trimmed) {
+                fence = Some((fc, fl));
+            }
+            if is_list_item(line) && indent_width(line) < 4 {
+                in_list = true;
+            } else if indent_width(line) == 0 && (after_blank || line.starts_with("# ")) {
+                in_list = false;
+            }
+            out.push(line.to_string());
+        }
+        after_blank = false;
+    }
+    out.join("\n")
+}
+
+/// The width of the leading spaces and tabs of `line`, with tab stops every
+/// four columns.
+fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 4 - width % 4,
+            _ => break,
+        }
+    }
+    width
+}
+
+/// Returns `true` if `line` starts a list item, such as `- a` or `1. a`.
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = if digits > 0 {
+        line[digits..].strip_prefix(['.', ')'])
+    } else {
+        line.strip_prefix(['-', '*', '+'])
+    };
+    rest.is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+}
+
+/// Remove up to `width` columns of leading whitespace from `line`.
+fn strip_indent(line: &str, width: usize) -> &str {
+    let mut column = 0;
+    for (i, c) in line.char_indices() {
+        if column >= width {
+            return &line[i..];
+        }
+        match c {
+            ' ' => column += 1,
+            '\t' => column += 4 - column % 4,
+            _ => return &line[i..],
+        }
+    }
+    ""
+}
+
 /// Parse the normalized content into a (description, sections) pair.
 ///
-/// A level-1 Markdown heading (`# Heading`) at the start of a line begins a
-/// new section, except when inside a fenced code block where `# comment`
-/// lines are not headings.
+/// A level-1 Markdown heading (`# Heading`), indented by at most
+/// `max_indent` spaces, begins a new section, except when inside a fenced
+/// code block where `# comment` lines are not headings.
 ///
 /// Everything before the first heading is the description.
-fn parse_sections(content: &str, warnings: &mut Vec<ParseWarning>) -> (String, Vec<Section>) {
+fn parse_sections(
+    content: &str,
+    max_indent: usize,
+    warnings: &mut Vec<ParseWarning>,
+) -> (String, Vec<Section>) {
     let mut sections: Vec<Section> = Vec::new();
 
     // Lines accumulated before the first section heading.
@@ -160,7 +1296,7 @@ fn parse_sections(content: &str, warnings: &mut Vec<ParseWarning>) -> (String, V
     let mut in_description = true;
 
     // Current section being accumulated.
-    let mut current_heading: Option<String> = None;
+    let mut current_heading: Option<&str> = None;
     let mut section_lines: Vec<&str> = Vec::new();
 
     // Fenced-code-block tracking to avoid treating `# comment` inside code
@@ -170,7 +1306,11 @@ fn parse_sections(content: &str, warnings: &mut Vec<ParseWarning>) -> (String, V
     let mut fence_char: char = '`';
     let mut fence_len: usize = 3;
 
-    for line in content.lines() {
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!("sections").entered();
+
+    #[cfg_attr(not(feature = "trace"), allow(unused_variables))]
+    for (index, line) in content.lines().enumerate() {
         let trimmed = line.trim_start();
 
         // Update code-block state before deciding if the line is a heading.
@@ -179,23 +1319,44 @@ fn parse_sections(content: &str, warnings: &mut Vec<ParseWarning>) -> (String, V
                 in_code_block = true;
                 fence_char = fc;
                 fence_len = fl;
+                #[cfg(feature = "trace")]
+                tracing::trace!(line = index + 1, fence = %trimmed, "code block opened");
             }
         } else if is_closing_fence(trimmed, fence_char, fence_len) {
             in_code_block = false;
+            #[cfg(feature = "trace")]
+            tracing::trace!(line = index + 1, "code block closed");
         }
 
         // Lines inside a code block are never section headings.
-        let is_heading_candidate = !in_code_block && line.starts_with("# ");
+        let candidate = heading_text(line, max_indent).filter(|_| !in_code_block);
+        #[cfg(feature = "trace")]
+        if candidate.is_none() && trimmed.starts_with("# ") {
+            let reason = if in_code_block {
+                "inside a code block"
+            } else {
+                "indented too far"
+            };
+            tracing::debug!(line = index + 1, text = %trimmed, reason, "not a section heading");
+        }
 
-        if is_heading_candidate {
-            let heading = line["# ".len()..].trim().to_string();
+        if let Some(candidate) = candidate {
+            let (heading, untidy) = trim_heading(candidate.trim());
 
             if !heading.is_empty() {
+                if untidy {
+                    warnings.push(ParseWarning {
+                        kind: WarningKind::HeadingStyle,
+                        message: format!("heading '{heading}' has a closing '#' sequence or colon"),
+                    });
+                }
+                #[cfg(feature = "trace")]
+                tracing::debug!(line = index + 1, heading, "section heading");
                 // Finalize what we were accumulating.
                 if in_description {
                     in_description = false;
                 } else if let Some(h) = current_heading.take() {
-                    flush_section(&h, &section_lines, &mut sections, warnings);
+                    flush_section(h, &section_lines, &mut sections, warnings);
                     section_lines.clear();
                 }
                 current_heading = Some(heading);
@@ -213,7 +1374,7 @@ fn parse_sections(content: &str, warnings: &mut Vec<ParseWarning>) -> (String, V
 
     // Flush the last section or absorb remaining lines into the description.
     if let Some(h) = current_heading {
-        flush_section(&h, &section_lines, &mut sections, warnings);
+        flush_section(h, &section_lines, &mut sections, warnings);
     } else {
         // No headings were ever seen; everything is the description.
         description_lines.extend_from_slice(&section_lines);
@@ -223,12 +1384,108 @@ fn parse_sections(content: &str, warnings: &mut Vec<ParseWarning>) -> (String, V
     (description, sections)
 }
 
+/// If `line` is a level-1 heading indented by at most `max_indent` spaces,
+/// returns the text after its `# `.
+fn heading_text(line: &str, max_indent: usize) -> Option<&str> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > max_indent {
+        return None;
+    }
+    line[indent..].strip_prefix("# ")
+}
+
+/// Remove a closing `#` sequence, as in `# Examples #`, and a trailing
+/// colon, as in `# Example:`, from a trimmed heading.
+///
+/// Returns the heading and whether anything was removed. As in CommonMark,
+/// closing hashes must follow a space, so `# C#` keeps its `#`.
+pub(crate) fn trim_heading(heading: &str) -> (&str, bool) {
+    let mut trimmed = heading;
+    let without_hashes = trimmed.trim_end_matches('#');
+    if without_hashes.len() < trimmed.len()
+        && (without_hashes.is_empty() || without_hashes.ends_with([' ', '\t']))
+    {
+        trimmed = without_hashes.trim_end();
+    }
+    if let Some(without_colon) = trimmed.strip_suffix(':') {
+        trimmed = without_colon.trim_end();
+    }
+    (trimmed, trimmed.len() < heading.len())
+}
+
+/// Split an explicit anchor such as `{#ex-foo}` off the end of a heading.
+///
+/// Returns the heading without the anchor and the anchor's ID, if there is
+/// one. IDs must be non-empty and contain no whitespace or braces.
+pub(crate) fn split_explicit_id(heading: &str) -> (&str, Option<&str>) {
+    let trimmed = heading.trim_end();
+    let Some(open) = trimmed.rfind("{#") else {
+        return (heading, None);
+    };
+    let Some(id) = trimmed[open + 2..].strip_suffix('}') else {
+        return (heading, None);
+    };
+    let valid = !id.is_empty() && !id.contains(|c: char| c.is_whitespace() || c == '{' || c == '}');
+    let text = trimmed[..open].trim_end();
+    if valid && (open == 0 || trimmed[..open].ends_with(char::is_whitespace)) {
+        (text, Some(id))
+    } else {
+        (heading, None)
+    }
+}
+
+/// Returns the byte offset and text of every section heading in `content`,
+/// using the same rules as [`parse_sections`] with the default options.
+pub(crate) fn section_headings(content: &str) -> Vec<(usize, String)> {
+    let mut headings = Vec::new();
+    let mut in_code_block = false;
+    let mut fence_char: char = '`';
+    let mut fence_len: usize = 3;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches('\n');
+        let trimmed = line.trim_start();
+
+        if !in_code_block {
+            if let Some((fc, fl, _)) = parse_fence_open(trimmed) {
+                in_code_block = true;
+                fence_char = fc;
+                fence_len = fl;
+            }
+        } else if is_closing_fence(trimmed, fence_char, fence_len) {
+            in_code_block = false;
+        }
+
+        if !in_code_block && let Some(heading) = heading_text(line, DEFAULT_HEADING_INDENT) {
+            let (heading, _) = trim_heading(heading.trim());
+            if !heading.is_empty() {
+                headings.push((start, split_explicit_id(heading).0.to_string()));
+            }
+        }
+    }
+    headings
+}
+
+/// Returns the heading of the section containing byte `offset`, given the
+/// output of [`section_headings`].
+pub(crate) fn section_at(headings: &[(usize, String)], offset: usize) -> Option<&str> {
+    headings
+        .iter()
+        .rev()
+        .find(|(start, _)| *start <= offset)
+        .map(|(_, heading)| heading.as_str())
+}
+
 fn flush_section(
     heading: &str,
     lines: &[&str],
     sections: &mut Vec<Section>,
     warnings: &mut Vec<ParseWarning>,
 ) {
+    let (heading, explicit_id) = split_explicit_id(heading);
     let content = lines.join("\n").trim().to_string();
     if content.is_empty() {
         warnings.push(ParseWarning {
@@ -239,6 +1496,7 @@ fn flush_section(
     sections.push(Section {
         heading: heading.to_string(),
         content,
+        explicit_id: explicit_id.map(str::to_string),
     });
 }
 
@@ -259,20 +1517,78 @@ fn flush_section(
 ///
 /// Continuation lines must be indented (start with whitespace). Non-indented
 /// lines that are not argument entries are treated as prose and ignored.
+///
+/// The definition lists of the nixpkgs manual work too:
+///
+/// ```text
+/// `name`
+/// : Description text
+/// ```
+///
+/// Arguments may also be the rows of a Markdown table whose header names
+/// `name`, `type`, `default` and `description` columns, in any order:
+///
+/// ```text
+/// | name | type | default | description |
+/// |------|------|---------|-------------|
+/// | `xs` | list | `[ ]`   | The items.  |
+/// ```
+///
+/// Without a `name` column, the first column holds the names.
 pub(crate) fn parse_arguments(content: &str) -> Vec<Argument> {
     let mut arguments: Vec<Argument> = Vec::new();
     let mut current_name: Option<String> = None;
     let mut current_desc = String::new();
+    let mut table: Option<Vec<TableColumn>> = None;
+    let mut lines = content.lines().peekable();
 
-    for line in content.lines() {
+    while let Some(line) = lines.next() {
         let trimmed = line.trim();
 
-        if let Some(rest) = trimmed.strip_prefix("- [") {
+        if let Some(columns) = &table {
+            if trimmed.starts_with('|') {
+                arguments.extend(table_argument(columns, trimmed));
+                continue;
+            }
+            table = None;
+        }
+
+        if trimmed.starts_with('|') && lines.peek().is_some_and(|next| is_table_separator(next)) {
+            if let Some(name) = current_name.take() {
+                arguments.push(Argument {
+                    name,
+                    description: current_desc.trim().to_string(),
+                    type_sig: None,
+                    default: None,
+                });
+                current_desc.clear();
+            }
+            table = Some(table_columns(trimmed));
+            lines.next();
+        } else if let Some(term) = definition_term(trimmed)
+            && let Some(definition) = lines
+                .peek()
+                .and_then(|next| next.trim_start().strip_prefix(':'))
+        {
+            if let Some(name) = current_name.take() {
+                arguments.push(Argument {
+                    name,
+                    description: current_desc.trim().to_string(),
+                    type_sig: None,
+                    default: None,
+                });
+            }
+            current_name = Some(term.to_string());
+            current_desc = definition.trim().to_string();
+            lines.next();
+        } else if let Some(rest) = trimmed.strip_prefix("- [") {
             // Flush the previous argument before starting a new one.
             if let Some(name) = current_name.take() {
                 arguments.push(Argument {
                     name,
                     description: current_desc.trim().to_string(),
+                    type_sig: None,
+                    default: None,
                 });
                 current_desc.clear();
             }
@@ -302,31 +1618,688 @@ pub(crate) fn parse_arguments(content: &str) -> Vec<Argument> {
         arguments.push(Argument {
             name,
             description: current_desc.trim().to_string(),
+            type_sig: None,
+            default: None,
         });
     }
 
     arguments
 }
 
+/// The name of a definition list term such as `` `name` ``.
+fn definition_term(line: &str) -> Option<&str> {
+    let name = line.strip_prefix('`')?.strip_suffix('`')?.trim();
+    (!name.is_empty() && !name.contains('`')).then_some(name)
+}
+
+/// Parse an `# Output` section: the prose before the first entry, then
+/// entries as [`parse_arguments`] reads them.
+pub(crate) fn parse_output(content: &str) -> Output {
+    let lines: Vec<&str> = content.lines().collect();
+    let first_entry = lines
+        .iter()
+        .enumerate()
+        .position(|(i, line)| {
+            let trimmed = line.trim();
+            let next = lines.get(i + 1).copied().unwrap_or_default();
+            trimmed.starts_with("- [")
+                || (trimmed.starts_with('|') && is_table_separator(next))
+                || (definition_term(trimmed).is_some() && next.trim_start().starts_with(':'))
+        })
+        .unwrap_or(lines.len());
+    Output {
+        description: lines[..first_entry].join("\n").trim().to_string(),
+        attributes: parse_arguments(content),
+    }
+}
+
+/// Parse a `# Laws` section.
+///
+/// A list item is one law: `` - [name] `expr` ``, `` - name: `expr` ``, or
+/// just the expression, with or without backticks. In a fenced code block,
+/// every line is one law, and a `# name` comment names the law after it.
+pub(crate) fn parse_laws(content: &str) -> Vec<Law> {
+    let mut laws = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    let mut name: Option<String> = None;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some((fence_char, fence_len)) = fence {
+            if is_closing_fence(trimmed, fence_char, fence_len) {
+                fence = None;
+                name = None;
+            } else if let Some(comment) = trimmed.strip_prefix('#') {
+                name = Some(comment.trim().to_string()).filter(|name| !name.is_empty());
+            } else if !trimmed.is_empty() {
+                laws.push(Law {
+                    name: name.take(),
+                    expression: trimmed.to_string(),
+                });
+            }
+        } else if let Some((fence_char, fence_len, _)) = parse_fence_open(trimmed) {
+            fence = Some((fence_char, fence_len));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            laws.extend(list_law(item.trim()));
+        }
+    }
+    laws
+}
+
+/// Parse the text of a `# Laws` list item.
+fn list_law(item: &str) -> Option<Law> {
+    let (name, rest) = if let Some(rest) = item.strip_prefix('[')
+        && let Some((name, rest)) = rest.split_once(']')
+    {
+        (Some(name.trim()), rest.trim())
+    } else if let Some((name, rest)) = item.split_once(':')
+        && rest.trim_start().starts_with('`')
+        && !name.contains('`')
+    {
+        (Some(name.trim()), rest.trim())
+    } else {
+        (None, item)
+    };
+    let expression = match rest.strip_prefix('`') {
+        Some(code) => code.split('`').next().unwrap_or_default(),
+        None => rest,
+    };
+    let expression = expression.trim();
+    (!expression.is_empty()).then(|| Law {
+        name: name.filter(|name| !name.is_empty()).map(str::to_string),
+        expression: expression.to_string(),
+    })
+}
+
+/// What a column of an arguments table holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableColumn {
+    Name,
+    Type,
+    Default,
+    Description,
+    Other,
+}
+
+/// Classify the columns of a table header row. Without a recognised name
+/// column, the first column is taken to hold the names.
+fn table_columns(header: &str) -> Vec<TableColumn> {
+    let mut columns: Vec<TableColumn> = table_cells(header)
+        .iter()
+        .map(|cell| {
+            match cell
+                .trim_matches(|c| matches!(c, '*' | '_' | '`'))
+                .to_lowercase()
+                .as_str()
+            {
+                "name" | "argument" | "parameter" => TableColumn::Name,
+                "type" => TableColumn::Type,
+                "default" => TableColumn::Default,
+                "description" => TableColumn::Description,
+                _ => TableColumn::Other,
+            }
+        })
+        .collect();
+    if !columns.contains(&TableColumn::Name)
+        && let Some(first) = columns.first_mut()
+    {
+        *first = TableColumn::Name;
+    }
+    columns
+}
+
+/// Returns `true` if `line` is a table delimiter row such as `|---|:--:|`.
+fn is_table_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('|')
+        && line.contains('-')
+        && line
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+/// The trimmed cells of a table row, with `\|` unescaped.
+fn table_cells(row: &str) -> Vec<String> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = row.strip_suffix('|').unwrap_or(row);
+    let mut cells = vec![String::new()];
+    let mut chars = row.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cells.last_mut().unwrap().push('|');
+                chars.next();
+            }
+            '|' => cells.push(String::new()),
+            _ => cells.last_mut().unwrap().push(c),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+/// The argument in a table row, if it names one.
+fn table_argument(columns: &[TableColumn], row: &str) -> Option<Argument> {
+    let mut argument = Argument {
+        name: String::new(),
+        description: String::new(),
+        type_sig: None,
+        default: None,
+    };
+    for (column, cell) in columns.iter().zip(table_cells(row)) {
+        let code = cell
+            .strip_prefix('`')
+            .and_then(|c| c.strip_suffix('`'))
+            .unwrap_or(&cell)
+            .trim();
+        match column {
+            TableColumn::Name => {
+                argument.name = code
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .trim()
+                    .to_string();
+            }
+            TableColumn::Type if !code.is_empty() => argument.type_sig = Some(code.to_string()),
+            TableColumn::Default if !code.is_empty() => argument.default = Some(code.to_string()),
+            TableColumn::Description => argument.description = cell,
+            _ => {}
+        }
+    }
+    (!argument.name.is_empty()).then_some(argument)
+}
+
+/// Returns the span and title of each fenced code block in the `content` of
+/// an examples section, with titles chosen as in [`parse_examples`].
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub(crate) fn example_blocks(content: &str) -> Vec<(Span, Option<String>)> {
+    FenceParser::parse_blocks(content)
+        .into_iter()
+        .map(|block| {
+            let caption = block
+                .caption
+                .as_deref()
+                .map(|caption| split_explicit_id(caption).0.to_string());
+            let title = block
+                .info
+                .attributes
+                .get("title")
+                .cloned()
+                .or(caption)
+                .filter(|title| !title.is_empty());
+            (block.span, title)
+        })
+        .collect()
+}
+
+/// Every fenced code block of `raw`, a comment's raw content, with the kind
+/// of the section it is in.
+pub(crate) fn code_blocks(raw: &str) -> Vec<CodeBlock> {
+    let headings = section_headings(raw);
+    let description_end = headings.first().map_or(raw.len(), |(start, _)| *start);
+    let regions = core::iter::once((None, 0, description_end)).chain(
+        headings.iter().enumerate().map(|(i, (start, heading))| {
+            let end = headings.get(i + 1).map_or(raw.len(), |(next, _)| *next);
+            let body_start = raw[*start..end].find('\n').map_or(end, |n| start + n + 1);
+            (Some(SectionKind::from_heading(heading)), body_start, end)
+        }),
+    );
+
+    let mut blocks = Vec::new();
+    for (section_kind, start, end) in regions {
+        for block in FenceParser::parse_blocks(&raw[start..end]) {
+            let span = Span::new(start + block.span.start, start + block.span.end);
+            // As in CommonMark, the code loses the indentation of its fence,
+            // such as that of a list item.
+            let line_start = raw[..span.start].rfind('\n').map_or(0, |i| i + 1);
+            let indent = span.start - line_start;
+            let code = if indent == 0 {
+                block.code
+            } else {
+                block
+                    .code
+                    .split_inclusive('\n')
+                    .map(|line| strip_indent(line, indent))
+                    .collect()
+            };
+            blocks.push(CodeBlock {
+                section_kind: section_kind.clone(),
+                language: block.info.language,
+                code,
+                span,
+            });
+        }
+    }
+    blocks
+}
+
 /// Parse code examples from the body of an `# Example` or `# Examples` section.
 ///
 /// Each example is a fenced code block delimited by ` ``` ` or `~~~`. Multiple
 /// examples may appear in a single section, separated by prose or other content.
 /// Fences of 4 or more backticks/tildes are handled correctly.
+///
+/// Transcripts of `nix repl` sessions (`nix-repl> expr` followed by output)
+/// yield one example per prompted expression, see [`split_repl`].
+///
+/// Each example takes its title from a `title` attribute on the fence, or
+/// failing that from the nearest caption preceding it within the section:
+/// either a `## Title` subheading or a line consisting solely of bold text
+/// (`**Title**`, optionally with a trailing colon).
+///
+/// A block flagged `setup` holds bindings shared by the blocks after it under
+/// the same caption, which receive them in [`Example::setup`]. A new caption
+/// starts a new group; a block flagged `standalone` opts out.
 pub(crate) fn parse_examples(content: &str) -> Vec<Example> {
-    FenceParser::parse_blocks(content)
+    spanned_examples(content)
         .into_iter()
-        .map(|(language, code)| Example { language, code })
+        .map(|(_, example)| example)
         .collect()
 }
 
+/// Parse examples as [`parse_examples`] does, with the span of the code
+/// block each one comes from.
+pub(crate) fn spanned_examples(content: &str) -> Vec<(Span, Example)> {
+    let mut examples = Vec::new();
+    // Setup code shared by the blocks under the current caption.
+    let mut setup: Vec<String> = Vec::new();
+    let mut setup_caption: Option<String> = None;
+
+    for block in FenceParser::parse_blocks(content) {
+        if block.caption != setup_caption {
+            setup.clear();
+            setup_caption = block.caption.clone();
+        }
+
+        let pairs = if is_repl_transcript(&block.code) {
+            split_repl(&block.code)
+        } else {
+            split_expected(&block.code)
+        };
+        // An explicit `title` attribute beats a caption above the block, and
+        // an `id` attribute beats the caption's `{#id}`.
+        let (caption, caption_id) = match &block.caption {
+            Some(caption) => {
+                let (text, id) = split_explicit_id(caption);
+                (Some(text.to_string()), id.map(str::to_string))
+            }
+            None => (None, None),
+        };
+        let attributes = &block.info.attributes;
+        let title = attributes
+            .get("title")
+            .cloned()
+            .or(caption)
+            .filter(|title| !title.is_empty());
+        let id = attributes.get("id").cloned().or(caption_id);
+        let shared_setup = if setup.is_empty() || attributes.contains_key("standalone") {
+            None
+        } else {
+            Some(setup.join("\n"))
+        };
+
+        if attributes.contains_key("setup") {
+            setup.push(block.code.trim_end().to_string());
+        }
+
+        let info = &block.info;
+        examples.extend(pairs.into_iter().map(|(input, expected)| {
+            let example = Example {
+                language: info.language.clone(),
+                info: (!info.info.is_empty()).then(|| info.info.clone()),
+                code: block.code.clone(),
+                title: title.clone(),
+                id: id.clone(),
+                attributes: info.attributes.clone(),
+                setup: shared_setup.clone(),
+                input,
+                expected,
+            };
+            (block.span, example)
+        }));
+    }
+
+    examples
+}
+
+/// Parse the examples of every example section in a comment's
+/// [`raw_content`](DocComment::raw_content), with the span of the code block
+/// each one comes from, relative to `raw`.
+#[cfg(any(feature = "doctest", feature = "rnix"))]
+pub(crate) fn raw_examples(raw: &str) -> Vec<(Span, Example)> {
+    let headings = section_headings(raw);
+    let mut examples = Vec::new();
+    for (i, (start, heading)) in headings.iter().enumerate() {
+        if !matches!(
+            SectionKind::from_heading(heading),
+            SectionKind::Example | SectionKind::Examples
+        ) {
+            continue;
+        }
+        let end = headings.get(i + 1).map_or(raw.len(), |(next, _)| *next);
+        let body_start = raw[*start..end].find('\n').map_or(end, |n| start + n + 1);
+        examples.extend(spanned_examples(&raw[body_start..end]).into_iter().map(
+            |(span, example)| {
+                (
+                    Span::new(body_start + span.start, body_start + span.end),
+                    example,
+                )
+            },
+        ));
+    }
+    examples
+}
+
+/// The prompt printed by `nix repl` before each input line.
+const REPL_PROMPT: &str = "nix-repl>";
+
+/// Returns `true` if `code` is a `nix repl` transcript rather than plain code.
+fn is_repl_transcript(code: &str) -> bool {
+    code.lines()
+        .any(|line| line.trim_start().starts_with(REPL_PROMPT))
+}
+
+/// Split a `nix repl` transcript into `(input, expected)` pairs.
+///
+/// Older comments show REPL sessions instead of using the `=>` convention:
+///
+/// ```text
+/// nix-repl> x = 3
+/// nix-repl> lib.toList x
+/// [ 3 ]
+/// ```
+///
+/// Every prompted expression becomes one pair, with the lines printed after
+/// it as the expected result. Prompted bindings (`name = value`) print nothing;
+/// they are carried into later pairs through a `let` expression so each input
+/// stands on its own. The `:p` command is treated as its argument, and other
+/// REPL commands such as `:l` are ignored.
+fn split_repl(code: &str) -> Vec<(String, Option<String>)> {
+    let mut pairs = Vec::new();
+    let mut bindings: Vec<String> = Vec::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    let mut finish = |input: String, output: &[&str], bindings: &mut Vec<String>| {
+        let output = output.join("\n").trim().to_string();
+        if output.is_empty() && is_repl_binding(&input) {
+            bindings.push(input);
+            return;
+        }
+        let input = with_bindings(bindings, input);
+        pairs.push((input, (!output.is_empty()).then_some(output)));
+    };
+
+    for line in code.lines() {
+        if let Some(rest) = line.trim_start().strip_prefix(REPL_PROMPT) {
+            if let Some((input, output)) = current.take() {
+                finish(input, &output, &mut bindings);
+            }
+            let rest = rest.trim();
+            let input = match rest.strip_prefix(':') {
+                Some(command) => match command.strip_prefix("p ") {
+                    Some(expr) => expr.trim(),
+                    None => continue,
+                },
+                None => rest,
+            };
+            if !input.is_empty() {
+                current = Some((input.to_string(), Vec::new()));
+            }
+        } else if let Some((_, output)) = current.as_mut() {
+            output.push(line);
+        }
+    }
+    if let Some((input, output)) = current.take() {
+        finish(input, &output, &mut bindings);
+    }
+
+    if pairs.is_empty() {
+        pairs.push((code.trim_end().to_string(), None));
+    }
+    pairs
+}
+
+/// Put `bindings` in scope of `input` with a `let` expression, unless there
+/// are none.
+fn with_bindings(bindings: &[String], input: String) -> String {
+    if bindings.is_empty() {
+        return input;
+    }
+    let mut with = String::from("let\n");
+    for binding in bindings {
+        with.push_str(&format!("  {};\n", binding.trim_end_matches(';')));
+    }
+    with.push_str("in\n");
+    with.push_str(&input);
+    with
+}
+
+/// Returns `true` if a REPL input line is a `name = value` binding.
+fn is_repl_binding(input: &str) -> bool {
+    let Some((name, value)) = input.split_once('=') else {
+        return false;
+    };
+    let name = name.trim();
+    !value.starts_with('=')
+        && !value.trim().is_empty()
+        && name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '\'')
+}
+
+/// Split example code on the `=>` convention into `(input, expected)` pairs.
+///
+/// Each line starting with `=>` (after indentation) separates an input
+/// expression from its expected result. The marker itself is removed. The
+/// result continues on the following lines while it has unclosed brackets or
+/// strings, so multi-line results such as attribute sets are kept intact;
+/// the line after it starts the next input.
+///
+/// ```text
+/// concat = foldr (a: b: a + b) "z"
+/// concat [ "a" "b" "c" ]
+/// => "abcz"
+/// # different types
+/// strange = foldr (int: str: toString (int + 1) + str) "a"
+/// strange [ 1 2 3 4 ]
+/// => "2345a"
+/// ```
+///
+/// As in [`split_repl`], an input may start with `name = value` bindings.
+/// They are carried into this and every later pair through a `let`
+/// expression, and comment lines next to them are dropped.
+///
+/// Without a marker the whole block is the input and the expected output is
+/// `None`. Both parts have trailing whitespace removed.
+fn split_expected(code: &str) -> Vec<(String, Option<String>)> {
+    let mut pairs = Vec::new();
+    let mut bindings: Vec<String> = Vec::new();
+    let mut input_lines: Vec<&str> = Vec::new();
+    let mut lines = code.lines();
+
+    while let Some(line) = lines.next() {
+        let Some(rest) = line.trim_start().strip_prefix("=>") else {
+            input_lines.push(line);
+            continue;
+        };
+        let mut expected = rest.trim().to_string();
+        while expected.trim().is_empty() || open_brackets(&expected) > 0 {
+            let Some(more) = lines.next() else {
+                break;
+            };
+            expected.push('\n');
+            expected.push_str(more);
+        }
+        let input = expected_input(&input_lines, &mut bindings);
+        pairs.push((input, Some(expected.trim().to_string())));
+        input_lines.clear();
+    }
+
+    if pairs.is_empty() {
+        return vec![(code.trim_end().to_string(), None)];
+    }
+    // Code after the last result is an example without one.
+    if input_lines
+        .iter()
+        .any(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+    {
+        let input = expected_input(&input_lines, &mut bindings);
+        pairs.push((input, None));
+    }
+    pairs
+}
+
+/// Build the input of one `=>` pair from its lines, moving leading
+/// `name = value` bindings into `bindings`; see [`split_expected`].
+///
+/// A line continues the statement before it if it is indented or that
+/// statement has unclosed brackets.
+fn expected_input(lines: &[&str], bindings: &mut Vec<String>) -> String {
+    let lines: Vec<&str> = lines
+        .iter()
+        .copied()
+        .skip_while(|line| line.trim().is_empty())
+        .collect();
+    let mut statements: Vec<String> = Vec::new();
+    for line in &lines {
+        match statements.last_mut() {
+            Some(last) if line.starts_with(char::is_whitespace) || open_brackets(last) > 0 => {
+                last.push('\n');
+                last.push_str(line);
+            }
+            _ => statements.push(line.to_string()),
+        }
+    }
+
+    let mut expression: Vec<String> = Vec::new();
+    for statement in statements {
+        let trimmed = statement.trim();
+        if expression.is_empty() && is_repl_binding(trimmed) {
+            bindings.push(trimmed.to_string());
+        } else if !trimmed.is_empty() && (!trimmed.starts_with('#') || !expression.is_empty()) {
+            expression.push(statement);
+        }
+    }
+
+    if bindings.is_empty() || expression.is_empty() {
+        return lines.join("\n").trim_end().to_string();
+    }
+    with_bindings(bindings, expression.join("\n").trim_end().to_string())
+}
+
+/// Count the brackets, braces and parentheses `code` leaves open, ignoring
+/// those in comments. An unclosed string counts as one more.
+fn open_brackets(code: &str) -> isize {
+    let mut depth = 0;
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            '#' => chars.by_ref().take_while(|&c| c != '\n').for_each(drop),
+            '"' => {
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                depth += isize::from(!closed);
+            }
+            '\'' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    if c != '\'' || chars.next_if_eq(&'\'').is_none() {
+                        continue;
+                    }
+                    // `'''`, `''$` and `''\` escape the next character.
+                    match chars.peek() {
+                        Some('\'' | '$') => {
+                            chars.next();
+                        }
+                        Some('\\') => {
+                            chars.next();
+                            chars.next();
+                        }
+                        _ => {
+                            closed = true;
+                            break;
+                        }
+                    }
+                }
+                depth += isize::from(!closed);
+            }
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// If `trimmed` is an example caption, return the caption text.
+///
+/// Two caption styles are recognised:
+///
+/// - A Markdown subheading of level 2 to 6 (`## Title`); closing `#`
+///   sequences are removed.
+/// - A line made up entirely of bold text (`**Title**` or `__Title__`),
+///   optionally followed by a colon (`**Title:**` and `**Title**:` both work).
+fn parse_caption(trimmed: &str) -> Option<String> {
+    let trimmed = trimmed.trim_end();
+
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (2..=6).contains(&hashes) {
+        let rest = &trimmed[hashes..];
+        if !rest.starts_with(' ') {
+            return None;
+        }
+        let title = rest.trim().trim_end_matches('#').trim();
+        return if title.is_empty() {
+            None
+        } else {
+            Some(title.to_string())
+        };
+    }
+
+    for marker in ["**", "__"] {
+        let line = trimmed.strip_suffix(':').unwrap_or(trimmed);
+        if let Some(inner) = line
+            .strip_prefix(marker)
+            .and_then(|rest| rest.strip_suffix(marker))
+        {
+            let title = inner.trim().trim_end_matches(':').trim();
+            if !title.is_empty() && !title.contains(marker) {
+                return Some(title.to_string());
+            }
+        }
+    }
+
+    None
+}
+
 /// Extract the content of the first fenced code block in a string.
 ///
 /// Used by [`DocComment::type_sig`] to pull the type signature out of a
 /// `# Type` section. Returns `None` if no code block is found.
 /// Fences of 4 or more backticks/tildes are handled correctly.
 pub(crate) fn extract_first_code_block(content: &str) -> Option<String> {
-    FenceParser::first_block(content)
+    FenceParser::first_block(content).map(|(code, _)| code)
+}
+
+/// The info string of the first fenced code block in a string, see
+/// [`extract_first_code_block`].
+pub(crate) fn first_code_block_info(content: &str) -> Option<FenceInfo> {
+    FenceParser::first_block(content).map(|(_, info)| info)
 }
 
 /// Extract a legacy inline type annotation from a description string.
@@ -344,11 +2317,43 @@ pub(crate) fn extract_first_code_block(content: &str) -> Option<String> {
 /// This function scans lines for the `identifier :: type` pattern and returns
 /// the first matching line. Returns `None` if no such line is found.
 ///
+/// A signature wrapped onto following lines, either after a trailing `\\` or
+/// on lines indented deeper than the signature, is joined into one line:
+///
+/// ```text
+/// mapAttrsRecursiveCond ::
+///   (AttrSet -> Bool) -> ([String] -> a -> b) -> AttrSet -> AttrSet
+/// ```
+///
 /// This is a fallback for [`DocComment::type_sig`]; the modern `# Type` section
 /// always takes precedence.
 pub(crate) fn extract_inline_type_sig(content: &str) -> Option<String> {
-    for line in content.lines() {
-        if let Some(sig) = parse_inline_type_line(line.trim()) {
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let lines: Vec<&str> = content.lines().collect();
+
+    for (i, line) in lines.iter().enumerate() {
+        let Some(first) = parse_inline_type_line(line.trim()) else {
+            continue;
+        };
+        let mut sig = first.to_string();
+        for next in &lines[i + 1..] {
+            let next_trimmed = next.trim();
+            if next_trimmed.is_empty() {
+                break;
+            }
+            if let Some(wrapped) = sig.strip_suffix('\\') {
+                sig = wrapped.trim_end().to_string();
+            } else if indent_of(next) <= indent_of(line)
+                || parse_inline_type_line(next_trimmed).is_some()
+            {
+                break;
+            }
+            sig.push(' ');
+            sig.push_str(next_trimmed);
+        }
+
+        let sig = sig.strip_suffix('\\').unwrap_or(&sig).trim_end();
+        if !sig.ends_with("::") {
             return Some(sig.to_string());
         }
     }
@@ -359,13 +2364,13 @@ pub(crate) fn extract_inline_type_sig(content: &str) -> Option<String> {
 ///
 /// The identifier before `::` must be a valid Nix name (letters, digits,
 /// underscores, hyphens, primes). An empty or multi-word prefix is rejected
-/// to avoid false positives in prose descriptions.
+/// to avoid false positives in prose descriptions. The type may be empty,
+/// for a signature that continues on the next line.
 fn parse_inline_type_line(line: &str) -> Option<&str> {
     let sep = line.find("::")?;
     let before = line[..sep].trim();
-    let after = line[sep + 2..].trim();
 
-    if before.is_empty() || after.is_empty() {
+    if before.is_empty() {
         return None;
     }
 
@@ -377,12 +2382,22 @@ fn parse_inline_type_line(line: &str) -> Option<&str> {
     if is_valid_ident { Some(line) } else { None }
 }
 
+/// A fenced code block found by [`FenceParser::parse_blocks`].
+struct FencedBlock {
+    info: FenceInfo,
+    code: String,
+    /// The nearest caption preceding the block, see [`parse_caption`].
+    caption: Option<String>,
+    /// The block, fences included, within the parsed content.
+    span: Span,
+}
+
 struct FenceParser {
     in_block: bool,
     fence_char: char,
     fence_len: usize,
     content: String,
-    language: Option<String>,
+    info: FenceInfo,
 }
 
 impl FenceParser {
@@ -392,69 +2407,84 @@ impl FenceParser {
             fence_char: '`',
             fence_len: 3,
             content: String::new(),
-            language: None,
+            info: FenceInfo::default(),
         }
     }
 
-    fn parse_blocks(content: &str) -> Vec<(Option<String>, String)> {
+    fn parse_blocks(content: &str) -> Vec<FencedBlock> {
         let mut parser = Self::new();
         let mut blocks = Vec::new();
+        let mut caption: Option<String> = None;
+        let mut block_start = 0;
+        let mut offset = 0;
 
-        for line in content.lines() {
+        for line in content.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
             let trimmed = line.trim_start();
 
             if !parser.in_block {
-                if let Some((fc, fl, lang)) = parse_fence_open(trimmed) {
+                if let Some((fc, fl, info)) = parse_fence_open(trimmed) {
+                    block_start = line_start + (line.len() - trimmed.len());
                     parser.in_block = true;
                     parser.fence_char = fc;
                     parser.fence_len = fl;
-                    parser.language = lang;
+                    parser.info = info;
                     parser.content.clear();
+                } else if let Some(c) = parse_caption(trimmed) {
+                    caption = Some(c);
                 }
             } else if is_closing_fence(trimmed, parser.fence_char, parser.fence_len) {
-                blocks.push((parser.language.take(), std::mem::take(&mut parser.content)));
+                blocks.push(FencedBlock {
+                    info: core::mem::take(&mut parser.info),
+                    code: core::mem::take(&mut parser.content),
+                    caption: caption.clone(),
+                    span: Span::new(block_start, line_start + line.trim_end().len()),
+                });
                 parser.in_block = false;
             } else {
-                if !parser.content.is_empty() {
-                    parser.content.push('\n');
-                }
                 parser.content.push_str(line);
                 parser.content.push('\n');
             }
         }
 
         if parser.in_block && !parser.content.is_empty() {
-            blocks.push((parser.language.take(), parser.content));
+            blocks.push(FencedBlock {
+                info: parser.info,
+                code: parser.content,
+                caption,
+                span: Span::new(block_start, content.trim_end().len()),
+            });
         }
 
         blocks
     }
 
-    fn first_block(content: &str) -> Option<String> {
+    fn first_block(content: &str) -> Option<(String, FenceInfo)> {
         let mut parser = Self::new();
 
         for line in content.lines() {
             let trimmed = line.trim_start();
 
             if !parser.in_block {
-                if let Some((fc, fl, _)) = parse_fence_open(trimmed) {
+                if let Some((fc, fl, info)) = parse_fence_open(trimmed) {
                     parser.in_block = true;
                     parser.fence_char = fc;
                     parser.fence_len = fl;
+                    parser.info = info;
                 }
             } else if is_closing_fence(trimmed, parser.fence_char, parser.fence_len) {
-                return Some(parser.content);
+                return Some((parser.content, parser.info));
             } else {
-                if !parser.content.is_empty() {
-                    parser.content.push('\n');
-                }
                 parser.content.push_str(line);
                 parser.content.push('\n');
             }
         }
 
         if parser.in_block && !parser.content.is_empty() {
-            Some(parser.content)
+            Some((parser.content, parser.info))
         } else {
             None
         }
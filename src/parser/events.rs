@@ -0,0 +1,398 @@
+//! A streaming view of a doc comment, line by line.
+
+use super::{
+    fence_open, indent_width, is_closing_fence, is_list_item, is_zero_width, split_explicit_id,
+    strip_indent, trim_heading,
+};
+use crate::{ParseError, ParserOptions, Span, UnicodePolicy};
+
+/// One step of a doc comment, as produced by [`events`].
+///
+/// Text is borrowed from the input, with the comment's common indentation
+/// removed. Section and fence events are balanced: every
+/// [`SectionStart`](Self::SectionStart) is followed by a
+/// [`SectionEnd`](Self::SectionEnd), and every
+/// [`FenceStart`](Self::FenceStart) by a [`FenceEnd`](Self::FenceEnd), even
+/// if the comment ends inside a code block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseEvent<'a> {
+    /// A line of the description, before the first section heading.
+    DescriptionText(&'a str),
+    /// A level-1 heading starts a section.
+    SectionStart {
+        /// The heading, without its `#` and explicit ID.
+        heading: &'a str,
+        /// The explicit ID of a heading such as `# Examples {#ex}`.
+        id: Option<&'a str>,
+    },
+    /// A line of prose within a section.
+    SectionText(&'a str),
+    /// A blank line outside code blocks.
+    BlankLine,
+    /// A fenced code block starts, or an indented one with
+    /// [`ParserOptions::indented_code`].
+    FenceStart {
+        /// The info string after the fence, trimmed, such as `nix`. Empty
+        /// for an indented code block.
+        info: &'a str,
+    },
+    /// A line within a code block. The four columns of an indented code
+    /// block's indentation are removed.
+    CodeLine(&'a str),
+    /// A fenced code block ends.
+    FenceEnd,
+    /// The current section ends, at the next heading or at the end of the
+    /// comment.
+    SectionEnd,
+    /// The input is not a doc comment. This is the only event.
+    Error(ParseError),
+}
+
+/// Stream the events of a `/** … */` doc comment without building a
+/// [`DocComment`](crate::DocComment).
+///
+/// Nothing is allocated: every event borrows from `input`. The events follow
+/// the lines as written, so link definitions, front matter and gutters come
+/// through as text. Uses the default [`ParserOptions`]; see [`events_with`].
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::parser::{ParseEvent, events};
+///
+/// let input = "/**\n  Add one.\n\n  # Example\n\n  ```nix\n  inc 1\n  ```\n*/";
+/// let events: Vec<_> = events(input).collect();
+/// assert_eq!(
+///     events,
+///     [
+///         ParseEvent::DescriptionText("Add one."),
+///         ParseEvent::BlankLine,
+///         ParseEvent::SectionStart { heading: "Example", id: None },
+///         ParseEvent::BlankLine,
+///         ParseEvent::FenceStart { info: "nix" },
+///         ParseEvent::CodeLine("inc 1"),
+///         ParseEvent::FenceEnd,
+///         ParseEvent::SectionEnd,
+///     ]
+/// );
+/// ```
+pub fn events(input: &str) -> Events<'_> {
+    events_with(input, &ParserOptions::default())
+}
+
+/// Stream the events of a doc comment as [`events`] does, with `options`.
+///
+/// The options that decide where the comment, its sections and its code
+/// blocks are apply as they do to
+/// [`DocComment::parse_with`](crate::DocComment::parse_with):
+/// [`delimiters`](ParserOptions::delimiters),
+/// [`legacy_comments`](ParserOptions::legacy_comments),
+/// [`recover_unclosed`](ParserOptions::recover_unclosed),
+/// [`max_heading_indent`](ParserOptions::max_heading_indent),
+/// [`indented_code`](ParserOptions::indented_code), and
+/// [`unicode`](ParserOptions::unicode), which unless it is
+/// [`UnicodePolicy::Preserve`] skips a byte order mark and counts zero-width
+/// characters as spaces in indentation and headings. Events carry no
+/// warnings and borrow the text unchanged, so
+/// [`deny_warnings`](ParserOptions::deny_warnings),
+/// [`profile`](ParserOptions::profile) and
+/// [`dialect`](ParserOptions::dialect) do not change them.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::ParserOptions;
+/// use nixdoc::parser::{ParseEvent, events_with};
+///
+/// let options = ParserOptions::new().with_delimiters("##", "##");
+/// let events: Vec<_> = events_with("## Add one. ##", &options).collect();
+/// assert_eq!(events, [ParseEvent::DescriptionText("Add one.")]);
+/// ```
+pub fn events_with<'a>(input: &'a str, options: &ParserOptions) -> Events<'a> {
+    Events::new(input, options)
+}
+
+/// The iterator returned by [`events`] and [`events_with`].
+///
+/// [`spanned`](Self::spanned) also yields where each event is in the input,
+/// for consumers such as syntax highlighters.
+#[derive(Debug, Clone)]
+pub struct Events<'a> {
+    input: &'a str,
+    /// The byte offset of the next line.
+    offset: usize,
+    /// The byte offset where the content ends.
+    end: usize,
+    /// The common indentation, in characters.
+    indent: usize,
+    max_heading_indent: usize,
+    indented_code: bool,
+    /// Whether zero-width characters count as spaces.
+    lenient_unicode: bool,
+    in_section: bool,
+    fence: Option<(char, usize)>,
+    /// Whether the current line is in an indented code block.
+    indented: bool,
+    /// Whether the previous line outside code blocks was blank.
+    after_blank: bool,
+    in_list: bool,
+    /// An event to yield before reading the next line.
+    pending: Option<(ParseEvent<'a>, Span)>,
+    done: bool,
+}
+
+impl<'a> Events<'a> {
+    fn new(input: &'a str, options: &ParserOptions) -> Self {
+        let mut events = Self {
+            input,
+            offset: 0,
+            end: 0,
+            indent: 0,
+            max_heading_indent: options.max_heading_indent,
+            indented_code: options.indented_code,
+            lenient_unicode: options.unicode != UnicodePolicy::Preserve,
+            in_section: false,
+            fence: None,
+            indented: false,
+            after_blank: true,
+            in_list: false,
+            pending: None,
+            done: false,
+        };
+
+        let start = input.len() - input.trim_start_matches(|c| events.is_space(c)).len();
+        let trimmed = input[start..].trim_end();
+        let (inner_start, inner, is_legacy) = match &options.delimiters {
+            None => (start, trimmed, false),
+            Some((open, close)) => {
+                let opener = [Some(open.as_str()), options.legacy_open()]
+                    .into_iter()
+                    .flatten()
+                    .find(|opener| trimmed.starts_with(opener));
+                let Some(opener) = opener else {
+                    return events.fail(ParseError::NotDocComment, Span::new(start, start));
+                };
+                let rest = &trimmed[opener.len()..];
+                let inner = match rest.strip_suffix(close.as_str()) {
+                    Some(inner) => inner,
+                    None if options.recover_unclosed => rest,
+                    None => {
+                        return events.fail(ParseError::UnclosedComment, Span::new(start, start));
+                    }
+                };
+                (start + opener.len(), inner, opener != open)
+            }
+        };
+        let content = inner.trim();
+        if content.is_empty() {
+            let span = Span::new(inner_start, inner_start + inner.len());
+            return events.fail(ParseError::EmptyComment, span);
+        }
+
+        // Start at the line of the first text, so its indentation counts.
+        let content_start = inner_start + (inner.len() - inner.trim_start().len());
+        events.offset = input[..content_start]
+            .rfind('\n')
+            .map_or(inner_start, |i| (i + 1).max(inner_start));
+        events.end = content_start + content.len();
+        // A legacy comment's first line may follow `/*` directly; as in the
+        // parser, it does not count towards the common indentation.
+        let skip_first = is_legacy && events.offset == inner_start;
+        events.indent = input[events.offset..events.end]
+            .lines()
+            .skip(usize::from(skip_first))
+            .filter(|line| !line.trim_matches(|c| events.is_space(c)).is_empty())
+            .map(|line| line.chars().take_while(|&c| events.is_space(c)).count())
+            .min()
+            .unwrap_or(0);
+        events
+    }
+
+    /// End the stream with an [`ParseEvent::Error`].
+    fn fail(mut self, error: ParseError, span: Span) -> Self {
+        self.pending = Some((ParseEvent::Error(error), span));
+        self.done = true;
+        self
+    }
+
+    fn is_space(&self, c: char) -> bool {
+        c.is_whitespace() || (self.lenient_unicode && is_zero_width(c))
+    }
+
+    /// `line` without the common indentation.
+    fn unindent(&self, line: &'a str) -> &'a str {
+        let skip: usize = line
+            .chars()
+            .take(self.indent)
+            .take_while(|&c| self.is_space(c))
+            .map(char::len_utf8)
+            .sum();
+        &line[skip..]
+    }
+
+    /// Returns `true` if the next non-blank line continues an indented code
+    /// block.
+    fn indented_code_follows(&self) -> bool {
+        self.input[self.offset.min(self.end)..self.end]
+            .lines()
+            .find(|line| !line.trim_matches(|c| self.is_space(c)).is_empty())
+            .is_some_and(|line| indent_width(self.unindent(line)) >= 4)
+    }
+
+    /// Yield each event with its span in the input.
+    ///
+    /// Text spans cover the unindented text. [`ParseEvent::SectionEnd`] and
+    /// the closing [`ParseEvent::FenceEnd`] of an unclosed block are empty
+    /// spans where they take effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::parser::{ParseEvent, events};
+    ///
+    /// let input = "/**\n  Title.\n\n  # Note {#n}\n*/";
+    /// let spans: Vec<_> = events(input)
+    ///     .spanned()
+    ///     .map(|(_, span)| &input[span.start..span.end])
+    ///     .collect();
+    /// assert_eq!(spans, ["Title.", "", "# Note {#n}", ""]);
+    /// ```
+    pub fn spanned(self) -> Spanned<'a> {
+        Spanned(self)
+    }
+
+    fn next_spanned(&mut self) -> Option<(ParseEvent<'a>, Span)> {
+        if let Some(pending) = self.pending.take() {
+            return Some(pending);
+        }
+        if self.done {
+            return None;
+        }
+        if self.offset >= self.end {
+            self.done = true;
+            let at = Span::new(self.end, self.end);
+            if self.fence.take().is_some() || core::mem::take(&mut self.indented) {
+                if self.in_section {
+                    self.pending = Some((ParseEvent::SectionEnd, at));
+                }
+                return Some((ParseEvent::FenceEnd, at));
+            }
+            return self.in_section.then_some((ParseEvent::SectionEnd, at));
+        }
+
+        let rest = &self.input[self.offset..self.end];
+        let line = rest.split('\n').next().unwrap_or_default();
+        let line_start = self.offset;
+        self.offset += line.len() + 1;
+        let line = line.trim_end_matches('\r');
+        let blank = line.trim_matches(|c| self.is_space(c)).is_empty();
+        let text = self.unindent(line);
+        let span = Span::new(
+            line_start + line.len() - text.len(),
+            line_start + line.len(),
+        );
+
+        if self.indented {
+            let continues = if blank {
+                self.indented_code_follows()
+            } else {
+                indent_width(text) >= 4
+            };
+            if continues {
+                let code = if blank { "" } else { strip_indent(text, 4) };
+                let span = Span::new(span.end - code.len(), span.end);
+                return Some((ParseEvent::CodeLine(code), span));
+            }
+            // Read this line again after the block.
+            self.indented = false;
+            self.offset = line_start;
+            return Some((ParseEvent::FenceEnd, Span::new(line_start, line_start)));
+        }
+
+        if blank {
+            let span = Span::new(line_start, line_start);
+            return Some(match self.fence {
+                Some(_) => (ParseEvent::CodeLine(""), span),
+                None => {
+                    self.after_blank = true;
+                    (ParseEvent::BlankLine, span)
+                }
+            });
+        }
+
+        let trimmed = text.trim_start();
+        if let Some((fence_char, fence_len)) = self.fence {
+            if is_closing_fence(trimmed, fence_char, fence_len) {
+                self.fence = None;
+                return Some((ParseEvent::FenceEnd, span));
+            }
+            return Some((ParseEvent::CodeLine(text), span));
+        }
+
+        let after_blank = core::mem::replace(&mut self.after_blank, false);
+        if self.indented_code {
+            if after_blank && !self.in_list && indent_width(text) >= 4 {
+                // Read this line again as the first line of code.
+                self.indented = true;
+                self.offset = line_start;
+                let at = Span::new(line_start, line_start);
+                return Some((ParseEvent::FenceStart { info: "" }, at));
+            }
+            if is_list_item(text) && indent_width(text) < 4 {
+                self.in_list = true;
+            } else if indent_width(text) == 0 && (after_blank || text.starts_with("# ")) {
+                self.in_list = false;
+            }
+        }
+
+        if let Some((fence_char, fence_len, info)) = fence_open(trimmed) {
+            self.fence = Some((fence_char, fence_len));
+            return Some((ParseEvent::FenceStart { info }, span));
+        }
+
+        let indent = text.len()
+            - text
+                .trim_start_matches(|c| c == ' ' || (self.lenient_unicode && is_zero_width(c)))
+                .len();
+        if indent <= self.max_heading_indent
+            && let Some(heading) = text[indent..].strip_prefix("# ")
+        {
+            let (heading, _) = trim_heading(heading.trim_matches(|c| self.is_space(c)));
+            if !heading.is_empty() {
+                let (heading, id) = split_explicit_id(heading);
+                let start = (ParseEvent::SectionStart { heading, id }, span);
+                if core::mem::replace(&mut self.in_section, true) {
+                    self.pending = Some(start);
+                    return Some((ParseEvent::SectionEnd, Span::new(line_start, line_start)));
+                }
+                return Some(start);
+            }
+        }
+
+        Some(if self.in_section {
+            (ParseEvent::SectionText(text), span)
+        } else {
+            (ParseEvent::DescriptionText(text), span)
+        })
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = ParseEvent<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_spanned().map(|(event, _)| event)
+    }
+}
+
+/// The iterator returned by [`Events::spanned`].
+#[derive(Debug, Clone)]
+pub struct Spanned<'a>(Events<'a>);
+
+impl<'a> Iterator for Spanned<'a> {
+    type Item = (ParseEvent<'a>, Span);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_spanned()
+    }
+}
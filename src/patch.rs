@@ -0,0 +1,199 @@
+//! Documentation patches.
+//!
+//! A [`DocPatch`] is a list of edits to a doc comment, such as "set the
+//! `# Type` section" or "rename argument `list` to `xs`". Patches are plain
+//! data, so a refactor across a corpus can be written down, reviewed, and
+//! applied with [`DocComment::apply`] instead of with `sed`.
+
+use thiserror::Error;
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, ParserOptions, Section, SectionKind};
+
+/// One edit in a [`DocPatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatchOp {
+    /// Replace the description.
+    SetDescription(String),
+    /// Replace the content of the first section with this heading, matched
+    /// case-insensitively, or append a new section.
+    SetSection {
+        /// The section heading.
+        heading: String,
+        /// The new section content.
+        content: String,
+    },
+    /// Remove every section with this heading, matched case-insensitively.
+    RemoveSection(String),
+    /// Rename an argument in the `# Arguments` sections.
+    RenameArgument {
+        /// The current name.
+        from: String,
+        /// The new name.
+        to: String,
+    },
+    /// Append a fenced `nix` code block to the first `# Examples` or
+    /// `# Example` section, creating an `# Examples` section if there is
+    /// none.
+    AppendExample {
+        /// The example code.
+        code: String,
+        /// An optional title, written as a `title="..."` attribute.
+        title: Option<String>,
+    },
+}
+
+/// A list of edits to apply to a doc comment, in order.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+/// use nixdoc::patch::{DocPatch, PatchOp};
+///
+/// let doc = DocComment::parse("/**\n  Map.\n\n  # Arguments\n\n  - [list] The list\n*/").unwrap();
+/// let patch = DocPatch::new()
+///     .with(PatchOp::RenameArgument { from: "list".into(), to: "xs".into() })
+///     .with(PatchOp::SetSection { heading: "Since".into(), content: "1.0".into() });
+///
+/// let patched = doc.apply(&patch).unwrap();
+/// assert_eq!(patched.arguments()[0].name, "xs");
+/// assert_eq!(patched.since().unwrap().to_string(), "1.0");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocPatch {
+    /// The edits, applied in order.
+    pub ops: Vec<PatchOp>,
+}
+
+impl DocPatch {
+    /// Create an empty patch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an edit to the end of the patch.
+    pub fn with(mut self, op: PatchOp) -> Self {
+        self.ops.push(op);
+        self
+    }
+}
+
+/// An edit in a [`DocPatch`] that does not apply to the comment.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// [`PatchOp::RemoveSection`] named a section the comment does not have.
+    #[error("no section named `{0}`")]
+    SectionNotFound(String),
+    /// [`PatchOp::RenameArgument`] named an argument the comment does not
+    /// document.
+    #[error("no argument named `{0}`")]
+    ArgumentNotFound(String),
+    /// The patched comment is empty.
+    #[error("the patched comment is empty")]
+    Empty,
+}
+
+/// Apply `patch` to `doc`, see [`DocComment::apply`].
+pub(crate) fn apply(doc: &DocComment, patch: &DocPatch) -> Result<DocComment, PatchError> {
+    let mut description = doc.description.trim().to_string();
+    let mut sections = doc.sections.clone();
+
+    for op in &patch.ops {
+        match op {
+            PatchOp::SetDescription(text) => description = text.trim().to_string(),
+            PatchOp::SetSection { heading, content } => {
+                match sections
+                    .iter_mut()
+                    .find(|s| s.heading.eq_ignore_ascii_case(heading.trim()))
+                {
+                    Some(section) => section.content = content.clone(),
+                    None => sections.push(Section {
+                        heading: heading.trim().to_string(),
+                        content: content.clone(),
+                        explicit_id: None,
+                    }),
+                }
+            }
+            PatchOp::RemoveSection(heading) => {
+                let before = sections.len();
+                sections.retain(|s| !s.heading.eq_ignore_ascii_case(heading.trim()));
+                if sections.len() == before {
+                    return Err(PatchError::SectionNotFound(heading.clone()));
+                }
+            }
+            PatchOp::RenameArgument { from, to } => {
+                let mut renamed = false;
+                for section in &mut sections {
+                    if section.kind() == SectionKind::Arguments {
+                        let (content, found) = rename_argument(&section.content, from, to);
+                        section.content = content;
+                        renamed |= found;
+                    }
+                }
+                if !renamed {
+                    return Err(PatchError::ArgumentNotFound(from.clone()));
+                }
+            }
+            PatchOp::AppendExample { code, title } => {
+                let attributes = title
+                    .as_ref()
+                    .map(|title| format!(" title={title:?}"))
+                    .unwrap_or_default();
+                let block = format!("```nix{attributes}\n{}\n```", code.trim_end());
+                let examples = sections
+                    .iter_mut()
+                    .find(|s| matches!(s.kind(), SectionKind::Examples | SectionKind::Example));
+                match examples {
+                    Some(section) => {
+                        section.content = format!("{}\n\n{block}", section.content.trim_end());
+                    }
+                    None => sections.push(Section {
+                        heading: "Examples".to_string(),
+                        content: block,
+                        explicit_id: None,
+                    }),
+                }
+            }
+        }
+    }
+
+    let mut blocks = vec![description];
+    blocks.extend(sections.iter().map(Section::to_markdown));
+    let definitions: Vec<String> = doc
+        .link_definitions
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    blocks.push(definitions.join("\n"));
+    blocks.retain(|block| !block.is_empty());
+
+    DocComment::parse_with(
+        &blocks.join("\n\n"),
+        &ParserOptions::new().without_delimiters(),
+    )
+    .map_err(|_| PatchError::Empty)
+}
+
+/// Rename the `- [from]` list items in an `# Arguments` section.
+fn rename_argument(content: &str, from: &str, to: &str) -> (String, bool) {
+    let mut found = false;
+    let lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let renamed = line
+                .trim_start()
+                .strip_prefix("- [")
+                .and_then(|rest| rest.split_once(']'))
+                .filter(|(name, _)| name.trim() == from)
+                .map(|(_, rest)| format!("{indent}- [{to}]{rest}"));
+            found |= renamed.is_some();
+            renamed.unwrap_or_else(|| line.to_string())
+        })
+        .collect();
+    (lines.join("\n"), found)
+}
@@ -0,0 +1,195 @@
+//! Export in the JSON format of noogle's pesto.
+//!
+//! Enabled by the `serde` feature.
+//!
+//! [noogle] builds its search data from the output of pesto, which lists one
+//! object per documented attribute:
+//!
+//! ```json
+//! {
+//!   "path": ["lib", "trivial", "id"],
+//!   "aliases": [],
+//!   "docs": {
+//!     "attr": {
+//!       "position": { "file": "lib/trivial.nix", "line": 12, "column": 3 },
+//!       "content": "The identity function."
+//!     },
+//!     "lambda": {
+//!       "isPrimop": false,
+//!       "name": "id",
+//!       "position": null,
+//!       "args": null,
+//!       "experimental": null,
+//!       "arity": null,
+//!       "content": null,
+//!       "countApplied": null
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! The lambda metadata comes from evaluating the library, which this crate
+//! does not do, so everything but its name is left `null` for the pipeline
+//! to fill in.
+//!
+//! [noogle]: https://noogle.dev
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::DocSet;
+use crate::extract::DocumentedItem;
+
+/// Options for [`export`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PestoOptions {
+    /// Attribute path components placed before every item's own path.
+    pub prefix: Vec<String>,
+    /// Place each item in the category named by its file, after the prefix,
+    /// as pesto does: `assertMsg` in `lib/asserts.nix` is at
+    /// `lib.asserts.assertMsg`. An item in a `default.nix` takes the name
+    /// of its directory instead, unless that is the directory the prefix
+    /// names, such as `lib/default.nix`. On by default.
+    pub categories: bool,
+}
+
+impl Default for PestoOptions {
+    fn default() -> Self {
+        Self {
+            prefix: vec!["lib".to_string()],
+            categories: true,
+        }
+    }
+}
+
+/// One documented attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PestoDoc {
+    /// The full attribute path, prefix included.
+    pub path: Vec<String>,
+    /// Other paths under which the same value is reachable. Always empty.
+    pub aliases: Vec<Vec<String>>,
+    /// The attribute and lambda metadata.
+    pub docs: DocsMeta,
+}
+
+/// Metadata on a documented attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocsMeta {
+    /// The attribute definition and its doc comment.
+    pub attr: AttrMeta,
+    /// The attribute's value, if it is a function.
+    pub lambda: Option<LambdaMeta>,
+}
+
+/// Where an attribute is defined and what its doc comment says.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttrMeta {
+    /// The position of the doc comment.
+    pub position: Option<FilePosition>,
+    /// The doc comment's content, without delimiters and indentation.
+    pub content: Option<String>,
+}
+
+/// Metadata on a function value, normally gathered by evaluation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LambdaMeta {
+    /// Whether the function is a builtin.
+    pub is_primop: bool,
+    /// The function's name.
+    pub name: Option<String>,
+    /// Where the function is defined.
+    pub position: Option<FilePosition>,
+    /// The function's formal arguments.
+    pub args: Option<Vec<String>>,
+    /// Whether the builtin is experimental.
+    pub experimental: Option<bool>,
+    /// The number of arguments the function takes.
+    pub arity: Option<usize>,
+    /// The builtin's documentation.
+    pub content: Option<String>,
+    /// How many arguments have already been applied.
+    pub count_applied: Option<usize>,
+}
+
+/// A position in a source file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilePosition {
+    /// The file, as it was added to the [`DocSet`].
+    pub file: PathBuf,
+    /// The 1-based line.
+    pub line: usize,
+    /// The 1-based column.
+    pub column: usize,
+}
+
+/// Convert the named items of `set` to pesto's format.
+///
+/// Items without a binding name have no attribute path and are skipped.
+pub fn export(set: &DocSet, options: &PestoOptions) -> Vec<PestoDoc> {
+    set.iter()
+        .filter_map(|item| {
+            let name = item.name.as_deref()?;
+            Some(pesto_doc(item, name, options))
+        })
+        .collect()
+}
+
+/// Convert `set` to pesto's format and serialize it as pretty-printed JSON.
+pub fn to_json(set: &DocSet, options: &PestoOptions) -> String {
+    serde_json::to_string_pretty(&export(set, options)).expect("pesto docs serialize to JSON")
+}
+
+fn pesto_doc(item: &DocumentedItem, name: &str, options: &PestoOptions) -> PestoDoc {
+    let mut path = options.prefix.clone();
+    if options.categories {
+        path.extend(
+            item.file
+                .as_deref()
+                .and_then(|file| category(file, &options.prefix)),
+        );
+    }
+    path.extend(name.split('.').map(str::to_string));
+
+    let position = item.file.as_ref().map(|file| FilePosition {
+        file: file.clone(),
+        line: item.line,
+        column: item.column,
+    });
+
+    PestoDoc {
+        aliases: Vec::new(),
+        docs: DocsMeta {
+            attr: AttrMeta {
+                position,
+                content: Some(item.doc.raw_content.clone()),
+            },
+            lambda: Some(LambdaMeta {
+                is_primop: false,
+                name: path.last().cloned(),
+                position: None,
+                args: None,
+                experimental: None,
+                arity: None,
+                content: None,
+                count_applied: None,
+            }),
+        },
+        path,
+    }
+}
+
+/// The category of the items of `file`: its name without extension, or the
+/// name of its directory for a `default.nix`. `None` if that would repeat
+/// the last component of `prefix`.
+fn category(file: &Path, prefix: &[String]) -> Option<String> {
+    let stem = file.file_stem()?.to_str()?;
+    let category = if stem == "default" {
+        file.parent()?.file_name()?.to_str()?
+    } else {
+        stem
+    };
+    (prefix.last().map(String::as_str) != Some(category)).then(|| category.to_string())
+}
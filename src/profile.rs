@@ -0,0 +1,159 @@
+//! Named revisions of the doc comment format.
+//!
+//! RFC 145 fixed the comment syntax, but the conventions around it kept
+//! moving: nixpkgs added sections such as `# Inputs`, and older code still
+//! uses indented code and plain `/* … */` comments. A [`SpecProfile`]
+//! bundles the parser options, the recognized sections, and the
+//! [lint rules](crate::lint) that count as violations for one such
+//! revision, so a corpus can be validated against the revision it targets.
+
+use crate::diagnostic::Diagnostic;
+use crate::lint::{Linter, Rule};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::{DocComment, ParseError, ParserOptions, SectionKind};
+
+/// A revision of the doc comment format to parse and validate against.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::SpecProfile;
+/// use nixdoc::lint::Rule;
+///
+/// let input = "/**\n  Id.\n\n  # Since\n\n  1.0\n*/";
+///
+/// let doc = SpecProfile::NixpkgsCurrent.parse(input).unwrap();
+/// assert!(SpecProfile::NixpkgsCurrent.violations(&doc).is_empty());
+///
+/// let doc = SpecProfile::Rfc145_2023.parse(input).unwrap();
+/// let violations = SpecProfile::Rfc145_2023.violations(&doc);
+/// assert_eq!(violations[0].code, Rule::UnknownSection.code());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpecProfile {
+    /// RFC 145 as accepted in 2023: `/** … */` comments with the sections
+    /// in use at the time (`Type`, `Arguments`, `Inputs`, `Example`,
+    /// `Note`, `Warning`, `Deprecated`), headings at the start of their
+    /// line, and unusual Unicode whitespace reported.
+    Rfc145_2023,
+    /// The conventions of the nixpkgs manual today. Every section this
+    /// crate knows is recognized. This is what [`ParserOptions::default`]
+    /// parses.
+    #[default]
+    NixpkgsCurrent,
+    /// Whatever can be read: plain `/* … */` comments and indented code are
+    /// accepted, and no warning counts as a violation.
+    Permissive,
+}
+
+impl SpecProfile {
+    /// Every profile, oldest first.
+    pub const ALL: [Self; 3] = [Self::Rfc145_2023, Self::NixpkgsCurrent, Self::Permissive];
+
+    /// Look up a profile by its [`name`](Self::name), case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::SpecProfile;
+    ///
+    /// assert_eq!(SpecProfile::parse_name("rfc145-2023"), Some(SpecProfile::Rfc145_2023));
+    /// assert_eq!(SpecProfile::parse_name("Nixpkgs"), Some(SpecProfile::NixpkgsCurrent));
+    /// assert_eq!(SpecProfile::parse_name("rfc999"), None);
+    /// ```
+    pub fn parse_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "rfc145-2023" | "rfc145" => Some(Self::Rfc145_2023),
+            "nixpkgs" | "nixpkgs-current" => Some(Self::NixpkgsCurrent),
+            "permissive" => Some(Self::Permissive),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the profile, such as `rfc145-2023`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rfc145_2023 => "rfc145-2023",
+            Self::NixpkgsCurrent => "nixpkgs",
+            Self::Permissive => "permissive",
+        }
+    }
+
+    /// Returns the parser options of the profile.
+    ///
+    /// Options can still be changed afterwards; the profile only supplies
+    /// the defaults.
+    pub fn options(self) -> ParserOptions {
+        let options = ParserOptions {
+            profile: self,
+            ..ParserOptions::default()
+        };
+        match self {
+            Self::Rfc145_2023 => options
+                .with_max_heading_indent(0)
+                .with_unicode(crate::UnicodePolicy::Warn),
+            Self::NixpkgsCurrent => options,
+            Self::Permissive => options.with_legacy_comments(true).with_indented_code(true),
+        }
+    }
+
+    /// Returns `true` if sections of `kind` belong to the profile. Others
+    /// carry a [`WarningKind::UnknownSection`](crate::WarningKind::UnknownSection) warning.
+    pub fn recognizes(self, kind: &SectionKind) -> bool {
+        match self {
+            Self::Rfc145_2023 => matches!(
+                kind,
+                SectionKind::Type
+                    | SectionKind::Arguments
+                    | SectionKind::Inputs
+                    | SectionKind::Example
+                    | SectionKind::Examples
+                    | SectionKind::Note
+                    | SectionKind::Notes
+                    | SectionKind::Warning
+                    | SectionKind::Deprecated
+            ),
+            Self::NixpkgsCurrent | Self::Permissive => kind.is_known(),
+        }
+    }
+
+    /// Returns the lint rules whose diagnostics violate the profile, in code
+    /// order.
+    pub fn lints(self) -> &'static [Rule] {
+        match self {
+            Self::Rfc145_2023 => &[
+                Rule::UnknownSection,
+                Rule::EmptySection,
+                Rule::MixedStyle,
+                Rule::LegacyComment,
+                Rule::JavadocGutter,
+                Rule::UnusualWhitespace,
+                Rule::HeadingStyle,
+            ],
+            Self::NixpkgsCurrent => &[Rule::EmptySection, Rule::MixedStyle, Rule::LegacyComment],
+            Self::Permissive => &[],
+        }
+    }
+
+    /// Parse `input` with the profile's [`options`](Self::options).
+    pub fn parse(self, input: &str) -> Result<DocComment, ParseError> {
+        DocComment::parse_with(input, &self.options())
+    }
+
+    /// Returns the diagnostics of `doc` that violate the profile, those of
+    /// [`Linter::for_profile`].
+    ///
+    /// `doc` should have been parsed with the profile's options, so that
+    /// its warnings are those the profile expects.
+    pub fn violations(self, doc: &DocComment) -> Vec<Diagnostic> {
+        Linter::for_profile(self).check(doc)
+    }
+}
+
+impl core::fmt::Display for SpecProfile {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
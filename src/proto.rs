@@ -0,0 +1,388 @@
+//! Protocol Buffers encoding of the document model.
+//!
+//! [`SCHEMA`] is the `.proto` definition (package `nixdoc.v1`), for
+//! services in other languages to generate their types from. The messages
+//! here are the same schema as [prost] types, converted to and from the
+//! crate's own types with `From` and `TryFrom`; [`encode_set`] and
+//! [`decode_set`] do both steps at once.
+//!
+//! Requires the `prost` feature.
+
+use std::path::PathBuf;
+
+use prost::Message;
+use thiserror::Error;
+
+use crate::links::LinkDefinition as CrateLinkDefinition;
+
+/// The `.proto` definition of the messages in this module.
+pub const SCHEMA: &str = include_str!("../proto/nixdoc.proto");
+
+/// A byte range of the source.
+#[derive(Clone, Copy, PartialEq, Eq, Message)]
+pub struct Span {
+    /// The first byte.
+    #[prost(uint64, tag = "1")]
+    pub start: u64,
+    /// The byte after the last.
+    #[prost(uint64, tag = "2")]
+    pub end: u64,
+}
+
+/// A section of a doc comment.
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct Section {
+    /// The heading text.
+    #[prost(string, tag = "1")]
+    pub heading: String,
+    /// The section body.
+    #[prost(string, tag = "2")]
+    pub content: String,
+    /// The explicit anchor of the heading.
+    #[prost(string, optional, tag = "3")]
+    pub explicit_id: Option<String>,
+    /// The [`name`](crate::SectionKind::name) of the section's kind.
+    #[prost(string, tag = "4")]
+    pub kind: String,
+}
+
+/// A link reference definition.
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct LinkDefinition {
+    /// The label.
+    #[prost(string, tag = "1")]
+    pub label: String,
+    /// The destination.
+    #[prost(string, tag = "2")]
+    pub url: String,
+    /// The title.
+    #[prost(string, optional, tag = "3")]
+    pub title: Option<String>,
+}
+
+/// The kind of a [`ParseWarning`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum WarningKind {
+    /// Not set; rejected when decoding.
+    Unspecified = 0,
+    /// See [`crate::WarningKind::EmptySection`].
+    EmptySection = 1,
+    /// See [`crate::WarningKind::UnknownSection`].
+    UnknownSection = 2,
+    /// See [`crate::WarningKind::UnclosedComment`].
+    UnclosedComment = 3,
+    /// See [`crate::WarningKind::JavadocGutter`].
+    JavadocGutter = 4,
+    /// See [`crate::WarningKind::UnusualWhitespace`].
+    UnusualWhitespace = 5,
+    /// See [`crate::WarningKind::AmbiguousCommentEnd`].
+    AmbiguousCommentEnd = 6,
+    /// See [`crate::WarningKind::MixedStyle`].
+    MixedStyle = 7,
+    /// See [`crate::WarningKind::HeadingStyle`].
+    HeadingStyle = 8,
+    /// See [`crate::WarningKind::LegacyComment`].
+    LegacyComment = 9,
+}
+
+/// A parse warning.
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct ParseWarning {
+    /// The kind of warning.
+    #[prost(enumeration = "WarningKind", tag = "1")]
+    pub kind: i32,
+    /// The message.
+    #[prost(string, tag = "2")]
+    pub message: String,
+}
+
+/// A parsed doc comment.
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct DocComment {
+    /// The normalized comment body.
+    #[prost(string, tag = "1")]
+    pub raw_content: String,
+    /// The Markdown before the first section.
+    #[prost(string, tag = "2")]
+    pub description: String,
+    /// The sections, in order.
+    #[prost(message, repeated, tag = "3")]
+    pub sections: Vec<Section>,
+    /// The link reference definitions, in order.
+    #[prost(message, repeated, tag = "4")]
+    pub link_definitions: Vec<LinkDefinition>,
+    /// The parse warnings.
+    #[prost(message, repeated, tag = "5")]
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// A doc comment and the binding it documents.
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct DocumentedItem {
+    /// The attribute path of the binding.
+    #[prost(string, optional, tag = "1")]
+    pub name: Option<String>,
+    /// The file the comment was read from.
+    #[prost(string, optional, tag = "2")]
+    pub file: Option<String>,
+    /// The comment within its source.
+    #[prost(message, optional, tag = "3")]
+    pub span: Option<Span>,
+    /// The 1-based line on which the comment starts.
+    #[prost(uint64, tag = "4")]
+    pub line: u64,
+    /// The 1-based column at which the comment starts.
+    #[prost(uint64, tag = "5")]
+    pub column: u64,
+    /// The comment.
+    #[prost(message, optional, tag = "6")]
+    pub doc: Option<DocComment>,
+    /// The formal arguments of the documented function.
+    #[prost(string, repeated, tag = "7")]
+    pub formals: Vec<String>,
+}
+
+/// The documented items of a corpus.
+#[derive(Clone, PartialEq, Eq, Message)]
+pub struct DocSet {
+    /// The items, in order.
+    #[prost(message, repeated, tag = "1")]
+    pub items: Vec<DocumentedItem>,
+}
+
+/// An error decoding a message.
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    /// The bytes are not a valid message.
+    #[error("invalid protobuf message: {0}")]
+    Invalid(#[from] prost::DecodeError),
+    /// A required field is missing.
+    #[error("missing field `{0}`")]
+    MissingField(&'static str),
+    /// A warning kind that this version does not know.
+    #[error("unknown warning kind {0}")]
+    UnknownWarningKind(i32),
+}
+
+/// Encode the items of `set` as a [`DocSet`] message. Parse failures are
+/// not included.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::proto::{decode_set, encode_set};
+///
+/// let mut set = nixdoc::DocSet::new();
+/// set.add_source("{\n  /** Id. */\n  id = x: x;\n}", Some("lib.nix".as_ref()));
+/// let items = decode_set(&encode_set(&set)).unwrap();
+/// assert_eq!(items, set.items());
+/// ```
+pub fn encode_set(set: &crate::DocSet) -> Vec<u8> {
+    DocSet {
+        items: set.iter().map(DocumentedItem::from).collect(),
+    }
+    .encode_to_vec()
+}
+
+/// Decode the items of a [`DocSet`] message.
+///
+/// # Errors
+///
+/// Returns an error if `bytes` is not a valid message, or a warning kind
+/// is unknown.
+pub fn decode_set(bytes: &[u8]) -> Result<Vec<crate::extract::DocumentedItem>, DecodeError> {
+    DocSet::decode(bytes)?
+        .items
+        .into_iter()
+        .map(TryFrom::try_from)
+        .collect()
+}
+
+/// Encode one item as a [`DocumentedItem`] message.
+pub fn encode_item(item: &crate::extract::DocumentedItem) -> Vec<u8> {
+    DocumentedItem::from(item).encode_to_vec()
+}
+
+/// Decode one item from a [`DocumentedItem`] message.
+///
+/// # Errors
+///
+/// Returns an error if `bytes` is not a valid message, the comment is
+/// missing, or a warning kind is unknown.
+pub fn decode_item(bytes: &[u8]) -> Result<crate::extract::DocumentedItem, DecodeError> {
+    DocumentedItem::decode(bytes)?.try_into()
+}
+
+impl From<crate::Span> for Span {
+    fn from(span: crate::Span) -> Self {
+        Self {
+            start: span.start as u64,
+            end: span.end as u64,
+        }
+    }
+}
+
+impl From<Span> for crate::Span {
+    fn from(span: Span) -> Self {
+        Self::new(span.start as usize, span.end as usize)
+    }
+}
+
+impl From<&crate::Section> for Section {
+    fn from(section: &crate::Section) -> Self {
+        Self {
+            heading: section.heading.clone(),
+            content: section.content.clone(),
+            explicit_id: section.explicit_id.clone(),
+            kind: section.kind().name().to_string(),
+        }
+    }
+}
+
+impl From<Section> for crate::Section {
+    fn from(section: Section) -> Self {
+        Self {
+            heading: section.heading,
+            content: section.content,
+            explicit_id: section.explicit_id,
+        }
+    }
+}
+
+impl From<&CrateLinkDefinition> for LinkDefinition {
+    fn from(link: &CrateLinkDefinition) -> Self {
+        Self {
+            label: link.label.clone(),
+            url: link.url.clone(),
+            title: link.title.clone(),
+        }
+    }
+}
+
+impl From<LinkDefinition> for CrateLinkDefinition {
+    fn from(link: LinkDefinition) -> Self {
+        Self {
+            label: link.label,
+            url: link.url,
+            title: link.title,
+        }
+    }
+}
+
+impl From<&crate::WarningKind> for WarningKind {
+    fn from(kind: &crate::WarningKind) -> Self {
+        use crate::WarningKind as K;
+        match kind {
+            K::EmptySection => Self::EmptySection,
+            K::UnknownSection => Self::UnknownSection,
+            K::UnclosedComment => Self::UnclosedComment,
+            K::JavadocGutter => Self::JavadocGutter,
+            K::UnusualWhitespace => Self::UnusualWhitespace,
+            K::AmbiguousCommentEnd => Self::AmbiguousCommentEnd,
+            K::MixedStyle => Self::MixedStyle,
+            K::HeadingStyle => Self::HeadingStyle,
+            K::LegacyComment => Self::LegacyComment,
+        }
+    }
+}
+
+impl TryFrom<ParseWarning> for crate::ParseWarning {
+    type Error = DecodeError;
+
+    fn try_from(warning: ParseWarning) -> Result<Self, DecodeError> {
+        use crate::WarningKind as K;
+        let kind = match WarningKind::try_from(warning.kind) {
+            Ok(WarningKind::EmptySection) => K::EmptySection,
+            Ok(WarningKind::UnknownSection) => K::UnknownSection,
+            Ok(WarningKind::UnclosedComment) => K::UnclosedComment,
+            Ok(WarningKind::JavadocGutter) => K::JavadocGutter,
+            Ok(WarningKind::UnusualWhitespace) => K::UnusualWhitespace,
+            Ok(WarningKind::AmbiguousCommentEnd) => K::AmbiguousCommentEnd,
+            Ok(WarningKind::MixedStyle) => K::MixedStyle,
+            Ok(WarningKind::HeadingStyle) => K::HeadingStyle,
+            Ok(WarningKind::LegacyComment) => K::LegacyComment,
+            Ok(WarningKind::Unspecified) | Err(_) => {
+                return Err(DecodeError::UnknownWarningKind(warning.kind));
+            }
+        };
+        Ok(Self {
+            kind,
+            message: warning.message,
+        })
+    }
+}
+
+impl From<&crate::DocComment> for DocComment {
+    fn from(doc: &crate::DocComment) -> Self {
+        Self {
+            raw_content: doc.raw_content.clone(),
+            description: doc.description.clone(),
+            sections: doc.sections.iter().map(Section::from).collect(),
+            link_definitions: doc
+                .link_definitions
+                .iter()
+                .map(LinkDefinition::from)
+                .collect(),
+            warnings: doc
+                .warnings
+                .iter()
+                .map(|warning| ParseWarning {
+                    kind: WarningKind::from(&warning.kind) as i32,
+                    message: warning.message.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<DocComment> for crate::DocComment {
+    type Error = DecodeError;
+
+    fn try_from(doc: DocComment) -> Result<Self, DecodeError> {
+        Ok(Self {
+            raw_content: doc.raw_content,
+            description: doc.description,
+            sections: doc.sections.into_iter().map(Into::into).collect(),
+            link_definitions: doc.link_definitions.into_iter().map(Into::into).collect(),
+            warnings: doc
+                .warnings
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<&crate::extract::DocumentedItem> for DocumentedItem {
+    fn from(item: &crate::extract::DocumentedItem) -> Self {
+        Self {
+            name: item.name.clone(),
+            file: item.file.as_ref().map(|f| f.to_string_lossy().into_owned()),
+            span: Some(item.span.into()),
+            line: item.line as u64,
+            column: item.column as u64,
+            doc: Some((&item.doc).into()),
+            formals: item.formals.clone(),
+        }
+    }
+}
+
+impl TryFrom<DocumentedItem> for crate::extract::DocumentedItem {
+    type Error = DecodeError;
+
+    fn try_from(item: DocumentedItem) -> Result<Self, DecodeError> {
+        Ok(Self {
+            name: item.name,
+            file: item.file.map(PathBuf::from),
+            span: item.span.ok_or(DecodeError::MissingField("span"))?.into(),
+            line: item.line as usize,
+            column: item.column as usize,
+            formals: item.formals,
+            doc: item
+                .doc
+                .ok_or(DecodeError::MissingField("doc"))?
+                .try_into()?,
+        })
+    }
+}
@@ -0,0 +1,57 @@
+//! Where a piece of documentation came from.
+//!
+//! Extraction records the file, position and binding of every comment in
+//! its [`DocumentedItem`](crate::extract::DocumentedItem).
+//! [`DocumentedItem::provenance`](crate::extract::DocumentedItem::provenance)
+//! bundles them as a [`Provenance`], which derived values carry along:
+//! [views](crate::DocView), [diagnostics](crate::Diagnostic), and rendered
+//! pages when [`RenderOptions::show_provenance`] is set.
+//!
+//! [`RenderOptions::show_provenance`]: crate::render::RenderOptions::show_provenance
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::Span;
+
+/// The origin of a doc comment.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::extract::documented_items;
+///
+/// let src = "{\n  /** Map over a list. */\n  lists.map = f: xs: xs;\n}";
+/// let item = &documented_items(src, Some("lib/lists.nix".as_ref()))[0];
+/// let provenance = item.provenance();
+/// assert_eq!(provenance.attr_path.as_deref(), Some("lists.map"));
+/// assert_eq!(&src[provenance.byte_range.start..provenance.byte_range.end], "/** Map over a list. */");
+/// assert_eq!(provenance.to_string(), "lib/lists.nix:2 (lists.map)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Provenance {
+    /// The file the comment was read from, if any.
+    pub file: Option<PathBuf>,
+    /// The comment, delimiters included, within its source.
+    pub byte_range: Span,
+    /// The 1-based line on which the comment starts.
+    pub line: usize,
+    /// The attribute path of the documented binding, if known.
+    pub attr_path: Option<String>,
+}
+
+/// Formats as `file:line (attr.path)`, with `<input>` for a comment that
+/// was not read from a file.
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}", file.display(), self.line)?,
+            None => write!(f, "<input>:{}", self.line)?,
+        }
+        if let Some(path) = &self.attr_path {
+            write!(f, " ({path})")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,503 @@
+//! Selecting single fields of a doc comment with a small path language.
+//!
+//! [`DocComment::query`] evaluates a path such as
+//! `sections[kind=arguments].items[0].name` and returns the values it
+//! selects. A path is a sequence of field names separated by `.`, each
+//! optionally followed by selectors in brackets:
+//!
+//! | Selector | Selects |
+//! |----------|---------|
+//! | `[2]` | the element at an index; negative indices count from the end |
+//! | `[*]` | every element |
+//! | `[key=value]` | the elements whose field `key` equals `value`, ignoring case; the value may be quoted |
+//!
+//! A field name applied to a list applies to each element, so
+//! `sections.heading` selects every heading.
+//!
+//! The fields of a comment are `title`, `description`, `raw_content`, `type`,
+//! `deprecated`, `since`, `stability`, `tags`, `metadata`, `sections`,
+//! `arguments`, `examples`, `link_definitions` and `warnings`. A section has
+//! `heading`, `kind`, `content`, `anchor`, `id` and `items`, the latter
+//! holding the arguments or examples of the section. Arguments, examples,
+//! link definitions and warnings have the fields of their Rust types.
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Write as _};
+
+use thiserror::Error;
+
+use crate::error::ParseWarning;
+use crate::links::LinkDefinition;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::section::{Argument, Example, Section, SectionKind};
+use crate::{DocComment, parser};
+
+/// A value selected by a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// An absent optional field.
+    Null,
+    /// A flag.
+    Bool(bool),
+    /// Text.
+    String(String),
+    /// A list.
+    Array(Vec<Value>),
+    /// Named fields, ordered by name.
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// Returns the text of a [`Value::String`].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the field `name` of a [`Value::Object`].
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Self::Object(fields) => fields.get(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the value at a JSON Pointer (RFC 6901) such as
+    /// `/sections/0/heading`.
+    ///
+    /// The empty pointer selects the value itself. Returns `None` if the
+    /// pointer is malformed or selects nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use nixdoc::query::Value;
+    ///
+    /// let value = Value::Object(BTreeMap::from([(
+    ///     "a/b".to_string(),
+    ///     Value::Array(vec!["x".into(), "y".into()]),
+    /// )]));
+    /// assert_eq!(value.pointer("/a~1b/1"), Some(&"y".into()));
+    /// assert_eq!(value.pointer("/a~1b/01"), None);
+    /// assert_eq!(value.pointer(""), Some(&value));
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        let mut current = self;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            current = match current {
+                Self::Object(fields) => fields.get(&token)?,
+                Self::Array(items) => {
+                    let is_index = token == "0"
+                        || (!token.starts_with('0') && token.bytes().all(|b| b.is_ascii_digit()));
+                    if !is_index {
+                        return None;
+                    }
+                    items.get(token.parse::<usize>().ok()?)?
+                }
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Format as compact JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::query::Value;
+    ///
+    /// let value = Value::Array(vec![Value::String("a \"b\"".to_string()), Value::Null]);
+    /// assert_eq!(value.to_json(), r#"["a \"b\"",null]"#);
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+
+    fn write_json(&self, out: &mut String) {
+        match self {
+            Self::Null => out.push_str("null"),
+            Self::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Self::String(s) => write_json_string(s, out),
+            Self::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    item.write_json(out);
+                }
+                out.push(']');
+            }
+            Self::Object(fields) => {
+                out.push('{');
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_json_string(name, out);
+                    out.push(':');
+                    value.write_json(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+
+    /// The text a `[key=value]` selector compares against.
+    fn comparable(&self) -> Option<String> {
+        match self {
+            Self::Null => Some("null".to_string()),
+            Self::Bool(b) => Some(b.to_string()),
+            Self::String(s) => Some(s.clone()),
+            Self::Array(_) | Self::Object(_) => None,
+        }
+    }
+}
+
+/// Formats strings as their text and everything else as JSON.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(s) => f.write_str(s),
+            other => f.write_str(&other.to_json()),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+pub(crate) fn write_json_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// A malformed query path.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("invalid query at offset {position}: {message}")]
+pub struct QueryError {
+    /// The byte offset in the path where the problem was found.
+    pub position: usize,
+    /// What is wrong.
+    pub message: String,
+}
+
+#[derive(Debug)]
+enum Step {
+    Field(String),
+    Index(i64),
+    All,
+    Filter(String, String),
+}
+
+fn parse_path(path: &str) -> Result<Vec<Step>, QueryError> {
+    let error = |position: usize, message: &str| QueryError {
+        position,
+        message: message.to_string(),
+    };
+    let bytes = path.as_bytes();
+    let mut steps = Vec::new();
+    let mut i = 0;
+
+    loop {
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+            i += 1;
+        }
+        if i == start {
+            return Err(error(i, "expected a field name"));
+        }
+        steps.push(Step::Field(path[start..i].to_string()));
+
+        while i < bytes.len() && bytes[i] == b'[' {
+            let close = path[i..]
+                .find(']')
+                .map(|offset| i + offset)
+                .ok_or_else(|| error(i, "unclosed '['"))?;
+            let inner = path[i + 1..close].trim();
+            steps.push(if inner == "*" {
+                Step::All
+            } else if let Some((key, value)) = inner.split_once('=') {
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                    .unwrap_or(value);
+                Step::Filter(key.trim().to_string(), value.to_string())
+            } else {
+                Step::Index(
+                    inner
+                        .parse()
+                        .map_err(|_| error(i + 1, "expected an index, '*' or key=value"))?,
+                )
+            });
+            i = close + 1;
+        }
+
+        match bytes.get(i) {
+            None => return Ok(steps),
+            Some(b'.') => i += 1,
+            Some(_) => return Err(error(i, "expected '.' or '['")),
+        }
+    }
+}
+
+/// Evaluate `path` against `doc`, see the [module documentation](self).
+pub(crate) fn query(doc: &DocComment, path: &str) -> Result<Vec<Value>, QueryError> {
+    let steps = parse_path(path)?;
+    let root = doc_value(doc);
+    let mut current: Vec<&Value> = vec![&root];
+    let mut filtered: Vec<Value>;
+
+    for step in &steps {
+        let mut next: Vec<&Value> = Vec::new();
+        let mut produced: Vec<Value> = Vec::new();
+        for value in &current {
+            match (step, value) {
+                (Step::Field(name), Value::Array(items)) => {
+                    next.extend(items.iter().filter_map(|item| item.get(name)));
+                }
+                (Step::Field(name), value) => next.extend(value.get(name)),
+                (Step::Index(index), Value::Array(items)) => {
+                    let index = if *index < 0 {
+                        items.len().checked_sub(index.unsigned_abs() as usize)
+                    } else {
+                        Some(*index as usize)
+                    };
+                    next.extend(index.and_then(|i| items.get(i)));
+                }
+                (Step::All, Value::Array(items)) => next.extend(items),
+                (Step::Filter(key, expected), Value::Array(items)) => {
+                    produced.push(Value::Array(
+                        items
+                            .iter()
+                            .filter(|item| {
+                                item.get(key)
+                                    .and_then(Value::comparable)
+                                    .is_some_and(|v| v.eq_ignore_ascii_case(expected))
+                            })
+                            .cloned()
+                            .collect(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        if produced.is_empty() {
+            current = next;
+        } else {
+            // Filtered lists are new values; keep them alive for the next step.
+            filtered = produced;
+            current = filtered.iter().collect();
+        }
+    }
+
+    Ok(current.into_iter().cloned().collect())
+}
+
+/// Build the tree that JSON Pointers are evaluated against: the shape of a
+/// serialized [`DocComment`].
+pub(crate) fn serialized_value(doc: &DocComment) -> Value {
+    Value::Object(BTreeMap::from([
+        ("raw_content".to_string(), doc.raw_content.as_str().into()),
+        ("description".to_string(), doc.description.as_str().into()),
+        (
+            "sections".to_string(),
+            Value::Array(
+                doc.sections
+                    .iter()
+                    .map(|s| {
+                        Value::Object(BTreeMap::from([
+                            ("heading".to_string(), s.heading.as_str().into()),
+                            ("content".to_string(), s.content.as_str().into()),
+                            ("explicit_id".to_string(), s.explicit_id.as_deref().into()),
+                        ]))
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "link_definitions".to_string(),
+            Value::Array(
+                doc.link_definitions
+                    .iter()
+                    .map(link_definition_value)
+                    .collect(),
+            ),
+        ),
+        (
+            "warnings".to_string(),
+            Value::Array(doc.warnings.iter().map(warning_value).collect()),
+        ),
+    ]))
+}
+
+/// Build the tree that queries are evaluated against.
+fn doc_value(doc: &DocComment) -> Value {
+    let mut fields = BTreeMap::new();
+    fields.insert("title".to_string(), doc.title().into());
+    fields.insert("description".to_string(), doc.description().into());
+    fields.insert("raw_content".to_string(), doc.raw_content.as_str().into());
+    fields.insert("type".to_string(), doc.type_sig().into());
+    fields.insert("deprecated".to_string(), Value::Bool(doc.is_deprecated()));
+    fields.insert(
+        "since".to_string(),
+        doc.since().map(|v| v.to_string()).into(),
+    );
+    fields.insert(
+        "stability".to_string(),
+        doc.stability().map(|s| s.to_string()).into(),
+    );
+    fields.insert(
+        "tags".to_string(),
+        Value::Array(doc.tags().into_iter().map(Value::String).collect()),
+    );
+    fields.insert(
+        "metadata".to_string(),
+        Value::Object(
+            doc.metadata()
+                .into_iter()
+                .map(|(k, v)| (k, Value::String(v)))
+                .collect(),
+        ),
+    );
+    fields.insert(
+        "sections".to_string(),
+        Value::Array(doc.sections.iter().map(section_value).collect()),
+    );
+    fields.insert(
+        "arguments".to_string(),
+        Value::Array(doc.arguments().iter().map(argument_value).collect()),
+    );
+    fields.insert(
+        "examples".to_string(),
+        Value::Array(doc.examples().iter().map(example_value).collect()),
+    );
+    fields.insert(
+        "link_definitions".to_string(),
+        Value::Array(
+            doc.link_definitions
+                .iter()
+                .map(link_definition_value)
+                .collect(),
+        ),
+    );
+    fields.insert(
+        "warnings".to_string(),
+        Value::Array(doc.warnings.iter().map(warning_value).collect()),
+    );
+    Value::Object(fields)
+}
+
+fn section_value(section: &Section) -> Value {
+    let kind = section.kind();
+    let items = match kind {
+        SectionKind::Arguments => parser::parse_arguments(&section.content)
+            .iter()
+            .map(argument_value)
+            .collect(),
+        SectionKind::Example | SectionKind::Examples => parser::parse_examples(&section.content)
+            .iter()
+            .map(example_value)
+            .collect(),
+        _ => Vec::new(),
+    };
+    Value::Object(BTreeMap::from([
+        ("heading".to_string(), section.heading.as_str().into()),
+        ("kind".to_string(), kind.name().into()),
+        ("content".to_string(), section.content.as_str().into()),
+        ("anchor".to_string(), section.anchor().into()),
+        ("id".to_string(), section.explicit_id.as_deref().into()),
+        ("items".to_string(), Value::Array(items)),
+    ]))
+}
+
+fn link_definition_value(definition: &LinkDefinition) -> Value {
+    Value::Object(BTreeMap::from([
+        ("label".to_string(), definition.label.as_str().into()),
+        ("url".to_string(), definition.url.as_str().into()),
+        ("title".to_string(), definition.title.as_deref().into()),
+    ]))
+}
+
+fn warning_value(warning: &ParseWarning) -> Value {
+    Value::Object(BTreeMap::from([
+        ("kind".to_string(), format!("{:?}", warning.kind).into()),
+        ("message".to_string(), warning.message.as_str().into()),
+    ]))
+}
+
+fn argument_value(argument: &Argument) -> Value {
+    Value::Object(BTreeMap::from([
+        ("name".to_string(), argument.name.as_str().into()),
+        (
+            "description".to_string(),
+            argument.description.as_str().into(),
+        ),
+        ("type_sig".to_string(), argument.type_sig.as_deref().into()),
+        ("default".to_string(), argument.default.as_deref().into()),
+    ]))
+}
+
+fn example_value(example: &Example) -> Value {
+    Value::Object(BTreeMap::from([
+        ("language".to_string(), example.language.as_deref().into()),
+        ("info".to_string(), example.info.as_deref().into()),
+        ("code".to_string(), example.code.as_str().into()),
+        ("input".to_string(), example.input.as_str().into()),
+        ("expected".to_string(), example.expected.as_deref().into()),
+        ("title".to_string(), example.title.as_deref().into()),
+        ("id".to_string(), example.id.as_deref().into()),
+        (
+            "attributes".to_string(),
+            Value::Object(
+                example
+                    .attributes
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.as_str().into()))
+                    .collect(),
+            ),
+        ),
+    ]))
+}
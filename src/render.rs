@@ -0,0 +1,297 @@
+//! Rendering documentation to other formats.
+//!
+//! - [`compat`] writes Markdown in the layout of the original nixdoc tool.
+//! - [`markdown`] writes one comment as normalized Markdown.
+//! - `html` writes one comment as an HTML fragment, with the `html`
+//!   feature.
+//! - `site` writes a static HTML site, with the `site` feature.
+//! - `dash` writes a Dash or Zeal docset, with the `dash` feature.
+//! - `inventory` writes a Sphinx `objects.inv` for a site, with the `site`
+//!   feature.
+//! - `serve` serves a site and a JSON API over HTTP, with the `serve`
+//!   feature.
+//!
+//! Options shared by all renderers are in [`RenderOptions`]. Most
+//! importantly, a [`LinkResolver`] decides where each intra-doc reference
+//! such as `` [`lib.attrsets.mapAttrs`] `` links to, so a site spread over
+//! many pages gets correct relative links.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::xref::{self, SymbolTable};
+use crate::{DocComment, Stability};
+
+pub mod compat;
+#[cfg(feature = "dash")]
+pub mod dash;
+#[cfg(feature = "html")]
+pub mod html;
+#[cfg(feature = "site")]
+pub mod inventory;
+pub mod markdown;
+#[cfg(feature = "serve")]
+pub mod serve;
+#[cfg(feature = "site")]
+pub mod site;
+
+/// Decides the link target of intra-doc references.
+///
+/// Renderers call [`resolve`](Self::resolve) for every reference that
+/// resolved against [`RenderOptions::symbols`]. Closures of type
+/// `Fn(&str, &str) -> Option<String>` implement this trait.
+pub trait LinkResolver {
+    /// Returns the URL of `target` as linked from the documentation of
+    /// `from`, or `None` to leave the reference unlinked.
+    ///
+    /// Both are identifiers from the symbol table, such as
+    /// `attrsets.mapAttrs`.
+    fn resolve(&self, from: &str, target: &str) -> Option<String>;
+}
+
+impl<F: Fn(&str, &str) -> Option<String>> LinkResolver for F {
+    fn resolve(&self, from: &str, target: &str) -> Option<String> {
+        self(from, target)
+    }
+}
+
+/// A [`LinkResolver`] for a site with one page per group of identifiers.
+///
+/// Each identifier is placed on a page, a path relative to the site root,
+/// under an anchor. Links between identifiers on the same page are bare
+/// anchors; other links are relative paths from one page to the other.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::render::{LinkResolver, PageLinks};
+///
+/// let mut pages = PageLinks::new();
+/// pages.insert("strings.concatStrings", "lib/strings.html", "concatStrings");
+/// pages.insert("strings.concatMapStrings", "lib/strings.html", "concatMapStrings");
+/// pages.insert("attrsets.mapAttrs", "lib/attrsets/index.html", "mapAttrs");
+///
+/// assert_eq!(
+///     pages.resolve("strings.concatMapStrings", "strings.concatStrings").as_deref(),
+///     Some("#concatStrings"),
+/// );
+/// assert_eq!(
+///     pages.resolve("strings.concatStrings", "attrsets.mapAttrs").as_deref(),
+///     Some("attrsets/index.html#mapAttrs"),
+/// );
+/// assert_eq!(
+///     pages.resolve("attrsets.mapAttrs", "strings.concatStrings").as_deref(),
+///     Some("../strings.html#concatStrings"),
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageLinks {
+    pages: BTreeMap<String, (PathBuf, String)>,
+}
+
+impl PageLinks {
+    /// Create a resolver that places no identifiers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Place `identifier` on `page` under `anchor`.
+    pub fn insert(
+        &mut self,
+        identifier: impl Into<String>,
+        page: impl Into<PathBuf>,
+        anchor: impl Into<String>,
+    ) {
+        self.pages
+            .insert(identifier.into(), (page.into(), anchor.into()));
+    }
+}
+
+impl LinkResolver for PageLinks {
+    fn resolve(&self, from: &str, target: &str) -> Option<String> {
+        let (target_page, anchor) = self.pages.get(target)?;
+        let Some((from_page, _)) = self.pages.get(from) else {
+            return Some(format!("{}#{anchor}", url_path(target_page)));
+        };
+        if from_page == target_page {
+            return Some(format!("#{anchor}"));
+        }
+        let relative = relative_path(from_page.parent().unwrap_or(Path::new("")), target_page);
+        Some(format!("{}#{anchor}", url_path(&relative)))
+    }
+}
+
+/// The path from directory `from` to `to`, both relative to the same root.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut path = PathBuf::new();
+    for _ in common..from.len() {
+        path.push("..");
+    }
+    path.extend(&to[common..]);
+    path
+}
+
+/// Format `path` with `/` separators, as URLs use.
+fn url_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Options shared by all renderers.
+#[derive(Clone, Default)]
+pub struct RenderOptions {
+    /// The identifiers references are resolved against, see
+    /// [`SymbolTable::resolve`].
+    pub symbols: SymbolTable,
+    /// Where resolved references link to. Without a resolver, references
+    /// are rendered as written.
+    pub link_resolver: Option<Arc<dyn LinkResolver + Send + Sync>>,
+    /// Leave out items whose [`Stability`](crate::Stability) is `Internal`.
+    pub hide_internal: bool,
+    /// Say under each item where its comment came from, see
+    /// [`Provenance`](crate::Provenance).
+    pub show_provenance: bool,
+}
+
+impl RenderOptions {
+    /// Create options with an empty symbol table and no link resolver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the identifiers references are resolved against.
+    pub fn with_symbols(mut self, symbols: SymbolTable) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Set the hook deciding where references link to.
+    pub fn with_link_resolver(
+        mut self,
+        resolver: impl LinkResolver + Send + Sync + 'static,
+    ) -> Self {
+        self.link_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Set whether internal items are left out.
+    pub fn with_hide_internal(mut self, hide_internal: bool) -> Self {
+        self.hide_internal = hide_internal;
+        self
+    }
+
+    /// Set whether each item says where its comment came from.
+    pub fn with_show_provenance(mut self, show_provenance: bool) -> Self {
+        self.show_provenance = show_provenance;
+        self
+    }
+
+    /// Returns `true` if `doc` should be rendered under these options.
+    pub fn includes(&self, doc: &DocComment) -> bool {
+        !(self.hide_internal && doc.stability() == Some(Stability::Internal))
+    }
+
+    /// Turn the `` [`name`] `` references in `markdown` into links, as seen
+    /// from the documentation of `from`.
+    ///
+    /// References that do not resolve, that the resolver declines, or that
+    /// are already links are left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::render::RenderOptions;
+    ///
+    /// let options = RenderOptions::new()
+    ///     .with_symbols(["lists.map"].into_iter().collect())
+    ///     .with_link_resolver(|_: &str, target: &str| Some(format!("lists.html#{target}")));
+    /// assert_eq!(
+    ///     options.link_references("Like [`lib.lists.map`] or [`nope`].", "lists.imap0"),
+    ///     "Like [`lib.lists.map`](lists.html#lists.map) or [`nope`].",
+    /// );
+    /// ```
+    pub fn link_references(&self, markdown: &str, from: &str) -> String {
+        let Some(resolver) = &self.link_resolver else {
+            return markdown.to_string();
+        };
+
+        let mut out = String::with_capacity(markdown.len());
+        let mut last = 0;
+        for (span, text) in xref::inline_mentions(markdown) {
+            let is_link = markdown[span.end..].starts_with(['(', '[']);
+            let url = self
+                .symbols
+                .resolve(text)
+                .filter(|_| !is_link)
+                .and_then(|target| resolver.resolve(from, target));
+            if let Some(url) = url {
+                out.push_str(&markdown[last..span.end]);
+                out.push('(');
+                out.push_str(&url);
+                out.push(')');
+                last = span.end;
+            }
+        }
+        out.push_str(&markdown[last..]);
+        out
+    }
+}
+
+impl fmt::Debug for RenderOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderOptions")
+            .field("symbols", &self.symbols)
+            .field("link_resolver", &self.link_resolver.as_ref().map(|_| ".."))
+            .field("hide_internal", &self.hide_internal)
+            .field("show_provenance", &self.show_provenance)
+            .finish()
+    }
+}
+
+/// Increase the level of every ATX heading in `markdown` by `levels`,
+/// leaving fenced code blocks untouched.
+///
+/// Headings are capped at level 6.
+pub(crate) fn shift_headings(markdown: &str, levels: usize) -> String {
+    let mut out = String::with_capacity(markdown.len() + levels * 8);
+    let mut fence: Option<(char, usize)> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'));
+        let run = marker.map_or(0, |c| trimmed.len() - trimmed.trim_start_matches(c).len());
+
+        match (fence, marker) {
+            (None, Some(c)) if run >= 3 => fence = Some((c, run)),
+            (Some((open, len)), Some(c))
+                if c == open && run >= len && trimmed[run..].trim().is_empty() =>
+            {
+                fence = None
+            }
+            (None, _) => {
+                let hashes = trimmed.len() - trimmed.trim_start_matches('#').len();
+                let is_heading = (1..=6).contains(&hashes)
+                    && trimmed[hashes..].starts_with([' ', '\t'])
+                    && trimmed.len() == line.len();
+                if is_heading {
+                    out.push_str(&"#".repeat((hashes + levels).min(6)));
+                    out.push_str(&trimmed[hashes..]);
+                    out.push('\n');
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,236 @@
+//! Output in the layout of the original nixdoc tool.
+//!
+//! The nixpkgs manual generates its library reference by running
+//! [NixOS/nixdoc] on each file of `lib/`. [`render`] writes a file's doc
+//! comments in the layout of that tool's CommonMark: its headings, anchors,
+//! type lines and example blocks. A test compares the output byte for byte
+//! with the original tool's on the fixture corpus, using the nixdoc of the
+//! nixpkgs revision in `flake.lock` (`nix build .#goldens`):
+//!
+//! ````text
+//! # String manipulation functions {#sec-functions-library-strings}
+//!
+//! ## `lib.strings.concatStrings` {#function-library-lib.strings.concatStrings}
+//!
+//! Concatenate a list of strings.
+//!
+//! `concatStrings :: [string] -> string`
+//!
+//! `list`
+//!
+//! : The strings to concatenate
+//!
+//! ::: {.example #function-library-example-lib.strings.concatStrings}
+//! # `lib.strings.concatStrings` usage example
+//!
+//! ```nix
+//! concatStrings ["foo" "bar"]
+//! => "foobar"
+//! ```
+//! :::
+//! ````
+//!
+//! That is the layout of plain `/* */` comments. RFC 145 doc comments are
+//! copied as written instead, with their headings shifted below the entry
+//! heading and their `:::{.example}` blocks and IDs kept, as nixdoc 3 does
+//! for the current nixpkgs manual.
+//!
+//! [NixOS/nixdoc]: https://github.com/NixOS/nixdoc
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::{RenderOptions, shift_headings};
+use crate::extract::DocumentedItem;
+use crate::{DocSet, SectionKind};
+
+/// Options for [`render`], mirroring the command line of the original tool.
+#[derive(Debug, Clone)]
+pub struct CompatOptions {
+    /// The attribute set the file is exposed under, such as `lib`.
+    pub prefix: String,
+    /// The file's category, such as `strings`. Part of every identifier.
+    pub category: String,
+    /// The title of the generated section.
+    pub description: String,
+    /// Prepended to each function's identifier to form its anchor.
+    pub anchor_prefix: String,
+    /// Markdown describing where each identifier is defined, keyed by
+    /// identifier. Rendered as `Located at <location>.`
+    pub locations: BTreeMap<String, String>,
+    /// Options shared with other renderers, such as how references link.
+    ///
+    /// The original tool does not link references, so by default none are.
+    pub render: RenderOptions,
+}
+
+impl Default for CompatOptions {
+    fn default() -> Self {
+        Self {
+            prefix: "lib".to_string(),
+            category: String::new(),
+            description: String::new(),
+            anchor_prefix: "function-library-".to_string(),
+            locations: BTreeMap::new(),
+            render: RenderOptions::default(),
+        }
+    }
+}
+
+impl CompatOptions {
+    /// Returns the full identifier of the binding `name`, such as
+    /// `lib.strings.concatStrings`.
+    pub fn identifier(&self, name: &str) -> String {
+        [self.prefix.as_str(), self.category.as_str(), name]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+/// Render the doc comments of one file.
+///
+/// A leading comment that documents no binding is the file's own
+/// documentation and follows the section heading. Other unnamed comments are
+/// skipped.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+/// use nixdoc::render::compat::{CompatOptions, render};
+///
+/// let mut set = DocSet::new();
+/// set.add_source("{\n  /** The identity. */\n  id = x: x;\n}", None);
+/// let options = CompatOptions {
+///     category: "trivial".to_string(),
+///     description: "Trivial functions".to_string(),
+///     ..CompatOptions::default()
+/// };
+/// let out = render(&set, &options);
+/// assert!(out.starts_with("# Trivial functions {#sec-functions-library-trivial}\n"));
+/// assert!(out.contains("\n## `lib.trivial.id` {#function-library-lib.trivial.id}\n"));
+/// ```
+pub fn render(set: &DocSet, options: &CompatOptions) -> String {
+    let mut out = format!(
+        "# {} {{#sec-functions-library-{}}}\n\n",
+        options.description, options.category
+    );
+
+    let mut items = set.iter().peekable();
+    if let Some(file_doc) = items.next_if(|item| item.name.is_none()) {
+        let content = options
+            .render
+            .link_references(file_doc.doc.raw_content.trim(), "");
+        out.push_str(&shift_headings(&content, 1));
+        out.push('\n');
+    }
+
+    for item in items.filter(|item| options.render.includes(&item.doc)) {
+        if let Some(name) = &item.name {
+            out.push_str(&render_entry(item, name, options));
+        }
+    }
+    out
+}
+
+/// Render one documented binding.
+fn render_entry(item: &DocumentedItem, name: &str, options: &CompatOptions) -> String {
+    let doc = &item.doc;
+    let ident = options.identifier(name);
+    let link = |markdown: &str| options.render.link_references(markdown, name);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "## `{ident}` {{#{}{ident}}}\n", options.anchor_prefix);
+
+    if !doc.is_legacy() {
+        let content = shift_headings(&link(doc.raw_content.trim()), 2);
+        let _ = writeln!(out, "{content}");
+        if let Some(location) = options.locations.get(&ident) {
+            let _ = writeln!(out, "Located at {location}.\n");
+        }
+        return out;
+    }
+
+    if !doc.description().is_empty() {
+        let _ = writeln!(out, "{}", shift_headings(&link(doc.description()), 2));
+    }
+
+    if let Some(sig) = doc.type_sig() {
+        let sig = sig.trim();
+        if sig.lines().count() > 1 {
+            let _ = writeln!(out, "**Type**:\n```\n{sig}\n```\n");
+        } else {
+            let _ = writeln!(out, "`{sig}`\n");
+        }
+    }
+
+    for arg in doc.arguments() {
+        let _ = writeln!(
+            out,
+            "`{}`\n\n: {}\n",
+            arg.name,
+            link(arg.description.trim())
+        );
+    }
+
+    // Sections without dedicated formatting keep their content, one level
+    // below the entry heading. `# Meta` is for tools, not readers.
+    for section in &doc.sections {
+        let kind = section.kind();
+        if matches!(
+            kind,
+            SectionKind::Type
+                | SectionKind::Arguments
+                | SectionKind::Example
+                | SectionKind::Examples
+                | SectionKind::Meta
+        ) || section.content.trim().is_empty()
+        {
+            continue;
+        }
+        match &section.explicit_id {
+            Some(id) => {
+                let _ = writeln!(out, "### {} {{#{id}}}\n", section.heading);
+            }
+            None => {
+                let _ = writeln!(out, "### {}\n", section.heading);
+            }
+        }
+        let _ = writeln!(out, "{}", shift_headings(&link(section.content.trim()), 3));
+    }
+
+    // Definitions were lifted out of the text above; without them, its
+    // reference links would not resolve.
+    if !doc.link_definitions.is_empty() {
+        for definition in &doc.link_definitions {
+            let _ = writeln!(out, "{definition}");
+        }
+        out.push('\n');
+    }
+
+    for (i, example) in doc.examples().iter().enumerate() {
+        let anchor = match (&example.id, i) {
+            (Some(id), _) => id.clone(),
+            (None, 0) => format!("{}example-{ident}", options.anchor_prefix),
+            (None, _) => format!("{}example-{ident}-{}", options.anchor_prefix, i + 1),
+        };
+        let caption = match &example.title {
+            Some(title) => title.clone(),
+            None => format!("`{ident}` usage example"),
+        };
+        let language = example.language.as_deref().unwrap_or("nix");
+        let _ = writeln!(
+            out,
+            "::: {{.example #{anchor}}}\n# {caption}\n\n```{language}\n{}\n```\n:::\n",
+            example.code.trim()
+        );
+    }
+
+    if let Some(location) = options.locations.get(&ident) {
+        let _ = writeln!(out, "Located at {location}.\n");
+    }
+
+    out
+}
@@ -0,0 +1,191 @@
+//! [Dash] and [Zeal] docsets.
+//!
+//! A docset is a directory bundle holding the HTML pages of a
+//! [`Site`] and a SQLite index of the documented names, so the
+//! documentation can be browsed and searched offline. [`write_docset`]
+//! writes one for a [`DocSet`].
+//!
+//! Requires the `dash` feature.
+//!
+//! [Dash]: https://kapeli.com/dash
+//! [Zeal]: https://zealdocs.org
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, params};
+use thiserror::Error;
+
+use super::html::escape;
+use super::site::{Site, SiteOptions};
+use crate::DocSet;
+
+/// Options for [`write_docset`].
+#[derive(Debug, Clone)]
+pub struct DocsetOptions {
+    /// The name shown in Dash and used for the bundle, as in
+    /// `<name>.docset`.
+    pub name: String,
+    /// A unique identifier for the docset, also the keyword that restricts
+    /// a Dash search to it, such as `nixpkgs-lib`.
+    pub identifier: String,
+    /// How the pages are rendered. The site title defaults to
+    /// [`Self::name`].
+    pub site: SiteOptions,
+}
+
+impl DocsetOptions {
+    /// Create options for a docset called `name`, identified by
+    /// `identifier`.
+    pub fn new(name: impl Into<String>, identifier: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            site: SiteOptions::new().with_title(name.clone()),
+            name,
+            identifier: identifier.into(),
+        }
+    }
+
+    /// Set how the pages are rendered.
+    pub fn with_site(mut self, site: SiteOptions) -> Self {
+        self.site = site;
+        self
+    }
+}
+
+/// An error writing a docset.
+#[derive(Debug, Error)]
+pub enum DocsetError {
+    /// A file of the bundle could not be written.
+    #[error("could not write the docset: {0}")]
+    Io(#[from] io::Error),
+    /// The search index could not be written.
+    #[error("could not write the docset index: {0}")]
+    Index(#[from] rusqlite::Error),
+}
+
+/// Write `set` as a docset bundle in `dir`, replacing an existing bundle of
+/// the same name, and return the bundle's path.
+///
+/// Every named item is indexed as a `Function` and every page as a `File`.
+/// The pages carry Dash anchors, so Dash lists the functions of a page in
+/// its table of contents.
+///
+/// # Errors
+///
+/// Returns an error if a file or the index cannot be written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use nixdoc::DocSet;
+/// use nixdoc::render::dash::{DocsetOptions, write_docset};
+///
+/// let set = DocSet::from_paths(&["lib"]).unwrap();
+/// let options = DocsetOptions::new("Nixpkgs lib", "nixpkgs-lib");
+/// let bundle = write_docset(&set, &options, "out".as_ref()).unwrap();
+/// assert!(bundle.ends_with("Nixpkgs lib.docset"));
+/// ```
+pub fn write_docset(
+    set: &DocSet,
+    options: &DocsetOptions,
+    dir: &Path,
+) -> Result<PathBuf, DocsetError> {
+    let bundle = dir.join(format!("{}.docset", options.name));
+    let contents = bundle.join("Contents");
+    let resources = contents.join("Resources");
+    if bundle.exists() {
+        fs::remove_dir_all(&bundle)?;
+    }
+    fs::create_dir_all(&resources)?;
+
+    let mut site = Site::build(set, &options.site);
+    let entries = entries(set, &site, options);
+    add_dash_anchors(&mut site, set);
+    site.write(&resources.join("Documents"))?;
+    fs::write(contents.join("Info.plist"), info_plist(options))?;
+
+    let mut db = Connection::open(resources.join("docSet.dsidx"))?;
+    db.execute_batch(
+        "CREATE TABLE searchIndex(id INTEGER PRIMARY KEY, name TEXT, type TEXT, path TEXT);
+         CREATE UNIQUE INDEX anchor ON searchIndex (name, type, path);",
+    )?;
+    let tx = db.transaction()?;
+    {
+        let mut insert =
+            tx.prepare("INSERT OR IGNORE INTO searchIndex(name, type, path) VALUES (?1, ?2, ?3)")?;
+        for (name, kind, path) in &entries {
+            insert.execute(params![name, kind, path])?;
+        }
+    }
+    tx.commit()?;
+    Ok(bundle)
+}
+
+/// The `(name, type, path)` rows of the search index.
+fn entries(
+    set: &DocSet,
+    site: &Site,
+    options: &DocsetOptions,
+) -> Vec<(String, &'static str, String)> {
+    let mut entries = Vec::new();
+    for path in site.files.keys() {
+        let is_page = path.extension().is_some_and(|ext| ext == "html");
+        if is_page && path != Path::new("index.html") {
+            let path = super::url_path(path);
+            entries.push((path.clone(), "File", path));
+        }
+    }
+    for (item, url) in set.iter().zip(&site.urls) {
+        if let Some(name) = &item.name
+            && options.site.render.includes(&item.doc)
+        {
+            entries.push((name.clone(), "Function", url.clone()));
+        }
+    }
+    entries
+}
+
+/// Put a Dash table of contents anchor before the section of each named
+/// item.
+fn add_dash_anchors(site: &mut Site, set: &DocSet) {
+    for (item, url) in set.iter().zip(&site.urls) {
+        let (Some(name), Some((page, anchor))) = (&item.name, url.split_once('#')) else {
+            continue;
+        };
+        let Some(page) = site.files.get_mut(Path::new(page)) else {
+            continue;
+        };
+        let section = format!("<section id=\"{}\">", escape(anchor));
+        let anchor = format!(
+            "<a name=\"//apple_ref/cpp/Function/{}\" class=\"dashAnchor\"></a>\n{section}",
+            escape(name)
+        );
+        *page = page.replacen(&section, &anchor, 1);
+    }
+}
+
+fn info_plist(options: &DocsetOptions) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>CFBundleIdentifier</key>
+	<string>{identifier}</string>
+	<key>CFBundleName</key>
+	<string>{name}</string>
+	<key>DocSetPlatformFamily</key>
+	<string>{identifier}</string>
+	<key>isDashDocset</key>
+	<true/>
+	<key>dashIndexFilePath</key>
+	<string>index.html</string>
+</dict>
+</plist>
+"#,
+        identifier = escape(&options.identifier),
+        name = escape(&options.name),
+    )
+}
@@ -0,0 +1,287 @@
+//! HTML for a single doc comment.
+//!
+//! [`render`] converts a comment to an HTML fragment that a documentation
+//! page can embed as it is, without a second Markdown pipeline:
+//!
+//! - each section gets a heading whose `id` is the section's
+//!   [anchor](crate::Section::anchor), unique within the comment;
+//! - notes, warnings and deprecation notices become
+//!   `<div class="admonition note">` blocks;
+//! - `nix` code blocks are highlighted with `<span>` elements whose classes
+//!   start with `nix-`, such as `nix-keyword`, for a stylesheet to color.
+//!   The `=>` before the result of an example is a `nix-result`;
+//! - `# Meta` sections are left out, as they are for machines.
+//!
+//! Requires the `html` feature.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd, html};
+
+use crate::{AdmonitionLevel, DocComment, SectionKind};
+
+/// Options for [`render`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlOptions {
+    /// Prepended to the `id` of every section heading, such as
+    /// `concatStrings-`, so several comments can share a page. Empty by
+    /// default.
+    pub id_prefix: String,
+    /// The level of section headings, from 1 to 6. Defaults to 2, for
+    /// `<h2>`.
+    pub heading_level: u8,
+    /// Highlight `nix` code blocks. Defaults to `true`; other languages
+    /// only get a `language-…` class either way.
+    pub highlight: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            id_prefix: String::new(),
+            heading_level: 2,
+            highlight: true,
+        }
+    }
+}
+
+impl HtmlOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the prefix of heading `id`s.
+    pub fn with_id_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.id_prefix = prefix.into();
+        self
+    }
+
+    /// Set the level of section headings, clamped to 1 to 6.
+    pub fn with_heading_level(mut self, level: u8) -> Self {
+        self.heading_level = level.clamp(1, 6);
+        self
+    }
+
+    /// Set whether `nix` code blocks are highlighted.
+    pub fn with_highlight(mut self, highlight: bool) -> Self {
+        self.highlight = highlight;
+        self
+    }
+}
+
+/// Render `doc` as an HTML fragment, see the [module documentation](self).
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+/// use nixdoc::render::html::{HtmlOptions, render};
+///
+/// let doc = DocComment::parse(
+///     "/**\n  Identity.\n\n  # Examples\n\n  ```nix\n  id 1\n  => 1\n  ```\n*/",
+/// )
+/// .unwrap();
+/// let html = render(&doc, &HtmlOptions::new().with_id_prefix("id-"));
+/// assert!(html.starts_with("<p>Identity.</p>\n"));
+/// assert!(html.contains("<h2 id=\"id-examples\"><a href=\"#id-examples\">Examples</a></h2>"));
+/// assert!(html.contains("<span class=\"nix-result\">=&gt;</span> <span class=\"nix-number\">1</span>"));
+/// ```
+pub fn render(doc: &DocComment, options: &HtmlOptions) -> String {
+    let level = options.heading_level.clamp(1, 6);
+    let mut out = markdown_to_html(doc.description.trim(), options.highlight);
+    let mut ids = BTreeSet::new();
+
+    for section in &doc.sections {
+        if section.kind() == SectionKind::Meta {
+            continue;
+        }
+        let content = markdown_to_html(section.content.trim(), options.highlight);
+        if let Some(level) = section.admonition_level() {
+            let class = match level {
+                AdmonitionLevel::Info => "note",
+                AdmonitionLevel::Warning => "warning",
+                AdmonitionLevel::Danger => "caution",
+                AdmonitionLevel::Deprecated => "deprecated",
+            };
+            let _ = write!(
+                out,
+                "<div class=\"admonition {class}\">\n<p class=\"admonition-title\">{}</p>\n{content}</div>\n",
+                escape(&section.heading)
+            );
+            continue;
+        }
+        if content.is_empty() {
+            continue;
+        }
+
+        let anchor = format!("{}{}", options.id_prefix, section.anchor());
+        let mut id = anchor.clone();
+        let mut n = 1;
+        while !ids.insert(id.clone()) {
+            n += 1;
+            id = format!("{anchor}-{n}");
+        }
+        let id = escape(&id);
+        let _ = write!(
+            out,
+            "<h{level} id=\"{id}\"><a href=\"#{id}\">{}</a></h{level}>\n{content}",
+            escape(&section.heading)
+        );
+    }
+    out
+}
+
+/// Convert Markdown to HTML, highlighting `nix` code blocks if `highlight`
+/// is set.
+pub(crate) fn markdown_to_html(markdown: &str, highlight: bool) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut out = String::new();
+    let mut events = Parser::new_ext(markdown, options);
+    let mut highlighted = Vec::new();
+    while let Some(event) = events.next() {
+        let is_nix = matches!(
+            &event,
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                if info.split_whitespace().next() == Some("nix")
+        );
+        if !(highlight && is_nix) {
+            highlighted.push(event);
+            continue;
+        }
+        let mut code = String::new();
+        for event in events.by_ref() {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => break,
+                _ => {}
+            }
+        }
+        highlighted.push(Event::Html(CowStr::from(format!(
+            "<pre><code class=\"language-nix\">{}</code></pre>\n",
+            highlight_nix(&code)
+        ))));
+    }
+    html::push_html(&mut out, highlighted.into_iter());
+    out
+}
+
+/// Highlight Nix code as HTML, see the [module documentation](self).
+fn highlight_nix(code: &str) -> String {
+    let mut out = String::with_capacity(code.len() * 2);
+    let mut rest = code;
+    let mut line_start = true;
+    while let Some(c) = rest.chars().next() {
+        let (len, class) = if line_start && rest.trim_start_matches([' ', '\t']).starts_with("=>") {
+            let indent = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+            out.push_str(&rest[..indent]);
+            rest = &rest[indent..];
+            (2, Some("nix-result"))
+        } else if c == '#' {
+            (rest.find('\n').unwrap_or(rest.len()), Some("nix-comment"))
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").map_or(rest.len(), |end| end + 4);
+            (end, Some("nix-comment"))
+        } else if c == '"' {
+            (string_len(rest, "\"", "\\"), Some("nix-string"))
+        } else if rest.starts_with("''") {
+            (string_len(rest, "''", "''"), Some("nix-string"))
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))
+                .unwrap_or(rest.len());
+            (len, Some("nix-number"))
+        } else if let Some(len) = path_len(rest) {
+            (len, Some("nix-path"))
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '\'' | '-')))
+                .unwrap_or(rest.len());
+            let class = match &rest[..len] {
+                "assert" | "else" | "if" | "in" | "inherit" | "let" | "or" | "rec" | "then"
+                | "with" => Some("nix-keyword"),
+                "true" | "false" | "null" => Some("nix-constant"),
+                _ => None,
+            };
+            (len, class)
+        } else {
+            (c.len_utf8(), None)
+        };
+
+        let (token, tail) = rest.split_at(len);
+        match class {
+            Some(class) => {
+                let _ = write!(out, "<span class=\"{class}\">{}</span>", escape(token));
+            }
+            None => out.push_str(&escape(token)),
+        }
+        line_start = token.ends_with('\n') || (line_start && token.trim().is_empty());
+        rest = tail;
+    }
+    out
+}
+
+/// The length of the string literal at the start of `text`, which opens
+/// and closes with `delimiter` and escapes with `escape`. Interpolations
+/// are part of the string.
+fn string_len(text: &str, delimiter: &str, escape: &str) -> usize {
+    let mut depth = 0usize;
+    let mut i = delimiter.len();
+    while i < text.len() {
+        let rest = &text[i..];
+        if depth == 0 && rest.starts_with(escape) && rest.len() > escape.len() {
+            // `''` ends an indented string unless `'`, `$` or `\` follows.
+            let escaped = rest[escape.len()..].chars().next().unwrap_or_default();
+            if escape == "\\" || matches!(escaped, '\'' | '$' | '\\') {
+                i += escape.len() + escaped.len_utf8();
+                continue;
+            }
+        }
+        if rest.starts_with("${") {
+            depth += 1;
+            i += 2;
+            continue;
+        }
+        if depth > 0 && rest.starts_with('{') {
+            depth += 1;
+        } else if depth > 0 && rest.starts_with('}') {
+            depth -= 1;
+        } else if depth == 0 && rest.starts_with(delimiter) {
+            return i + delimiter.len();
+        }
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    text.len()
+}
+
+/// The length of the path literal at the start of `text`, such as
+/// `./default.nix` or `<nixpkgs>`, if there is one.
+fn path_len(text: &str) -> Option<usize> {
+    let is_path_char = |c: char| c.is_alphanumeric() || matches!(c, '.' | '_' | '-' | '+' | '/');
+    if let Some(inner) = text.strip_prefix('<') {
+        let len = inner.find(|c| !is_path_char(c))?;
+        return (len > 0 && inner[len..].starts_with('>')).then_some(len + 2);
+    }
+    if !["./", "../", "~/", "/"].iter().any(|p| text.starts_with(p)) || text.starts_with("//") {
+        return None;
+    }
+    let len = text.find(|c| !is_path_char(c)).unwrap_or(text.len());
+    (len > 1).then_some(len)
+}
+
+/// Escape text for HTML content and attribute values.
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,184 @@
+//! Sphinx `objects.inv` inventories.
+//!
+//! An inventory lists the objects a documentation site defines and where
+//! they are, so Sphinx projects can link to them with [intersphinx]. An
+//! [`Inventory`] built from a [`Site`] maps each documented binding to its
+//! anchor, as a `std:label` that `:ref:` roles resolve:
+//!
+//! ```rst
+//! See :external+nixlib:ref:`concatStrings`.
+//! ```
+//!
+//! Requires the `site` feature.
+//!
+//! [intersphinx]: https://www.sphinx-doc.org/en/master/usage/extensions/intersphinx.html
+
+use std::fmt::Write as _;
+
+use super::site::Site;
+use crate::DocSet;
+
+/// One object of an [`Inventory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryEntry {
+    /// The name links refer to, such as `concatStrings`.
+    pub name: String,
+    /// The Sphinx domain and role, such as `std:label`.
+    pub role: String,
+    /// The search priority: 1 is the default, -1 hides the object from
+    /// search results.
+    pub priority: i32,
+    /// The URL of the object, relative to the site root.
+    pub uri: String,
+    /// The link text, if it differs from [`Self::name`].
+    pub display_name: Option<String>,
+}
+
+/// An intersphinx inventory.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+/// use nixdoc::render::inventory::Inventory;
+/// use nixdoc::render::site::{Site, SiteOptions};
+///
+/// let mut set = DocSet::new();
+/// set.add_source("/** The identity. */\nid = x: x;", Some("trivial.nix".as_ref()));
+/// let site = Site::build(&set, &SiteOptions::new());
+///
+/// let inventory = Inventory::from_site(&set, &site, "lib", "1.0");
+/// assert_eq!(inventory.to_text(), "id std:label -1 trivial.html#$ The identity.\n");
+/// assert!(inventory.to_bytes().starts_with(b"# Sphinx inventory version 2\n"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inventory {
+    /// The project name.
+    pub project: String,
+    /// The project version.
+    pub version: String,
+    /// The objects, in order.
+    pub entries: Vec<InventoryEntry>,
+}
+
+impl Inventory {
+    /// Create an empty inventory.
+    pub fn new(project: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            project: project.into(),
+            version: version.into(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// List the named items of `set` at their anchors in `site`, a site
+    /// built from `set`, as `std:label`s titled with their
+    /// [`DocComment::title`](crate::DocComment::title).
+    ///
+    /// Labels have priority -1, like the section labels Sphinx itself
+    /// writes, so they do not crowd search results. Items `site` hides are
+    /// left out.
+    pub fn from_site(
+        set: &DocSet,
+        site: &Site,
+        project: impl Into<String>,
+        version: impl Into<String>,
+    ) -> Self {
+        let mut inventory = Self::new(project, version);
+        for (item, uri) in set.iter().zip(&site.urls) {
+            let Some(name) = &item.name else {
+                continue;
+            };
+            let (page, anchor) = uri.split_once('#').unwrap_or((uri, ""));
+            if !site.page(page).is_some_and(|page| {
+                page.contains(&format!("<section id=\"{}\">", super::html::escape(anchor)))
+            }) {
+                continue;
+            }
+            inventory.entries.push(InventoryEntry {
+                name: name.clone(),
+                role: "std:label".to_string(),
+                priority: -1,
+                uri: uri.clone(),
+                display_name: item.doc.title().map(str::to_string),
+            });
+        }
+        inventory
+    }
+
+    /// Add an object.
+    pub fn push(&mut self, entry: InventoryEntry) {
+        self.entries.push(entry);
+    }
+
+    /// The object lines, uncompressed.
+    ///
+    /// A URL ending in `#` followed by the name is shortened to `#$`, and a
+    /// missing display name is written as `-`, as Sphinx does.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for entry in &self.entries {
+            let uri = match entry.uri.strip_suffix(entry.name.as_str()) {
+                Some(page) if page.ends_with('#') => format!("{page}$"),
+                _ => entry.uri.clone(),
+            };
+            // Names cannot contain whitespace; display names run to the end
+            // of the line.
+            let name: String = entry.name.split_whitespace().collect::<Vec<_>>().join("_");
+            let display = entry
+                .display_name
+                .as_deref()
+                .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|text| !text.is_empty() && *text != entry.name)
+                .unwrap_or_else(|| "-".to_string());
+            let _ = writeln!(
+                out,
+                "{name} {} {} {uri} {display}",
+                entry.role, entry.priority
+            );
+        }
+        out
+    }
+
+    /// The `objects.inv` file: a plain text header followed by the object
+    /// lines, zlib-compressed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = format!(
+            "# Sphinx inventory version 2\n# Project: {}\n# Version: {}\n\
+             # The remainder of this file is compressed using zlib.\n",
+            self.project.replace('\n', " "),
+            self.version.replace('\n', " "),
+        )
+        .into_bytes();
+        out.extend(zlib_stored(self.to_text().as_bytes()));
+        out
+    }
+}
+
+/// Wrap `data` in a zlib stream of uncompressed ("stored") deflate blocks.
+///
+/// Any zlib decoder reads it; inventories are small enough that
+/// compressing them is not worth a dependency.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+    let mut out = vec![0x78, 0x01];
+    let mut chunks = data.chunks(MAX_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        out.extend([0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        out.push(u8::from(chunks.peek().is_none()));
+        let len = chunk.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(chunk);
+    }
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend(((b << 16) | a).to_be_bytes());
+    out
+}
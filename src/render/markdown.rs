@@ -0,0 +1,97 @@
+//! Normalized CommonMark for a single doc comment.
+//!
+//! [`render`] writes a comment as a Markdown document with a fixed layout,
+//! whatever order and spacing it was written in:
+//!
+//! 1. the description, which starts with the title;
+//! 2. the sections, as `#` headings in a canonical order: summary,
+//!    deprecation, type, arguments, output, examples, laws, then metadata,
+//!    with sections of the same rank and unknown sections in their original
+//!    order;
+//! 3. the link reference definitions.
+//!
+//! Notes, warnings and deprecation notices become `::: {.note}`-style
+//! admonition blocks, as the nixpkgs manual writes them. Blocks are
+//! separated by one blank line. Headings keep their explicit IDs; shift
+//! them to fit the page the comment is placed on.
+
+use crate::{AdmonitionLevel, DocComment, Section, SectionKind};
+
+/// Render `doc` as normalized Markdown, see the [module
+/// documentation](self).
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+///
+/// let doc = DocComment::parse(
+///     "/**\n  Sort a list.\n\n  # Note\n\n  Stable.\n\n  # Type\n\n  ```\n  sort :: [a] -> [a]\n  ```\n*/",
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     nixdoc::render::markdown::render(&doc),
+///     "Sort a list.\n\n# Type\n\n```\nsort :: [a] -> [a]\n```\n\n::: {.note}\nStable.\n:::\n",
+/// );
+/// ```
+pub fn render(doc: &DocComment) -> String {
+    let mut blocks = vec![doc.description.trim().to_string()];
+
+    let mut sections: Vec<&Section> = doc.sections.iter().collect();
+    sections.sort_by_key(|section| rank(&section.kind()));
+    blocks.extend(sections.into_iter().map(block));
+
+    let definitions: Vec<String> = doc
+        .link_definitions
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    blocks.push(definitions.join("\n"));
+
+    blocks.retain(|block| !block.is_empty());
+    let mut out = blocks.join("\n\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// The position of sections of `kind` in the canonical order.
+fn rank(kind: &SectionKind) -> u8 {
+    match kind {
+        SectionKind::Summary => 0,
+        SectionKind::Deprecated => 1,
+        SectionKind::Type => 2,
+        SectionKind::Inputs | SectionKind::Arguments => 3,
+        SectionKind::Output => 4,
+        SectionKind::Example | SectionKind::Examples => 5,
+        SectionKind::Laws => 6,
+        SectionKind::Note | SectionKind::Notes | SectionKind::Warning => 7,
+        SectionKind::Stability => 8,
+        SectionKind::Since => 9,
+        SectionKind::Tags => 10,
+        SectionKind::Maintainers | SectionKind::Authors => 11,
+        SectionKind::Meta => 12,
+        SectionKind::Unknown(_) => 13,
+    }
+}
+
+/// A section as Markdown: an admonition block for callouts, otherwise the
+/// heading and content.
+fn block(section: &Section) -> String {
+    let Some(level) = section.admonition_level() else {
+        return section.to_markdown();
+    };
+    let (class, label) = match level {
+        AdmonitionLevel::Info => ("note", None),
+        AdmonitionLevel::Warning => ("warning", None),
+        AdmonitionLevel::Danger => ("caution", None),
+        AdmonitionLevel::Deprecated => ("warning", Some("**Deprecated.**")),
+    };
+    let content = section.content.trim();
+    let body: Vec<&str> = label
+        .into_iter()
+        .chain(Some(content).filter(|content| !content.is_empty()))
+        .collect();
+    format!("::: {{.{class}}}\n{}\n:::", body.join("\n\n"))
+}
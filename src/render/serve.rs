@@ -0,0 +1,272 @@
+//! A local HTTP server for browsing a [`DocSet`].
+//!
+//! [`DocServer`] renders a set once, as a [`Site`], and answers requests
+//! from memory: the pages of the site, plus two JSON endpoints for editors
+//! and scripts.
+//!
+//! | Path | Response |
+//! |------|----------|
+//! | `/`, `/<page>` | the pages, scripts and styles of the site |
+//! | `/search?q=<query>&limit=<n>` | the best matches, as [`SearchResult`]s |
+//! | `/api/symbol/<name>` | every item named, or referred to as, `<name>`, as [`Symbol`]s |
+//!
+//! [`DocServer::respond`] handles a request without any networking;
+//! [`DocServer::serve`] listens on a socket.
+//!
+//! Requires the `serve` feature.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::site::{Site, SiteOptions};
+use crate::DocSet;
+use crate::search::SearchIndex;
+use crate::view::DocView;
+use crate::xref::SymbolTable;
+
+/// The number of search results returned when the request does not say.
+const DEFAULT_LIMIT: usize = 20;
+
+/// An in-memory documentation server.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+/// use nixdoc::render::serve::DocServer;
+/// use nixdoc::render::site::SiteOptions;
+///
+/// let mut set = DocSet::new();
+/// set.add_source("{\n  /** Map over a list. */\n  map = f: xs: xs;\n}", Some("lists.nix".as_ref()));
+/// let server = DocServer::new(set, &SiteOptions::new());
+///
+/// assert!(server.respond("/lists.html").body.contains("Map over a list."));
+/// let search = server.respond("/search?q=list");
+/// assert_eq!(search.content_type, "application/json");
+/// assert!(search.body.contains("\"identifier\":\"map\""));
+/// assert_eq!(server.respond("/api/symbol/nope").status, 404);
+/// ```
+#[derive(Debug)]
+pub struct DocServer {
+    set: DocSet,
+    site: Site,
+    index: SearchIndex,
+    symbols: SymbolTable,
+}
+
+/// The answer to a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The media type of the body.
+    pub content_type: &'static str,
+    /// The body.
+    pub body: String,
+}
+
+/// One match of a `/search` request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SearchResult {
+    /// The documented binding's name, if known.
+    pub identifier: Option<String>,
+    /// The item's title.
+    pub title: Option<String>,
+    /// The item's page and anchor, relative to the server root.
+    pub url: String,
+    /// How well the item matches; higher is better.
+    pub score: u32,
+}
+
+/// One item of an `/api/symbol` request.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Symbol {
+    /// The documented binding's name.
+    pub identifier: Option<String>,
+    /// The file containing the comment, if known.
+    pub file: Option<PathBuf>,
+    /// The 1-based line on which the comment starts.
+    pub line: usize,
+    /// The item's page and anchor, relative to the server root.
+    pub url: String,
+    /// The documentation.
+    pub doc: DocView,
+}
+
+impl DocServer {
+    /// Render `set` with `options`, ready to serve.
+    pub fn new(set: DocSet, options: &SiteOptions) -> Self {
+        Self {
+            site: Site::build(&set, options),
+            index: set.search_index(),
+            symbols: SymbolTable::from_set(&set),
+            set,
+        }
+    }
+
+    /// Returns the served set.
+    pub fn set(&self) -> &DocSet {
+        &self.set
+    }
+
+    /// Answer a `GET` request for `url`, a path with an optional query
+    /// string.
+    pub fn respond(&self, url: &str) -> Response {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let path = percent_decode(path);
+        if path == "/search" {
+            return self.search(query);
+        }
+        if let Some(name) = path.strip_prefix("/api/symbol/") {
+            return self.symbol(name);
+        }
+
+        let file = match path.trim_start_matches('/') {
+            "" => "index.html",
+            file => file,
+        };
+        match self.site.page(file) {
+            Some(page) => Response {
+                status: 200,
+                content_type: content_type(Path::new(file)),
+                body: page.to_string(),
+            },
+            None => not_found(&path),
+        }
+    }
+
+    fn search(&self, query: &str) -> Response {
+        let mut terms = String::new();
+        let mut limit = DEFAULT_LIMIT;
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            match key {
+                "q" => terms = percent_decode(&value.replace('+', " ")),
+                "limit" => limit = value.parse().unwrap_or(DEFAULT_LIMIT),
+                _ => {}
+            }
+        }
+
+        let results: Vec<SearchResult> = self
+            .index
+            .search(&terms)
+            .into_iter()
+            .take(limit)
+            .map(|hit| {
+                let index = self
+                    .index
+                    .documents
+                    .iter()
+                    .position(|document| core::ptr::eq(document, hit.document))
+                    .unwrap_or_default();
+                SearchResult {
+                    identifier: hit.document.identifier.clone(),
+                    title: hit.document.title.clone(),
+                    url: format!("/{}", self.site.urls[index]),
+                    score: hit.score,
+                }
+            })
+            .collect();
+        json(&results)
+    }
+
+    fn symbol(&self, name: &str) -> Response {
+        let target = self.symbols.resolve(name).unwrap_or(name);
+        let symbols: Vec<Symbol> = self
+            .set
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.name.as_deref() == Some(target))
+            .map(|(index, item)| Symbol {
+                identifier: item.name.clone(),
+                file: item.file.clone(),
+                line: item.line,
+                url: format!("/{}", self.site.urls[index]),
+                doc: item.view(),
+            })
+            .collect();
+        if symbols.is_empty() {
+            return not_found(&format!("/api/symbol/{name}"));
+        }
+        json(&symbols)
+    }
+
+    /// Serve requests on `addr`, such as `127.0.0.1:8080`, until the
+    /// process ends.
+    ///
+    /// Requests other than `GET` and `HEAD` are refused.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `addr` cannot be listened on.
+    pub fn serve(&self, addr: &str) -> io::Result<()> {
+        let server = tiny_http::Server::http(addr).map_err(io::Error::other)?;
+        for request in server.incoming_requests() {
+            let response = match request.method() {
+                tiny_http::Method::Get | tiny_http::Method::Head => self.respond(request.url()),
+                _ => Response {
+                    status: 405,
+                    content_type: "text/plain; charset=utf-8",
+                    body: "method not allowed\n".to_string(),
+                },
+            };
+            let header = tiny_http::Header::from_bytes("Content-Type", response.content_type)
+                .expect("content types are valid header values");
+            let reply = tiny_http::Response::from_string(response.body)
+                .with_status_code(response.status)
+                .with_header(header);
+            // A client that went away is no reason to stop serving others.
+            let _ = request.respond(reply);
+        }
+        Ok(())
+    }
+}
+
+fn json(value: &impl serde::Serialize) -> Response {
+    Response {
+        status: 200,
+        content_type: "application/json",
+        body: serde_json::to_string(value).expect("responses serialize to JSON"),
+    }
+}
+
+fn not_found(path: &str) -> Response {
+    Response {
+        status: 404,
+        content_type: "text/plain; charset=utf-8",
+        body: format!("not found: {path}\n"),
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Decode the `%XX` escapes of a URL component. Invalid escapes are kept
+/// as they are.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
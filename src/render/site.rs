@@ -0,0 +1,340 @@
+//! A static HTML documentation site.
+//!
+//! [`Site::build`] renders a [`DocSet`] as a handful of HTML pages: an
+//! index listing the documented files, one page per file with an anchor per
+//! documented binding, and a client-side search over the set's
+//! [`SearchIndex`](crate::search::SearchIndex). [`Site::write`] saves the
+//! pages to a directory that can be opened locally or served as it is.
+//!
+//! Requires the `site` feature.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use super::html::{escape, markdown_to_html};
+use super::{PageLinks, RenderOptions, url_path};
+use crate::anchor::Anchors;
+use crate::extract::DocumentedItem;
+use crate::xref::SymbolTable;
+use crate::{DocSet, SectionKind};
+
+/// Options for [`Site::build`].
+#[derive(Debug, Clone)]
+pub struct SiteOptions {
+    /// The site title, shown on every page. Defaults to "Documentation".
+    pub title: String,
+    /// A directory the documented files are in. Page paths are the file
+    /// paths relative to it; files outside it keep their full path.
+    pub root: Option<PathBuf>,
+    /// Options shared with other renderers. By default, references link to
+    /// the pages of the site.
+    pub render: RenderOptions,
+}
+
+impl Default for SiteOptions {
+    fn default() -> Self {
+        Self {
+            title: "Documentation".to_string(),
+            root: None,
+            render: RenderOptions::default(),
+        }
+    }
+}
+
+impl SiteOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the site title.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Set the directory page paths are relative to.
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Set the options shared with other renderers.
+    pub fn with_render(mut self, render: RenderOptions) -> Self {
+        self.render = render;
+        self
+    }
+}
+
+/// The files of a generated site, keyed by their path relative to the site
+/// root.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+/// use nixdoc::render::site::{Site, SiteOptions};
+///
+/// let mut set = DocSet::new();
+/// set.add_source(
+///     "{\n  /** The identity. */\n  id = x: x;\n}",
+///     Some("lib/trivial.nix".as_ref()),
+/// );
+/// let site = Site::build(&set, &SiteOptions::new().with_title("My library"));
+///
+/// let page = site.page("lib/trivial.html").unwrap();
+/// assert!(page.contains("<section id=\"id\">"));
+/// assert!(page.contains("<p>The identity.</p>"));
+/// assert!(site.page("index.html").unwrap().contains("href=\"lib/trivial.html\""));
+/// assert!(site.page("search-index.js").is_some());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Site {
+    /// The content of each file.
+    pub files: BTreeMap<PathBuf, String>,
+    /// The URL of each item of the set, in order, relative to the site
+    /// root, such as `lib/strings.html#concatStrings`.
+    pub urls: Vec<String>,
+}
+
+impl Site {
+    /// Render `set` as a site.
+    ///
+    /// Items without a file are placed on `input.html`. Items hidden by
+    /// [`RenderOptions::includes`] are left out. Items and sections are
+    /// given the anchors of [`DocSet::anchors`], which are unique across
+    /// the site.
+    pub fn build(set: &DocSet, options: &SiteOptions) -> Self {
+        let anchors = set.anchors();
+        let mut by_page: BTreeMap<PathBuf, Vec<(usize, &DocumentedItem)>> = BTreeMap::new();
+        let mut links = PageLinks::new();
+        let mut urls = Vec::new();
+        for (index, item) in set.iter().enumerate() {
+            let page = page_path(item.file.as_deref(), options.root.as_deref());
+            let anchor = anchors.item(index).unwrap_or_default();
+            if let Some(name) = &item.name {
+                links.insert(name.clone(), page.clone(), anchor.to_string());
+            }
+            urls.push(format!("{}#{anchor}", url_path(&page)));
+            by_page.entry(page).or_default().push((index, item));
+        }
+
+        let mut render = options.render.clone();
+        if render.symbols.is_empty() {
+            render.symbols = SymbolTable::from_set(set);
+        }
+        if render.link_resolver.is_none() {
+            render = render.with_link_resolver(links);
+        }
+
+        let mut site = Self {
+            urls,
+            ..Self::default()
+        };
+        for (page, items) in &by_page {
+            let body = items
+                .iter()
+                .filter(|(_, item)| render.includes(&item.doc))
+                .map(|&(index, item)| render_item(item, index, &anchors, &render))
+                .collect::<String>();
+            let title = url_path(page);
+            site.files.insert(
+                page.clone(),
+                layout(&options.title, &title, &root_prefix(page), &body),
+            );
+        }
+
+        let mut index = String::from("<h1>Files</h1>\n<ul>\n");
+        for (page, items) in &by_page {
+            let _ = writeln!(
+                index,
+                "<li><a href=\"{}\">{}</a> ({})</li>",
+                escape(&url_path(page)),
+                escape(&url_path(page)),
+                items.len()
+            );
+        }
+        index.push_str("</ul>\n");
+        site.files.insert(
+            PathBuf::from("index.html"),
+            layout(&options.title, &options.title, "", &index),
+        );
+
+        site.files.insert(
+            PathBuf::from("search-index.js"),
+            format!(
+                "window.NIXDOC_INDEX = {};\nwindow.NIXDOC_URLS = {};\n",
+                set.search_index().to_json(),
+                serde_json::to_string(&site.urls).expect("URLs serialize to JSON"),
+            ),
+        );
+        site.files
+            .insert(PathBuf::from("search.js"), SEARCH_JS.to_string());
+        site.files
+            .insert(PathBuf::from("style.css"), STYLE_CSS.to_string());
+        site
+    }
+
+    /// Returns the content of the file at `path`, relative to the site root.
+    pub fn page(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.files.get(path.as_ref()).map(String::as_str)
+    }
+
+    /// Write every file of the site below `dir`, creating directories as
+    /// needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first I/O error encountered.
+    pub fn write(&self, dir: &Path) -> io::Result<()> {
+        for (path, content) in &self.files {
+            let path = dir.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+/// The page documenting the items of `file`.
+fn page_path(file: Option<&Path>, root: Option<&Path>) -> PathBuf {
+    let Some(file) = file else {
+        return PathBuf::from("input.html");
+    };
+    let file = root
+        .and_then(|root| file.strip_prefix(root).ok())
+        .unwrap_or(file);
+    let mut page: PathBuf = file
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect();
+    page.set_extension("html");
+    // Keep the site's own index.
+    if page == Path::new("index.html") {
+        page.set_file_name("index.nix.html");
+    }
+    page
+}
+
+/// The relative path from `page` back to the site root, such as `../`.
+fn root_prefix(page: &Path) -> String {
+    "../".repeat(page.components().count().saturating_sub(1))
+}
+
+/// Render one documented binding, the item at `index` of the set.
+fn render_item(
+    item: &DocumentedItem,
+    index: usize,
+    anchors: &Anchors,
+    render: &RenderOptions,
+) -> String {
+    let doc = &item.doc;
+    let name = item.name.as_deref().unwrap_or_default();
+    let markdown = |text: &str| markdown_to_html(&render.link_references(text, name), false);
+    let mut out = String::new();
+
+    match (&item.name, anchors.item(index)) {
+        (Some(name), Some(anchor)) => {
+            let id = escape(anchor);
+            let _ = writeln!(
+                out,
+                "<section id=\"{id}\">\n<h2><a href=\"#{id}\"><code>{}</code></a></h2>",
+                escape(name)
+            );
+        }
+        _ => out.push_str("<section>\n"),
+    }
+    if render.show_provenance {
+        let _ = writeln!(
+            out,
+            "<p class=\"provenance\">Defined in <code>{}</code></p>",
+            escape(&item.provenance().to_string())
+        );
+    }
+    out.push_str(&markdown(doc.description()));
+
+    for (i, section) in doc.sections.iter().enumerate() {
+        if section.kind() == SectionKind::Meta || section.content.trim().is_empty() {
+            continue;
+        }
+        let id = anchors.section(index, i).unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "<h3 id=\"{}\">{}</h3>",
+            escape(id),
+            escape(&section.heading)
+        );
+        out.push_str(&markdown(&section.content));
+    }
+    out.push_str("</section>\n");
+    out
+}
+
+/// A complete HTML page.
+fn layout(site_title: &str, title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title} - {site_title}</title>\n<link rel=\"stylesheet\" href=\"{root}style.css\">\n\
+         </head>\n<body>\n<header>\n<a href=\"{root}index.html\">{site_title}</a>\n\
+         <input id=\"search\" type=\"search\" placeholder=\"Search\" data-root=\"{root}\">\n\
+         <ul id=\"results\"></ul>\n</header>\n<main>\n{body}</main>\n\
+         <script src=\"{root}search-index.js\"></script>\n<script src=\"{root}search.js\"></script>\n\
+         </body>\n</html>\n",
+        title = escape(title),
+        site_title = escape(site_title),
+    )
+}
+
+/// Searches `NIXDOC_INDEX` as [`SearchIndex::search`](crate::search::SearchIndex::search)
+/// does, with the same tokenization.
+const SEARCH_JS: &str = r#"(function () {
+  const input = document.getElementById("search");
+  const results = document.getElementById("results");
+  const root = input.dataset.root;
+
+  function tokenize(text) {
+    const terms = [];
+    for (const word of text.match(/[\p{L}\p{N}]+/gu) || []) {
+      const parts = word.split(/(?<=[\p{Ll}\p{N}])(?=\p{Lu})/u);
+      for (const term of [word, ...(parts.length > 1 ? parts : [])]) {
+        if ([...term].length >= 2) terms.push(term.toLowerCase());
+      }
+    }
+    return terms;
+  }
+
+  input.addEventListener("input", function () {
+    const scores = new Map();
+    for (const term of tokenize(input.value)) {
+      for (const [doc, score] of NIXDOC_INDEX.terms[term] || []) {
+        scores.set(doc, (scores.get(doc) || 0) + score);
+      }
+    }
+    const hits = [...scores].sort((a, b) => b[1] - a[1] || a[0] - b[0]).slice(0, 20);
+    results.replaceChildren(...hits.map(function ([doc]) {
+      const item = document.createElement("li");
+      const link = document.createElement("a");
+      const documented = NIXDOC_INDEX.documents[doc];
+      link.href = root + NIXDOC_URLS[doc];
+      link.textContent = documented.identifier || documented.title || NIXDOC_URLS[doc];
+      item.append(link);
+      return item;
+    }));
+  });
+})();
+"#;
+
+const STYLE_CSS: &str = "body { font-family: sans-serif; max-width: 60rem; margin: 0 auto; padding: 0 1rem; }
+header { display: flex; gap: 1rem; align-items: center; padding: 1rem 0; border-bottom: 1px solid #ddd; position: relative; }
+#results { position: absolute; top: 100%; right: 0; background: #fff; list-style: none; margin: 0; padding: 0; }
+#results a { display: block; padding: 0.25rem 0.5rem; }
+section { border-bottom: 1px solid #eee; padding-bottom: 1rem; }
+.provenance { color: #666; font-size: 0.875rem; }
+pre { background: #f5f5f5; padding: 0.5rem; overflow-x: auto; }
+";
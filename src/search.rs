@@ -0,0 +1,497 @@
+//! Full-text search over a [`DocSet`].
+//!
+//! [`SearchIndex`] is a small inverted index mapping terms to the documented
+//! items that contain them. It serializes to compact JSON (with the `serde`
+//! feature), so static documentation sites can ship it and search on the
+//! client without a separate indexing step.
+//!
+//! With the `tantivy` feature, the [`tantivy`](self::tantivy) module builds an
+//! equivalent index for the tantivy search engine instead.
+//!
+//! Within a single comment, [`DocComment::find`](crate::DocComment::find)
+//! locates a term and returns [`Match`]es with spans for highlighting.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::extract::DocumentedItem;
+use crate::parser::{LineRole, fence_lines, section_at, section_headings};
+use crate::summary::plain_text;
+use crate::xref::is_identifier;
+use crate::{DocComment, DocSet, Span};
+
+/// Score multipliers for terms found in each field of a documented item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldBoosts {
+    /// Terms in the documented binding's name.
+    pub identifier: u32,
+    /// Terms in the title, the first line of the description.
+    pub title: u32,
+    /// Terms anywhere else in the comment.
+    pub body: u32,
+}
+
+impl Default for FieldBoosts {
+    fn default() -> Self {
+        Self {
+            identifier: 5,
+            title: 3,
+            body: 1,
+        }
+    }
+}
+
+/// A documented item referred to by a [`SearchIndex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchDocument {
+    /// The documented binding's name, if known.
+    pub identifier: Option<String>,
+    /// The file containing the comment, if known.
+    pub file: Option<PathBuf>,
+    /// The 1-based line on which the comment starts.
+    pub line: usize,
+    /// The comment's title.
+    pub title: Option<String>,
+}
+
+/// One search result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit<'a> {
+    /// The matching item.
+    pub document: &'a SearchDocument,
+    /// The sum of the boosted term frequencies of all query terms.
+    pub score: u32,
+}
+
+/// An inverted index from terms to documented items.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+///
+/// let mut set = DocSet::new();
+/// set.add_source("/** Map over an attribute set. */\nmapAttrs = f: s: s;", None);
+/// set.add_source("/** Filter a list. */\nfilter = f: xs: xs;", None);
+///
+/// let index = set.search_index();
+/// let hits = index.search("attrs");
+/// assert_eq!(hits[0].document.identifier.as_deref(), Some("mapAttrs"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchIndex {
+    /// The indexed items. Postings refer to them by position.
+    pub documents: Vec<SearchDocument>,
+    /// Each term with its `(document, score)` postings, ordered by document.
+    pub terms: BTreeMap<String, Vec<(usize, u32)>>,
+}
+
+impl SearchIndex {
+    /// Index every item of `set`, weighting fields by `boosts`.
+    pub fn build(set: &DocSet, boosts: &FieldBoosts) -> Self {
+        let mut index = Self::default();
+        for item in set {
+            index.add(item, boosts);
+        }
+        index
+    }
+
+    fn add(&mut self, item: &DocumentedItem, boosts: &FieldBoosts) {
+        let id = self.documents.len();
+        let title = item.doc.title();
+        self.documents.push(SearchDocument {
+            identifier: item.name.clone(),
+            file: item.file.clone(),
+            line: item.line,
+            title: title.map(str::to_string),
+        });
+
+        let mut scores: BTreeMap<String, u32> = BTreeMap::new();
+        let mut score = |text: &str, boost: u32| {
+            for term in tokenize(text) {
+                *scores.entry(term).or_default() += boost;
+            }
+        };
+
+        if let Some(name) = &item.name {
+            score(name, boosts.identifier);
+        }
+        if let Some(title) = title {
+            score(title, boosts.title);
+        }
+        let description = item.doc.description();
+        let rest = description
+            .trim_start()
+            .strip_prefix(title.unwrap_or_default())
+            .unwrap_or(description);
+        score(rest, boosts.body);
+        for section in &item.doc.sections {
+            score(&section.heading, boosts.body);
+            score(&section.content, boosts.body);
+        }
+
+        for (term, score) in scores {
+            self.terms.entry(term).or_default().push((id, score));
+        }
+    }
+
+    /// Find the items matching any term of `query`, best first.
+    ///
+    /// Scores of all query terms are summed per item; ties are broken by
+    /// document order.
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        let mut scores: BTreeMap<usize, u32> = BTreeMap::new();
+        for term in tokenize(query) {
+            for &(doc, score) in self.terms.get(&term).into_iter().flatten() {
+                *scores.entry(doc).or_default() += score;
+            }
+        }
+
+        let mut hits: Vec<(usize, u32)> = scores.into_iter().collect();
+        hits.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hits.into_iter()
+            .map(|(doc, score)| SearchHit {
+                document: &self.documents[doc],
+                score,
+            })
+            .collect()
+    }
+
+    /// Serialize the index as compact JSON.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("search index serializes to JSON")
+    }
+}
+
+/// Split `text` into lowercase search terms.
+///
+/// Terms are runs of alphanumeric characters of at least two characters.
+/// camelCase words are indexed both whole and by their parts, so `mapAttrs`
+/// yields `mapattrs`, `map`, and `attrs`.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        if word.chars().count() < 2 {
+            continue;
+        }
+        terms.push(word.to_lowercase());
+
+        let parts = camel_case_parts(word);
+        if parts.len() > 1 {
+            terms.extend(
+                parts
+                    .into_iter()
+                    .filter(|part| part.chars().count() >= 2)
+                    .map(|part| part.to_lowercase()),
+            );
+        }
+    }
+    terms
+}
+
+/// Split a camelCase word at each lowercase-to-uppercase transition.
+fn camel_case_parts(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut prev_lower = false;
+    for (i, c) in word.char_indices() {
+        if c.is_uppercase() && prev_lower {
+            parts.push(&word[start..i]);
+            start = i;
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+    }
+    parts.push(&word[start..]);
+    parts
+}
+
+/// A [tantivy] backend for searching a [`DocSet`].
+///
+/// Requires the `tantivy` feature.
+/// How [`DocComment::find`](crate::DocComment::find) compares text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FindOptions {
+    /// Match letter case exactly. Off by default.
+    pub case_sensitive: bool,
+    /// Only match whole words, not parts of longer words. Off by default.
+    pub whole_word: bool,
+}
+
+impl FindOptions {
+    /// Create options for case-insensitive matching anywhere.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether letter case must match.
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Set whether only whole words match.
+    pub fn with_whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+}
+
+/// An occurrence of a search term in a comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Match {
+    /// The heading of the section the match is in, or `None` in the
+    /// description.
+    pub section: Option<String>,
+    /// Where the match is, relative to
+    /// [`DocComment::raw_content`](crate::DocComment::raw_content).
+    pub span: Span,
+    /// The trimmed line containing the match.
+    pub snippet: String,
+}
+
+/// Find the non-overlapping occurrences of `query` in `doc`.
+pub(crate) fn find(doc: &DocComment, query: &str, options: &FindOptions) -> Vec<Match> {
+    let text = doc.raw_content.as_str();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let headings = section_headings(text);
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let found = match_at(&text[start..], query, options.case_sensitive).filter(|&len| {
+            !options.whole_word
+                || (!text[..start].ends_with(is_word) && !text[start + len..].starts_with(is_word))
+        });
+        let Some(len) = found else {
+            start += text[start..].chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+
+        let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+        matches.push(Match {
+            section: section_at(&headings, start).map(str::to_string),
+            span: Span::new(start, start + len),
+            snippet: text[line_start..line_end].trim().to_string(),
+        });
+        start += len;
+    }
+    matches
+}
+
+/// Returns the length in bytes of the prefix of `text` matching `query`.
+fn match_at(text: &str, query: &str, case_sensitive: bool) -> Option<usize> {
+    if case_sensitive {
+        return text.starts_with(query).then_some(query.len());
+    }
+    let mut chars = text.char_indices();
+    for q in query.chars() {
+        let (_, c) = chars.next()?;
+        if !c.to_lowercase().eq(q.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(chars.next().map_or(text.len(), |(i, _)| i))
+}
+
+/// Words left out of [`DocComment::keywords`](crate::DocComment::keywords):
+/// common English words and phrasing shared by most doc comments.
+#[rustfmt::skip]
+pub const STOPWORDS: &[&str] = &[
+    "about", "above", "after", "again", "all", "also", "always", "and", "any", "are", "argument",
+    "arguments", "because", "been", "before", "being", "below", "between", "both", "but", "can",
+    "cannot", "could", "does", "doing", "done", "each", "either", "else", "etc", "even", "every",
+    "example", "for", "from", "function", "further", "given", "had", "has", "have", "here", "how",
+    "however", "into", "its", "itself", "just", "like", "may", "more", "most", "must", "not",
+    "note", "off", "once", "one", "only", "other", "otherwise", "our", "out", "over", "own",
+    "result", "return", "returns", "same", "see", "should", "since", "some", "such", "than", "that",
+    "the", "their", "them", "then", "there", "these", "they", "this", "those", "through", "too",
+    "two", "under", "until", "use", "used", "uses", "using", "value", "very", "was", "way", "were",
+    "what", "when", "where", "whether", "which", "while", "who", "why", "will", "with", "within",
+    "without", "would", "yet", "you", "your",
+];
+
+/// Collect the salient terms of `doc`, see
+/// [`DocComment::keywords`](crate::DocComment::keywords).
+pub(crate) fn keywords(doc: &DocComment) -> Vec<String> {
+    let mut identifiers: Vec<String> = Vec::new();
+    let mut add_identifier = |identifier: &str| {
+        if identifier.chars().count() >= 2
+            && is_identifier(identifier)
+            && !identifiers.iter().any(|known| known == identifier)
+        {
+            identifiers.push(identifier.to_string());
+        }
+    };
+
+    if let Some(sig) = doc.type_sig() {
+        sig.split(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '_' | '\'' | '-')))
+            .map(|word| word.trim_matches(['.', '-', '\'']))
+            .for_each(&mut add_identifier);
+    }
+    for argument in doc.arguments() {
+        add_identifier(&argument.name);
+    }
+    for reference in doc.references() {
+        add_identifier(&reference.text);
+    }
+
+    // Prose terms by frequency, then first appearance.
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for (_, line, role) in fence_lines(doc.description()) {
+        if role != LineRole::Prose {
+            continue;
+        }
+        for (text, code) in plain_text(line).segments() {
+            if code {
+                add_identifier(text.trim());
+                continue;
+            }
+            for word in text.split(|c: char| !c.is_alphanumeric()) {
+                let word = word.to_lowercase();
+                if word.chars().count() < 3
+                    || word.chars().all(|c| c.is_numeric())
+                    || STOPWORDS.contains(&word.as_str())
+                {
+                    continue;
+                }
+                let next = counts.len();
+                counts.entry(word).or_insert((0, next)).0 += 1;
+            }
+        }
+    }
+    let mut prose: Vec<(String, (usize, usize))> = counts
+        .into_iter()
+        .filter(|(word, _)| {
+            !identifiers
+                .iter()
+                .any(|identifier| identifier.eq_ignore_ascii_case(word))
+        })
+        .collect();
+    prose.sort_by(|(_, (a_count, a_first)), (_, (b_count, b_first))| {
+        b_count.cmp(a_count).then(a_first.cmp(b_first))
+    });
+
+    identifiers.extend(prose.into_iter().map(|(word, _)| word));
+    identifiers
+}
+
+#[cfg(feature = "tantivy")]
+pub mod tantivy {
+    use std::path::Path;
+
+    use tantivy::collector::TopDocs;
+    use tantivy::query::QueryParser;
+    use tantivy::schema::{Field, STORED, STRING, Schema, TEXT, Value};
+    use tantivy::{Index, IndexWriter, TantivyDocument};
+
+    use super::FieldBoosts;
+    use crate::DocSet;
+
+    struct Fields {
+        identifier: Field,
+        title: Field,
+        body: Field,
+        file: Field,
+    }
+
+    fn schema() -> (Schema, Fields) {
+        let mut builder = Schema::builder();
+        let fields = Fields {
+            identifier: builder.add_text_field("identifier", TEXT | STORED),
+            title: builder.add_text_field("title", TEXT | STORED),
+            body: builder.add_text_field("body", TEXT),
+            file: builder.add_text_field("file", STRING | STORED),
+        };
+        (builder.build(), fields)
+    }
+
+    fn fields(index: &Index) -> tantivy::Result<Fields> {
+        let schema = index.schema();
+        Ok(Fields {
+            identifier: schema.get_field("identifier")?,
+            title: schema.get_field("title")?,
+            body: schema.get_field("body")?,
+            file: schema.get_field("file")?,
+        })
+    }
+
+    /// Build a tantivy index of `set`.
+    ///
+    /// The index is created in `dir` if given, which must be an existing empty
+    /// directory, and in memory otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by tantivy while creating or writing the index.
+    pub fn build(set: &DocSet, dir: Option<&Path>) -> tantivy::Result<Index> {
+        let (schema, fields) = schema();
+        let index = match dir {
+            Some(dir) => Index::create_in_dir(dir, schema)?,
+            None => Index::create_in_ram(schema),
+        };
+
+        let mut writer: IndexWriter = index.writer(15_000_000)?;
+        for item in set {
+            let mut doc = TantivyDocument::default();
+            if let Some(name) = &item.name {
+                doc.add_text(fields.identifier, name);
+            }
+            if let Some(title) = item.doc.title() {
+                doc.add_text(fields.title, title);
+            }
+            doc.add_text(fields.body, &item.doc.raw_content);
+            if let Some(file) = &item.file {
+                doc.add_text(fields.file, file.to_string_lossy());
+            }
+            writer.add_document(doc)?;
+        }
+        writer.commit()?;
+        Ok(index)
+    }
+
+    /// Search an index built by [`build`], returning up to `limit`
+    /// `(identifier, score)` pairs, best first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query cannot be parsed or the index cannot be
+    /// read.
+    pub fn search(
+        index: &Index,
+        query: &str,
+        boosts: &FieldBoosts,
+        limit: usize,
+    ) -> tantivy::Result<Vec<(Option<String>, f32)>> {
+        let fields = fields(index)?;
+        let mut parser =
+            QueryParser::for_index(index, vec![fields.identifier, fields.title, fields.body]);
+        parser.set_field_boost(fields.identifier, boosts.identifier as f32);
+        parser.set_field_boost(fields.title, boosts.title as f32);
+        parser.set_field_boost(fields.body, boosts.body as f32);
+        let query = parser.parse_query(query)?;
+
+        let searcher = index.reader()?.searcher();
+        let top = searcher.search(&query, &TopDocs::with_limit(limit))?;
+        top.into_iter()
+            .map(|(score, address)| {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                let identifier = doc
+                    .get_first(fields.identifier)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+                Ok((identifier, score))
+            })
+            .collect()
+    }
+}
@@ -1,3 +1,11 @@
+use alloc::collections::BTreeMap;
+use core::fmt;
+
+use crate::Span;
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
 /// A section in a Nixdoc comment.
 ///
 /// Sections are delimited by level-1 Markdown headings (`# Section Name`).
@@ -21,13 +29,16 @@
 ///
 /// The `# Type` heading produces a `Section` with `heading = "Type"` whose
 /// `content` is the fenced code block for the type signature.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Section {
     /// The heading text (without the leading `# `).
     pub heading: String,
     /// The section body as normalized Markdown text.
     pub content: String,
+    /// The ID given by an explicit anchor on the heading, as in
+    /// `# Examples {#ex-foo}`. The anchor is not part of [`Self::heading`].
+    pub explicit_id: Option<String>,
 }
 
 impl Section {
@@ -35,13 +46,95 @@ impl Section {
     pub fn kind(&self) -> SectionKind {
         SectionKind::from_heading(&self.heading)
     }
+
+    /// Returns the language tag of a translated section, such as `fr` for
+    /// `# Description (fr)`, see [`localize`](crate::localize).
+    pub fn language(&self) -> Option<&str> {
+        crate::localize::split_language(&self.heading).1
+    }
+
+    /// Returns the anchor to link to this section by: the explicit ID if
+    /// there is one, otherwise a [`slug`] of the heading.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  f.\n\n  # Type Signature {#f-type}\n\n  a\n\n  # See Also\n\n  b\n*/",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.sections[0].heading, "Type Signature");
+    /// assert_eq!(doc.sections[0].anchor(), "f-type");
+    /// assert_eq!(doc.sections[1].anchor(), "see-also");
+    /// ```
+    pub fn anchor(&self) -> String {
+        match &self.explicit_id {
+            Some(id) => id.clone(),
+            None => slug(&self.heading),
+        }
+    }
+
+    /// The level of the section if it is a callout: a note, warning,
+    /// caution or deprecation notice.
+    pub(crate) fn admonition_level(&self) -> Option<AdmonitionLevel> {
+        Some(match self.kind() {
+            SectionKind::Note | SectionKind::Notes => AdmonitionLevel::Info,
+            SectionKind::Warning if self.heading.eq_ignore_ascii_case("caution") => {
+                AdmonitionLevel::Danger
+            }
+            SectionKind::Warning => AdmonitionLevel::Warning,
+            SectionKind::Deprecated => AdmonitionLevel::Deprecated,
+            _ => return None,
+        })
+    }
+
+    /// Write the section back as Markdown: the heading, with its explicit
+    /// ID if any, then a blank line and the trimmed content.
+    pub(crate) fn to_markdown(&self) -> String {
+        let mut text = format!("# {}", self.heading);
+        if let Some(id) = &self.explicit_id {
+            text.push_str(&format!(" {{#{id}}}"));
+        }
+        if !self.content.trim().is_empty() {
+            text.push_str("\n\n");
+            text.push_str(self.content.trim());
+        }
+        text
+    }
+}
+
+/// Generate an anchor from heading text.
+///
+/// Letters and digits are lowercased and kept, `-` and `_` are kept, runs of
+/// whitespace become a single `-`, and everything else is dropped.
+///
+/// ```
+/// assert_eq!(nixdoc::section::slug("Example: `mapAttrs` usage"), "example-mapattrs-usage");
+/// ```
+pub fn slug(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pending_dash = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            pending_dash = !out.is_empty();
+        } else if c.is_alphanumeric() || c == '-' || c == '_' {
+            if pending_dash {
+                out.push('-');
+                pending_dash = false;
+            }
+            out.extend(c.to_lowercase());
+        }
+    }
+    out
 }
 
 /// The semantic kind of a Nixdoc section, derived from its heading.
 ///
 /// The Nixdoc specification (RFC145) defines a set of well-known section
 /// names. Any heading not in this set produces `SectionKind::Unknown`.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SectionKind {
     /// `# Type` - the Haskell-style type signature of the function.
@@ -68,6 +161,39 @@ pub enum SectionKind {
     /// `# Deprecated` - a deprecation notice.
     Deprecated,
 
+    /// `# Meta` - machine-readable `key: value` attributes.
+    Meta,
+
+    /// `# Since` - the version that introduced the item.
+    Since,
+
+    /// `# Maintainers` or `# Maintainer` - who looks after the item.
+    Maintainers,
+
+    /// `# Authors` or `# Author` - who wrote the item.
+    Authors,
+
+    /// `# Tags` or `# Categories` - topics for grouping items.
+    Tags,
+
+    /// `# Stability` - how settled the item's interface is.
+    Stability,
+
+    /// `# Summary` - a one-line summary, overriding the first paragraph of
+    /// the description.
+    Summary,
+
+    /// `# Inputs` or `# Input` - the attributes a builder function accepts,
+    /// documented like arguments.
+    Inputs,
+
+    /// `# Output` or `# Outputs` - what a builder function produces.
+    Output,
+
+    /// `# Laws` or `# Properties` - equations the function satisfies, see
+    /// [`Law`].
+    Laws,
+
     /// Any other section heading not covered above.
     Unknown(String),
 }
@@ -98,37 +224,435 @@ impl SectionKind {
             "notes" => Self::Notes,
             "warning" | "warnings" | "caution" => Self::Warning,
             "deprecated" => Self::Deprecated,
+            "meta" => Self::Meta,
+            "since" => Self::Since,
+            "maintainers" | "maintainer" => Self::Maintainers,
+            "authors" | "author" => Self::Authors,
+            "tags" | "categories" => Self::Tags,
+            "stability" => Self::Stability,
+            "summary" => Self::Summary,
+            "inputs" | "input" => Self::Inputs,
+            "output" | "outputs" => Self::Output,
+            "laws" | "law" | "properties" | "property" => Self::Laws,
             other => Self::Unknown(other.to_string()),
         }
     }
 
+    /// Returns the lowercase name of the kind, such as `arguments`, or the
+    /// lowercased heading of an unknown section.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nixdoc::SectionKind;
+    ///
+    /// assert_eq!(SectionKind::from_heading("Args").name(), "arguments");
+    /// assert_eq!(SectionKind::from_heading("See Also").name(), "see also");
+    /// ```
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Type => "type",
+            Self::Arguments => "arguments",
+            Self::Example => "example",
+            Self::Examples => "examples",
+            Self::Note => "note",
+            Self::Notes => "notes",
+            Self::Warning => "warning",
+            Self::Deprecated => "deprecated",
+            Self::Meta => "meta",
+            Self::Since => "since",
+            Self::Maintainers => "maintainers",
+            Self::Authors => "authors",
+            Self::Tags => "tags",
+            Self::Stability => "stability",
+            Self::Summary => "summary",
+            Self::Inputs => "inputs",
+            Self::Output => "output",
+            Self::Laws => "laws",
+            Self::Unknown(heading) => heading,
+        }
+    }
+
     /// Returns `true` if this is a recognized/known section kind.
     pub fn is_known(&self) -> bool {
         !matches!(self, Self::Unknown(_))
     }
 }
 
-/// A parsed function argument from the `# Arguments` section.
+/// How settled an item's interface is, see
+/// [`DocComment::stability`](crate::DocComment::stability).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stability {
+    /// Part of the public interface, changed only with care.
+    Stable,
+    /// Public, but may change without notice. Also written `unstable`.
+    Experimental,
+    /// Not meant for use outside the library. Also written `private`.
+    Internal,
+}
+
+impl Stability {
+    /// Parse a stability name, case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::Stability;
+    ///
+    /// assert_eq!(Stability::parse("Experimental"), Some(Stability::Experimental));
+    /// assert_eq!(Stability::parse("private"), Some(Stability::Internal));
+    /// assert_eq!(Stability::parse("frozen"), None);
+    /// ```
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "stable" => Some(Self::Stable),
+            "experimental" | "unstable" => Some(Self::Experimental),
+            "internal" | "private" => Some(Self::Internal),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Stability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Stable => "stable",
+            Self::Experimental => "experimental",
+            Self::Internal => "internal",
+        })
+    }
+}
+
+/// A parsed function argument from the `# Arguments` section, or an
+/// attribute from the `# Inputs` section.
 ///
 /// Arguments are expected in the form `- [name] Description text` where
-/// `name` is the argument identifier and the rest is an optional description.
-#[derive(Debug, Clone, PartialEq)]
+/// `name` is the argument identifier and the rest is an optional description,
+/// as a definition list entry (`` `name` `` followed by `: Description`),
+/// or as the rows of a Markdown table with `name`, `type`, `default` and
+/// `description` columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Argument {
-    /// The argument name, as written inside `[...]`.
+    /// The argument name, as written inside `[...]` or in the name column.
     pub name: String,
     /// The argument description text (may be empty).
     pub description: String,
+    /// The type from a table's `type` column, if any.
+    pub type_sig: Option<String>,
+    /// The default value from a table's `default` column, if any.
+    pub default: Option<String>,
+}
+
+/// What a builder function produces, from the `# Output` section.
+///
+/// Returned by [`DocComment::output`](crate::DocComment::output).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Output {
+    /// The text before the first attribute entry, trimmed.
+    pub description: String,
+    /// The attributes of the output, such as `passthru` attributes or
+    /// derivation outputs, written like [`Argument`]s.
+    pub attributes: Vec<Argument>,
+}
+
+/// An equation from the `# Laws` section, such as `map id xs == xs`.
+///
+/// Returned by [`DocComment::laws`](crate::DocComment::laws).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Law {
+    /// The name of the law, such as `identity`, if it has one.
+    pub name: Option<String>,
+    /// The Nix expression that must evaluate to `true`.
+    pub expression: String,
+}
+
+/// How serious an [`Admonition`] is.
+///
+/// Levels are ordered from least to most serious, with
+/// [`Deprecated`](Self::Deprecated) last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdmonitionLevel {
+    /// `# Note` or `# Notes`.
+    Info,
+    /// `# Warning` or `# Warnings`.
+    Warning,
+    /// `# Caution`.
+    Danger,
+    /// `# Deprecated`.
+    Deprecated,
+}
+
+impl fmt::Display for AdmonitionLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Danger => "danger",
+            Self::Deprecated => "deprecated",
+        })
+    }
+}
+
+/// A callout section: a note, warning, caution or deprecation notice.
+///
+/// Returned by [`DocComment::admonitions`](crate::DocComment::admonitions).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Admonition {
+    /// How serious the callout is.
+    pub level: AdmonitionLevel,
+    /// The section heading, as written.
+    pub heading: String,
+    /// The section content, trimmed.
+    pub content: String,
+}
+
+/// A fenced code block anywhere in a comment.
+///
+/// Returned by [`DocComment::code_blocks`](crate::DocComment::code_blocks).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodeBlock {
+    /// The kind of the section containing the block, or `None` in the
+    /// description.
+    pub section_kind: Option<SectionKind>,
+    /// The language specifier from the opening fence, if present.
+    pub language: Option<String>,
+    /// The code, without the fences.
+    pub code: String,
+    /// The block, fences included, within
+    /// [`DocComment::raw_content`](crate::DocComment::raw_content).
+    pub span: Span,
+}
+
+/// The info string of a fenced code block, split into its parts.
+///
+/// Returned by [`DocComment::type_info`](crate::DocComment::type_info).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FenceInfo {
+    /// The first token of the info string, unless it is a `key=value`
+    /// attribute.
+    pub language: Option<String>,
+    /// The whole info string, trimmed. Empty if the fence has none.
+    pub info: String,
+    /// The attributes following the language, as in [`Example::attributes`].
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// A person named in a `# Maintainers` or `# Authors` section.
+///
+/// Entries are list items such as `- Jane Doe <jane@example.org>`,
+/// `- @jdoe`, or `- Jane Doe (@jdoe) <jane@example.org>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attribution {
+    /// The name, if one was given.
+    pub name: Option<String>,
+    /// The email address written inside `<...>`, if any.
+    pub email: Option<String>,
+    /// The `@handle`, without the `@`, if any.
+    pub handle: Option<String>,
+}
+
+/// Formats as `name (@handle) <email>`, leaving out the missing parts.
+impl fmt::Display for Attribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let handle = self.handle.as_ref().map(|h| format!("@{h}"));
+        let email = self.email.as_ref().map(|e| format!("<{e}>"));
+        let mut parts = Vec::new();
+        parts.extend(self.name.clone());
+        parts.extend(match (&self.name, handle) {
+            (Some(_), Some(handle)) => Some(format!("({handle})")),
+            (_, handle) => handle,
+        });
+        parts.extend(email);
+        f.write_str(&parts.join(" "))
+    }
 }
 
 /// A code example extracted from an `# Example` or `# Examples` section.
 ///
 /// Each example corresponds to a single fenced code block (` ``` ` or `~~~`).
+/// Examples following the `=>` convention are additionally split into the
+/// input expression and its expected result:
+///
+/// ```text
+/// lib.id 1
+/// => 1
+/// ```
+///
+/// A block with several `=>` markers yields one example per marker, and a
+/// block containing a `nix repl` transcript one example per `nix-repl>`
+/// prompt; all of them share the block's `code`.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Example {
     /// The language specifier from the fenced code block, if present (e.g., `"nix"`).
     pub language: Option<String>,
+    /// The whole info string after the opening fence, if there is one, such
+    /// as `nix title="Basic usage" expect-error`.
+    pub info: Option<String>,
     /// The raw code content.
     pub code: String,
+    /// The code before the `=>` marker, or the whole code if there is none.
+    ///
+    /// Bindings such as `concat = foldr (a: b: a + b) "z"` written before
+    /// the expression are put in scope with a `let` expression.
+    pub input: String,
+    /// The expected result following the `=>` marker, if present.
+    pub expected: Option<String>,
+    /// The example's title, if it has one.
+    ///
+    /// A `title` fence attribute takes precedence. Otherwise the nearest
+    /// caption before the code block is used: a `## Title` subheading or a
+    /// line of bold text such as `**Basic usage**`.
+    pub title: Option<String>,
+    /// The example's explicit anchor ID, from an `id` fence attribute or a
+    /// caption such as `## Basic usage {#ex-basic}`.
+    pub id: Option<String>,
+    /// Attributes following the language in the fence info string.
+    ///
+    /// For ```` ```nix title="Basic usage" expect-error ```` this maps `title`
+    /// to `Basic usage` and the bare flag `expect-error` to an empty string.
+    pub attributes: BTreeMap<String, String>,
+    /// Bindings from earlier `setup` blocks that this example builds on.
+    ///
+    /// A fence flagged `setup` contains `name = value;` bindings shared by
+    /// the blocks that follow it under the same caption:
+    ///
+    /// ````text
+    /// ```nix setup
+    /// xs = [ 1 2 3 ];
+    /// ```
+    ///
+    /// ```nix
+    /// lib.length xs
+    /// => 3
+    /// ```
+    /// ````
+    pub setup: Option<String>,
+}
+
+impl Example {
+    /// Render the example as a fenced code block in the `=>` style.
+    ///
+    /// This converts `nix repl` transcripts into the convention used by
+    /// current Nixdoc comments. Examples without an expected result are
+    /// emitted as their input alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  f.\n\n  # Example\n\n  ```nix\n  nix-repl> lib.id 1\n  1\n  ```\n*/",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.examples()[0].to_fenced(), "```nix\nlib.id 1\n=> 1\n```\n");
+    /// ```
+    pub fn to_fenced(&self) -> String {
+        // Use a fence longer than any backtick run inside the example.
+        let longest_run = self
+            .input
+            .split(|c| c != '`')
+            .chain(self.expected.iter().flat_map(|e| e.split(|c| c != '`')))
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        let fence = "`".repeat(longest_run.max(2) + 1);
+
+        let mut out = format!("{fence}{}", self.language.as_deref().unwrap_or(""));
+        for (key, value) in &self.attributes {
+            if value.is_empty() {
+                out.push_str(&format!(" {key}"));
+            } else {
+                out.push_str(&format!(" {key}={value:?}"));
+            }
+        }
+        out.push_str(&format!("\n{}\n", self.input));
+        if let Some(expected) = &self.expected {
+            out.push_str(&format!("=> {expected}\n"));
+        }
+        out.push_str(&fence);
+        out.push('\n');
+        out
+    }
+
+    /// Returns the input expression with any [`Self::setup`] bindings in scope.
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  f.\n\n  # Example\n\n  ```nix setup\n  x = 1;\n  ```\n\n  ```nix\n  x + 1\n  => 2\n  ```\n*/",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.examples()[1].input_with_setup(), "let\n  x = 1;\nin\nx + 1");
+    /// ```
+    pub fn input_with_setup(&self) -> String {
+        self.with_setup(&self.input)
+    }
+
+    /// Wrap `expr` in a `let` expression binding this example's setup code.
+    ///
+    /// Returns `expr` unchanged if the example has no setup.
+    pub fn with_setup(&self, expr: &str) -> String {
+        match &self.setup {
+            Some(setup) => {
+                let indented: Vec<String> = setup
+                    .lines()
+                    .map(|line| {
+                        if line.is_empty() {
+                            String::new()
+                        } else {
+                            format!("  {line}")
+                        }
+                    })
+                    .collect();
+                format!("let\n{}\nin\n{expr}", indented.join("\n"))
+            }
+            None => expr.to_string(),
+        }
+    }
+
+    /// Returns `true` if the fence carries the given attribute or flag.
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  f.\n\n  # Example\n\n  ```nix no-run\n  f 1\n  ```\n*/",
+    /// )
+    /// .unwrap();
+    /// assert!(doc.examples()[0].has_attribute("no-run"));
+    /// ```
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attributes.contains_key(name)
+    }
+
+    /// Returns `true` if the example is Nix code, labeled `nix` in any
+    /// case. Only such examples are run as doctests or flake checks.
+    ///
+    /// ```
+    /// use nixdoc::DocComment;
+    ///
+    /// let doc = DocComment::parse(
+    ///     "/**\n  f.\n\n  # Example\n\n  ```Nix\n  f 1\n  ```\n\n  ```sh\n  f\n  ```\n*/",
+    /// )
+    /// .unwrap();
+    /// assert!(doc.examples()[0].is_nix());
+    /// assert!(!doc.examples()[1].is_nix());
+    /// ```
+    pub fn is_nix(&self) -> bool {
+        self.language
+            .as_deref()
+            .is_some_and(|lang| lang.eq_ignore_ascii_case("nix"))
+    }
 }
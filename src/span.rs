@@ -0,0 +1,59 @@
+/// A byte range within a source string.
+///
+/// Spans are half-open: `start` is the offset of the first byte and `end` the
+/// offset one past the last byte, so `&source[span.start..span.end]` yields the
+/// spanned text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// Byte offset of the first byte in the span.
+    pub start: usize,
+    /// Byte offset one past the last byte in the span.
+    pub end: usize,
+}
+
+impl Span {
+    /// Create a span covering `start..end`.
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns the length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns `true` if the span covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the 1-based `(line, column)` of the span start within `source`.
+    ///
+    /// Columns count characters, not bytes. A start inside a character
+    /// counts as the start of that character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nixdoc::Span;
+    ///
+    /// let src = "{\n  /** doc */\n  x = 1;\n}";
+    /// let span = Span::new(4, 14);
+    /// assert_eq!(span.line_col(src), (2, 3));
+    ///
+    /// assert_eq!(Span::new(2, 2).line_col("é = 1;"), (1, 2));
+    /// assert_eq!(Span::new(1, 1).line_col("é = 1;"), (1, 1));
+    /// ```
+    pub fn line_col(&self, source: &str) -> (usize, usize) {
+        let mut start = self.start.min(source.len());
+        while !source.is_char_boundary(start) {
+            start -= 1;
+        }
+        let before = &source[..start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        (line, column)
+    }
+}
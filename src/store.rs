@@ -0,0 +1,452 @@
+//! Saving a [`DocSet`] to an SQLite database.
+//!
+//! Parsing a corpus the size of nixpkgs takes a while, and tools that run
+//! over and over, such as a search command or a server, should not have to
+//! do it every time. [`save`] writes a set to an SQLite file and [`open`]
+//! reads it back as it was, without parsing anything. [`search`] runs a
+//! full-text query against the saved set directly.
+//!
+//! The database is also meant to be read by other tools. It has one table
+//! per part of the document model:
+//!
+//! | Table | Rows |
+//! |-------|------|
+//! | `files` | the files added to the set |
+//! | `items` | the documented items, with their comment's text |
+//! | `sections` | the sections of each item |
+//! | `link_definitions` | the link reference definitions of each item |
+//! | `warnings` | the parse warnings of each item |
+//! | `refs` | the [references](crate::DocComment::references) of each item |
+//! | `failures` | the comments that failed to parse, with the kind and message of a denied warning |
+//! | `file_warnings` | problems reading whole files |
+//! | `items_fts` | an FTS5 index of each item's name, title and text |
+//!
+//! Rows refer to items by `items.id`, the item's position in the set.
+//!
+//! Requires the `sqlite` feature.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, params};
+use thiserror::Error;
+
+use crate::extract::{DocumentedItem, FileWarning, ParseFailure};
+use crate::links::LinkDefinition;
+use crate::{DocComment, DocSet, ParseError, ParseWarning, Section, Span, WarningKind};
+
+/// The version of the database layout, stored as `PRAGMA user_version`.
+const SCHEMA_VERSION: i64 = 2;
+
+const SCHEMA: &str = "
+CREATE TABLE files (path TEXT NOT NULL);
+CREATE TABLE items (
+    id INTEGER PRIMARY KEY,
+    name TEXT,
+    file TEXT,
+    span_start INTEGER NOT NULL,
+    span_end INTEGER NOT NULL,
+    line INTEGER NOT NULL,
+    col INTEGER NOT NULL,
+    formals TEXT NOT NULL,
+    raw_content TEXT NOT NULL,
+    description TEXT NOT NULL
+);
+CREATE INDEX items_name ON items (name);
+CREATE TABLE sections (
+    item INTEGER NOT NULL REFERENCES items (id),
+    position INTEGER NOT NULL,
+    heading TEXT NOT NULL,
+    content TEXT NOT NULL,
+    explicit_id TEXT,
+    kind TEXT NOT NULL
+);
+CREATE TABLE link_definitions (
+    item INTEGER NOT NULL REFERENCES items (id),
+    position INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT
+);
+CREATE TABLE warnings (
+    item INTEGER NOT NULL REFERENCES items (id),
+    position INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    message TEXT NOT NULL
+);
+CREATE TABLE refs (
+    item INTEGER NOT NULL REFERENCES items (id),
+    target TEXT NOT NULL,
+    section TEXT,
+    span_start INTEGER NOT NULL,
+    span_end INTEGER NOT NULL
+);
+CREATE INDEX refs_target ON refs (target);
+CREATE TABLE failures (
+    file TEXT,
+    span_start INTEGER NOT NULL,
+    span_end INTEGER NOT NULL,
+    line INTEGER NOT NULL,
+    error TEXT NOT NULL,
+    warning_kind TEXT,
+    warning_message TEXT,
+    error_start INTEGER NOT NULL,
+    error_end INTEGER NOT NULL
+);
+CREATE TABLE file_warnings (file TEXT NOT NULL, message TEXT NOT NULL);
+CREATE VIRTUAL TABLE items_fts USING fts5 (name, title, body);
+";
+
+/// An error saving or opening a set.
+#[derive(Debug, Error)]
+pub enum StoreError {
+    /// The database file could not be replaced.
+    #[error("could not write the database: {0}")]
+    Io(#[from] io::Error),
+    /// SQLite reported an error.
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    /// The database was written by an incompatible version.
+    #[error("unsupported database version {0}, expected {SCHEMA_VERSION}")]
+    Version(i64),
+    /// The database holds a value this version does not know.
+    #[error("invalid database: {0}")]
+    Invalid(String),
+}
+
+/// An item found by [`search`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredHit {
+    /// The position of the item in the saved set.
+    pub index: usize,
+    /// The documented binding's name, if known.
+    pub identifier: Option<String>,
+    /// The file containing the comment, if known.
+    pub file: Option<PathBuf>,
+    /// The 1-based line on which the comment starts.
+    pub line: usize,
+}
+
+/// Save `set` to an SQLite database at `path`, replacing any file there.
+///
+/// The set's [`ParserOptions`](crate::ParserOptions) are not saved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be replaced or written.
+pub fn save(set: &DocSet, path: &Path) -> Result<(), StoreError> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+    let tx = conn.transaction()?;
+    for file in &set.files {
+        tx.execute("INSERT INTO files (path) VALUES (?1)", [path_text(file)])?;
+    }
+    for (id, item) in set.items.iter().enumerate() {
+        insert_item(&tx, id as i64, item)?;
+    }
+    for failure in &set.failures {
+        tx.execute(
+            "INSERT INTO failures VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                failure.file.as_deref().map(path_text),
+                failure.span.start as i64,
+                failure.span.end as i64,
+                failure.line as i64,
+                error_name(&failure.error),
+                denied_warning(&failure.error).map(|w| w.kind.name()),
+                denied_warning(&failure.error).map(|w| &w.message),
+                failure.error_span.start as i64,
+                failure.error_span.end as i64,
+            ],
+        )?;
+    }
+    for warning in &set.file_warnings {
+        tx.execute(
+            "INSERT INTO file_warnings VALUES (?1, ?2)",
+            params![path_text(&warning.file), warning.message],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn insert_item(
+    tx: &rusqlite::Transaction<'_>,
+    id: i64,
+    item: &DocumentedItem,
+) -> rusqlite::Result<()> {
+    let doc = &item.doc;
+    tx.execute(
+        "INSERT INTO items VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            id,
+            item.name,
+            item.file.as_deref().map(path_text),
+            item.span.start as i64,
+            item.span.end as i64,
+            item.line as i64,
+            item.column as i64,
+            item.formals.join("\n"),
+            doc.raw_content,
+            doc.description,
+        ],
+    )?;
+    for (position, section) in doc.sections.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO sections VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                position as i64,
+                section.heading,
+                section.content,
+                section.explicit_id,
+                section.kind().name(),
+            ],
+        )?;
+    }
+    for (position, link) in doc.link_definitions.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO link_definitions VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, position as i64, link.label, link.url, link.title],
+        )?;
+    }
+    for (position, warning) in doc.warnings.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO warnings VALUES (?1, ?2, ?3, ?4)",
+            params![id, position as i64, warning.kind.name(), warning.message],
+        )?;
+    }
+    for reference in doc.references() {
+        tx.execute(
+            "INSERT INTO refs VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                reference.text,
+                reference.section,
+                reference.span.start as i64,
+                reference.span.end as i64,
+            ],
+        )?;
+    }
+    tx.execute(
+        "INSERT INTO items_fts (rowid, name, title, body) VALUES (?1, ?2, ?3, ?4)",
+        params![id, item.name, doc.title(), doc.raw_content],
+    )?;
+    Ok(())
+}
+
+/// Open a set saved by [`save`].
+///
+/// # Errors
+///
+/// Returns an error if `path` is not a database written by this version.
+pub fn open(path: &Path) -> Result<DocSet, StoreError> {
+    let conn = connect(path)?;
+    let mut set = DocSet::new();
+
+    set.files = conn
+        .prepare("SELECT path FROM files ORDER BY rowid")?
+        .query_map([], |row| row.get::<_, String>(0).map(PathBuf::from))?
+        .collect::<Result<_, _>>()?;
+
+    let mut items = conn.prepare(
+        "SELECT id, name, file, span_start, span_end, line, col, formals, raw_content,
+         description FROM items ORDER BY id",
+    )?;
+    let mut rows = items.query([])?;
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get(0)?;
+        set.items.push(DocumentedItem {
+            name: row.get(1)?,
+            file: row.get::<_, Option<String>>(2)?.map(PathBuf::from),
+            span: Span::new(
+                row.get::<_, i64>(3)? as usize,
+                row.get::<_, i64>(4)? as usize,
+            ),
+            line: row.get::<_, i64>(5)? as usize,
+            column: row.get::<_, i64>(6)? as usize,
+            formals: row
+                .get::<_, String>(7)?
+                .split_terminator('\n')
+                .map(str::to_string)
+                .collect(),
+            doc: DocComment {
+                raw_content: row.get(8)?,
+                description: row.get(9)?,
+                sections: sections(&conn, id)?,
+                link_definitions: link_definitions(&conn, id)?,
+                warnings: warnings(&conn, id)?,
+            },
+        });
+    }
+
+    let mut failures = conn.prepare("SELECT * FROM failures ORDER BY rowid")?;
+    let mut rows = failures.query([])?;
+    while let Some(row) = rows.next()? {
+        let error: String = row.get(4)?;
+        let warning = match (row.get::<_, Option<String>>(5)?, row.get(6)?) {
+            (Some(kind), Some(message)) => Some(ParseWarning {
+                kind: WarningKind::from_name(&kind)
+                    .ok_or_else(|| StoreError::Invalid(format!("unknown warning kind `{kind}`")))?,
+                message,
+            }),
+            _ => None,
+        };
+        set.failures.push(ParseFailure {
+            file: row.get::<_, Option<String>>(0)?.map(PathBuf::from),
+            span: Span::new(
+                row.get::<_, i64>(1)? as usize,
+                row.get::<_, i64>(2)? as usize,
+            ),
+            line: row.get::<_, i64>(3)? as usize,
+            error: error_from_name(&error, warning)
+                .ok_or_else(|| StoreError::Invalid(format!("unknown parse error `{error}`")))?,
+            error_span: Span::new(
+                row.get::<_, i64>(7)? as usize,
+                row.get::<_, i64>(8)? as usize,
+            ),
+        });
+    }
+
+    set.file_warnings = conn
+        .prepare("SELECT file, message FROM file_warnings ORDER BY rowid")?
+        .query_map([], |row| {
+            Ok(FileWarning {
+                file: PathBuf::from(row.get::<_, String>(0)?),
+                message: row.get(1)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(set)
+}
+
+/// Find the items of a saved set matching any word of `query`, best first,
+/// using the database's full-text index.
+///
+/// # Errors
+///
+/// Returns an error if `path` is not a database written by this version.
+pub fn search(path: &Path, query: &str, limit: usize) -> Result<Vec<StoredHit>, StoreError> {
+    let conn = connect(path)?;
+    // Quote every word, so that the query cannot be FTS5 syntax.
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\""))
+        .collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut statement = conn.prepare(
+        "SELECT items.id, items.name, items.file, items.line
+         FROM items_fts JOIN items ON items.id = items_fts.rowid
+         WHERE items_fts MATCH ?1
+         ORDER BY bm25(items_fts, 5.0, 3.0, 1.0), items.id
+         LIMIT ?2",
+    )?;
+    let hits = statement
+        .query_map(params![terms.join(" OR "), limit as i64], |row| {
+            Ok(StoredHit {
+                index: row.get::<_, i64>(0)? as usize,
+                identifier: row.get(1)?,
+                file: row.get::<_, Option<String>>(2)?.map(PathBuf::from),
+                line: row.get::<_, i64>(3)? as usize,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(hits)
+}
+
+/// Open the database at `path`, checking its version.
+fn connect(path: &Path) -> Result<Connection, StoreError> {
+    let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version != SCHEMA_VERSION {
+        return Err(StoreError::Version(version));
+    }
+    Ok(conn)
+}
+
+fn sections(conn: &Connection, item: i64) -> Result<Vec<Section>, StoreError> {
+    let mut statement = conn.prepare_cached(
+        "SELECT heading, content, explicit_id FROM sections WHERE item = ?1 ORDER BY position",
+    )?;
+    let sections = statement
+        .query_map([item], |row| {
+            Ok(Section {
+                heading: row.get(0)?,
+                content: row.get(1)?,
+                explicit_id: row.get(2)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(sections)
+}
+
+fn link_definitions(conn: &Connection, item: i64) -> Result<Vec<LinkDefinition>, StoreError> {
+    let mut statement = conn.prepare_cached(
+        "SELECT label, url, title FROM link_definitions WHERE item = ?1 ORDER BY position",
+    )?;
+    let links = statement
+        .query_map([item], |row| {
+            Ok(LinkDefinition {
+                label: row.get(0)?,
+                url: row.get(1)?,
+                title: row.get(2)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(links)
+}
+
+fn warnings(conn: &Connection, item: i64) -> Result<Vec<ParseWarning>, StoreError> {
+    let mut statement = conn
+        .prepare_cached("SELECT kind, message FROM warnings WHERE item = ?1 ORDER BY position")?;
+    let rows: Vec<(String, String)> = statement
+        .query_map([item], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    rows.into_iter()
+        .map(|(kind, message)| {
+            let kind = WarningKind::from_name(&kind)
+                .ok_or_else(|| StoreError::Invalid(format!("unknown warning kind `{kind}`")))?;
+            Ok(ParseWarning { kind, message })
+        })
+        .collect()
+}
+
+fn path_text(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn error_name(error: &ParseError) -> &'static str {
+    match error {
+        ParseError::NotDocComment => "not-doc-comment",
+        ParseError::UnclosedComment => "unclosed-comment",
+        ParseError::EmptyComment => "empty-comment",
+        ParseError::DeniedWarning(_) => "denied-warning",
+    }
+}
+
+fn denied_warning(error: &ParseError) -> Option<&ParseWarning> {
+    match error {
+        ParseError::DeniedWarning(warning) => Some(warning),
+        _ => None,
+    }
+}
+
+fn error_from_name(name: &str, warning: Option<ParseWarning>) -> Option<ParseError> {
+    Some(match name {
+        "not-doc-comment" => ParseError::NotDocComment,
+        "unclosed-comment" => ParseError::UnclosedComment,
+        "empty-comment" => ParseError::EmptyComment,
+        "denied-warning" => ParseError::DeniedWarning(warning?),
+        _ => return None,
+    })
+}
@@ -0,0 +1,226 @@
+//! Short plain-text summaries, for search results, hover text and lists.
+//!
+//! [`plain_text`] removes the inline Markdown of a paragraph, and
+//! [`truncate`] shortens the result at a word boundary. Both treat inline
+//! code as a single word, so an identifier is never cut in half.
+//! [`DocComment::short_summary`](crate::DocComment::short_summary) combines
+//! them.
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+/// Plain text with the characters that came from inline code marked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlainText {
+    chars: Vec<(char, bool)>,
+}
+
+impl PlainText {
+    fn push(&mut self, c: char, code: bool) {
+        if c.is_whitespace() {
+            // Collapse runs of whitespace, and drop it at the start.
+            if self.chars.last().is_none_or(|&(last, _)| last == ' ') {
+                return;
+            }
+            self.chars.push((' ', code));
+        } else {
+            self.chars.push((c, code));
+        }
+    }
+
+    fn push_str(&mut self, text: &str, code: bool) {
+        text.chars().for_each(|c| self.push(c, code));
+    }
+
+    /// Returns the number of characters.
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Returns `true` if there is no text.
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Returns `true` if the text may be broken at `index`: a space outside
+    /// inline code.
+    fn breaks_at(&self, index: usize) -> bool {
+        self.chars
+            .get(index)
+            .is_some_and(|&(c, code)| c == ' ' && !code)
+    }
+
+    /// Returns the runs of prose and of inline code, in order.
+    #[cfg(feature = "std")]
+    pub(crate) fn segments(&self) -> Vec<(String, bool)> {
+        let mut segments: Vec<(String, bool)> = Vec::new();
+        for &(c, code) in &self.chars {
+            match segments.last_mut() {
+                Some((text, last)) if *last == code => text.push(c),
+                _ => segments.push((c.to_string(), code)),
+            }
+        }
+        segments
+    }
+
+    fn text(&self, end: usize) -> String {
+        self.chars[..end].iter().map(|&(c, _)| c).collect()
+    }
+}
+
+impl core::fmt::Display for PlainText {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.text(self.len()).trim_end())
+    }
+}
+
+/// Remove the inline Markdown of `markdown`: emphasis, code backticks,
+/// links and images, keeping their text, with whitespace collapsed.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::summary::plain_text;
+///
+/// let text = plain_text("Like **`map`**, see [the manual](https://x.org) or <https://y.org>.");
+/// assert_eq!(text.to_string(), "Like map, see the manual or https://y.org.");
+/// assert_eq!(plain_text("keeps snake_case_names").to_string(), "keeps snake_case_names");
+/// ```
+pub fn plain_text(markdown: &str) -> PlainText {
+    let mut out = PlainText::default();
+    push_markdown(&mut out, markdown);
+    out
+}
+
+fn push_markdown(out: &mut PlainText, markdown: &str) {
+    let mut rest = markdown;
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        let prev = out.chars.last().map(|&(c, _)| c);
+        match c {
+            '\\' if after.starts_with(|c: char| c.is_ascii_punctuation()) => {
+                let escaped = after.chars().next().unwrap_or_default();
+                out.push(escaped, false);
+                rest = &after[escaped.len_utf8()..];
+            }
+            '`' => {
+                let ticks = rest.len() - rest.trim_start_matches('`').len();
+                let body = &rest[ticks..];
+                match find_closing_ticks(body, ticks) {
+                    Some(end) => {
+                        out.push_str(body[..end].trim(), true);
+                        rest = &body[end + ticks..];
+                    }
+                    None => {
+                        out.push_str(&rest[..ticks], false);
+                        rest = body;
+                    }
+                }
+            }
+            '!' if after.starts_with('[') => rest = after,
+            '[' => match link(rest) {
+                Some((text, len)) => {
+                    push_markdown(out, text);
+                    rest = &rest[len..];
+                }
+                None => {
+                    out.push(c, false);
+                    rest = after;
+                }
+            },
+            '<' if after.starts_with("http") && after.contains('>') => {
+                let end = after.find('>').unwrap_or(after.len());
+                out.push_str(&after[..end], false);
+                rest = &after[(end + 1).min(after.len())..];
+            }
+            '*' | '~' => rest = after,
+            // Underscores inside words, as in `snake_case`, are not emphasis.
+            '_' if prev.is_none_or(|p| !p.is_alphanumeric())
+                || !after.starts_with(|c: char| c.is_alphanumeric()) =>
+            {
+                rest = after;
+            }
+            _ => {
+                out.push(c, false);
+                rest = after;
+            }
+        }
+    }
+}
+
+/// The offset of the backtick run of length `ticks` closing a code span in
+/// `body`.
+pub(crate) fn find_closing_ticks(body: &str, ticks: usize) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(start) = body[offset..].find('`') {
+        let start = offset + start;
+        let run = body[start..].len() - body[start..].trim_start_matches('`').len();
+        if run == ticks {
+            return Some(start);
+        }
+        offset = start + run;
+    }
+    None
+}
+
+/// The text and length of a link at the start of `rest`: `[text](url)`,
+/// `[text][label]` or `[text]`.
+pub(crate) fn link(rest: &str) -> Option<(&str, usize)> {
+    let close = rest.find(']')?;
+    let text = &rest[1..close];
+    let after = &rest[close + 1..];
+    let len = match after.chars().next() {
+        Some('(') => close + 1 + after.find(')')? + 1,
+        Some('[') => close + 1 + after.find(']')? + 1,
+        _ => close + 1,
+    };
+    Some((text, len))
+}
+
+/// Shorten `text` to at most `max_chars` characters.
+///
+/// Text that fits is returned as it is. Otherwise the first sentence is
+/// used if it fits, or else the text is cut at the last space before the
+/// limit and an ellipsis added. Text is never cut inside inline code; if
+/// not even the first word fits, only the ellipsis is left.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::summary::{plain_text, truncate};
+///
+/// let text = plain_text("Concatenate strings. Joins `lib.strings.concatStrings` with a separator.");
+/// assert_eq!(truncate(&text, 100), text.to_string());
+/// assert_eq!(truncate(&text, 30), "Concatenate strings.");
+/// assert_eq!(truncate(&text, 15), "Concatenate…");
+///
+/// let code = plain_text("Uses `lib.strings.concatStrings` to join.");
+/// assert_eq!(truncate(&code, 20), "Uses…");
+/// ```
+pub fn truncate(text: &PlainText, max_chars: usize) -> String {
+    let len = text.to_string().chars().count();
+    if len <= max_chars {
+        return text.to_string();
+    }
+
+    let sentence_end = (0..len).find(|&i| {
+        let (c, code) = text.chars[i];
+        !code && matches!(c, '.' | '!' | '?') && (i + 1 == len || text.breaks_at(i + 1))
+    });
+    if let Some(end) = sentence_end
+        && end < max_chars
+    {
+        return text.text(end + 1);
+    }
+
+    if max_chars == 0 {
+        return String::new();
+    }
+    let cut = (1..max_chars).rev().find(|&i| text.breaks_at(i));
+    let mut short = cut.map(|end| text.text(end)).unwrap_or_default();
+    while short.ends_with([',', ';', ':', ' ']) {
+        short.pop();
+    }
+    short.push('…');
+    short
+}
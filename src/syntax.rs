@@ -0,0 +1,71 @@
+//! Checking that examples are syntactically valid Nix.
+//!
+//! Enabled by the `rnix` feature.
+//!
+//! [`check_examples`] runs the code of every `nix` example through the
+//! [`rnix`] parser. Unlike [`doctest`](crate::doctest), nothing is
+//! evaluated, so it needs no Nix installation and is fast enough to run on
+//! all of nixpkgs, while still catching most broken examples: unbalanced
+//! brackets, missing semicolons, and pseudo-code such as `...`.
+
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::extract::DocumentedItem;
+use crate::parser::raw_examples;
+use crate::{DocComment, DocSet};
+
+/// Check that the `nix` examples of `doc` parse.
+///
+/// | Code | Severity | Problem |
+/// |------|----------|---------|
+/// | `invalid-nix` | error | the example is not a valid Nix expression |
+///
+/// Only the code before each `=>` marker is checked, as results are often
+/// written the way `nix repl` prints them. Blocks flagged `setup` hold
+/// bindings rather than an expression and are checked as such. The span
+/// covers the example's code block.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+/// use nixdoc::syntax::check_examples;
+///
+/// let doc = DocComment::parse("/**\n  f.\n\n  # Example\n\n  ```nix\n  f { a = 1; }\n  => 1\n  ```\n\n  ```nix\n  f { a = 1 }\n  ```\n*/").unwrap();
+/// let diagnostics = check_examples(&doc);
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].code, "invalid-nix");
+/// assert!(doc.raw_content[diagnostics[0].span.start..].starts_with("```nix\nf { a = 1 }"));
+/// ```
+pub fn check_examples(doc: &DocComment) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (span, example) in raw_examples(&doc.raw_content) {
+        if !example.is_nix() {
+            continue;
+        }
+        let code = if example.has_attribute("setup") {
+            format!("let\n{}\nin null", example.input)
+        } else {
+            example.input
+        };
+        if let Some(error) = rnix::parse(&code).errors().first() {
+            diagnostics.push(Diagnostic::new(
+                "invalid-nix",
+                Severity::Error,
+                format!("example is not valid Nix: {error}"),
+                span,
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// Check the `nix` examples of every item in `set`, see [`check_examples`].
+pub fn check_set(set: &DocSet) -> Vec<(&DocumentedItem, Diagnostic)> {
+    set.iter()
+        .flat_map(|item| {
+            check_examples(&item.doc)
+                .into_iter()
+                .map(move |diagnostic| (item, diagnostic))
+        })
+        .collect()
+}
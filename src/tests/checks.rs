@@ -0,0 +1,86 @@
+use expect_test::expect;
+
+use super::*;
+
+#[test]
+fn nix_string_escapes() {
+    assert_eq!(nix_string("plain"), "\"plain\"");
+    assert_eq!(nix_string("a \"b\" \\ c"), r#""a \"b\" \\ c""#);
+    assert_eq!(nix_string("${x} $y"), r#""\${x} $y""#);
+    assert_eq!(nix_string("two\nlines"), r#""two\nlines""#);
+}
+
+#[test]
+fn flake_checks_snapshot() {
+    let mut set = DocSet::new();
+    set.add_source(
+        r#"{
+  /**
+    Identity.
+
+    # Examples
+
+    ```nix setup
+    one = 1;
+    ```
+
+    ```nix
+    id one
+    => one
+    ```
+
+    ```nix no-run
+    id 2
+    => 2
+    ```
+
+    ```bash
+    echo 3
+    => 3
+    ```
+
+    ```nix
+    id 4
+    ```
+  */
+  id = x: x;
+}"#,
+        Some("lib/trivial.nix".as_ref()),
+    );
+
+    expect![[r#"
+        # Generated by nixdoc from documented examples. Do not edit.
+        { pkgs, lib ? pkgs.lib }:
+        let
+          failures = lib.runTests {
+            "test lib/trivial.nix:2 id example 2" = {
+              expr = (
+                with lib;
+                let
+                  one = 1;
+                in
+                id one
+              );
+              expected = (
+                with lib;
+                let
+                  one = 1;
+                in
+                one
+              );
+            };
+          };
+        in
+        if failures == [ ] then
+          pkgs.runCommand "doc-examples" { } "touch $out"
+        else
+          throw "documentation examples failed:\n${lib.generators.toPretty { } failures}"
+    "#]]
+    .assert_eq(&flake_checks(&set, &CheckOptions::default()));
+}
+
+#[test]
+fn flake_checks_empty_set() {
+    let nix = flake_checks(&DocSet::new(), &CheckOptions::default());
+    assert!(nix.contains("lib.runTests {\n  };"));
+}
@@ -40,34 +40,34 @@ fn normalize_zero_indent() {
 
 #[test]
 fn fence_open_three_backticks() {
-    let (fc, fl, lang) = parse_fence_open("```").unwrap();
+    let (fc, fl, info) = parse_fence_open("```").unwrap();
     assert_eq!(fc, '`');
     assert_eq!(fl, 3);
-    assert_eq!(lang, None);
+    assert_eq!(info.language, None);
 }
 
 #[test]
 fn fence_open_with_language() {
-    let (fc, fl, lang) = parse_fence_open("```nix").unwrap();
+    let (fc, fl, info) = parse_fence_open("```nix").unwrap();
     assert_eq!(fc, '`');
     assert_eq!(fl, 3);
-    assert_eq!(lang, Some("nix".to_string()));
+    assert_eq!(info.language, Some("nix".to_string()));
 }
 
 #[test]
 fn fence_open_four_backticks() {
-    let (fc, fl, lang) = parse_fence_open("````").unwrap();
+    let (fc, fl, info) = parse_fence_open("````").unwrap();
     assert_eq!(fc, '`');
     assert_eq!(fl, 4);
-    assert_eq!(lang, None);
+    assert_eq!(info.language, None);
 }
 
 #[test]
 fn fence_open_tildes() {
-    let (fc, fl, lang) = parse_fence_open("~~~nix").unwrap();
+    let (fc, fl, info) = parse_fence_open("~~~nix").unwrap();
     assert_eq!(fc, '~');
     assert_eq!(fl, 3);
-    assert_eq!(lang, Some("nix".to_string()));
+    assert_eq!(info.language, Some("nix".to_string()));
 }
 
 #[test]
@@ -190,6 +190,32 @@ fn parse_arguments_non_indented_prose_ignored() {
     assert_eq!(args[0].description, "Arg");
 }
 
+#[test]
+fn parse_arguments_table() {
+    let content = "| Name | Type | Default | Description |\n|---|---|:---:|---|\n| `xs` | `[a]` | `[ ]` | The items. |\n| f | a \\| null | | Applied to each item. |";
+    let args = parse_arguments(content);
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[0].name, "xs");
+    assert_eq!(args[0].type_sig.as_deref(), Some("[a]"));
+    assert_eq!(args[0].default.as_deref(), Some("[ ]"));
+    assert_eq!(args[0].description, "The items.");
+    assert_eq!(args[1].name, "f");
+    assert_eq!(args[1].type_sig.as_deref(), Some("a | null"));
+    assert_eq!(args[1].default, None);
+}
+
+#[test]
+fn parse_arguments_table_without_name_column() {
+    let content = "Takes:\n\n| Argument | Meaning |\n|-|-|\n| a | First |\n\n- [b] Second";
+    let args = parse_arguments(content);
+    let names: Vec<_> = args.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ["a", "b"]);
+    assert_eq!(args[0].description, "");
+
+    let content = "| Arg | What |\n|-|-|\n| a | First |";
+    assert_eq!(parse_arguments(content)[0].name, "a");
+}
+
 #[test]
 fn parse_examples_single_no_lang() {
     let content = "```\nfoo 1\n```";
@@ -299,7 +325,7 @@ fn extract_code_block_closing_fence_trailing_spaces() {
 fn parse_sections_does_not_treat_code_hash_as_heading() {
     let content = "Desc.\n\n# Example\n\n```nix\n# This is a Nix comment\nfoo\n```";
     let mut warnings = Vec::new();
-    let (desc, sections) = parse_sections(content, &mut warnings);
+    let (desc, sections) = parse_sections(content, 3, &mut warnings);
 
     assert_eq!(desc, "Desc.");
     assert_eq!(sections.len(), 1);
@@ -315,7 +341,7 @@ fn parse_sections_four_backtick_fence() {
     // sequences must not produce spurious sections.
     let content = "Desc.\n\n# Example\n\n````nix\n# not a heading\n```\ninner\n```\n````";
     let mut warnings = Vec::new();
-    let (_, sections) = parse_sections(content, &mut warnings);
+    let (_, sections) = parse_sections(content, 3, &mut warnings);
 
     assert_eq!(sections.len(), 1);
     assert_eq!(sections[0].heading, "Example");
@@ -327,7 +353,7 @@ fn parse_sections_four_backtick_fence() {
 fn parse_sections_closing_fence_with_trailing_spaces() {
     let content = "Desc.\n\n# Type\n\n```\nfoo :: Int\n```  \n\n# Arguments\n\n- [x] x";
     let mut warnings = Vec::new();
-    let (_, sections) = parse_sections(content, &mut warnings);
+    let (_, sections) = parse_sections(content, 3, &mut warnings);
 
     // Both sections must be parsed; the trailing-spaces closing fence
     // must not leave the parser stuck inside a code block.
@@ -381,6 +407,38 @@ fn inline_type_sig_rejected_empty_after() {
     assert_eq!(extract_inline_type_sig("mergeAttrs ::"), None);
 }
 
+#[test]
+fn inline_type_sig_indented_continuation() {
+    // lib/attrsets.nix, before RFC 145.
+    let content = "Like `mapAttrsRecursive`, but takes an additional predicate.\n\nmapAttrsRecursiveCond ::\n  (AttrSet -> Bool) -> ([String] -> a -> b) -> AttrSet -> AttrSet\n\nExample:";
+    assert_eq!(
+        extract_inline_type_sig(content).as_deref(),
+        Some(
+            "mapAttrsRecursiveCond :: (AttrSet -> Bool) -> ([String] -> a -> b) -> AttrSet -> AttrSet"
+        )
+    );
+}
+
+#[test]
+fn inline_type_sig_backslash_continuation() {
+    // lib/lists.nix, before RFC 145.
+    let content = "Fold a binary function over a list.\nfoldl' :: (b -> a -> b) \\\n-> b -> [a] -> b\nMore prose.";
+    assert_eq!(
+        extract_inline_type_sig(content).as_deref(),
+        Some("foldl' :: (b -> a -> b) -> b -> [a] -> b")
+    );
+}
+
+#[test]
+fn inline_type_sig_continuation_stops_at_next_signature() {
+    let content = "foo ::\n  a -> a\n  bar :: b";
+    assert_eq!(
+        extract_inline_type_sig(content).as_deref(),
+        Some("foo :: a -> a")
+    );
+    assert_eq!(extract_inline_type_sig("foo ::\nbar"), None);
+}
+
 #[test]
 fn inline_type_sig_none_when_absent() {
     assert_eq!(extract_inline_type_sig("Just a plain description."), None);
@@ -394,3 +452,418 @@ fn inline_type_sig_primes_in_name() {
         Some("f' :: a -> a".to_string())
     );
 }
+
+#[test]
+fn parse_examples_no_caption() {
+    let examples = parse_examples("```nix\nfoo 1\n```");
+    assert_eq!(examples[0].title, None);
+}
+
+#[test]
+fn parse_examples_subheading_caption() {
+    let content = "## `lib.id` usage example\n\n```nix\nid 1\n```";
+    let examples = parse_examples(content);
+    assert_eq!(examples.len(), 1);
+    assert_eq!(
+        examples[0].title,
+        Some("`lib.id` usage example".to_string())
+    );
+}
+
+#[test]
+fn parse_examples_bold_caption() {
+    let content =
+        "**Basic usage:**\n\n```nix\nfoo 1\n```\n\n**With overrides**\n\n```nix\nfoo 2\n```";
+    let examples = parse_examples(content);
+    assert_eq!(examples.len(), 2);
+    assert_eq!(examples[0].title, Some("Basic usage".to_string()));
+    assert_eq!(examples[1].title, Some("With overrides".to_string()));
+}
+
+#[test]
+fn parse_examples_nearest_caption_wins() {
+    // Prose between the caption and the block does not reset it, and a later
+    // caption replaces the earlier one.
+    let content =
+        "## First\n\nSome prose.\n\n```\na\n```\n\n```\nb\n```\n\n## Second ##\n\n```\nc\n```";
+    let examples = parse_examples(content);
+    let titles: Vec<_> = examples.iter().map(|e| e.title.as_deref()).collect();
+    assert_eq!(titles, vec![Some("First"), Some("First"), Some("Second")]);
+}
+
+#[test]
+fn parse_examples_caption_inside_code_ignored() {
+    let content = "```\n## not a caption\n**nor this**\n```";
+    let examples = parse_examples(content);
+    assert_eq!(examples[0].title, None);
+}
+
+#[test]
+fn caption_rejects_non_captions() {
+    assert_eq!(parse_caption("# Level one"), None);
+    assert_eq!(parse_caption("##NoSpace"), None);
+    assert_eq!(parse_caption("**bold** then prose"), None);
+    assert_eq!(parse_caption("****"), None);
+    assert_eq!(parse_caption("plain text"), None);
+}
+
+#[test]
+fn parse_examples_multiline_code_has_no_blank_lines() {
+    let examples = parse_examples("```nix\nf 1\n=> 1\n```");
+    assert_eq!(examples[0].code, "f 1\n=> 1\n");
+}
+
+#[test]
+fn code_blocks_end_with_one_newline_per_line() {
+    // Each line of a block ends with a single newline, with no blank line
+    // inserted before it, whichever way the block is read.
+    let content = "```\na\nb\n\nc\n```";
+    assert_eq!(
+        extract_first_code_block(content).as_deref(),
+        Some("a\nb\n\nc\n")
+    );
+    assert_eq!(parse_examples(content)[0].code, "a\nb\n\nc\n");
+    assert_eq!(code_blocks(content)[0].code, "a\nb\n\nc\n");
+
+    let unclosed = "```\na\nb";
+    assert_eq!(
+        extract_first_code_block(unclosed).as_deref(),
+        Some("a\nb\n")
+    );
+    assert_eq!(parse_examples(unclosed)[0].code, "a\nb\n");
+}
+
+#[test]
+fn fence_lines_respect_fence_length() {
+    let content = "a\n````md\n```nix\nx\n```\n````\nb";
+    let roles: Vec<(usize, &str, LineRole)> = fence_lines(content).collect();
+    assert_eq!(
+        roles,
+        [
+            (0, "a", LineRole::Prose),
+            (2, "````md", LineRole::OpeningFence),
+            (9, "```nix", LineRole::Code),
+            (16, "x", LineRole::Code),
+            (18, "```", LineRole::Code),
+            (22, "````", LineRole::ClosingFence),
+            (27, "b", LineRole::Prose),
+        ]
+    );
+    let roles: Vec<LineRole> = fence_lines("~~~\n```\n").map(|(_, _, role)| role).collect();
+    assert_eq!(roles, [LineRole::OpeningFence, LineRole::Code]);
+}
+
+#[test]
+fn split_expected_single_line() {
+    let examples = parse_examples("```nix\nmap (x: x + 1) [ 1 2 ]\n=> [ 2 3 ]\n```");
+    assert_eq!(examples[0].input, "map (x: x + 1) [ 1 2 ]");
+    assert_eq!(examples[0].expected.as_deref(), Some("[ 2 3 ]"));
+}
+
+#[test]
+fn split_expected_multiline_result() {
+    let pairs = split_expected("f {\n  a = 1;\n}\n=> {\n  a = 1;\n}\n");
+    assert_eq!(
+        pairs,
+        [(
+            "f {\n  a = 1;\n}".to_string(),
+            Some("{\n  a = 1;\n}".to_string())
+        )]
+    );
+}
+
+#[test]
+fn split_expected_indented_marker() {
+    let pairs = split_expected("f 1\n  => 2\n");
+    assert_eq!(pairs, [("f 1".to_string(), Some("2".to_string()))]);
+}
+
+#[test]
+fn split_expected_without_marker() {
+    let pairs = split_expected("let x = 1; in x\n");
+    assert_eq!(pairs, [("let x = 1; in x".to_string(), None)]);
+}
+
+#[test]
+fn split_expected_arrow_inside_line_is_not_a_marker() {
+    let pairs = split_expected("\"a => b\"\n");
+    assert_eq!(pairs, [("\"a => b\"".to_string(), None)]);
+}
+
+#[test]
+fn split_expected_one_pair_per_marker() {
+    let examples = parse_examples("```nix\ntoUpper \"a\"\n=> \"A\"\ntoUpper \"b\"\n=> \"B\"\n```");
+    assert_eq!(examples.len(), 2);
+    assert_eq!(examples[0].input, "toUpper \"a\"");
+    assert_eq!(examples[0].expected.as_deref(), Some("\"A\""));
+    assert_eq!(examples[1].input, "toUpper \"b\"");
+    assert_eq!(examples[1].expected.as_deref(), Some("\"B\""));
+}
+
+#[test]
+fn split_expected_carries_bindings() {
+    // `lib.lists.foldr` in nixpkgs.
+    let code = r#"concat = foldr (a: b: a + b) "z"
+concat [ "a" "b" "c" ]
+=> "abcz"
+# different types
+strange = foldr (int: str: toString (int + 1) + str) "a"
+strange [ 1 2 3 4 ]
+=> "2345a"
+"#;
+    let pairs = split_expected(code);
+    assert_eq!(
+        pairs,
+        [
+            (
+                "let\n  concat = foldr (a: b: a + b) \"z\";\nin\nconcat [ \"a\" \"b\" \"c\" ]"
+                    .to_string(),
+                Some("\"abcz\"".to_string())
+            ),
+            (
+                "let\n  concat = foldr (a: b: a + b) \"z\";\n  strange = foldr (int: str: toString (int + 1) + str) \"a\";\nin\nstrange [ 1 2 3 4 ]"
+                    .to_string(),
+                Some("\"2345a\"".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn split_expected_results_end_with_their_brackets() {
+    let pairs =
+        split_expected("f { a = 1; }\n=> {\n  a = 1;\n}\ng ''\n  x\n''\n=> ''\n  }\n''\nh [ ]\n");
+    assert_eq!(
+        pairs,
+        [
+            (
+                "f { a = 1; }".to_string(),
+                Some("{\n  a = 1;\n}".to_string())
+            ),
+            ("g ''\n  x\n''".to_string(), Some("''\n  }\n''".to_string())),
+            ("h [ ]".to_string(), None),
+        ]
+    );
+    // A `let` expression's bindings are indented, so they are not carried.
+    let pairs = split_expected("let\n  x = 1;\nin\nx\n=> 1\n");
+    assert_eq!(pairs[0].0, "let\n  x = 1;\nin\nx");
+}
+
+#[test]
+fn parse_examples_repl_single_prompt() {
+    let examples = parse_examples("```nix\nnix-repl> lib.toList 3\n[ 3 ]\n```");
+    assert_eq!(examples.len(), 1);
+    assert_eq!(examples[0].input, "lib.toList 3");
+    assert_eq!(examples[0].expected.as_deref(), Some("[ 3 ]"));
+    assert!(examples[0].code.starts_with("nix-repl>"));
+}
+
+#[test]
+fn parse_examples_repl_multiple_prompts() {
+    let content = "## Session\n\n```\nnix-repl> a\n1\n\nnix-repl> b\n{\n  x = 2;\n}\n```";
+    let examples = parse_examples(content);
+    assert_eq!(examples.len(), 2);
+    assert_eq!(examples[0].input, "a");
+    assert_eq!(examples[0].expected.as_deref(), Some("1"));
+    assert_eq!(examples[1].input, "b");
+    assert_eq!(examples[1].expected.as_deref(), Some("{\n  x = 2;\n}"));
+    assert_eq!(examples[1].title.as_deref(), Some("Session"));
+}
+
+#[test]
+fn split_repl_bindings_become_let() {
+    let pairs = split_repl("nix-repl> x = 3\nnix-repl> y = x + 1;\nnix-repl> x + y\n7\n");
+    assert_eq!(
+        pairs,
+        vec![(
+            "let\n  x = 3;\n  y = x + 1;\nin\nx + y".to_string(),
+            Some("7".to_string())
+        )]
+    );
+}
+
+#[test]
+fn split_repl_commands() {
+    let pairs = split_repl(
+        "nix-repl> :l <nixpkgs>\nAdded 1 variables.\nnix-repl> :p { a = 1; }\n{ a = 1; }\n",
+    );
+    assert_eq!(
+        pairs,
+        vec![("{ a = 1; }".to_string(), Some("{ a = 1; }".to_string()))]
+    );
+}
+
+#[test]
+fn split_repl_comparison_is_not_binding() {
+    assert!(is_repl_binding("x = 1"));
+    assert!(!is_repl_binding("x == 1"));
+    assert!(!is_repl_binding("{ a = 1; }"));
+    assert!(!is_repl_binding("f x = 1"));
+}
+
+#[test]
+fn to_fenced_converts_repl_style() {
+    let examples = parse_examples("```nix\nnix-repl> lib.id 1\n1\n```");
+    assert_eq!(examples[0].to_fenced(), "```nix\nlib.id 1\n=> 1\n```\n");
+}
+
+#[test]
+fn to_fenced_lengthens_fence_around_backticks() {
+    let examples = parse_examples("````md\n```\ncode\n```\n````");
+    assert_eq!(examples[0].to_fenced(), "````md\n```\ncode\n```\n````\n");
+}
+
+#[test]
+fn info_attributes_flags_and_values() {
+    let attrs = parse_info_attributes(r#"nix title="Basic usage" expect-error mode=strict"#);
+    assert_eq!(attrs.len(), 3);
+    assert_eq!(attrs["title"], "Basic usage");
+    assert_eq!(attrs["expect-error"], "");
+    assert_eq!(attrs["mode"], "strict");
+}
+
+#[test]
+fn info_attributes_quotes_and_escapes() {
+    let attrs = parse_info_attributes(r#"nix a='single quoted' b="say \"hi\"""#);
+    assert_eq!(attrs["a"], "single quoted");
+    assert_eq!(attrs["b"], "say \"hi\"");
+}
+
+#[test]
+fn info_attributes_without_language() {
+    let attrs = parse_info_attributes(r#"title="x" no-run"#);
+    assert_eq!(attrs["title"], "x");
+    assert!(attrs.contains_key("no-run"));
+    assert_eq!(
+        parse_fence_open(r#"```title="x""#).unwrap().2.language,
+        None
+    );
+}
+
+#[test]
+fn fence_open_keeps_whole_info_string() {
+    let (_, _, info) = parse_fence_open(r#"```  nix title="Basic usage" no-run  "#).unwrap();
+    assert_eq!(info.info, r#"nix title="Basic usage" no-run"#);
+    assert_eq!(info.attributes["title"], "Basic usage");
+    assert!(info.attributes.contains_key("no-run"));
+
+    let examples = parse_examples("```nix {.example}\nf 1\n```\n\n```\ng\n```");
+    assert_eq!(examples[0].info.as_deref(), Some("nix {.example}"));
+    assert_eq!(examples[1].info, None);
+}
+
+#[test]
+fn info_attributes_empty() {
+    assert!(parse_info_attributes("").is_empty());
+    assert!(parse_info_attributes("nix").is_empty());
+}
+
+#[test]
+fn parse_examples_title_attribute_beats_caption() {
+    let content = "## Caption\n\n```nix title=\"From fence\" no-run\nf 1\n```";
+    let examples = parse_examples(content);
+    assert_eq!(examples[0].language.as_deref(), Some("nix"));
+    assert_eq!(examples[0].title.as_deref(), Some("From fence"));
+    assert!(examples[0].has_attribute("no-run"));
+}
+
+#[test]
+fn to_fenced_keeps_attributes() {
+    let examples = parse_examples("```nix title=\"Basic usage\" no-run\nf 1\n=> 1\n```");
+    assert_eq!(
+        examples[0].to_fenced(),
+        "```nix no-run title=\"Basic usage\"\nf 1\n=> 1\n```\n"
+    );
+}
+
+#[test]
+fn parse_examples_setup_shared_within_caption() {
+    let content = "## One\n\n```nix setup\nxs = [ 1 2 ];\n```\n\n```nix\nlength xs\n=> 2\n```\n\n```nix\nhead xs\n=> 1\n```\n\n## Two\n\n```nix\nxs\n```";
+    let examples = parse_examples(content);
+    assert_eq!(examples.len(), 4);
+    assert_eq!(examples[0].setup, None);
+    assert_eq!(examples[1].setup.as_deref(), Some("xs = [ 1 2 ];"));
+    assert_eq!(examples[2].setup.as_deref(), Some("xs = [ 1 2 ];"));
+    // A new caption starts a new group.
+    assert_eq!(examples[3].setup, None);
+}
+
+#[test]
+fn parse_examples_setup_blocks_accumulate() {
+    let content = "```nix setup\na = 1;\n```\n```nix setup\nb = a + 1;\n```\n```nix\nb\n=> 2\n```";
+    let examples = parse_examples(content);
+    assert_eq!(examples[1].setup.as_deref(), Some("a = 1;"));
+    assert_eq!(examples[2].setup.as_deref(), Some("a = 1;\nb = a + 1;"));
+    assert_eq!(
+        examples[2].input_with_setup(),
+        "let\n  a = 1;\n  b = a + 1;\nin\nb"
+    );
+}
+
+#[test]
+fn parse_examples_standalone_opts_out_of_setup() {
+    let content = "```nix setup\na = 1;\n```\n```nix standalone\n2\n=> 2\n```";
+    let examples = parse_examples(content);
+    assert_eq!(examples[1].setup, None);
+    assert_eq!(examples[1].input_with_setup(), "2");
+}
+
+#[test]
+fn explicit_ids() {
+    assert_eq!(
+        split_explicit_id("Example {#ex-foo}"),
+        ("Example", Some("ex-foo"))
+    );
+    assert_eq!(split_explicit_id("{#only}"), ("", Some("only")));
+    assert_eq!(split_explicit_id("Set {#a b}"), ("Set {#a b}", None));
+    assert_eq!(split_explicit_id("Set{#glued}"), ("Set{#glued}", None));
+    assert_eq!(split_explicit_id("Set {#}"), ("Set {#}", None));
+    assert_eq!(split_explicit_id("Plain"), ("Plain", None));
+}
+
+#[test]
+fn example_ids_from_captions_and_attributes() {
+    let examples = parse_examples(
+        "## Basic {#ex-basic}\n\n```nix\n1\n```\n\n```nix id=ex-attr title=\"Titled\"\n2\n```\n",
+    );
+    assert_eq!(examples[0].title.as_deref(), Some("Basic"));
+    assert_eq!(examples[0].id.as_deref(), Some("ex-basic"));
+    assert_eq!(examples[1].title.as_deref(), Some("Titled"));
+    assert_eq!(examples[1].id.as_deref(), Some("ex-attr"));
+}
+
+#[test]
+fn fence_indented_code_blocks() {
+    let content = "Intro.\n\n    f 1\n\n    => 1\n\nAfter.";
+    assert_eq!(
+        fence_indented_code(content),
+        "Intro.\n\n```\nf 1\n\n=> 1\n```\n\nAfter."
+    );
+    // A paragraph cannot be interrupted, and fenced code is left alone.
+    let content = "Text\n    more text\n\n```\n\n    code\n```";
+    assert_eq!(fence_indented_code(content), content);
+}
+
+#[test]
+fn fence_indented_code_skips_list_items() {
+    let content = "- [xs] The list.\n\n    Continued.\n\nProse.\n\n\tcode";
+    assert_eq!(
+        fence_indented_code(content),
+        "- [xs] The list.\n\n    Continued.\n\nProse.\n\n```\ncode\n```"
+    );
+}
+
+#[test]
+fn parse_sections_indented_headings() {
+    let mut warnings = Vec::new();
+    let content = "Desc.\n\n   # Type\n\n```\n # not a heading\n```\n\n    # Code";
+    let (_, sections) = parse_sections(content, 3, &mut warnings);
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].heading, "Type");
+    assert!(sections[0].content.ends_with("    # Code"));
+
+    let (desc, sections) = parse_sections(content, 0, &mut warnings);
+    assert!(sections.is_empty());
+    assert!(desc.contains("# Type"));
+    assert_eq!(section_headings(content), [(7, "Type".to_string())]);
+}
@@ -0,0 +1,138 @@
+//! Versions, as found in `# Since` sections.
+//!
+//! Nix libraries name versions in several ways: semantic versions such as
+//! `1.2.3` or `v2.0.0-rc.1`, and NixOS releases such as `24.05`. [`Version`]
+//! accepts all of them and orders them numerically.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+/// A version number with up to three numeric components and an optional
+/// pre-release suffix.
+///
+/// Missing components count as zero when comparing, so `24.05` equals
+/// `24.5.0`. The text is kept as written for display.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::Version;
+///
+/// let nixos = Version::parse("24.05").unwrap();
+/// assert_eq!((nixos.major, nixos.minor, nixos.patch), (24, 5, 0));
+/// assert_eq!(nixos.to_string(), "24.05");
+///
+/// assert!(Version::parse("v2.0.0-rc.1").unwrap() < Version::parse("2.0.0").unwrap());
+/// assert!(Version::parse("23.11").unwrap() < nixos);
+/// assert_eq!(Version::parse("unstable"), None);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    /// The first component.
+    pub major: u64,
+    /// The second component, zero if absent.
+    pub minor: u64,
+    /// The third component, zero if absent.
+    pub patch: u64,
+    /// The text after a `-`, such as `rc.1`.
+    pub pre: Option<String>,
+    text: String,
+}
+
+impl Version {
+    /// Parse a version such as `1.2.3`, `v1.2`, `24.05` or `2.0.0-beta`.
+    ///
+    /// Returns `None` unless the text is one to three dot-separated numbers,
+    /// optionally preceded by `v` and followed by `-` and a suffix.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let unprefixed = text
+            .strip_prefix(['v', 'V'])
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(text);
+        let (numbers, pre) = match unprefixed.split_once('-') {
+            Some((numbers, pre)) if !pre.is_empty() => (numbers, Some(pre.to_string())),
+            Some(_) => return None,
+            None => (unprefixed, None),
+        };
+
+        let mut components = [0u64; 3];
+        for (i, part) in numbers.split('.').enumerate() {
+            if i == 3 || part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            components[i] = part.parse().ok()?;
+        }
+
+        Some(Self {
+            major: components[0],
+            minor: components[1],
+            patch: components[2],
+            pre,
+            text: text.to_string(),
+        })
+    }
+
+    /// Returns the version as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    fn key(&self) -> (u64, u64, u64) {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key() && self.pre == other.pre
+    }
+}
+
+impl Eq for Version {}
+
+impl Hash for Version {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+        self.pre.hash(state);
+    }
+}
+
+/// Orders numerically; a pre-release comes before the release it precedes.
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key()
+            .cmp(&other.key())
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Formats the version as it was written.
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Returns the first word of `text` that is a version, ignoring surrounding
+/// punctuation, as in `Nixpkgs 24.05.` or `(v1.2)`.
+pub(crate) fn find(text: &str) -> Option<Version> {
+    text.split_whitespace()
+        .find_map(|word| Version::parse(word.trim_matches(|c: char| !c.is_alphanumeric())))
+}
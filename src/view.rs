@@ -0,0 +1,95 @@
+//! Everything a renderer usually needs from a comment, computed once.
+
+use crate::{Argument, DocComment, Example, Provenance};
+
+/// The derived fields of a [`DocComment`], as returned by
+/// [`DocComment::view`].
+///
+/// Most consumers show the same handful of things: the title and summary,
+/// the type, the arguments and examples, and any notes. A view computes
+/// them in one go and owns the results, so it can be stored, serialized
+/// with the `serde` feature, or handed across a language boundary as one
+/// value.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocComment;
+///
+/// let doc = DocComment::parse(
+///     "/**\n  Map a list.\n\n  # Type\n\n  ```\n  map :: (a -> b) -> [a] -> [b]\n  ```\n\n  # Arguments\n\n  - [f] The function\n  - [xs] The list\n\n  # See Also\n\n  - `lib.imap0`\n*/",
+/// )
+/// .unwrap();
+/// let view = doc.view();
+/// assert_eq!(view.title.as_deref(), Some("Map a list."));
+/// assert_eq!(view.type_sig.as_deref(), Some("map :: (a -> b) -> [a] -> [b]\n"));
+/// assert_eq!(view.arguments.len(), 2);
+/// assert_eq!(view.see_also, ["lib.imap0"]);
+/// assert_eq!(view.deprecation, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocView {
+    /// See [`DocComment::title`].
+    pub title: Option<String>,
+    /// See [`DocComment::summary`].
+    pub summary: Option<String>,
+    /// See [`DocComment::type_sig`].
+    pub type_sig: Option<String>,
+    /// See [`DocComment::arguments`].
+    pub arguments: Vec<Argument>,
+    /// See [`DocComment::examples`].
+    pub examples: Vec<Example>,
+    /// See [`DocComment::notes`].
+    pub notes: Vec<String>,
+    /// The content of the warning sections, see
+    /// [`DocComment::warnings_content`]. Parse warnings are not included.
+    pub warnings: Vec<String>,
+    /// See [`DocComment::deprecation_notice`].
+    pub deprecation: Option<String>,
+    /// The identifiers the comment refers to, see
+    /// [`DocComment::references`], without repeats.
+    pub see_also: Vec<String>,
+    /// Where the comment came from. Set by
+    /// [`DocumentedItem::view`](crate::extract::DocumentedItem::view); a
+    /// view of a bare comment has none.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub provenance: Option<Provenance>,
+}
+
+impl DocView {
+    /// Compute the view of `doc`.
+    pub fn new(doc: &DocComment) -> Self {
+        let mut see_also: Vec<String> = Vec::new();
+        for reference in doc.references() {
+            if !see_also.contains(&reference.text) {
+                see_also.push(reference.text);
+            }
+        }
+        Self {
+            title: doc.title().map(str::to_string),
+            summary: doc.summary(),
+            type_sig: doc.type_sig(),
+            arguments: doc.arguments(),
+            examples: doc.examples(),
+            notes: doc.notes().into_iter().map(str::to_string).collect(),
+            warnings: doc
+                .warnings_content()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            deprecation: doc.deprecation_notice().map(str::to_string),
+            see_also,
+            provenance: None,
+        }
+    }
+}
+
+impl From<&DocComment> for DocView {
+    fn from(doc: &DocComment) -> Self {
+        Self::new(doc)
+    }
+}
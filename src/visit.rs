@@ -0,0 +1,85 @@
+//! Walking the parts of a doc comment.
+//!
+//! Lints, renderers and statistics often need to look at every section,
+//! argument and example of a comment. Instead of each re-parsing sections,
+//! they implement [`DocVisitor`] and call [`DocComment::walk`], which visits
+//! everything once, in document order.
+
+use crate::error::ParseWarning;
+use crate::links::LinkDefinition;
+use crate::section::{Argument, Example, Section, SectionKind};
+use crate::{DocComment, parser};
+
+/// Callbacks for [`DocComment::walk`].
+///
+/// Every method does nothing by default, so implementors only write the
+/// ones they need.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::visit::DocVisitor;
+/// use nixdoc::{Argument, DocComment, Section};
+///
+/// #[derive(Default)]
+/// struct Undescribed(Vec<String>);
+///
+/// impl DocVisitor for Undescribed {
+///     fn visit_argument(&mut self, _section: &Section, argument: &Argument) {
+///         if argument.description.is_empty() {
+///             self.0.push(argument.name.clone());
+///         }
+///     }
+/// }
+///
+/// let input = "/**\n  f.\n\n  # Arguments\n\n  - [a] First\n  - [b]\n*/";
+/// let mut visitor = Undescribed::default();
+/// DocComment::parse(input).unwrap().walk(&mut visitor);
+/// assert_eq!(visitor.0, ["b"]);
+/// ```
+pub trait DocVisitor {
+    /// Called first, with the text before the first section.
+    fn visit_description(&mut self, _description: &str) {}
+
+    /// Called for each section, before its arguments or examples.
+    fn visit_section(&mut self, _section: &Section) {}
+
+    /// Called for each argument of an `# Arguments` section.
+    fn visit_argument(&mut self, _section: &Section, _argument: &Argument) {}
+
+    /// Called for each example of an `# Example` or `# Examples` section.
+    fn visit_example(&mut self, _section: &Section, _example: &Example) {}
+
+    /// Called for each link reference definition, after the sections.
+    fn visit_link_definition(&mut self, _definition: &LinkDefinition) {}
+
+    /// Called for each parse warning, last.
+    fn visit_warning(&mut self, _warning: &ParseWarning) {}
+}
+
+/// Visit the parts of `doc` in document order, see [`DocComment::walk`].
+pub(crate) fn walk(doc: &DocComment, visitor: &mut (impl DocVisitor + ?Sized)) {
+    visitor.visit_description(&doc.description);
+    for section in &doc.sections {
+        visitor.visit_section(section);
+        match section.kind() {
+            SectionKind::Arguments => {
+                for argument in parser::parse_arguments(&section.content) {
+                    visitor.visit_argument(section, &argument);
+                }
+            }
+            SectionKind::Example | SectionKind::Examples => {
+                for example in parser::parse_examples(&section.content) {
+                    visitor.visit_example(section, &example);
+                }
+            }
+            _ => {}
+        }
+    }
+    for definition in &doc.link_definitions {
+        visitor.visit_link_definition(definition);
+    }
+    for warning in &doc.warnings {
+        visitor.visit_warning(warning);
+    }
+}
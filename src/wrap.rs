@@ -0,0 +1,231 @@
+//! Re-wrapping Markdown prose to a fixed width.
+//!
+//! [`wrap`] reflows the paragraphs, list items and block quotes of a
+//! Markdown text, and leaves headings, tables, fenced and indented code
+//! alone. [`fill`] wraps a single paragraph. Lines are only broken between
+//! words: inline code spans and links are kept on one line even when they
+//! contain spaces, so they still render, and a word longer than the width
+//! gets a line of its own.
+//!
+//! The [formatter](crate::format) reflows comments with it when given a
+//! [`Style::width`](crate::format::Style::width).
+
+use crate::parser::{LineRole, fence_lines};
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::summary::{find_closing_ticks, link};
+
+/// Wrap one paragraph of Markdown to lines of at most `width` characters.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::wrap::fill;
+///
+/// assert_eq!(
+///     fill("Calls `lib.foldl' op nul` on the\n[list of values](#values).", 20),
+///     ["Calls", "`lib.foldl' op nul`", "on the", "[list of values](#values)."]
+/// );
+/// ```
+pub fn fill(text: &str, width: usize) -> Vec<String> {
+    fill_words(&words(text), width)
+}
+
+/// Reflow the prose of `markdown` to lines of at most `width` characters.
+///
+/// Paragraphs are joined and re-broken, list items and block quotes keep
+/// their markers with continuation lines indented under the text, and
+/// blank lines are kept. A line ending in a backslash hard break ends its
+/// paragraph there. Headings, tables, HTML, and fenced or indented code are
+/// copied as they are.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::wrap::wrap;
+///
+/// let text = "A long\nparagraph of text.\n\n- an item that wraps\n\n```nix\nnot wrapped at all\n```";
+/// assert_eq!(
+///     wrap(text, 12),
+///     "A long\nparagraph of\ntext.\n\n- an item\n  that wraps\n\n```nix\nnot wrapped at all\n```"
+/// );
+/// ```
+pub fn wrap(markdown: &str, width: usize) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut block: Option<Block> = None;
+
+    for (_, line, role) in fence_lines(markdown) {
+        match role {
+            LineRole::Prose => {}
+            LineRole::OpeningFence => {
+                flush(&mut block, width, &mut out);
+                out.push(line.to_string());
+                continue;
+            }
+            LineRole::Code | LineRole::ClosingFence => {
+                out.push(line.to_string());
+                continue;
+            }
+        }
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+
+        if trimmed.is_empty() {
+            flush(&mut block, width, &mut out);
+            out.push(String::new());
+        } else if let Some((marker, text)) = list_marker(trimmed) {
+            flush(&mut block, width, &mut out);
+            block = Some(Block::new(
+                format!("{indent}{marker}"),
+                format!("{indent}{}", " ".repeat(marker.chars().count())),
+                text,
+            ));
+        } else if let Some(text) = trimmed.strip_prefix('>') {
+            let prefix = format!("{indent}> ");
+            match &mut block {
+                Some(quote) if quote.first == prefix => quote.push(text),
+                _ => {
+                    flush(&mut block, width, &mut out);
+                    block = Some(Block::new(prefix.clone(), prefix, text));
+                }
+            }
+        } else if is_verbatim(trimmed) || (block.is_none() && indent.len() >= 4) {
+            flush(&mut block, width, &mut out);
+            out.push(line.to_string());
+        } else if let Some(paragraph) = &mut block {
+            paragraph.push(trimmed);
+        } else {
+            block = Some(Block::new(indent.to_string(), indent.to_string(), trimmed));
+        }
+        // A backslash hard line break ends the line where it is.
+        if line.ends_with('\\') {
+            flush(&mut block, width, &mut out);
+        }
+    }
+    flush(&mut block, width, &mut out);
+
+    let mut wrapped = out.join("\n");
+    if markdown.ends_with('\n') {
+        wrapped.push('\n');
+    }
+    wrapped
+}
+
+/// A paragraph being collected: the prefix of its first line and of the
+/// rest, and its text.
+struct Block {
+    first: String,
+    rest: String,
+    text: String,
+}
+
+impl Block {
+    fn new(first: String, rest: String, text: &str) -> Self {
+        Self {
+            first,
+            rest,
+            text: text.trim().to_string(),
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.text.push(' ');
+        self.text.push_str(text.trim());
+    }
+}
+
+fn flush(block: &mut Option<Block>, width: usize, out: &mut Vec<String>) {
+    let Some(block) = block.take() else {
+        return;
+    };
+    let width = width.saturating_sub(block.rest.chars().count()).max(1);
+    for (i, line) in fill(&block.text, width).into_iter().enumerate() {
+        let prefix = if i == 0 { &block.first } else { &block.rest };
+        out.push(format!("{prefix}{line}"));
+    }
+}
+
+/// Returns the marker, with its following space, and the text of a list
+/// item or definition.
+fn list_marker(line: &str) -> Option<(&str, &str)> {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let len = match line[digits..].chars().next()? {
+        '-' | '*' | '+' | ':' if digits == 0 => 1,
+        '.' | ')' if (1..10).contains(&digits) => digits + 1,
+        _ => return None,
+    };
+    let rest = &line[len..];
+    if !rest.starts_with(' ') || rest.trim().is_empty() {
+        return None;
+    }
+    Some((&line[..len + 1], &rest[1..]))
+}
+
+/// Returns `true` for lines copied as they are: headings, table rows, HTML
+/// and thematic breaks.
+fn is_verbatim(line: &str) -> bool {
+    line.starts_with('#')
+        || line.starts_with('|')
+        || (line.starts_with('<') && !line.starts_with("<http"))
+        || (line.len() >= 3 && line.chars().all(|c| matches!(c, '-' | '*' | '_' | ' ')))
+}
+
+/// Split `text` into words at whitespace outside code spans and links.
+/// Whitespace inside them is collapsed to single spaces.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            if !current.is_empty() {
+                words.push(core::mem::take(&mut current));
+            }
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let len = match c {
+            '`' => {
+                let ticks = rest.len() - rest.trim_start_matches('`').len();
+                find_closing_ticks(&rest[ticks..], ticks).map_or(ticks, |end| 2 * ticks + end)
+            }
+            '[' => link(rest).map_or(1, |(_, len)| len),
+            _ => c.len_utf8(),
+        };
+        for (i, part) in rest[..len].split_whitespace().enumerate() {
+            if i > 0 {
+                current.push(' ');
+            }
+            current.push_str(part);
+        }
+        rest = &rest[len..];
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Fill lines of at most `width` characters with `words`, greedily.
+fn fill_words(words: &[String], width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+    for word in words {
+        let word_width = word.chars().count();
+        if !line.is_empty() && line_width + 1 + word_width > width {
+            lines.push(core::mem::take(&mut line));
+            line_width = 0;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(word);
+        line_width += word_width;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
@@ -0,0 +1,463 @@
+//! Cross-references between documented items.
+//!
+//! Doc comments mention other identifiers in two ways: inline, as
+//! `` [`lib.attrsets.mapAttrs`] ``, and in a `# See Also` section, where any
+//! code span naming an identifier counts. [`XrefGraph`] collects these
+//! mentions across a [`DocSet`] and resolves them against the names the set
+//! documents.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::parser::{LineRole, fence_lines, section_at, section_headings};
+use crate::{DocComment, DocSet, Span};
+
+/// A directed graph from each documented identifier to the identifiers its
+/// comment refers to, built by [`DocSet::xref_graph`].
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::DocSet;
+///
+/// let mut set = DocSet::new();
+/// set.add_source(
+///     "{\n  /** Like [`lib.map`]. */\n  imap = f: xs: xs;\n  /** Map. */\n  map = f: xs: xs;\n}",
+///     None,
+/// );
+/// let graph = set.xref_graph();
+/// assert_eq!(graph.references("imap").collect::<Vec<_>>(), ["map"]);
+/// assert_eq!(graph.referenced_by("map").collect::<Vec<_>>(), ["imap"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XrefGraph {
+    /// Every documented identifier with the documented identifiers it refers
+    /// to. Identifiers without references map to an empty set.
+    pub edges: BTreeMap<String, BTreeSet<String>>,
+    /// References that do not resolve to a documented identifier, keyed by
+    /// the identifier whose comment contains them.
+    pub dangling: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl XrefGraph {
+    /// Build the graph of every named item in `set`.
+    ///
+    /// Mentions are resolved against the set's names as described in
+    /// [`SymbolTable::resolve`]. Unnamed items and references of an item to
+    /// itself are ignored.
+    pub fn build(set: &DocSet) -> Self {
+        let symbols = SymbolTable::from_set(set);
+        let mut graph = Self::default();
+
+        for item in set {
+            let Some(name) = &item.name else {
+                continue;
+            };
+            let edges = graph.edges.entry(name.clone()).or_default();
+            for reference in references(&item.doc) {
+                match symbols.resolve(&reference.text) {
+                    Some(target) if target == name => {}
+                    Some(target) => {
+                        edges.insert(target.to_string());
+                    }
+                    None => {
+                        graph
+                            .dangling
+                            .entry(name.clone())
+                            .or_default()
+                            .insert(reference.text);
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    /// Returns the identifiers that `identifier`'s comment refers to.
+    pub fn references(&self, identifier: &str) -> impl Iterator<Item = &str> {
+        self.edges
+            .get(identifier)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Returns the identifiers whose comments refer to `identifier`.
+    pub fn referenced_by<'a>(&'a self, identifier: &'a str) -> impl Iterator<Item = &'a str> {
+        self.edges
+            .iter()
+            .filter(move |(_, targets)| targets.contains(identifier))
+            .map(|(source, _)| source.as_str())
+    }
+
+    /// Returns the documented identifiers that no other comment refers to.
+    pub fn orphans(&self) -> Vec<&str> {
+        let referenced: BTreeSet<&str> =
+            self.edges.values().flatten().map(String::as_str).collect();
+        self.edges
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !referenced.contains(name))
+            .collect()
+    }
+
+    /// Returns each group of identifiers that refer to one another in a
+    /// cycle, sorted by name.
+    pub fn cycles(&self) -> Vec<Vec<&str>> {
+        Tarjan::new(self).run()
+    }
+}
+
+/// Render the cross-reference graph of `set` in Graphviz DOT, see
+/// [`DocSet::to_dot`].
+pub(crate) fn to_dot(set: &DocSet) -> String {
+    let graph = XrefGraph::build(set);
+    let referenced: BTreeSet<&str> = graph.edges.values().flatten().map(String::as_str).collect();
+
+    // Each identifier is drawn once, in the file that documents it first.
+    let mut files: BTreeMap<Option<&Path>, Vec<&str>> = BTreeMap::new();
+    let mut placed = BTreeSet::new();
+    for item in set {
+        if let Some(name) = &item.name
+            && placed.insert(name.as_str())
+        {
+            files.entry(item.file.as_deref()).or_default().push(name);
+        }
+    }
+
+    let mut out = String::from("digraph xref {\n  rankdir=LR;\n  node [shape=box];\n");
+    for (i, (file, names)) in files.iter().enumerate() {
+        let indent = match file {
+            Some(file) => {
+                let _ = writeln!(out, "  subgraph cluster_{i} {{");
+                let _ = writeln!(out, "    label={};", dot_id(&file.display().to_string()));
+                "    "
+            }
+            None => "  ",
+        };
+        for name in names {
+            let isolated = graph.references(name).next().is_none() && !referenced.contains(name);
+            let style = if isolated { " [style=dashed]" } else { "" };
+            let _ = writeln!(out, "{indent}{}{style};", dot_id(name));
+        }
+        if file.is_some() {
+            out.push_str("  }\n");
+        }
+    }
+    for (source, targets) in &graph.edges {
+        for target in targets {
+            let _ = writeln!(out, "  {} -> {};", dot_id(source), dot_id(target));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Quote `s` as a DOT identifier.
+fn dot_id(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Tarjan's strongly connected components algorithm, keeping components of
+/// more than one node. Self-references never make it into the graph.
+struct Tarjan<'a> {
+    graph: &'a XrefGraph,
+    index: BTreeMap<&'a str, usize>,
+    lowlink: BTreeMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: BTreeSet<&'a str>,
+    components: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn new(graph: &'a XrefGraph) -> Self {
+        Self {
+            graph,
+            index: BTreeMap::new(),
+            lowlink: BTreeMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<Vec<&'a str>> {
+        for node in self.graph.edges.keys() {
+            if !self.index.contains_key(node.as_str()) {
+                self.visit(node);
+            }
+        }
+        self.components
+    }
+
+    fn visit(&mut self, node: &'a str) {
+        let index = self.index.len();
+        self.index.insert(node, index);
+        self.lowlink.insert(node, index);
+        self.stack.push(node);
+        self.on_stack.insert(node);
+
+        let graph = self.graph;
+        for target in graph.references(node) {
+            if !self.index.contains_key(target) {
+                self.visit(target);
+                let low = self.lowlink[node].min(self.lowlink[target]);
+                self.lowlink.insert(node, low);
+            } else if self.on_stack.contains(target) {
+                let low = self.lowlink[node].min(self.index[target]);
+                self.lowlink.insert(node, low);
+            }
+        }
+
+        if self.lowlink[node] == index {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(member);
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            if component.len() > 1 {
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+/// The identifiers references can point to.
+///
+/// # Examples
+///
+/// ```
+/// use nixdoc::xref::SymbolTable;
+///
+/// let table: SymbolTable = ["attrsets.mapAttrs", "lists.map", "strings.map"]
+///     .into_iter()
+///     .collect();
+/// assert_eq!(table.resolve("lib.attrsets.mapAttrs"), Some("attrsets.mapAttrs"));
+/// assert_eq!(table.resolve("mapAttrs"), Some("attrsets.mapAttrs"));
+/// // Both `lists.map` and `strings.map` end in `map`.
+/// assert_eq!(table.resolve("map"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolTable {
+    names: BTreeSet<String>,
+}
+
+impl SymbolTable {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the names of every named item in `set`.
+    pub fn from_set(set: &DocSet) -> Self {
+        set.iter().filter_map(|item| item.name.clone()).collect()
+    }
+
+    /// Add an identifier.
+    pub fn insert(&mut self, name: impl Into<String>) {
+        self.names.insert(name.into());
+    }
+
+    /// Returns `true` if `name` is in the table exactly as written.
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// Returns the number of identifiers in the table.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Find the identifier that `mention` refers to.
+    ///
+    /// A mention resolves to a name if it is equal to it, or if dropping
+    /// leading attribute path components makes it so, as in
+    /// `lib.attrsets.mapAttrs` for `attrsets.mapAttrs`. Failing that, a bare
+    /// name resolves to the only path ending in it.
+    pub fn resolve(&self, mention: &str) -> Option<&str> {
+        let mut candidate = mention;
+        loop {
+            if let Some(name) = self.names.get(candidate) {
+                return Some(name);
+            }
+            match candidate.split_once('.') {
+                Some((_, rest)) => candidate = rest,
+                None => break,
+            }
+        }
+
+        let mut suffixed = self.names.iter().filter(|name| {
+            name.rsplit_once('.')
+                .is_some_and(|(_, last)| last == mention)
+        });
+        match (suffixed.next(), suffixed.next()) {
+            (Some(name), None) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for SymbolTable {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        Self {
+            names: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<S: Into<String>> Extend<S> for SymbolTable {
+    fn extend<T: IntoIterator<Item = S>>(&mut self, iter: T) {
+        self.names.extend(iter.into_iter().map(Into::into));
+    }
+}
+
+/// An identifier mentioned in a doc comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reference {
+    /// The identifier as written, such as `lib.attrsets.mapAttrs`.
+    pub text: String,
+    /// The heading of the section containing the mention, or `None` in the
+    /// description.
+    pub section: Option<String>,
+    /// The span of the whole mention, brackets and backticks included,
+    /// within [`DocComment::raw_content`].
+    pub span: Span,
+}
+
+/// A [`Reference`] together with the identifier it resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResolvedReference {
+    /// The mention.
+    pub reference: Reference,
+    /// The identifier in the [`SymbolTable`] it refers to.
+    pub target: String,
+}
+
+/// The outcome of [`DocComment::resolve_references`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferenceResolution {
+    /// Mentions found in the symbol table, in order of appearance.
+    pub resolved: Vec<ResolvedReference>,
+    /// Mentions not found in the symbol table, in order of appearance.
+    pub unresolved: Vec<Reference>,
+}
+
+/// Resolve the references of `doc` against `symbols`.
+pub(crate) fn resolve_references(doc: &DocComment, symbols: &SymbolTable) -> ReferenceResolution {
+    let mut resolution = ReferenceResolution::default();
+    for reference in references(doc) {
+        match symbols.resolve(&reference.text) {
+            Some(target) => resolution.resolved.push(ResolvedReference {
+                target: target.to_string(),
+                reference,
+            }),
+            None => resolution.unresolved.push(reference),
+        }
+    }
+    resolution
+}
+
+/// Collect the identifiers mentioned by a comment, in order of appearance.
+///
+/// Inline mentions are `` [`name`] `` anywhere in the comment; in a
+/// `# See Also` section, every code span holding an identifier counts.
+pub(crate) fn references(doc: &DocComment) -> Vec<Reference> {
+    let headings = section_headings(&doc.raw_content);
+    code_spans(&doc.raw_content)
+        .into_iter()
+        .filter_map(|span| {
+            let section = section_at(&headings, span.span.start);
+            let in_see_also = section.is_some_and(|s| s.eq_ignore_ascii_case("see also"));
+            ((span.bracketed || in_see_also) && is_identifier(span.text)).then(|| Reference {
+                text: span.text.to_string(),
+                section: section.map(str::to_string),
+                span: span.span,
+            })
+        })
+        .collect()
+}
+
+/// Find the `` [`name`] `` mentions in arbitrary Markdown `text`, returning
+/// the span of each, brackets included, and the identifier.
+pub(crate) fn inline_mentions(text: &str) -> Vec<(Span, &str)> {
+    code_spans(text)
+        .into_iter()
+        .filter(|span| span.bracketed && is_identifier(span.text))
+        .map(|span| (span.span, span.text))
+        .collect()
+}
+
+struct CodeSpan<'a> {
+    text: &'a str,
+    /// The span of the code span, including its backticks and, if it is
+    /// bracketed, the brackets.
+    span: Span,
+    /// Whether the span is written as `` [`text`] ``.
+    bracketed: bool,
+}
+
+/// Find the inline code spans in `text`, outside fenced code blocks.
+fn code_spans(text: &str) -> Vec<CodeSpan<'_>> {
+    let mut spans = Vec::new();
+    for (line_start, line, role) in fence_lines(text) {
+        if role == LineRole::Prose {
+            line_code_spans(line, line_start, &mut spans);
+        }
+    }
+    spans
+}
+
+/// Find the code spans on one line starting at byte `offset`, matching
+/// backtick runs of equal length.
+fn line_code_spans<'a>(line: &'a str, offset: usize, spans: &mut Vec<CodeSpan<'a>>) {
+    let mut pos = 0;
+    while let Some(found) = line[pos..].find('`') {
+        let start = pos + found;
+        let ticks = line[start..].len() - line[start..].trim_start_matches('`').len();
+        let inner_start = start + ticks;
+        let closing = "`".repeat(ticks);
+        let Some(len) = line[inner_start..].find(&closing) else {
+            break;
+        };
+        let inner_end = inner_start + len;
+        let end = inner_end + ticks;
+        let bracketed = line[..start].ends_with('[') && line[end..].starts_with(']');
+        let span = if bracketed {
+            Span::new(offset + start - 1, offset + end + 1)
+        } else {
+            Span::new(offset + start, offset + end)
+        };
+        spans.push(CodeSpan {
+            text: line[inner_start..inner_end].trim(),
+            span,
+            bracketed,
+        });
+        pos = end;
+    }
+}
+
+/// Returns `true` if `s` looks like a Nix identifier or attribute path.
+pub(crate) fn is_identifier(s: &str) -> bool {
+    !s.is_empty()
+        && s.split('.').all(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '\'' | '-'))
+        })
+}
@@ -0,0 +1,68 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::DocSet;
+use nixdoc::anchor::AnchorRemap;
+
+#[test]
+fn later_duplicates_are_numbered() {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /** a */\n  foo = 1;\n  /** b */\n  foo-2 = 2;\n}",
+        None,
+    );
+    set.add_source("/** c */\nfoo = 3;", Some("other.nix".as_ref()));
+    set.add_source("/** d */\nfoo = 4;", Some("third.nix".as_ref()));
+
+    let anchors = set.anchors();
+    assert_eq!(anchors.item(0), Some("foo"));
+    assert_eq!(anchors.item(1), Some("foo-2"));
+    // `foo-2` is taken by the binding of that name.
+    assert_eq!(anchors.item(2), Some("foo-3"));
+    assert_eq!(anchors.item(3), Some("foo-4"));
+    assert_eq!(
+        anchors.remaps(),
+        [
+            AnchorRemap {
+                item: 2,
+                section: None,
+                original: "foo".to_string(),
+                anchor: "foo-3".to_string(),
+            },
+            AnchorRemap {
+                item: 3,
+                section: None,
+                original: "foo".to_string(),
+                anchor: "foo-4".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn sections_are_unique_too() {
+    let src = "/**\n  f.\n\n  # Note\n\n  One.\n\n  # Note\n\n  Two.\n\n  # Examples\n\n  x\n*/\nf = 1;\n/**\n  File.\n\n  # Note\n\n  Three.\n*/\n{ }";
+    let mut set = DocSet::new();
+    set.add_source(src, None);
+
+    let anchors = set.anchors();
+    assert_eq!(anchors.section(0, 0), Some("f-note"));
+    assert_eq!(anchors.section(0, 1), Some("f-note-2"));
+    assert_eq!(anchors.section(0, 2), Some("f-examples"));
+    assert_eq!(anchors.item(1), None);
+    assert_eq!(anchors.section(1, 0), Some("note"));
+    assert_eq!(anchors.remaps().len(), 1);
+    assert_eq!(anchors.remaps()[0].section, Some(1));
+    assert_eq!(anchors.section(0, 3), None);
+}
+
+#[test]
+fn adding_items_keeps_earlier_anchors() {
+    let mut set = DocSet::new();
+    set.add_source("/** a */\nmap = 1;", Some("lists.nix".as_ref()));
+    let before = set.anchors();
+    set.add_source("/** b */\nmap = 2;", Some("attrsets.nix".as_ref()));
+    let after = set.anchors();
+    assert_eq!(before.item(0), after.item(0));
+    assert_eq!(after.item(1), Some("map-2"));
+}
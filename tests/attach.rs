@@ -0,0 +1,78 @@
+// All tests in this file require `--features rnix`.
+#![cfg(feature = "rnix")]
+
+use nixdoc::extract::{attach, documented_items};
+
+const SRC: &str = r#"/** The library. */
+{ lib, ... }:
+let
+  /** A helper. */
+  helper = x: y: x;
+in
+{
+  /** Identity. */
+  id = x: x;
+
+  strings = (rec {
+    # Not a doc comment.
+    /** Join a list. */
+    concat = { sep ? "", list, ... }@args: list;
+
+    /** Nested. */
+    escape.nix = s: s;
+  });
+
+  /** A value. */
+  version = "/** not a comment */";
+}
+"#;
+
+#[test]
+fn comments_attach_to_qualified_bindings() {
+    let items = attach(SRC, Some("lib.nix".as_ref()));
+    let names: Vec<Option<&str>> = items.iter().map(|item| item.name.as_deref()).collect();
+    assert_eq!(
+        names,
+        [
+            None,
+            Some("helper"),
+            Some("id"),
+            Some("strings.concat"),
+            Some("strings.escape.nix"),
+            Some("version"),
+        ]
+    );
+    assert_eq!(items[3].doc.title(), Some("Join a list."));
+    assert_eq!(items[3].file.as_deref(), Some("lib.nix".as_ref()));
+    assert_eq!((items[3].line, items[3].column), (13, 5));
+}
+
+#[test]
+fn formals_come_from_the_syntax_tree() {
+    let items = attach(SRC, None);
+    let formals: Vec<&[String]> = items.iter().map(|item| item.formals.as_slice()).collect();
+    assert_eq!(formals[0], ["lib"]);
+    assert_eq!(formals[1], ["x", "y"]);
+    assert_eq!(formals[2], ["x"]);
+    assert_eq!(formals[3], ["sep", "list"]);
+    assert!(formals[5].is_empty());
+}
+
+#[test]
+fn spans_match_textual_extraction() {
+    let attached = attach(SRC, None);
+    let textual = documented_items(SRC, None);
+    assert_eq!(attached.len(), textual.len());
+    for (attached, textual) in attached.iter().zip(&textual) {
+        assert_eq!(attached.span, textual.span);
+        assert_eq!(attached.doc, textual.doc);
+        assert!(textual.formals.is_empty());
+    }
+}
+
+#[test]
+fn unparseable_comments_are_skipped() {
+    let items = attach("{\n  /** */\n  a = 1;\n  /** B. */\n  b = 2;\n}", None);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name.as_deref(), Some("b"));
+}
@@ -0,0 +1,83 @@
+// All tests in this file require `--features dash`.
+#![cfg(feature = "dash")]
+
+use std::fs;
+
+use nixdoc::DocSet;
+use nixdoc::render::RenderOptions;
+use nixdoc::render::dash::{DocsetOptions, write_docset};
+use nixdoc::render::site::SiteOptions;
+use rusqlite::Connection;
+
+const STRINGS: &str = r#"{
+  /** Concatenate a list of strings. */
+  concatStrings = builtins.concatStringsSep "";
+
+  /**
+    Split a string.
+
+    # Stability
+
+    internal
+  */
+  splitInternal = s: [ s ];
+}"#;
+
+#[test]
+fn writes_pages_plist_and_index() {
+    let mut set = DocSet::new();
+    set.add_source(STRINGS, Some("lib/strings.nix".as_ref()));
+    set.add_source(
+        "{\n  /** Apply `f` to each element. */\n  map = f: xs: xs;\n}",
+        Some("lib/lists.nix".as_ref()),
+    );
+
+    let dir = std::env::temp_dir().join(format!("nixdoc-dash-{}", std::process::id()));
+    let options = DocsetOptions::new("Test lib", "test-lib").with_site(
+        SiteOptions::new()
+            .with_title("Test lib")
+            .with_root("lib")
+            .with_render(RenderOptions::new().with_hide_internal(true)),
+    );
+    let bundle = write_docset(&set, &options, &dir).unwrap();
+    assert_eq!(bundle, dir.join("Test lib.docset"));
+
+    let plist = fs::read_to_string(bundle.join("Contents/Info.plist")).unwrap();
+    assert!(plist.contains("<key>CFBundleIdentifier</key>\n\t<string>test-lib</string>"));
+    assert!(plist.contains("<key>CFBundleName</key>\n\t<string>Test lib</string>"));
+
+    let documents = bundle.join("Contents/Resources/Documents");
+    let page = fs::read_to_string(documents.join("strings.html")).unwrap();
+    assert!(page.contains(
+        "<a name=\"//apple_ref/cpp/Function/concatStrings\" class=\"dashAnchor\"></a>\n<section id=\"concatStrings\">"
+    ));
+    assert!(!page.contains("splitInternal"));
+    assert!(documents.join("index.html").exists());
+
+    let db = Connection::open(bundle.join("Contents/Resources/docSet.dsidx")).unwrap();
+    let mut query = db
+        .prepare("SELECT name, type, path FROM searchIndex ORDER BY id")
+        .unwrap();
+    let rows: Vec<(String, String, String)> = query
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    let rows: Vec<(&str, &str, &str)> = rows
+        .iter()
+        .map(|(a, b, c)| (a.as_str(), b.as_str(), c.as_str()))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ("lists.html", "File", "lists.html"),
+            ("strings.html", "File", "strings.html"),
+            ("concatStrings", "Function", "strings.html#concatStrings"),
+            ("map", "Function", "lists.html#map"),
+        ]
+    );
+
+    // Writing again replaces the bundle.
+    write_docset(&set, &options, &dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,69 @@
+use nixdoc::dialect::{ArgumentSyntax, Dialect};
+use nixdoc::{DocComment, ParserOptions, SectionKind, WarningKind};
+
+#[test]
+fn nix_dialect_changes_nothing() {
+    let input =
+        "/**\n  Add.\n\n  # Arguments\n\n  - [a] First\n\n  # Parameters\n\n  - b: Second\n*/";
+    let plain = DocComment::parse(input).unwrap();
+    let nix = Dialect::nix().parse(input).unwrap();
+    assert_eq!(plain, nix);
+    assert_eq!(nix.warnings[0].kind, WarningKind::UnknownSection);
+}
+
+#[test]
+fn aliases_are_case_insensitive_and_renamed() {
+    let dialect = Dialect::new("x").with_section("Parameters", SectionKind::Arguments);
+    let doc = dialect
+        .parse("/**\n  Add.\n\n  # PARAMETERS\n\n  - [a] First\n\n  # See Also\n\n  `sub`\n*/")
+        .unwrap();
+    assert_eq!(doc.sections[0].heading, "Arguments");
+    assert_eq!(doc.arguments()[0].name, "a");
+    // Headings the dialect does not know are kept and still warned about.
+    assert_eq!(doc.sections[1].heading, "See Also");
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].kind, WarningKind::UnknownSection);
+}
+
+#[test]
+fn param_arguments() {
+    let dialect = Dialect::new("jsdoc-ish").with_arguments(ArgumentSyntax::Param);
+    let doc = dialect
+        .parse("/**\n  Add.\n\n  # Arguments\n\n  @param a The first number\n  @param b\n*/")
+        .unwrap();
+    let args = doc.arguments();
+    assert_eq!(args.len(), 2);
+    assert_eq!(
+        (args[0].name.as_str(), args[0].description.as_str()),
+        ("a", "The first number")
+    );
+    assert_eq!(
+        (args[1].name.as_str(), args[1].description.as_str()),
+        ("b", "")
+    );
+}
+
+#[test]
+fn colon_arguments_only_rewrite_names() {
+    let dialect = Dialect::new("x").with_arguments(ArgumentSyntax::Colon);
+    let doc = dialect
+        .parse("/**\n  f.\n\n  # Arguments\n\n  - `attrs`: The set\n  - a note: not an argument\n\n  # Note\n\n  - b: untouched\n*/")
+        .unwrap();
+    let names: Vec<_> = doc.arguments().into_iter().map(|a| a.name).collect();
+    assert_eq!(names, ["attrs"]);
+    assert_eq!(doc.notes(), ["- b: untouched"]);
+}
+
+#[test]
+fn nickel_doc_annotations() {
+    let input = "m%\"\n  Map a function over an array.\n\n  # Params\n\n  - f: The function\n  - xs: The array\n\n  # Returns\n\n  The mapped array.\n\"%";
+    let doc = DocComment::parse_with(input, &ParserOptions::new().with_dialect(Dialect::nickel()))
+        .unwrap();
+    assert_eq!(doc.title(), Some("Map a function over an array."));
+    assert_eq!(doc.arguments().len(), 2);
+    assert_eq!(doc.output().unwrap().description, "The mapped array.");
+    assert!(doc.warnings.is_empty());
+
+    // Nixdoc comments are not Nickel ones.
+    assert!(Dialect::nickel().parse("/** Id. */").is_err());
+}
@@ -0,0 +1,281 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::diff::{ArgumentChange, ChangeDetail, Hunk, Impact, ItemStatus, SectionChange};
+use nixdoc::{Argument, DocComment, DocSet};
+
+fn set(src: &str) -> DocSet {
+    let mut set = DocSet::new();
+    set.add_source(src, None);
+    set
+}
+
+const OLD: &str = r#"{
+  /**
+    Concatenate strings.
+
+    # Type
+
+    ```
+    concat :: [String] -> String
+    ```
+  */
+  concat = xs: xs;
+
+  /** Unchanged. */
+  same = 1;
+
+  /** Will be deprecated. */
+  old = 1;
+
+  /** Removed soon. */
+  gone = 1;
+}"#;
+
+const NEW: &str = r#"{
+  /**
+    Concatenate a list of strings.
+
+    # Type
+
+    ```
+    concat :: [String] -> String -> String
+    ```
+  */
+  concat = xs: xs;
+
+  /** Unchanged. */
+  same = 1;
+
+  /**
+    Will be deprecated.
+
+    # Deprecated
+
+    Use `new` instead.
+  */
+  old = 1;
+
+  /**
+    Brand new.
+
+    # Arguments
+
+    - [x] The input
+  */
+  fresh = x: x;
+}"#;
+
+#[test]
+fn classifies_identifiers() {
+    let diff = DocSet::diff(&set(OLD), &set(NEW));
+
+    assert_eq!(diff.added().collect::<Vec<_>>(), vec!["fresh"]);
+    assert_eq!(diff.removed().collect::<Vec<_>>(), vec!["gone"]);
+    assert_eq!(diff.changed().collect::<Vec<_>>(), vec!["concat", "old"]);
+}
+
+#[test]
+fn change_details() {
+    let diff = DocSet::diff(&set(OLD), &set(NEW));
+
+    let concat = diff
+        .items
+        .iter()
+        .find(|i| i.identifier == "concat")
+        .unwrap();
+    assert_eq!(
+        concat.status,
+        ItemStatus::Changed(vec![
+            ChangeDetail::DescriptionEdited,
+            ChangeDetail::TypeSignatureChanged {
+                old: Some("concat :: [String] -> String".to_string()),
+                new: Some("concat :: [String] -> String -> String".to_string()),
+            },
+        ])
+    );
+
+    let old = diff.items.iter().find(|i| i.identifier == "old").unwrap();
+    assert_eq!(
+        old.status,
+        ItemStatus::Changed(vec![ChangeDetail::NewlyDeprecated])
+    );
+}
+
+#[test]
+fn reverse_diff_undeprecates() {
+    let diff = DocSet::diff(&set(NEW), &set(OLD));
+    let old = diff.items.iter().find(|i| i.identifier == "old").unwrap();
+    assert_eq!(
+        old.status,
+        ItemStatus::Changed(vec![ChangeDetail::NoLongerDeprecated])
+    );
+}
+
+#[test]
+fn identical_sets_have_empty_diff() {
+    assert!(DocSet::diff(&set(OLD), &set(OLD)).is_empty());
+}
+
+#[test]
+fn markdown_summary() {
+    let diff = DocSet::diff(&set(OLD), &set(NEW));
+    expect_test::expect![[r#"
+        - Changed `concat`: description edited, type signature changed
+        - Added `fresh`
+        - Removed `gone`
+        - Changed `old`: newly deprecated
+    "#]]
+    .assert_eq(&diff.to_string());
+}
+
+const OLD_COMMENT: &str = r#"/**
+  Apply a function to each element.
+
+  # Arguments
+
+  - [f] The function
+  - [list] The list
+
+  # Note
+
+  Lazy in the list.
+
+  # Examples
+
+  ```nix
+  map (x: x + 1) [ 1 2 ]
+  ```
+*/"#;
+
+const NEW_COMMENT: &str = r#"/**
+  Apply a function to each element of a list.
+
+  # Arguments
+
+  - [f] The function to apply
+  - [xs] The list
+
+  # note
+
+  Lazy   in the
+  list.
+
+  # Type
+
+  ```
+  map :: (a -> b) -> [a] -> [b]
+  ```
+*/"#;
+
+#[test]
+fn comment_diff() {
+    let old = DocComment::parse(OLD_COMMENT).unwrap();
+    let new = DocComment::parse(NEW_COMMENT).unwrap();
+    let diff = old.diff(&new);
+
+    assert_eq!(
+        diff.description,
+        [
+            Hunk::Equal("Apply a function to each".into()),
+            Hunk::Removed("element.".into()),
+            Hunk::Inserted("element of a list.".into()),
+        ]
+    );
+    assert_eq!(
+        diff.arguments,
+        [
+            ArgumentChange::Changed {
+                name: "f".into(),
+                old: "The function".into(),
+                new: "The function to apply".into(),
+            },
+            ArgumentChange::Removed(Argument {
+                name: "list".into(),
+                description: "The list".into(),
+                type_sig: None,
+                default: None,
+            }),
+            ArgumentChange::Added(Argument {
+                name: "xs".into(),
+                description: "The list".into(),
+                type_sig: None,
+                default: None,
+            }),
+        ]
+    );
+    assert!(matches!(
+        &diff.sections[..],
+        [
+            SectionChange::Changed { heading, .. },
+            SectionChange::Removed(removed),
+            SectionChange::Added(added),
+        ] if heading == "Arguments" && removed == "Examples" && added == "Type"
+    ));
+
+    expect_test::expect![[r#"
+        Description: Apply a function to each [-element.-] {+element of a list.+}
+        Changed section `Arguments`: - [f] The function {+to apply+} - [-[list]-] {+[xs]+} The list
+        Removed section `Examples`
+        Added section `Type`
+        Changed argument `f`: The function {+to apply+}
+        Removed argument `list`
+        Added argument `xs`
+    "#]]
+    .assert_eq(&diff.to_string());
+}
+
+#[test]
+fn identical_comments_have_empty_diff() {
+    let old = DocComment::parse(OLD_COMMENT).unwrap();
+    assert!(old.diff(&old).is_empty());
+    assert_eq!(old.diff(&old).to_string(), "");
+}
+
+#[test]
+fn set_changes_by_impact() {
+    let diff = DocSet::diff(&set(OLD), &set(NEW));
+    let impacts: Vec<_> = diff
+        .items
+        .iter()
+        .map(|item| (item.identifier.as_str(), item.impact()))
+        .collect();
+    assert_eq!(
+        impacts,
+        [
+            ("concat", Impact::Semantic),
+            ("fresh", Impact::Lifecycle),
+            ("gone", Impact::Lifecycle),
+            ("old", Impact::Lifecycle),
+        ]
+    );
+    assert_eq!(
+        diff.with_impact(Impact::Semantic).to_string(),
+        "- Changed `concat`: description edited, type signature changed\n"
+    );
+    assert!(diff.with_impact(Impact::Cosmetic).is_empty());
+}
+
+#[test]
+fn comment_changes_by_impact() {
+    let old = DocComment::parse(OLD_COMMENT).unwrap();
+    let new = DocComment::parse(NEW_COMMENT).unwrap();
+    let diff = old.diff(&new);
+    assert_eq!(diff.impact(), Some(Impact::Semantic));
+    assert_eq!(
+        diff.sections
+            .iter()
+            .map(SectionChange::impact)
+            .collect::<Vec<_>>(),
+        [Impact::Semantic, Impact::Cosmetic, Impact::Semantic]
+    );
+
+    let reworded = DocComment::parse(&OLD_COMMENT.replace("Lazy in", "Lazy on")).unwrap();
+    assert_eq!(old.diff(&reworded).impact(), Some(Impact::Cosmetic));
+
+    let experimental = DocComment::parse(
+        &OLD_COMMENT.replace("# Note", "# Stability\n\n  experimental\n\n  # Note"),
+    )
+    .unwrap();
+    assert_eq!(old.diff(&experimental).impact(), Some(Impact::Lifecycle));
+}
@@ -156,12 +156,96 @@ fn arguments_empty_description() {
     assert_eq!(args[1].description, "");
 }
 
+#[test]
+fn arguments_table() {
+    let input = "/**\n  f.\n\n  # Arguments\n\n  | name | type | default | description |\n  | ---- | ---- | ------- | ----------- |\n  | `a` | int | `0` | First number |\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    let args = doc.arguments();
+    assert_eq!(args.len(), 1);
+    assert_eq!(args[0].name, "a");
+    assert_eq!(args[0].type_sig.as_deref(), Some("int"));
+    assert_eq!(args[0].default.as_deref(), Some("0"));
+    assert_eq!(args[0].description, "First number");
+}
+
+#[test]
+fn arguments_definition_list() {
+    let input = "/**\n  f.\n\n  # Arguments\n\n  `f`\n  : 1\\. Function argument\n    applied to each element\n\n  `list`\n  : 2\\. Function argument\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    let args = doc.arguments();
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[0].name, "f");
+    assert_eq!(
+        args[0].description,
+        "1\\. Function argument applied to each element"
+    );
+    assert_eq!(args[1].name, "list");
+}
+
 #[test]
 fn arguments_empty_when_no_section() {
     let doc = DocComment::parse("/** No args. */").unwrap();
     assert!(doc.arguments().is_empty());
 }
 
+#[test]
+fn builder_inputs_and_output() {
+    let input = r#"/**
+  Build a Go module.
+
+  # Inputs
+
+  `pname`
+  : The package name.
+
+  - [vendorHash] The hash of the vendored dependencies,
+    or `null` to use the vendor directory.
+
+  # Output
+
+  A derivation with the module's binaries.
+
+  | name | description |
+  | ---- | ----------- |
+  | `out` | The binaries. |
+  | `goModules` | The vendored dependencies. |
+*/"#;
+    let doc = DocComment::parse(input).unwrap();
+    assert_eq!(doc.sections[0].kind(), SectionKind::Inputs);
+    assert_eq!(doc.sections[1].kind(), SectionKind::Output);
+
+    let inputs = doc.inputs();
+    assert_eq!(inputs.len(), 2);
+    assert_eq!(inputs[0].name, "pname");
+    assert_eq!(inputs[0].description, "The package name.");
+    assert_eq!(
+        inputs[1].description,
+        "The hash of the vendored dependencies, or `null` to use the vendor directory."
+    );
+    assert!(doc.arguments().is_empty());
+
+    let output = doc.output().unwrap();
+    assert_eq!(
+        output.description,
+        "A derivation with the module's binaries."
+    );
+    let names: Vec<_> = output.attributes.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ["out", "goModules"]);
+    assert_eq!(
+        output.attributes[1].description,
+        "The vendored dependencies."
+    );
+}
+
+#[test]
+fn output_without_entries() {
+    let doc = DocComment::parse("/**\n  f.\n\n  # Outputs\n\n  A string.\n*/").unwrap();
+    let output = doc.output().unwrap();
+    assert_eq!(output.description, "A string.");
+    assert!(output.attributes.is_empty());
+    assert_eq!(DocComment::parse("/** f. */").unwrap().output(), None);
+}
+
 #[test]
 fn examples_basic() {
     let input = "/**\n  f.\n\n  # Example\n\n  ```nix\n  f 1\n  => 1\n  ```\n*/";
@@ -170,6 +254,8 @@ fn examples_basic() {
     assert_eq!(examples.len(), 1);
     assert_eq!(examples[0].language, Some("nix".to_string()));
     assert!(examples[0].code.contains("f 1"));
+    assert_eq!(examples[0].input, "f 1");
+    assert_eq!(examples[0].expected.as_deref(), Some("1"));
 }
 
 #[test]
@@ -410,3 +496,755 @@ fn section_kind_is_known() {
     assert!(SectionKind::Type.is_known());
     assert!(!SectionKind::Unknown("x".to_string()).is_known());
 }
+
+#[test]
+fn examples_titles_from_subheadings() {
+    let input = "/**\n  f.\n\n  # Examples\n\n  ## Basic\n\n  ```nix\n  f 1\n  ```\n\n  ## Advanced\n\n  ```nix\n  f 2\n  ```\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    let examples = doc.examples();
+    assert_eq!(examples.len(), 2);
+    assert_eq!(examples[0].title.as_deref(), Some("Basic"));
+    assert_eq!(examples[1].title.as_deref(), Some("Advanced"));
+}
+
+#[test]
+fn urls_with_sections() {
+    let doc = DocComment::parse(
+        "/**\n  See https://nixos.org.\n\n  # Example\n\n  ```nix\n  # https://in.code/block\n  ```\n\n  # Note\n\n  [manual](https://nixos.org/manual/nix)\n*/",
+    )
+    .unwrap();
+    let urls: Vec<_> = doc
+        .urls()
+        .into_iter()
+        .map(|link| {
+            assert_eq!(&doc.raw_content[link.span.start..link.span.end], link.url);
+            (link.url, link.section)
+        })
+        .collect();
+    assert_eq!(
+        urls,
+        [
+            ("https://nixos.org".to_string(), None),
+            (
+                "https://in.code/block".to_string(),
+                Some("Example".to_string())
+            ),
+            (
+                "https://nixos.org/manual/nix".to_string(),
+                Some("Note".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn explicit_section_ids() {
+    let doc = DocComment::parse(
+        "/**\n  f.\n\n  # Examples {#f-examples}\n\n  ```nix\n  f\n  ```\n\n  # Note\n\n  Careful.\n*/",
+    )
+    .unwrap();
+    assert_eq!(doc.sections[0].heading, "Examples");
+    assert_eq!(doc.sections[0].kind(), SectionKind::Examples);
+    assert_eq!(doc.sections[0].explicit_id.as_deref(), Some("f-examples"));
+    assert_eq!(doc.sections[0].anchor(), "f-examples");
+    assert_eq!(doc.sections[1].explicit_id, None);
+    assert_eq!(doc.sections[1].anchor(), "note");
+    assert!(doc.section("Examples").is_some());
+    assert!(doc.warnings.is_empty());
+}
+
+#[test]
+fn link_definitions_are_collected_document_wide() {
+    let doc = DocComment::parse(
+        "/**\n  See [RFC 145][rfc] and [nixpkgs].\n\n  [nixpkgs]: <https://github.com/NixOS/nixpkgs> 'The repo'\n\n  # Example\n\n  ```nix\n  [x]: not-a-definition\n  ```\n\n  # Note\n\n  Also [rfc].\n\n  [rfc]: https://github.com/NixOS/rfcs/pull/145 \"RFC 145\"\n  [^1]: a footnote\n*/",
+    )
+    .unwrap();
+
+    let defs: Vec<_> = doc
+        .link_definitions
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        defs,
+        [
+            "[nixpkgs]: https://github.com/NixOS/nixpkgs \"The repo\"",
+            "[rfc]: https://github.com/NixOS/rfcs/pull/145 \"RFC 145\"",
+        ]
+    );
+    assert_eq!(doc.description(), "See [RFC 145][rfc] and [nixpkgs].");
+    assert!(doc.examples()[0].code.contains("[x]: not-a-definition"));
+    assert_eq!(
+        doc.section("Note").unwrap().content,
+        "Also [rfc].\n\n[^1]: a footnote"
+    );
+    assert_eq!(
+        doc.link_definition("NixPkgs").unwrap().title.as_deref(),
+        Some("The repo")
+    );
+    assert!(doc.link_definition("missing").is_none());
+}
+
+#[test]
+fn parse_lossy_recovers_unclosed_comments() {
+    let doc = DocComment::parse_lossy(
+        "/**\n  Map a function.\n\n  # Arguments\n\n  - [f] The function\n\n  # Example\n\n  ```nix\n  map f [ 1 ]",
+    )
+    .unwrap();
+    assert_eq!(doc.title(), Some("Map a function."));
+    assert_eq!(doc.arguments()[0].name, "f");
+    assert!(doc.examples()[0].code.contains("map f [ 1 ]"));
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].kind, WarningKind::UnclosedComment);
+
+    // Closed comments parse exactly as with `parse`.
+    let closed = "/** Closed. */";
+    assert_eq!(DocComment::parse_lossy(closed), DocComment::parse(closed));
+
+    assert_eq!(
+        DocComment::parse_lossy("/* unclosed"),
+        Err(ParseError::NotDocComment)
+    );
+    assert_eq!(
+        DocComment::parse_lossy("/**  "),
+        Err(ParseError::EmptyComment)
+    );
+}
+
+#[test]
+fn parse_with_custom_delimiters() {
+    let options = ParserOptions::new().with_delimiters("/*!", "!*/");
+    let doc = DocComment::parse_with(
+        "/*!\n  Increment.\n\n  # Arguments\n\n  - [n] A number\n!*/",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(doc.title(), Some("Increment."));
+    assert_eq!(doc.arguments()[0].name, "n");
+
+    assert_eq!(
+        DocComment::parse_with("/** Standard. */", &options),
+        Err(ParseError::NotDocComment)
+    );
+    assert_eq!(
+        DocComment::parse_with("/*! unclosed */", &options),
+        Err(ParseError::UnclosedComment)
+    );
+    assert_eq!(
+        DocComment::parse_with("/** Same. */", &ParserOptions::new()),
+        DocComment::parse("/** Same. */")
+    );
+}
+
+#[test]
+fn parse_without_delimiters() {
+    let options = ParserOptions::new().without_delimiters();
+    let doc = DocComment::parse_with(
+        "Add two numbers.\n\n# Example\n\n```nix\nadd 1 2\n=> 3\n```\n",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(doc.title(), Some("Add two numbers."));
+    assert_eq!(doc.examples()[0].expected.as_deref(), Some("3"));
+
+    // Comment markers are content when none are expected.
+    let doc = DocComment::parse_with("/** kept */", &options).unwrap();
+    assert_eq!(doc.description(), "/** kept */");
+    assert_eq!(
+        DocComment::parse_with(" \n ", &options),
+        Err(ParseError::EmptyComment)
+    );
+}
+
+#[test]
+fn javadoc_gutters_are_stripped() {
+    let doc = DocComment::parse(
+        "/**\n * Add two numbers.\n *\n * # Arguments\n *\n * - [a] The first\n * - [b] The second\n *\n * # Example\n *\n * ```nix\n * add 1 2\n *   => 3\n * ```\n */",
+    )
+    .unwrap();
+    assert_eq!(doc.title(), Some("Add two numbers."));
+    assert_eq!(doc.arguments().len(), 2);
+    assert_eq!(doc.examples()[0].code, "add 1 2\n  => 3\n");
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].kind, WarningKind::JavadocGutter);
+
+    // Text after the opening marker is kept.
+    let doc = DocComment::parse("/** Title.\n * More text.\n */").unwrap();
+    assert_eq!(doc.description(), "Title.\nMore text.");
+
+    // A list that is not a gutter on every line is left alone.
+    let doc = DocComment::parse("/**\n  Items:\n  * one\n  * two\n*/").unwrap();
+    assert_eq!(doc.description(), "Items:\n* one\n* two");
+    assert!(doc.warnings.is_empty());
+}
+
+#[test]
+fn unicode_whitespace_in_indentation_and_headings() {
+    // Non-breaking spaces and a zero-width space in the indentation would
+    // otherwise keep the common indent from being stripped.
+    let input = "/**\n\u{a0}\u{a0}Title with\u{a0}nbsp.\n\n\u{200b}  # Type\u{200b}\n\n  ```\n  \u{a0}int\n  ```\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    assert_eq!(doc.title(), Some("Title with\u{a0}nbsp."));
+    assert_eq!(doc.section("Type").unwrap().heading, "Type");
+    assert_eq!(doc.type_sig().as_deref(), Some(" int\n"));
+    assert!(doc.warnings.is_empty());
+
+    let warn = ParserOptions::new().with_unicode(UnicodePolicy::Warn);
+    let doc = DocComment::parse_with(input, &warn).unwrap();
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].kind, WarningKind::UnusualWhitespace);
+    assert!(doc.warnings[0].message.contains("replaced 5 "));
+
+    let preserve = ParserOptions::new().with_unicode(UnicodePolicy::Preserve);
+    let doc = DocComment::parse_with(input, &preserve).unwrap();
+    assert!(doc.section("Type").is_none());
+}
+
+#[test]
+fn byte_order_mark() {
+    let doc = DocComment::parse("\u{feff}/** With a BOM. */").unwrap();
+    assert_eq!(doc.description(), "With a BOM.");
+
+    let preserve = ParserOptions::new().with_unicode(UnicodePolicy::Preserve);
+    assert_eq!(
+        DocComment::parse_with("\u{feff}/** With a BOM. */", &preserve),
+        Err(ParseError::NotDocComment)
+    );
+}
+
+#[test]
+fn parse_outcome_combines_doc_errors_and_warnings() {
+    let options = ParserOptions::new();
+
+    let outcome =
+        DocComment::parse_outcome("/**\n  Fine.\n\n  # Nonsense\n\n  Text.\n*/", &options);
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.warnings.len(), 1);
+    assert_eq!(outcome.warnings[0].kind, WarningKind::UnknownSection);
+    assert_eq!(outcome.doc.as_ref().unwrap().title(), Some("Fine."));
+
+    let input = "  /**\n  Unclosed.\n\n  # Nonsense\n\n  Text.\n";
+    let outcome = DocComment::parse_outcome(input, &options);
+    assert!(!outcome.is_ok());
+    assert_eq!(
+        outcome.errors,
+        [SpannedError {
+            error: ParseError::UnclosedComment,
+            span: Span::new(2, 5),
+        }]
+    );
+    assert_eq!(outcome.warnings.len(), 1);
+    assert_eq!(outcome.warnings[0].kind, WarningKind::UnknownSection);
+    assert_eq!(outcome.doc.unwrap().title(), Some("Unclosed."));
+
+    let outcome = DocComment::parse_outcome(" /** */ ", &options);
+    assert_eq!(outcome.doc, None);
+    assert_eq!(outcome.errors[0].error, ParseError::EmptyComment);
+    assert_eq!(outcome.errors[0].span, Span::new(4, 5));
+    assert_eq!(outcome.into_result(), Err(ParseError::EmptyComment));
+}
+
+#[test]
+fn parse_spanned_locates_errors() {
+    let options = ParserOptions::new();
+    let error = |input: &str| DocComment::parse_spanned(input, &options).unwrap_err();
+
+    let input = "\n  /* plain\n  comment */";
+    let spanned = error(input);
+    assert_eq!(spanned.error, ParseError::NotDocComment);
+    assert_eq!(&input[spanned.span.start..spanned.span.end], "/* ");
+
+    let input = "  # Heading";
+    assert_eq!(error(input).span, Span::new(2, 5));
+
+    let input = "\n  /**\n  Unclosed.\n";
+    let spanned = error(input);
+    assert_eq!(spanned.error, ParseError::UnclosedComment);
+    assert_eq!(&input[spanned.span.start..spanned.span.end], "/**");
+
+    let input = "/**\n\n*/";
+    let spanned = error(input);
+    assert_eq!(spanned.error, ParseError::EmptyComment);
+    assert_eq!(spanned.span, Span::new(3, 5));
+    assert_eq!(error("/***/").span, Span::new(3, 3));
+
+    let options = ParserOptions::new().with_delimiters("{-|", "-}");
+    let spanned = DocComment::parse_spanned("{-|  -}", &options).unwrap_err();
+    assert_eq!(spanned.span, Span::new(3, 5));
+
+    assert!(DocComment::parse_spanned("/** Fine. */", &ParserOptions::new()).is_ok());
+}
+
+#[test]
+fn legacy_comments_are_opt_in() {
+    let input =
+        "/* Reverse a list.\n\n   # Examples\n\n   ```nix\n   reverseList [ 1 2 ]\n   ```\n*/";
+    assert_eq!(DocComment::parse(input), Err(ParseError::NotDocComment));
+
+    let options = ParserOptions::new().with_legacy_comments(true);
+    let doc = DocComment::parse_with(input, &options).unwrap();
+    assert!(doc.is_legacy());
+    assert_eq!(doc.title(), Some("Reverse a list."));
+    assert_eq!(doc.examples().len(), 1);
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].kind, WarningKind::LegacyComment);
+
+    let doc = DocComment::parse_with("/** Reverse a list. */", &options).unwrap();
+    assert!(!doc.is_legacy());
+    assert!(doc.warnings.is_empty());
+
+    let custom = ParserOptions::new()
+        .with_delimiters("{-|", "-}")
+        .with_legacy_comments(true);
+    assert_eq!(
+        DocComment::parse_with("/* Not Haskell. */", &custom),
+        Err(ParseError::NotDocComment)
+    );
+}
+
+#[test]
+fn meta_section_and_frontmatter() {
+    let doc = DocComment::parse(
+        "/**\n  Fetch a tarball.\n\n  # Meta\n\n  category: fetchers\n  maintainer: @alice\n  homepage: https://example.org/docs\n  not metadata\n  category: builders\n*/",
+    )
+    .unwrap();
+    let meta = doc.metadata();
+    assert_eq!(meta.len(), 3);
+    assert_eq!(meta["category"], "builders");
+    assert_eq!(meta["maintainer"], "@alice");
+    assert_eq!(meta["homepage"], "https://example.org/docs");
+    assert!(doc.warnings.is_empty());
+
+    let doc = DocComment::parse(
+        "/**\n  ---\n  stability: experimental\n  ---\n\n  Title.\n\n  # Note\n\n  Careful.\n*/",
+    )
+    .unwrap();
+    assert_eq!(doc.description(), "Title.");
+    assert_eq!(doc.sections[0].kind(), SectionKind::Meta);
+    assert_eq!(doc.metadata()["stability"], "experimental");
+    assert_eq!(doc.notes(), ["Careful."]);
+
+    // A thematic break followed by prose is not frontmatter.
+    let doc = DocComment::parse("/**\n  ---\n  Just text.\n  ---\n  More.\n*/").unwrap();
+    assert!(doc.metadata().is_empty());
+    assert!(doc.description().starts_with("---"));
+}
+
+#[test]
+fn since_versions() {
+    let since = |input: &str| DocComment::parse(input).unwrap().since();
+
+    let version = since("/**\n  Title.\n\n  # Since\n\n  Added in Nixpkgs 24.05.\n*/").unwrap();
+    assert_eq!((version.major, version.minor, version.patch), (24, 5, 0));
+    assert_eq!(version.as_str(), "24.05");
+
+    let version = since("/**\n  Title.\n\n  # Note\n\n  Since: v2.1.0-rc.1\n*/").unwrap();
+    assert_eq!(version.pre.as_deref(), Some("rc.1"));
+    assert!(version < Version::parse("2.1.0").unwrap());
+
+    let version = since("/**\n  Title.\n\n  # Meta\n\n  since: 1.4\n*/").unwrap();
+    assert_eq!(version, Version::parse("1.4.0").unwrap());
+
+    // The section wins over a `Since:` line.
+    let version = since("/**\n  Title.\n  Since: 1.0\n\n  # Since\n\n  2.0\n*/").unwrap();
+    assert_eq!(version.major, 2);
+
+    // Example code is not searched.
+    assert_eq!(
+        since("/**\n  Title.\n\n  # Example\n\n  ```\n  Since: 1.0\n  ```\n*/"),
+        None
+    );
+    assert_eq!(since("/**\n  Title.\n\n  # Since\n\n  forever\n*/"), None);
+}
+
+#[test]
+fn maintainers_and_authors() {
+    let doc = DocComment::parse(
+        "/**\n  Title.\n\n  # Maintainers\n\n  - Jane Doe <jane@example.org>\n  - @jdoe\n  - Max Mustermann (@max) <max@example.org>\n  Prose is ignored.\n  @extra\n\n  # Author\n\n  * Ada\n*/",
+    )
+    .unwrap();
+    assert!(doc.warnings.is_empty());
+
+    let maintainers = doc.maintainers();
+    assert_eq!(
+        maintainers[0],
+        Attribution {
+            name: Some("Jane Doe".to_string()),
+            email: Some("jane@example.org".to_string()),
+            handle: None,
+        }
+    );
+    assert_eq!(maintainers[1].to_string(), "@jdoe");
+    assert_eq!(maintainers[2].name.as_deref(), Some("Max Mustermann"));
+    assert_eq!(maintainers[2].handle.as_deref(), Some("max"));
+    assert_eq!(
+        maintainers[2].to_string(),
+        "Max Mustermann (@max) <max@example.org>"
+    );
+    assert_eq!(maintainers[3].handle.as_deref(), Some("extra"));
+    assert_eq!(maintainers.len(), 4);
+
+    let authors = doc.authors();
+    assert_eq!(authors.len(), 1);
+    assert_eq!(authors[0].to_string(), "Ada");
+}
+
+#[test]
+fn stability_markers() {
+    let stability = |input: &str| DocComment::parse(input).unwrap().stability();
+
+    assert_eq!(
+        stability("/**\n  Title.\n\n  # Stability\n\n  **Internal**: do not use.\n*/"),
+        Some(Stability::Internal)
+    );
+    assert_eq!(
+        stability("/**\n  Title. @experimental\n*/"),
+        Some(Stability::Experimental)
+    );
+    assert_eq!(
+        stability("/**\n  Title.\n\n  # Meta\n\n  stability: stable\n*/"),
+        Some(Stability::Stable)
+    );
+    // The section wins over a marker.
+    assert_eq!(
+        stability("/**\n  Title. @internal\n\n  # Stability\n\n  stable\n*/"),
+        Some(Stability::Stable)
+    );
+    assert_eq!(stability("/** Mail me@stable.org. */"), None);
+    assert_eq!(stability("/** Title. */"), None);
+    assert_eq!(Stability::Experimental.to_string(), "experimental");
+}
+
+#[test]
+fn section_iteration_helpers() {
+    let doc = DocComment::parse(
+        "/**\n  Desc.\n\n  # Warning\n\n  One.\n\n  # Note\n\n  Two.\n\n  # Caution\n\n  Three.\n\n  # Custom\n\n  Four.\n*/",
+    )
+    .unwrap();
+
+    let headings: Vec<_> = doc
+        .sections_of(&[SectionKind::Warning])
+        .map(|s| s.heading.as_str())
+        .collect();
+    assert_eq!(headings, ["Warning", "Caution"]);
+    assert_eq!(doc.warnings_content(), ["One.", "Three."]);
+
+    let long: Vec<_> = doc
+        .sections_where(|s| s.heading.len() > 5)
+        .map(|s| s.heading.as_str())
+        .collect();
+    assert_eq!(long, ["Warning", "Caution", "Custom"]);
+
+    assert_eq!(
+        doc.find_map_sections(|s| s.content.strip_suffix("o.")),
+        Some("Tw")
+    );
+    assert_eq!(doc.find_map_sections(|s| s.explicit_id.as_deref()), None);
+}
+
+#[test]
+fn indented_code_blocks() {
+    let input = "/**\n  Add one.\n\n  # Example\n\n      inc 1\n      => 2\n\n  # Arguments\n\n  - [x] A number.\n\n        Not code.\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    assert!(doc.examples().is_empty());
+
+    let options = ParserOptions::new().with_indented_code(true);
+    let doc = DocComment::parse_with(input, &options).unwrap();
+    let examples = doc.examples();
+    assert_eq!(examples.len(), 1);
+    assert_eq!(examples[0].input, "inc 1");
+    assert_eq!(examples[0].expected.as_deref(), Some("2"));
+    assert_eq!(doc.arguments()[0].name, "x");
+    assert!(!doc.section("Arguments").unwrap().content.contains("```"));
+}
+
+#[test]
+fn mixed_style_warnings() {
+    let input = "/**\n  Id.\n\n  id :: a -> a\n\n  # Type\n\n  ```\n  id :: a -> a\n  ```\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].kind, WarningKind::MixedStyle);
+
+    let input = "/**\n  Id.\n\n  Example:\n    id 1\n\n  # Type\n\n  ```\n  Example: not a label\n  ```\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    assert_eq!(doc.warnings.len(), 1);
+    assert!(doc.warnings[0].message.contains("'Example:'"));
+
+    // Either style alone is fine.
+    let legacy = "/**\n  Id.\n\n  id :: a -> a\n\n  Example:\n    id 1\n*/";
+    assert!(DocComment::parse(legacy).unwrap().warnings.is_empty());
+}
+
+#[test]
+fn summary_section() {
+    let input = "/**\n  Turn a list of attribute sets into one attribute set, with later\n  sets taking precedence.\n\n  # Summary\n\n  Merge a list of attribute sets.\n\n  Ignored.\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    assert!(doc.warnings.is_empty());
+    assert_eq!(doc.sections[0].kind(), SectionKind::Summary);
+    assert_eq!(
+        doc.summary().as_deref(),
+        Some("Merge a list of attribute sets.")
+    );
+    assert_eq!(
+        doc.title(),
+        Some("Turn a list of attribute sets into one attribute set, with later")
+    );
+
+    let doc = DocComment::parse("/**\n  # Type\n\n  ```\n  a\n  ```\n*/").unwrap();
+    assert_eq!(doc.summary(), None);
+}
+
+#[test]
+fn untidy_headings() {
+    let input = "/**\n  Id.\n\n  # Type:\n\n  ```\n  id :: a -> a\n  ```\n\n  # Examples ##\n\n  ```nix\n  id 1\n  ```\n\n  # C#\n\n  Sharp.\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    let headings: Vec<_> = doc.sections.iter().map(|s| s.heading.as_str()).collect();
+    assert_eq!(headings, ["Type", "Examples", "C#"]);
+    assert_eq!(doc.type_sig().as_deref(), Some("id :: a -> a\n"));
+    assert_eq!(doc.examples().len(), 1);
+    let kinds: Vec<_> = doc.warnings.iter().map(|w| &w.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            &WarningKind::HeadingStyle,
+            &WarningKind::HeadingStyle,
+            &WarningKind::UnknownSection
+        ]
+    );
+}
+
+#[test]
+fn doc_comments_hash() {
+    use std::collections::HashSet;
+
+    let a = DocComment::parse("/** Identity. */").unwrap();
+    let b = DocComment::parse("/**\n  Identity.\n*/").unwrap();
+    let set: HashSet<_> = [a.clone(), b, a].into_iter().collect();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn semantic_eq_ignores_reformatting() {
+    let a = DocComment::parse(
+        "/**\n  Sort a list.\n\n  # Arguments\n\n  - [xs] The list\n\n  # Example\n\n  ```nix\n  sort [ 2 1 ]\n  ```\n*/",
+    )
+    .unwrap();
+    let b = DocComment::parse(
+        "/**\n    Sort a\n    list.\n\n    # arguments\n\n    - [xs]   The list\n\n\n    # EXAMPLE\n\n    ```nix\n    sort [ 2   1 ]\n    ```\n*/",
+    )
+    .unwrap();
+    assert_ne!(a, b);
+    assert!(a.semantic_eq(&b));
+    assert_eq!(a.semantic_hash(), b.semantic_hash());
+}
+
+#[test]
+fn semantic_eq_sees_real_changes() {
+    let base = DocComment::parse("/**\n  Sort.\n\n  # Note\n\n  Stable.\n*/").unwrap();
+    for changed in [
+        "/**\n  Sort.\n\n  # Note {#stable}\n\n  Stable.\n*/",
+        "/**\n  Sort.\n\n  # Warning\n\n  Stable.\n*/",
+        "/**\n  Sort.\n\n  # Note\n\n  Stable.\n\n  [docs]: https://x.org\n*/",
+        "/**\n  Sorts.\n\n  # Note\n\n  Stable.\n*/",
+    ] {
+        let changed = DocComment::parse(changed).unwrap();
+        assert!(!base.semantic_eq(&changed));
+        assert_ne!(base.semantic_hash(), changed.semantic_hash());
+    }
+}
+
+#[test]
+fn semantic_hash_is_stable() {
+    // Cached hashes must stay valid across releases.
+    let doc = DocComment::parse("/**\n  Identity.\n\n  # Type\n\n  ```\n  id :: a -> a\n  ```\n*/")
+        .unwrap();
+    assert_eq!(doc.semantic_hash(), 2308207551898254361);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn view_collects_derived_fields() {
+    let doc = DocComment::parse(
+        "/**\n  Old sort.\n\n  Sorts [`xs`] using [`lib.compare`].\n\n  # Example\n\n  ```nix\n  sort [ 2 1 ]\n  => [ 1 2 ]\n  ```\n\n  # Note\n\n  Stable.\n\n  # Warning\n\n  Slow.\n\n  # Deprecated\n\n  Use `lib.sort`.\n\n  # See Also\n\n  - `lib.compare`\n  - `lib.sort`\n*/",
+    )
+    .unwrap();
+    let view = doc.view();
+    assert_eq!(view.title.as_deref(), Some("Old sort."));
+    assert_eq!(view.summary.as_deref(), Some("Old sort."));
+    assert_eq!(view.type_sig, None);
+    assert!(view.arguments.is_empty());
+    assert_eq!(view.examples, doc.examples());
+    assert_eq!(view.notes, ["Stable."]);
+    assert_eq!(view.warnings, ["Slow."]);
+    assert_eq!(view.deprecation.as_deref(), Some("Use `lib.sort`."));
+    assert_eq!(view.see_also, ["xs", "lib.compare", "lib.sort"]);
+    assert_eq!(DocView::from(&doc), view);
+}
+
+#[test]
+fn accessors_use_section_kinds() {
+    let doc = DocComment::parse(
+        "/**\n  f.\n\n  # Args\n\n  - [a] First\n\n  # Arguments\n\n  - [b] Later\n\n  # type\n\n  ```\n  f :: a -> a\n  ```\n\n  # Caution\n\n  Careful.\n\n  # DEPRECATED\n\n  Use g.\n*/",
+    )
+    .unwrap();
+    // The first arguments section wins, whatever its heading.
+    assert_eq!(doc.arguments()[0].name, "a");
+    assert_eq!(doc.type_sig().as_deref(), Some("f :: a -> a\n"));
+    assert_eq!(doc.warnings_content(), ["Careful."]);
+    assert!(doc.is_deprecated());
+    assert_eq!(doc.deprecation_notice(), Some("Use g."));
+
+    assert_eq!(
+        doc.section_by_kind(SectionKind::Arguments).unwrap().heading,
+        "Args"
+    );
+    let headings: Vec<_> = doc
+        .sections_by_kind(SectionKind::Arguments)
+        .map(|s| s.heading.as_str())
+        .collect();
+    assert_eq!(headings, ["Args", "Arguments"]);
+    assert!(doc.section_by_kind(SectionKind::Since).is_none());
+}
+
+#[test]
+fn admonitions_merge_callout_sections() {
+    let doc = DocComment::parse(
+        "/**\n  f.\n\n  # Notes\n\n  One.\n\n  # Example\n\n  ```nix\n  f\n  ```\n\n  # Warnings\n\n  Two.\n\n  # CAUTION\n\n  Three.\n\n  # Deprecated\n\n  Use g.\n*/",
+    )
+    .unwrap();
+    let admonitions = doc.admonitions();
+    let levels: Vec<_> = admonitions.iter().map(|a| a.level).collect();
+    assert_eq!(
+        levels,
+        [
+            AdmonitionLevel::Info,
+            AdmonitionLevel::Warning,
+            AdmonitionLevel::Danger,
+            AdmonitionLevel::Deprecated,
+        ]
+    );
+    assert_eq!(admonitions[2].heading, "CAUTION");
+    assert_eq!(admonitions[3].content, "Use g.");
+    assert_eq!(AdmonitionLevel::Danger.to_string(), "danger");
+    assert!(AdmonitionLevel::Info < AdmonitionLevel::Danger);
+
+    assert!(
+        DocComment::parse("/** Plain. */")
+            .unwrap()
+            .admonitions()
+            .is_empty()
+    );
+}
+
+#[test]
+fn short_summary_strips_markdown_and_keeps_code_whole() {
+    let doc = DocComment::parse(
+        "/**\n  Map `builtins.attrValues` over an _attribute set_, see\n  [the manual](https://nixos.org/manual).\n\n  Details.\n*/",
+    )
+    .unwrap();
+    assert_eq!(
+        doc.short_summary(200).as_deref(),
+        Some("Map builtins.attrValues over an attribute set, see the manual.")
+    );
+    assert_eq!(
+        doc.short_summary(30).as_deref(),
+        Some("Map builtins.attrValues over…")
+    );
+    // The code span would be cut, so the break goes before it.
+    assert_eq!(doc.short_summary(15).as_deref(), Some("Map…"));
+    assert_eq!(doc.short_summary(2).as_deref(), Some("…"));
+    assert_eq!(doc.short_summary(0).as_deref(), Some(""));
+
+    assert_eq!(
+        DocComment::parse("/**\n  # Type\n\n  a\n*/")
+            .unwrap()
+            .short_summary(10),
+        None
+    );
+}
+
+#[test]
+fn localized_replaces_sections_by_kind() {
+    let doc = DocComment::parse(
+        "/**\n  Sort a list.\n\n  # Examples (fr) {#sort-exemples}\n\n  Exemples.\n\n  # Examples {#sort-examples}\n\n  Examples.\n\n  # Type\n\n  `sort :: [a] -> [a]`\n\n  # Beispiel (de)\n\n  Beispiel.\n\n  # Warning (FR)\n\n  Attention.\n*/",
+    )
+    .unwrap();
+    // Translated headings are not unknown sections.
+    assert!(doc.warnings.is_empty());
+    assert_eq!(doc.sections[3].language(), Some("de"));
+
+    let fr = doc.localized("fr");
+    let headings: Vec<_> = fr.sections.iter().map(|s| s.heading.as_str()).collect();
+    assert_eq!(headings, ["Examples", "Type", "Warning"]);
+    assert_eq!(fr.description(), "Sort a list.");
+    assert_eq!(fr.sections[0].content, "Exemples.");
+    assert_eq!(fr.sections[0].anchor(), "sort-exemples");
+    assert_eq!(fr.warnings_content(), ["Attention."]);
+
+    let de = doc.localized("de");
+    let headings: Vec<_> = de.sections.iter().map(|s| s.heading.as_str()).collect();
+    assert_eq!(headings, ["Examples", "Type", "Beispiel"]);
+    assert_eq!(de.sections[0].anchor(), "sort-examples");
+
+    assert_eq!(doc.localized("en").sections.len(), 2);
+}
+
+#[test]
+fn bare_type_section_signature() {
+    let doc = DocComment::parse(
+        "/**\n  Fold.\n\n  # Type\n\n  foldl' ::\n    (b -> a -> b) -> b -> [a] -> b\n*/",
+    )
+    .unwrap();
+    assert_eq!(
+        doc.type_sig().as_deref(),
+        Some("foldl' :: (b -> a -> b) -> b -> [a] -> b")
+    );
+    assert_eq!(doc.warnings.len(), 1);
+    assert_eq!(doc.warnings[0].kind, WarningKind::MixedStyle);
+    assert!(doc.warnings[0].message.contains("not in a code block"));
+
+    // Prose alone is not a signature.
+    let doc = DocComment::parse("/**\n  Fold.\n\n  # Type\n\n  Like foldr.\n*/").unwrap();
+    assert_eq!(doc.type_sig(), None);
+    assert!(doc.warnings.is_empty());
+}
+
+#[test]
+fn code_blocks_cover_every_section() {
+    let doc = DocComment::parse(
+        "/**\n  Run a command.\n\n  ````md\n  ```nix\n  x\n  ```\n  ````\n\n  # Type\n\n  ```\n  run :: String -> Drv\n  ```\n\n  # See Also\n\n  - A list item:\n\n    ~~~bash\n    echo hi\n    ~~~\n\n  # Example\n\n  ```nix\n  run \"ls\"\n  => <drv>\n  ```\n*/",
+    )
+    .unwrap();
+    let blocks = doc.code_blocks();
+    let summary: Vec<_> = blocks
+        .iter()
+        .map(|b| {
+            (
+                b.section_kind.clone(),
+                b.language.as_deref(),
+                b.code.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (None, Some("md"), "```nix\nx\n```\n"),
+            (Some(SectionKind::Type), None, "run :: String -> Drv\n"),
+            (
+                Some(SectionKind::Unknown("see also".to_string())),
+                Some("bash"),
+                "echo hi\n"
+            ),
+            (
+                Some(SectionKind::Example),
+                Some("nix"),
+                "run \"ls\"\n=> <drv>\n"
+            ),
+        ]
+    );
+    for block in &blocks {
+        let text = &doc.raw_content[block.span.start..block.span.end];
+        assert!(text.starts_with(['`', '~']) && text.ends_with(['`', '~']));
+    }
+}
@@ -0,0 +1,369 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use std::path::Path;
+
+use nixdoc::chunk::ChunkOptions;
+use nixdoc::extract::{
+    binding_name_after, documented_items, extract_doc_comments, parse_source, parse_source_lossy,
+    read_lossy,
+};
+use nixdoc::{DocSet, ParseError, ParserOptions, SectionKind, Span, WarningKind};
+
+const LIB: &str = r#"{ lib }:
+{
+  /**
+    Filter an attribute set.
+
+    # Example
+
+    ```nix title="Keep truthy values"
+    filterAttrs (n: v: v) { a = true; b = false; }
+    => { a = true; }
+    ```
+  */
+  filterAttrs = pred: set: set;
+
+  /**
+    Map over values.
+
+    # Examples
+
+    ```nix
+    mapAttrs (n: v: v + 1) { a = 1; }
+    ```
+
+    ```bash
+    nix eval .#lib.mapAttrs
+    ```
+  */
+  attrsets.mapAttrs = f: set: set;
+}
+"#;
+
+#[test]
+fn binding_names() {
+    assert_eq!(binding_name_after("  foo = 1;", 0), Some("foo".to_string()));
+    assert_eq!(
+        binding_name_after("\n  a.b-c = 1;", 0),
+        Some("a.b-c".to_string())
+    );
+    assert_eq!(binding_name_after(" x == 1", 0), None);
+    assert_eq!(binding_name_after("{ lib }: {", 0), None);
+    assert_eq!(binding_name_after("", 0), None);
+}
+
+#[test]
+fn documented_items_are_named() {
+    let items = documented_items(LIB, None);
+    let names: Vec<_> = items.iter().map(|i| i.name.as_deref()).collect();
+    assert_eq!(names, vec![Some("filterAttrs"), Some("attrsets.mapAttrs")]);
+    assert_eq!(items[0].line, 3);
+}
+
+#[test]
+fn lossy_extraction_keeps_a_trailing_unclosed_comment() {
+    let src =
+        "{\n  /** The identity. */\n  id = x: x;\n\n  /**\n    Half-typed.\n\n    # Arguments";
+    assert_eq!(parse_source(src, None).len(), 1);
+
+    let items = parse_source_lossy(src, None);
+    assert_eq!(items.len(), 2);
+    let last = items[1].as_ref().unwrap();
+    assert_eq!(last.line, 5);
+    assert_eq!(last.span.end, src.len());
+    assert_eq!(last.doc.title(), Some("Half-typed."));
+    assert_eq!(last.doc.warnings[0].kind, WarningKind::UnclosedComment);
+    assert!(items[0].as_ref().unwrap().doc.warnings.is_empty());
+}
+
+#[test]
+fn comment_end_inside_escaped_fence() {
+    let src = r#"{
+  /**
+    Document a binding.
+
+    # Example
+
+    ````nix
+    /** The identity. */
+    id = x: x;
+    ````
+  */
+  document = x: x;
+}"#;
+    let items = documented_items(src, None);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name.as_deref(), Some("document"));
+    assert!(
+        items[0].doc.examples()[0]
+            .code
+            .contains("/** The identity. */")
+    );
+    assert!(items[0].doc.warnings.is_empty());
+    assert_eq!(extract_doc_comments(src)[0].0, items[0].span);
+}
+
+#[test]
+fn ambiguous_comment_end_is_cut_with_a_warning() {
+    let src =
+        "{\n  /**\n    Doc.\n\n    ```nix\n    /** Inner. */\n    ```\n  */\n  doc = x: x;\n}";
+    let items = documented_items(src, None);
+    assert_eq!(items.len(), 1);
+    assert!(items[0].doc.raw_content.ends_with("Inner."));
+    assert_eq!(
+        items[0].doc.warnings[0].kind,
+        WarningKind::AmbiguousCommentEnd
+    );
+
+    // An escaped block that never closes is cut at the first `*/` too.
+    let src = "/**\n  ````\n  /** a */\n*/\nx = 1;";
+    let items = documented_items(src, None);
+    assert_eq!(items[0].span.end, src.find("a */").unwrap() + 4);
+    assert_eq!(
+        items[0].doc.warnings[0].kind,
+        WarningKind::AmbiguousCommentEnd
+    );
+
+    // A plain `*/` after a closed block is not ambiguous.
+    let src = "/**\n  ```\n  x\n  ```\n*/";
+    assert!(documented_items(src, None)[0].doc.warnings.is_empty());
+}
+
+#[test]
+fn comment_starts_inside_strings_and_comments_are_skipped() {
+    let src = r#"{
+  glob = "src/**/*.nix";
+  # See /** the docs */ upstream.
+  /* Not /** a doc */
+  script = ''
+    echo '/**' ''${x} ''' "/**"
+  '';
+  f'' = "${ { a = "/**"; }.a } \" /**";
+  /** Real. */
+  real = 1;
+  /**/
+  /** Also real. */
+  also = 2;
+}"#;
+    let comments = extract_doc_comments(src);
+    let texts: Vec<&str> = comments.iter().map(|(_, text)| text.as_str()).collect();
+    assert_eq!(texts, ["/** Real. */", "/** Also real. */"]);
+    for (span, text) in &comments {
+        assert_eq!(&src[span.start..span.end], text);
+    }
+}
+
+#[test]
+fn identifiers_ending_in_quotes_do_not_open_strings() {
+    let src = "let x'' = 1; in {\n  /** Documented. */\n  y = x'';\n}";
+    let comments = extract_doc_comments(src);
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].1, "/** Documented. */");
+}
+
+#[test]
+fn lossy_reading_decodes_legacy_files() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/encoding/latin1.nix");
+    assert!(DocSet::from_paths(&[&path]).is_err());
+
+    let set = DocSet::from_paths_lossy(&[&path]).unwrap();
+    assert_eq!(set.len(), 1);
+    assert_eq!(
+        set.items()[0].doc.title(),
+        Some("Returns «café» € 5, with ✓ kept.")
+    );
+    assert_eq!(set.file_warnings().len(), 1);
+    assert_eq!(set.file_warnings()[0].file, path);
+    assert!(set.file_warnings()[0].message.contains("decoded 4 byte(s)"));
+
+    let utf8 = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures/asserts.nix");
+    let (_, warning) = read_lossy(&utf8).unwrap();
+    assert_eq!(warning, None);
+}
+
+#[test]
+fn examples_index_entries() {
+    let mut set = DocSet::new();
+    set.add_source(LIB, Some("lib/attrsets.nix".as_ref()));
+    let index = set.examples_index();
+    assert_eq!(index.entries.len(), 3);
+
+    let first = &index.entries[0];
+    assert_eq!(first.identifier.as_deref(), Some("filterAttrs"));
+    assert_eq!(first.title.as_deref(), Some("Keep truthy values"));
+    assert_eq!(first.expected.as_deref(), Some("{ a = true; }"));
+    assert_eq!(first.file.as_deref(), Some(Path::new("lib/attrsets.nix")));
+}
+
+#[test]
+fn examples_index_search_and_filter() {
+    let mut set = DocSet::new();
+    set.add_source(LIB, None);
+    let index = set.examples_index();
+
+    let hits = index.search("FILTERATTRS");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].identifier.as_deref(), Some("filterAttrs"));
+
+    assert_eq!(index.search("mapAttrs").len(), 2);
+    assert_eq!(index.by_language("nix").len(), 2);
+    assert!(index.search("nonexistent").is_empty());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn examples_index_json() {
+    let mut set = DocSet::new();
+    set.add_source(LIB, None);
+    let json: serde_json::Value = serde_json::from_str(&set.examples_index().to_json()).unwrap();
+    assert_eq!(json[0]["identifier"], "filterAttrs");
+    assert_eq!(json[2]["language"], "bash");
+}
+
+#[test]
+fn stats_and_failures() {
+    let mut set = DocSet::new();
+    set.add_source(LIB, Some("lib/attrsets.nix".as_ref()));
+    set.add_source(
+        "{\n  /** */\n  x = 1;\n  /**\n    y.\n\n    # See Also\n\n    x\n  */\n  y = 2;\n}",
+        Some("lib/other.nix".as_ref()),
+    );
+    set.add_source("{ }", Some("lib/empty.nix".as_ref()));
+
+    let stats = set.stats();
+    assert_eq!(stats.files, 3);
+    assert_eq!(stats.comments, 4);
+    assert_eq!(stats.parsed, 3);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(stats.sections, 3);
+    assert_eq!(stats.warnings.get(&WarningKind::UnknownSection), Some(&1));
+
+    assert_eq!(set.failures()[0].error, ParseError::EmptyComment);
+    assert_eq!(set.failures()[0].line, 2);
+    assert_eq!(set.failures()[0].error_span, Span::new(7, 8));
+
+    let counts = set.warning_counts();
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts.get(&Some(Path::new("lib/other.nix"))), Some(&1));
+}
+
+#[test]
+fn legacy_comments_count_when_enabled() {
+    let src = "{\n  /* Old style.\n\n     # Since\n\n     1.0\n  */\n  old = 1;\n  /* */\n  empty = 2;\n  /** New style. */\n  new = 3;\n}";
+
+    let mut set = DocSet::new();
+    set.add_source(src, None);
+    assert_eq!(set.stats().comments, 1);
+
+    let options = ParserOptions::new().with_legacy_comments(true);
+    let mut set = DocSet::new().with_options(options);
+    set.add_source(src, None);
+    let stats = set.stats();
+    assert_eq!(stats.comments, 3);
+    assert_eq!(stats.parsed, 2);
+    assert_eq!(stats.warnings.get(&WarningKind::LegacyComment), Some(&1));
+
+    let old = set.find("old")[0];
+    assert!(old.doc.is_legacy());
+    assert_eq!(old.doc.since().unwrap().to_string(), "1.0");
+    assert!(!set.find("new")[0].doc.is_legacy());
+    assert_eq!(set.failures()[0].error, ParseError::EmptyComment);
+}
+
+#[test]
+fn section_histogram_and_iteration() {
+    let mut set = DocSet::new();
+    set.add_source(LIB, None);
+
+    let histogram = set.section_histogram();
+    assert_eq!(histogram.get(&SectionKind::Example), Some(&1));
+    assert_eq!(histogram.get(&SectionKind::Examples), Some(&1));
+
+    assert_eq!(set.len(), 2);
+    assert_eq!((&set).into_iter().count(), 2);
+    assert_eq!(set.find("filterAttrs").len(), 1);
+
+    let filtered: DocSet = set
+        .iter()
+        .filter(|item| item.name.as_deref() == Some("filterAttrs"))
+        .cloned()
+        .collect();
+    assert_eq!(filtered.len(), 1);
+}
+
+#[test]
+fn grouping_by_tag() {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /**\n    Head.\n\n    # Tags\n\n    - lists\n    - `Core`\n  */\n  head = 1;\n\n  /**\n    Tail.\n\n    # Categories\n\n    lists, lists\n  */\n  tail = 2;\n\n  /** Untagged. */\n  id = 3;\n}",
+        None,
+    );
+    assert_eq!(set.items()[0].doc.tags(), ["lists", "Core"]);
+    assert_eq!(set.items()[1].doc.tags(), ["lists"]);
+
+    let groups = set.by_tag();
+    assert_eq!(groups.keys().collect::<Vec<_>>(), ["Core", "lists"]);
+    let names: Vec<_> = groups["lists"]
+        .iter()
+        .map(|item| item.name.as_deref())
+        .collect();
+    assert_eq!(names, [Some("head"), Some("tail")]);
+
+    assert_eq!(set.with_tag("core").len(), 1);
+    assert!(set.with_tag("strings").is_empty());
+}
+
+#[test]
+fn chunks_split_long_sections_with_overlap() {
+    let words: Vec<String> = (1..=30).map(|i| format!("word{i:02}")).collect();
+    let src = format!(
+        "{{\n  /**\n    {}\n\n    # Note\n\n    Short **note**.\n  */\n  f = x: x;\n\n  /** Unnamed. */\n  1\n}}",
+        words.join(" ")
+    );
+    let mut set = DocSet::new();
+    set.add_source(&src, Some("f.nix".as_ref()));
+
+    let options = ChunkOptions::new()
+        .with_max_chars(50)
+        .with_overlap(14)
+        .with_header(false);
+    let chunks = set.chunks(&options);
+    let texts: Vec<_> = chunks.iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        [
+            "word01 word02 word03 word04 word05 word06 word07",
+            "word06 word07 word08 word09 word10 word11 word12",
+            "word11 word12 word13 word14 word15 word16 word17",
+            "word16 word17 word18 word19 word20 word21 word22",
+            "word21 word22 word23 word24 word25 word26 word27",
+            "word26 word27 word28 word29 word30",
+            "Short note.",
+            "Unnamed.",
+        ]
+    );
+    assert!(chunks.iter().all(|c| c.text.chars().count() <= 50));
+    assert_eq!(chunks[0].identifier.as_deref(), Some("f"));
+    assert_eq!(chunks[0].anchor.as_deref(), Some("f"));
+    assert_eq!(chunks[0].file.as_deref(), Some("f.nix".as_ref()));
+    assert_eq!(chunks[6].heading.as_deref(), Some("Note"));
+    assert_eq!(chunks[6].anchor.as_deref(), Some("f-note"));
+    assert_eq!(chunks[7].identifier, None);
+    assert_eq!(chunks[7].anchor, None);
+
+    // Headers count towards the size.
+    let chunks = set.chunks(&options.with_header(true));
+    assert!(chunks.iter().all(|c| c.text.chars().count() <= 50));
+    assert!(chunks[0].text.starts_with("f\n\nword01"));
+}
+
+#[test]
+fn chunks_keep_code_blocks_verbatim() {
+    let src = "{\n  /**\n    Quoting.\n\n    ````md\n    ```\n    **kept**\n    ````\n  */\n  f = x: x;\n}";
+    let mut set = DocSet::new();
+    set.add_source(src, None);
+
+    let chunks = set.chunks(&ChunkOptions::new().with_header(false));
+    assert_eq!(chunks[0].text, "Quoting.\n\n```\n**kept**");
+}
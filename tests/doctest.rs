@@ -0,0 +1,139 @@
+// All tests in this file require `--features doctest`.
+#![cfg(all(feature = "doctest", unix))]
+
+use nixdoc::DocComment;
+use nixdoc::doctest::{Doctest, Evaluator, Location, Outcome};
+
+/// An evaluator that prints its expression back, so two examples compare
+/// equal exactly when their source text is identical.
+fn echo() -> Doctest {
+    Doctest::new(Evaluator::Command {
+        program: "echo".to_string(),
+        args: Vec::new(),
+    })
+}
+
+fn first_example(input: &str) -> nixdoc::Example {
+    DocComment::parse(input).unwrap().examples().remove(0)
+}
+
+#[test]
+fn passes_when_results_match() {
+    let example = first_example("/**\n  f.\n\n  # Example\n\n  ```nix\n  1\n  => 1\n  ```\n*/");
+    assert_eq!(echo().run_example(&example), Outcome::Passed);
+}
+
+#[test]
+fn fails_when_results_differ() {
+    let example = first_example("/**\n  f.\n\n  # Example\n\n  ```nix\n  1\n  => 2\n  ```\n*/");
+    assert!(matches!(
+        echo().run_example(&example),
+        Outcome::Failed { .. }
+    ));
+}
+
+#[test]
+fn skipped_without_expectation() {
+    let example = first_example("/**\n  f.\n\n  # Example\n\n  ```nix\n  f 1\n  ```\n*/");
+    assert_eq!(echo().run_example(&example), Outcome::Skipped);
+}
+
+#[test]
+fn evaluator_failure_is_an_error() {
+    let runner = Doctest::new(Evaluator::Command {
+        program: "false".to_string(),
+        args: Vec::new(),
+    });
+    let example = first_example("/**\n  f.\n\n  # Example\n\n  ```nix\n  1\n  => 1\n  ```\n*/");
+    let outcome = runner.run_example(&example);
+    assert!(matches!(outcome, Outcome::Error(_)));
+    assert!(!outcome.is_success());
+}
+
+#[test]
+fn run_source_reports_locations_and_skips_other_languages() {
+    let src = "{\n  /**\n    f.\n\n    # Examples\n\n    ```bash\n    echo hi\n    ```\n\n    ## Basic\n\n    ```nix\n    1\n    => 1\n    ```\n  */\n  f = x: x;\n}\n";
+    let results = echo().run_source(src, Some("lib/f.nix".as_ref()));
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0].location,
+        Location {
+            file: Some("lib/f.nix".into()),
+            line: 13,
+        }
+    );
+    assert_eq!(results[0].location.to_string(), "lib/f.nix:13");
+    // The `bash` example is not a doctest, so it is not counted.
+    assert_eq!(results[0].index, 1);
+    assert_eq!(results[0].title.as_deref(), Some("Basic"));
+    assert_eq!(results[0].outcome, Outcome::Passed);
+}
+
+#[test]
+fn no_run_flag_skips() {
+    let example =
+        first_example("/**\n  f.\n\n  # Example\n\n  ```nix no-run\n  1\n  => 2\n  ```\n*/");
+    assert_eq!(echo().run_example(&example), Outcome::Skipped);
+}
+
+#[test]
+fn expect_error_flag() {
+    let example = first_example(
+        "/**\n  f.\n\n  # Example\n\n  ```nix expect-error\n  throw \"x\"\n  ```\n*/",
+    );
+    let failing = Doctest::new(Evaluator::Command {
+        program: "false".to_string(),
+        args: Vec::new(),
+    });
+    assert_eq!(failing.run_example(&example), Outcome::Passed);
+    assert!(matches!(
+        echo().run_example(&example),
+        Outcome::Failed { .. }
+    ));
+}
+
+#[test]
+fn setup_bindings_are_in_scope() {
+    let doc = DocComment::parse(
+        "/**\n  f.\n\n  # Example\n\n  ```nix setup\n  x = 1;\n  ```\n\n  ```nix\n  x\n  => x\n  ```\n*/",
+    )
+    .unwrap();
+    let results = echo().run_doc(
+        &doc,
+        &Location {
+            file: None,
+            line: 1,
+        },
+    );
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].outcome, Outcome::Skipped);
+    assert_eq!(results[1].outcome, Outcome::Passed);
+}
+
+#[test]
+fn every_pair_of_a_block_is_run() {
+    let src = "{\n  /**\n    Upper case.\n\n    # Example\n\n    ```nix\n    toUpper \"a\"\n    => toUpper \"a\"\n    toUpper \"b\"\n    => \"B\"\n    ```\n  */\n  toUpper = s: s;\n}\n";
+    let results = echo().run_source(src, None);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].index, 1);
+    assert_eq!(results[0].input, "toUpper \"a\"");
+    assert_eq!(results[0].outcome, Outcome::Passed);
+    assert_eq!(results[1].index, 2);
+    assert_eq!(results[1].input, "toUpper \"b\"");
+    assert!(matches!(results[1].outcome, Outcome::Failed { .. }));
+    assert!(
+        results
+            .iter()
+            .all(|r| r.location.to_string() == "<input>:7")
+    );
+
+    let doc = DocComment::parse(&src[4..src.find("*/").unwrap() + 2]).unwrap();
+    let results = echo().run_doc(
+        &doc,
+        &Location {
+            file: None,
+            line: 3,
+        },
+    );
+    assert_eq!(results[0].location.line, 7);
+}
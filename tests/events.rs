@@ -0,0 +1,204 @@
+use std::fs;
+use std::path::PathBuf;
+
+use nixdoc::parser::{ParseEvent, events, events_with};
+use nixdoc::{DocComment, ParseError, ParserOptions, UnicodePolicy};
+
+#[test]
+fn unclosed_fences_and_sections_are_balanced() {
+    let input = "/** Title.\n    More.\n\n    # Examples {#ex}\n\n    ~~~\n    # not a heading\n*/";
+    let events: Vec<_> = events(input).collect();
+    assert_eq!(
+        events,
+        [
+            ParseEvent::DescriptionText("Title."),
+            ParseEvent::DescriptionText("   More."),
+            ParseEvent::BlankLine,
+            ParseEvent::SectionStart {
+                heading: "Examples",
+                id: Some("ex"),
+            },
+            ParseEvent::BlankLine,
+            ParseEvent::FenceStart { info: "" },
+            ParseEvent::CodeLine("   # not a heading"),
+            ParseEvent::FenceEnd,
+            ParseEvent::SectionEnd,
+        ]
+    );
+}
+
+#[test]
+fn consecutive_sections() {
+    let input = "/**\n  # Type\n  ```\n  int\n  ```\n  # Note:\n  Careful.\n*/";
+    let events: Vec<_> = events(input).collect();
+    assert_eq!(
+        events,
+        [
+            ParseEvent::SectionStart {
+                heading: "Type",
+                id: None,
+            },
+            ParseEvent::FenceStart { info: "" },
+            ParseEvent::CodeLine("int"),
+            ParseEvent::FenceEnd,
+            ParseEvent::SectionEnd,
+            ParseEvent::SectionStart {
+                heading: "Note",
+                id: None,
+            },
+            ParseEvent::SectionText("Careful."),
+            ParseEvent::SectionEnd,
+        ]
+    );
+}
+
+#[test]
+fn errors_are_the_only_event() {
+    let only = |input| events(input).collect::<Vec<_>>();
+    assert_eq!(
+        only("/* plain */"),
+        [ParseEvent::Error(ParseError::NotDocComment)]
+    );
+    assert_eq!(
+        only("/** open"),
+        [ParseEvent::Error(ParseError::UnclosedComment)]
+    );
+    assert_eq!(
+        only("/**  */"),
+        [ParseEvent::Error(ParseError::EmptyComment)]
+    );
+}
+
+/// A backtick in a backtick fence's info string opens a block, as it does
+/// for [`DocComment::parse`].
+#[test]
+fn fences_match_the_parser() {
+    let input = "/**\n  ```nix `x`\n  # not a heading\n  ```\n*/";
+    assert!(DocComment::parse(input).unwrap().sections.is_empty());
+    let events: Vec<_> = events(input).collect();
+    assert_eq!(
+        events,
+        [
+            ParseEvent::FenceStart { info: "nix `x`" },
+            ParseEvent::CodeLine("# not a heading"),
+            ParseEvent::FenceEnd,
+        ]
+    );
+}
+
+#[test]
+fn options_choose_the_comment() {
+    let collect = |input, options: &ParserOptions| events_with(input, options).collect::<Vec<_>>();
+
+    let custom = ParserOptions::new().with_delimiters("{-|", "-}");
+    assert_eq!(
+        collect("{-| Add one. -}", &custom),
+        [ParseEvent::DescriptionText("Add one.")]
+    );
+    assert_eq!(
+        collect("/** Add one. */", &custom),
+        [ParseEvent::Error(ParseError::NotDocComment)]
+    );
+
+    let legacy = ParserOptions::new().with_legacy_comments(true);
+    assert_eq!(
+        collect("/* Filter.\n     More.\n*/", &legacy),
+        [
+            ParseEvent::DescriptionText("Filter."),
+            ParseEvent::DescriptionText("More."),
+        ]
+    );
+
+    let recover = ParserOptions::new().with_recover_unclosed(true);
+    assert_eq!(
+        collect("/** Open.\n  # Note", &recover),
+        [
+            ParseEvent::DescriptionText("Open."),
+            ParseEvent::SectionStart {
+                heading: "Note",
+                id: None,
+            },
+            ParseEvent::SectionEnd,
+        ]
+    );
+
+    let bom = "\u{feff}/** Marked. */";
+    assert_eq!(
+        collect(bom, &ParserOptions::new()),
+        [ParseEvent::DescriptionText("Marked.")]
+    );
+    assert_eq!(
+        collect(
+            bom,
+            &ParserOptions::new().with_unicode(UnicodePolicy::Preserve)
+        ),
+        [ParseEvent::Error(ParseError::NotDocComment)]
+    );
+}
+
+#[test]
+fn options_choose_the_blocks() {
+    let input = "/**\n  Text.\n\n      # Indented\n\n      more\n\n  # Example\n*/";
+    let options = ParserOptions::new().with_max_heading_indent(4);
+    let headings: Vec<_> = events_with(input, &options)
+        .filter_map(|event| match event {
+            ParseEvent::SectionStart { heading, .. } => Some(heading),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(headings, ["Indented", "Example"]);
+
+    let options = ParserOptions::new().with_indented_code(true);
+    let events: Vec<_> = events_with(input, &options).collect();
+    assert_eq!(
+        events,
+        [
+            ParseEvent::DescriptionText("Text."),
+            ParseEvent::BlankLine,
+            ParseEvent::FenceStart { info: "" },
+            ParseEvent::CodeLine("# Indented"),
+            ParseEvent::CodeLine(""),
+            ParseEvent::CodeLine("more"),
+            ParseEvent::FenceEnd,
+            ParseEvent::BlankLine,
+            ParseEvent::SectionStart {
+                heading: "Example",
+                id: None,
+            },
+            ParseEvent::SectionEnd,
+        ]
+    );
+    let doc = DocComment::parse_with(input, &options).unwrap();
+    assert_eq!(doc.sections.len(), 1);
+}
+
+/// The events agree with [`DocComment::parse`] on where sections start.
+#[test]
+fn sections_match_the_parser() {
+    let dir: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "tests",
+        "fixtures",
+        "doc_comments",
+    ]
+    .iter()
+    .collect();
+    for entry in fs::read_dir(dir).unwrap().flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "txt") {
+            continue;
+        }
+        let input = fs::read_to_string(&path).unwrap();
+        let Ok(doc) = DocComment::parse(&input) else {
+            continue;
+        };
+        let headings: Vec<_> = events(&input)
+            .filter_map(|event| match event {
+                ParseEvent::SectionStart { heading, .. } => Some(heading.to_string()),
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<_> = doc.sections.iter().map(|s| s.heading.clone()).collect();
+        assert_eq!(headings, expected, "{}", path.display());
+    }
+}
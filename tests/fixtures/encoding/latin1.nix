@@ -0,0 +1,6 @@
+{
+  /**
+    Returns caf  5, with ✓ kept.
+  */
+  cafe = 5;
+}
@@ -0,0 +1,136 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::format::{Style, apply_edits, format_range};
+use nixdoc::lsp::{Position, Range};
+
+const SRC: &str = "{
+  /**
+      Concatenate strings.   
+    # Arguments
+    - [sep] The separator
+
+
+
+    # Example
+
+    ```nix
+    concatStringsSep \", \" [ ]
+
+
+    => \"\"
+    ```
+      */
+  concatStringsSep = sep: list: \"\";
+
+  /**
+    Already formatted.
+  */
+  id = x: x;
+}";
+
+fn whole_file() -> Range {
+    Range {
+        start: Position::default(),
+        end: Position::of_offset(SRC, SRC.len()),
+    }
+}
+
+#[test]
+fn formats_overlapping_comments() {
+    let edits = format_range(SRC, whole_file(), &Style::new());
+    assert_eq!(edits.len(), 1);
+    expect_test::expect![[r#"
+        {
+          /**
+            Concatenate strings.
+
+            # Arguments
+
+            - [sep] The separator
+
+            # Example
+
+            ```nix
+            concatStringsSep ", " [ ]
+
+
+            => ""
+            ```
+          */
+          concatStringsSep = sep: list: "";
+
+          /**
+            Already formatted.
+          */
+          id = x: x;
+        }"#]]
+    .assert_eq(&apply_edits(SRC, &edits));
+}
+
+#[test]
+fn edits_are_minimal() {
+    let edits = format_range(SRC, whole_file(), &Style::new());
+    let edit = &edits[0];
+    // The edit starts at the first difference, after `/**\n    `.
+    assert_eq!(&SRC[..edit.span.start], "{\n  /**\n    ");
+    assert_eq!(
+        edit.range.start,
+        Position {
+            line: 2,
+            character: 4
+        }
+    );
+    // And ends at the last, in the indentation of the closing `*/`.
+    assert!(SRC[edit.span.end..].starts_with("  */\n  concatStringsSep"));
+}
+
+#[test]
+fn only_touched_comments_change() {
+    let line = SRC.lines().position(|l| l.contains("Already")).unwrap() as u32;
+    let range = Range {
+        start: Position { line, character: 0 },
+        end: Position { line, character: 0 },
+    };
+    assert!(format_range(SRC, range, &Style::new()).is_empty());
+}
+
+#[test]
+fn style_options() {
+    let src = "  /** Id. */";
+    let range = Range::default();
+    let style = Style::new().with_indent(4).with_keep_single_line(false);
+    let edits = format_range(src, range, &style);
+    assert_eq!(apply_edits(src, &edits), "  /**\n      Id.\n  */");
+}
+
+#[test]
+fn width_reflows_prose() {
+    let src = "{\n  /**\n    Apply `f` to every\n    element of the list, keeping the order. See [`lib.imap0`].\n\n    ```nix\n    map (x: x * 2) [ 1 2 3 ] # a long line that must not be wrapped\n    ```\n  */\n  map = f: list: list;\n}";
+    let style = Style::new().with_width(40);
+    let all = Range {
+        start: Position::default(),
+        end: Position::of_offset(src, src.len()),
+    };
+    let edits = format_range(src, all, &style);
+    assert_eq!(
+        apply_edits(src, &edits),
+        "{\n  /**\n    Apply `f` to every element of the\n    list, keeping the order. See\n    [`lib.imap0`].\n\n    ```nix\n    map (x: x * 2) [ 1 2 3 ] # a long line that must not be wrapped\n    ```\n  */\n  map = f: list: list;\n}"
+    );
+    let formatted = apply_edits(src, &edits);
+    assert!(format_range(&formatted, all, &style).is_empty());
+}
+
+#[test]
+fn normalizes_after_longer_closing_fence() {
+    let src = "/**\n  Run.\n\n  ```nix\n  f 1\n  ````\n  # Example\n\n\n  Text.\n*/";
+    let all = Range {
+        start: Position::default(),
+        end: Position::of_offset(src, src.len()),
+    };
+    let edits = format_range(src, all, &Style::new());
+    assert_eq!(
+        apply_edits(src, &edits),
+        "/**\n  Run.\n\n  ```nix\n  f 1\n  ````\n\n  # Example\n\n  Text.\n*/"
+    );
+}
@@ -0,0 +1,174 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::gettext::{Catalog, PoError, messages, to_pot};
+use nixdoc::{DocComment, DocSet, SectionKind};
+
+const STRINGS: &str = r#"{
+  /**
+    Concatenate a list of strings.
+    Nothing is put between them.
+
+    # Type
+
+    ```
+    concatStrings :: [string] -> string
+    ```
+
+    # Examples
+    :::{.example}
+    ## `lib.strings.concatStrings` usage example
+
+    ```nix
+    concatStrings ["foo" "bar"]
+    ```
+
+    :::
+
+    # Meta
+
+    category: strings
+  */
+  concatStrings = builtins.concatStringsSep "";
+
+  /**
+    Split a string.
+
+    # Examples
+    :::{.example}
+    ## Usage example {#split-example}
+
+    ```nix
+    split "a"
+    ```
+    :::
+  */
+  split = s: [ s ];
+
+  /** Concatenate a list of strings.
+    Nothing is put between them.
+  */
+  concat = concatStrings;
+}"#;
+
+fn set() -> DocSet {
+    let mut set = DocSet::new();
+    set.add_source(STRINGS, Some("lib/strings.nix".as_ref()));
+    set
+}
+
+#[test]
+fn messages_leave_code_and_tool_sections_out() {
+    let set = set();
+    assert_eq!(
+        messages(&set.items()[0].doc),
+        [
+            "Concatenate a list of strings.\nNothing is put between them.",
+            "`lib.strings.concatStrings` usage example",
+        ]
+    );
+    assert_eq!(
+        messages(&set.items()[1].doc),
+        ["Split a string.", "Usage example"]
+    );
+}
+
+#[test]
+fn pot_lists_each_message_once_with_its_references() {
+    let pot = to_pot(&set());
+    assert!(
+        pot.starts_with(
+            "msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n"
+        )
+    );
+    assert!(pot.contains(
+        "\n#. concatStrings, concat\n#: lib/strings.nix:2\n#: lib/strings.nix:42\nmsgid \"\"\n\
+         \"Concatenate a list of strings.\\n\"\n\"Nothing is put between them.\"\nmsgstr \"\"\n"
+    ));
+    assert!(pot.contains("msgid \"`lib.strings.concatStrings` usage example\"\n"));
+    assert_eq!(pot.matches("msgid").count(), 5);
+    assert!(!pot.contains("category"));
+    assert!(!pot.contains("concatStrings ["));
+}
+
+#[test]
+fn translates_messages_and_keeps_structure() {
+    let po = r#"
+# German translation.
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+#. concatStrings, concat
+#: lib/strings.nix:2
+msgid ""
+"Concatenate a list of strings.\n"
+"Nothing is put between them."
+msgstr ""
+"Verkettet eine Liste von Zeichenketten.\n"
+"Ohne \"Trenner\"."
+
+#, fuzzy
+msgid "Split a string."
+msgstr "Teilt eine Zeichenkette."
+
+msgid "Usage example"
+msgstr "Anwendungsbeispiel"
+
+msgid "`lib.strings.concatStrings` usage example"
+msgstr ""
+"#;
+    let catalog = Catalog::parse(po).unwrap();
+    assert_eq!(catalog.len(), 2);
+
+    let german = catalog.translate_set(&set());
+    let concat = &german.items()[0].doc;
+    assert_eq!(
+        concat.description(),
+        "Verkettet eine Liste von Zeichenketten.\nOhne \"Trenner\"."
+    );
+    assert_eq!(
+        concat.type_sig().as_deref(),
+        Some("concatStrings :: [string] -> string\n")
+    );
+    assert!(
+        concat
+            .raw_content
+            .contains("## `lib.strings.concatStrings` usage example")
+    );
+    assert_eq!(concat.sections.last().unwrap().kind(), SectionKind::Meta);
+
+    let split = &german.items()[1].doc;
+    assert_eq!(split.description(), "Split a string.");
+    assert!(
+        split
+            .raw_content
+            .contains("## Anwendungsbeispiel {#split-example}")
+    );
+    assert_eq!(german.len(), 3);
+}
+
+#[test]
+fn untranslatable_comments_are_unchanged() {
+    let doc = DocComment::parse("/** Hello. */").unwrap();
+    let mut catalog = Catalog::new();
+    assert_eq!(catalog.translate(&doc), doc);
+    // A translation that leaves the comment empty cannot be applied.
+    catalog.insert("Hello.", " ");
+    assert_eq!(catalog.translate(&doc).raw_content, doc.raw_content);
+}
+
+#[test]
+fn malformed_po_reports_the_line() {
+    assert_eq!(
+        Catalog::parse("msgid \"a\"\nmsgstr \"b\n").unwrap_err(),
+        PoError {
+            line: 2,
+            message: "malformed string".to_string(),
+        }
+    );
+    assert_eq!(
+        Catalog::parse("\"orphan\"").unwrap_err().to_string(),
+        "invalid PO file at line 1: string outside an entry"
+    );
+}
@@ -0,0 +1,69 @@
+use nixdoc::highlight::{TokenKind, tokens};
+
+fn highlighted(text: &str) -> Vec<(&str, TokenKind)> {
+    tokens(text)
+        .into_iter()
+        .map(|(span, kind)| (&text[span.start..span.end], kind))
+        .collect()
+}
+
+#[test]
+fn classifies_nixdoc_structure() {
+    let text = r#"/**
+  Concatenate strings with a separator.
+
+  # Type {#concat-type}
+
+  ```
+  concatStringsSep :: String -> [String]
+    -> String
+  ```
+
+  # Arguments
+
+  - [ sep ] The separator
+  - [list] The strings
+
+  # Examples
+
+  ```nix title="Commas"
+  # Not a heading
+  concatStringsSep ", " [ "a" "b" ]
+  => "a, b"
+  => not a marker
+  ```
+*/"#;
+
+    use TokenKind::*;
+    assert_eq!(
+        highlighted(text),
+        [
+            ("# Type {#concat-type}", Heading),
+            ("```", Fence),
+            ("concatStringsSep :: String -> [String]", TypeSignature),
+            ("-> String", TypeSignature),
+            ("```", Fence),
+            ("# Arguments", Heading),
+            ("sep", ArgumentName),
+            ("list", ArgumentName),
+            ("# Examples", Heading),
+            ("```", Fence),
+            ("nix title=\"Commas\"", InfoString),
+            ("=>", ResultMarker),
+            ("```", Fence),
+        ]
+    );
+}
+
+#[test]
+fn list_items_outside_arguments_are_plain() {
+    let text = "/**\n  Notes.\n\n  # Note\n\n  - [x] not an argument\n\n  ~~~~\n  ```\n  ~~~~\n*/";
+    assert_eq!(
+        highlighted(text),
+        [
+            ("# Note", TokenKind::Heading),
+            ("~~~~", TokenKind::Fence),
+            ("~~~~", TokenKind::Fence),
+        ]
+    );
+}
@@ -0,0 +1,50 @@
+use nixdoc::interpolation::{Unescaped, check_interpolation, escape_indented, find_unescaped};
+use nixdoc::{DocComment, Span};
+
+#[test]
+fn examples_and_prose_are_checked() {
+    let input = "/**\n  Wrap a script, see ''man bash''.\n\n  # Example\n\n  ```nix\n  writeShellScript \"hi\" ''\n    ${pkgs.hello}/bin/hello\n  ''\n  ```\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    let codes: Vec<_> = check_interpolation(&doc)
+        .into_iter()
+        .map(|d| d.code)
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            "unescaped-quotes",
+            "unescaped-quotes",
+            "unescaped-quotes",
+            "unescaped-interpolation",
+            "unescaped-quotes",
+        ]
+    );
+}
+
+#[test]
+fn escaped_sequences_are_accepted() {
+    assert!(find_unescaped("''${x} ''' ''\\n $${literal} $x {y}").is_empty());
+}
+
+#[test]
+fn escape_round_trips_through_find() {
+    let text = "a ${b} c '' d ''${e} '''";
+    let escaped = escape_indented(text);
+    assert_eq!(escaped, "a ''${b} c ''' d ''${e} '''");
+    assert!(find_unescaped(&escaped).is_empty());
+    assert_eq!(escape_indented(&escaped), escaped);
+}
+
+#[test]
+fn multibyte_text_is_scanned() {
+    assert_eq!(
+        find_unescaped("é ${x} ''\\é"),
+        [(Span::new(3, 5), Unescaped::Interpolation)]
+    );
+}
+
+#[test]
+fn clean_comments_have_no_diagnostics() {
+    let doc = DocComment::parse("/**\n  Returns `$HOME` or `{ a = 1; }`.\n*/").unwrap();
+    assert!(check_interpolation(&doc).is_empty());
+}
@@ -0,0 +1,63 @@
+// All tests in this file require `--features site`.
+#![cfg(feature = "site")]
+
+use nixdoc::DocSet;
+use nixdoc::render::RenderOptions;
+use nixdoc::render::inventory::{Inventory, InventoryEntry};
+use nixdoc::render::site::{Site, SiteOptions};
+
+const STRINGS: &str = r#"{
+  /** Concatenate a list of strings. */
+  concatStrings = builtins.concatStringsSep "";
+
+  /**
+    Split a string.
+
+    # Stability
+
+    internal
+  */
+  splitInternal = s: [ s ];
+}"#;
+
+#[test]
+fn lists_items_at_their_site_anchors() {
+    let mut set = DocSet::new();
+    set.add_source(STRINGS, Some("lib/strings.nix".as_ref()));
+    let options = SiteOptions::new()
+        .with_root("lib")
+        .with_render(RenderOptions::new().with_hide_internal(true));
+    let site = Site::build(&set, &options);
+
+    let inventory = Inventory::from_site(&set, &site, "lib", "24.05");
+    assert_eq!(
+        inventory.to_text(),
+        "concatStrings std:label -1 strings.html#$ Concatenate a list of strings.\n"
+    );
+}
+
+#[test]
+fn writes_a_zlib_stream_after_the_header() {
+    let mut inventory = Inventory::new("My lib", "1.0");
+    inventory.push(InventoryEntry {
+        name: "id".to_string(),
+        role: "std:label".to_string(),
+        priority: 1,
+        uri: "trivial.html#identity".to_string(),
+        display_name: Some("id".to_string()),
+    });
+    let body = inventory.to_text();
+    assert_eq!(body, "id std:label 1 trivial.html#identity -\n");
+
+    let header = "# Sphinx inventory version 2\n# Project: My lib\n# Version: 1.0\n\
+                  # The remainder of this file is compressed using zlib.\n";
+    let bytes = inventory.to_bytes();
+    let stream = bytes.strip_prefix(header.as_bytes()).unwrap();
+    // A zlib header, then one final stored block.
+    assert_eq!(stream[..3], [0x78, 0x01, 0x01]);
+    let len = u16::from_le_bytes([stream[3], stream[4]]);
+    assert_eq!(usize::from(len), body.len());
+    assert_eq!(u16::from_le_bytes([stream[5], stream[6]]), !len);
+    assert_eq!(&stream[7..7 + body.len()], body.as_bytes());
+    assert_eq!(stream.len(), 7 + body.len() + 4);
+}
@@ -0,0 +1,84 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::{DocComment, DocSet, Law, SectionKind};
+
+const MAP: &str = r#"/**
+  Map over a list.
+
+  # Properties
+
+  - [identity] `map id xs == xs`
+  - length: `builtins.length (map f xs) == builtins.length xs`
+  - xs: map (x: x) xs == xs
+
+  ```nix
+  # composition
+  map f (map g xs) == map (x: f (g x)) xs
+
+  map f [ ] == [ ]
+  ```
+*/"#;
+
+fn law(name: Option<&str>, expression: &str) -> Law {
+    Law {
+        name: name.map(str::to_string),
+        expression: expression.to_string(),
+    }
+}
+
+#[test]
+fn parses_list_items_and_code_blocks() {
+    let doc = DocComment::parse(MAP).unwrap();
+    assert_eq!(doc.sections.len(), 1);
+    assert_eq!(doc.sections[0].kind(), SectionKind::Laws);
+    assert!(doc.warnings.is_empty());
+    assert_eq!(
+        doc.laws(),
+        [
+            law(Some("identity"), "map id xs == xs"),
+            law(
+                Some("length"),
+                "builtins.length (map f xs) == builtins.length xs"
+            ),
+            law(None, "xs: map (x: x) xs == xs"),
+            law(
+                Some("composition"),
+                "map f (map g xs) == map (x: f (g x)) xs"
+            ),
+            law(None, "map f [ ] == [ ]"),
+        ]
+    );
+}
+
+#[test]
+fn exports_assertions() {
+    let mut set = DocSet::new();
+    let source = format!(
+        "{{\n  {}\n  map = f: xs: xs;\n}}",
+        MAP.replace('\n', "\n  ")
+    );
+    set.add_source(&source, Some("lists.nix".as_ref()));
+    set.add_source(
+        "/**\n  Loose.\n\n  # Laws\n\n  - `\"${x}\" == x`\n*/\nx: x",
+        Some("loose.nix".as_ref()),
+    );
+
+    expect_test::expect![[r#"
+        # The `# Laws` of the documentation, as generated by nixdoc.
+        # Evaluates to `true` if every law holds.
+        with import ./lib;
+        let
+          law = name: holds: holds || throw "law does not hold: ${name}";
+        in
+        builtins.all (holds: holds) [
+          (law "map: identity" (map id xs == xs))
+          (law "map: length" (builtins.length (map f xs) == builtins.length xs))
+          (law "map: law 3" (xs: map (x: x) xs == xs))
+          (law "map: composition" (map f (map g xs) == map (x: f (g x)) xs))
+          (law "map: law 5" (map f [ ] == [ ]))
+          (law "loose.nix:1: law 1" ("${x}" == x))
+        ]
+    "#]]
+    .assert_eq(&nixdoc::laws::assertions(&set, "with import ./lib;"));
+}
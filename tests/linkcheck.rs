@@ -0,0 +1,99 @@
+// All tests in this file require `--features linkcheck`.
+#![cfg(all(feature = "linkcheck", unix))]
+
+use nixdoc::linkcheck::{Fetcher, LinkChecker, check_doc, extract_links};
+use nixdoc::{DocComment, DocSet, Severity};
+
+/// A fetcher that answers with the status code in the URL's last path
+/// segment, e.g. `https://example.org/404`, by running `basename`.
+fn fake() -> LinkChecker {
+    LinkChecker::new().with_fetcher(Fetcher::Command {
+        program: "basename".to_string(),
+        args: Vec::new(),
+    })
+}
+
+#[test]
+fn extracts_links_in_markdown() {
+    let text = "A <https://a.org/x>, [b](https://b.org/y?q=1#z) and http://c.org/d_(e). Done.";
+    let links = extract_links(text);
+    let urls: Vec<_> = links.iter().map(|l| l.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://a.org/x",
+            "https://b.org/y?q=1#z",
+            "http://c.org/d_(e)"
+        ]
+    );
+    for link in &links {
+        assert_eq!(&text[link.span.start..link.span.end], link.url);
+    }
+}
+
+#[test]
+fn offline_checks() {
+    let doc = DocComment::parse(
+        "/**\n  See https://nixos.org, http://nixos.org,\n  http://localhost:8080/x and https:///nothing.\n*/",
+    )
+    .unwrap();
+    let codes: Vec<_> = check_doc(&doc)
+        .into_iter()
+        .map(|d| (d.code, d.severity))
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            ("insecure-url".to_string(), Severity::Info),
+            ("local-url".to_string(), Severity::Warning),
+            ("invalid-url".to_string(), Severity::Error),
+        ]
+    );
+}
+
+#[test]
+fn spans_point_into_raw_content() {
+    let doc =
+        DocComment::parse("/**\n  Docs.\n\n  # Note\n\n  Moved to http://x.org.\n*/").unwrap();
+    let diagnostic = &check_doc(&doc)[0];
+    let span = diagnostic.span;
+    assert_eq!(&doc.raw_content[span.start..span.end], "http://x.org");
+    assert_eq!(
+        diagnostic.to_string(),
+        "info[insecure-url]: 'http://x.org' uses http; prefer https"
+    );
+}
+
+#[test]
+fn network_statuses() {
+    let doc = DocComment::parse(
+        "/** Ok https://a.org/200, gone https://a.org/404, broken https://a.org/x. */",
+    )
+    .unwrap();
+    let diagnostics = fake().with_concurrency(2).check_doc(&doc);
+    let codes: Vec<_> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+    assert_eq!(codes, ["broken-url", "unreachable-url"]);
+    assert_eq!(
+        diagnostics[0].message,
+        "'https://a.org/404' returned HTTP 404"
+    );
+}
+
+#[test]
+fn check_set_labels_items() {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /** https://a.org/500 */\n  a = 1;\n  /** https://a.org/500 https://a.org/204 */\n  b = 2;\n}",
+        None,
+    );
+    let diagnostics = fake().check_set(&set);
+    let names: Vec<_> = diagnostics
+        .iter()
+        .map(|(item, d)| (item.name.as_deref().unwrap(), d.code.as_str()))
+        .collect();
+    assert_eq!(names, [("a", "broken-url"), ("b", "broken-url")]);
+
+    let provenance = diagnostics[1].1.provenance.as_ref().unwrap();
+    assert_eq!(provenance.attr_path.as_deref(), Some("b"));
+    assert_eq!(provenance.line, 4);
+}
@@ -0,0 +1,120 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::lint::{Linter, Rule};
+use nixdoc::{DocComment, DocSet, Severity, Span, SpecProfile};
+
+const INPUT: &str = "/**
+  # Type
+
+  ```
+  f :: a
+  ```
+
+  # Example
+
+  ```
+  f 1
+  ```
+
+  # Since
+
+  1.0
+
+  # Gotchas
+
+  None.
+*/";
+
+fn doc() -> DocComment {
+    DocComment::parse(INPUT).unwrap()
+}
+
+#[test]
+fn every_rule_reports_with_its_code() {
+    let doc = doc();
+    let diagnostics = Linter::new().check(&doc);
+    let found: Vec<(&str, Severity)> = diagnostics
+        .iter()
+        .map(|d| (d.code.as_str(), d.severity))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("ND002", Severity::Warning),
+            ("ND003", Severity::Error),
+            ("ND004", Severity::Info),
+        ]
+    );
+    let raw = &doc.raw_content;
+    let span = diagnostics[0].span;
+    assert_eq!(&raw[span.start..span.end], "```\nf 1\n```");
+    let span = diagnostics[2].span;
+    assert_eq!(&raw[span.start..span.end], "# Gotchas");
+    assert_eq!(diagnostics[2].message, "unknown section 'Gotchas'");
+}
+
+#[test]
+fn missing_type_points_at_the_first_line() {
+    let doc = DocComment::parse("/**\n  Identity.\n\n  More.\n*/").unwrap();
+    let diagnostics = Linter::new().check(&doc);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, Rule::MissingType.code());
+    assert_eq!(diagnostics[0].span, Span::new(0, "Identity.".len()));
+}
+
+#[test]
+fn rules_and_profiles_are_configurable() {
+    let linter = Linter::new()
+        .without_rule(Rule::EmptyDescription)
+        .with_rule(Rule::UnknownSection, Severity::Error)
+        .with_profile(SpecProfile::Rfc145_2023);
+    let codes: Vec<String> = linter
+        .check(&doc())
+        .into_iter()
+        .map(|d| format!("{}", d))
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            "warning[ND002]: example code block has no language; label it `nix`",
+            "error[ND004]: unknown section 'Since'",
+            "error[ND004]: unknown section 'Gotchas'",
+        ]
+    );
+    assert_eq!(linter.rules().len(), Rule::ALL.len() - 1);
+}
+
+#[test]
+fn localized_headings_are_judged_by_their_text() {
+    let doc = DocComment::parse(
+        "/**\n  F.\n\n  # Type\n\n  ```\n  f :: a\n  ```\n\n  # Examples (de)\n\n  ```nix\n  f\n  ```\n*/",
+    )
+    .unwrap();
+    assert_eq!(Linter::new().check(&doc), []);
+}
+
+#[test]
+fn sets_carry_provenance() {
+    let mut set = DocSet::new();
+    set.add_source("{\n  /** Id. */\n  id = x: x;\n}", Some("lib.nix".as_ref()));
+    let diagnostics = Linter::new().check_set(&set);
+    assert_eq!(diagnostics.len(), 1);
+    let provenance = diagnostics[0].1.provenance.as_ref().unwrap();
+    assert_eq!(provenance.to_string(), "lib.nix:2 (id)");
+}
+
+#[test]
+fn parse_warnings_are_rules_too() {
+    let doc =
+        DocComment::parse("/**\n  F.\n\n  # Type\n\n  # Example:\n\n  ```nix\n  f\n  ```\n*/")
+            .unwrap();
+    let codes: Vec<String> = Linter::new()
+        .without_rule(Rule::MissingType)
+        .check(&doc)
+        .into_iter()
+        .map(|d| d.code)
+        .collect();
+    assert_eq!(codes, ["ND005", "ND010"]);
+    assert_eq!(Rule::from_name("heading-style"), Some(Rule::HeadingStyle));
+}
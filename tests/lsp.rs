@@ -0,0 +1,364 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::lsp::{
+    CompletionKind, CompletionOptions, MarkupKind, Position, Range, Symbol, completions_at,
+    completions_with, hover_markup, symbols,
+};
+use nixdoc::{DocSet, Span};
+
+const SRC: &str = r#"{
+  /**
+    Concatenate strings with a separator.
+
+    Each string is placed between separators.
+
+    # Type
+
+    ```
+    concatStringsSep :: String -> [String] -> String
+    ```
+
+    # Examples
+
+    ```nix title="Commas"
+    concatStringsSep ", " [ "a" "b" ]
+    => "a, b"
+    ```
+
+    ```nix
+    concatStringsSep "" [ ]
+    ```
+  */
+  concatStringsSep = sep: list: "";
+}"#;
+
+#[test]
+fn hover_has_summary_type_and_first_example() {
+    let mut set = DocSet::new();
+    set.add_source(SRC, None);
+    let hover = hover_markup(&set.items()[0]);
+    assert_eq!(hover.kind, MarkupKind::Markdown);
+    expect_test::expect![[r#"
+        `concatStringsSep`
+
+        Concatenate strings with a separator.
+
+        ```nix
+        concatStringsSep :: String -> [String] -> String
+        ```
+
+        **Example:** Commas
+
+        ```nix
+        concatStringsSep ", " [ "a" "b" ]
+        => "a, b"
+        ```"#]]
+    .assert_eq(&hover.value);
+}
+
+#[test]
+fn positions_count_utf16_units() {
+    // "𝔸" is four bytes and two UTF-16 units.
+    let src = "a𝔸b\nc";
+    let cases = [
+        (0, (0, 0)),
+        (1, (0, 1)),
+        (5, (0, 3)),
+        (6, (0, 4)),
+        (7, (1, 0)),
+        (8, (1, 1)),
+    ];
+    for (offset, (line, character)) in cases {
+        let position = Position::of_offset(src, offset);
+        assert_eq!(position, Position { line, character }, "offset {offset}");
+        assert_eq!(position.to_offset(src), Some(offset), "offset {offset}");
+    }
+
+    // Inside a character, past the end of a line or of the source.
+    assert_eq!(
+        Position::of_offset(src, 3),
+        Position {
+            line: 0,
+            character: 1
+        }
+    );
+    assert_eq!(
+        Position {
+            line: 0,
+            character: 99
+        }
+        .to_offset(src),
+        Some(6)
+    );
+    assert_eq!(
+        Position {
+            line: 2,
+            character: 0
+        }
+        .to_offset(src),
+        None
+    );
+}
+
+#[test]
+fn spans_and_ranges() {
+    let mut set = DocSet::new();
+    set.add_source(SRC, None);
+    let item = &set.items()[0];
+
+    let range = Range::of_span(SRC, item.span);
+    assert_eq!(
+        range.start,
+        Position {
+            line: 1,
+            character: 2
+        }
+    );
+    assert_eq!(
+        range.end,
+        Position {
+            line: 22,
+            character: 4
+        }
+    );
+    assert_eq!(range.to_span(SRC), Some(item.span));
+    assert_eq!(Range::default().to_span(SRC), Some(Span::new(0, 0)));
+}
+
+fn labels(source: &str, marker: &str, options: &CompletionOptions) -> Vec<String> {
+    let offset = source.find(marker).unwrap() + marker.len();
+    completions_with(source, offset, options)
+        .into_iter()
+        .map(|c| c.label)
+        .collect()
+}
+
+const EDITING: &str = r#"{
+  /**
+    Fold a list.
+
+    # Arguments
+
+    - [op] The operator
+    - [n|
+
+    # s|
+
+    ```
+    # T|
+    ```
+  */
+  foldl = op: { nul ? null, strict ? false, ... }@args: list: nul;
+}"#;
+
+#[test]
+fn heading_completions() {
+    let options = CompletionOptions::new().with_custom_heading("See also");
+    assert_eq!(
+        labels(EDITING, "# s", &options),
+        ["Summary", "Since", "Stability", "See also"]
+    );
+    assert_eq!(
+        labels(EDITING, "# s", &CompletionOptions::new()),
+        ["Summary", "Since", "Stability"]
+    );
+    // Not inside code blocks.
+    assert!(labels(EDITING, "# T", &options).is_empty());
+    // Not after a shorter fence inside a longer one either.
+    let nested = EDITING.replace("```\n    # T|", "````\n    ```\n    # T|");
+    assert!(labels(&nested, "# T", &options).is_empty());
+
+    let offset = EDITING.find("# s").unwrap() + 3;
+    let completion = &completions_at(EDITING, offset)[0];
+    assert_eq!(completion.kind, CompletionKind::SectionHeading);
+    assert_eq!(
+        &EDITING[completion.replace.start..completion.replace.end],
+        "s"
+    );
+}
+
+#[test]
+fn argument_completions() {
+    let options = CompletionOptions::new();
+    assert_eq!(labels(EDITING, "- [n", &options), ["nul"]);
+
+    let fresh = EDITING.replace("- [n|", "- [|");
+    // `op` is already documented.
+    assert_eq!(labels(&fresh, "- [", &options), ["nul", "strict", "list"]);
+}
+
+#[test]
+fn formals() {
+    use nixdoc::extract::formals_after;
+
+    let cases: &[(&str, &[&str])] = &[
+        ("f = x: y: x;", &["x", "y"]),
+        (
+            "f = { a, b ? { c = 1; }, d ? \"}\", ... }: a;",
+            &["a", "b", "d"],
+        ),
+        ("f = args@{ a }: a;", &["a"]),
+        ("f =\n  # comment\n  x:\n  x;", &["x"]),
+        ("f = x:y;", &[]),
+        ("f = 1;", &[]),
+        ("f = g x;", &[]),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(formals_after(src, 0), *expected, "{src}");
+    }
+}
+
+#[test]
+fn raw_spans_map_to_the_source() {
+    let src = "{\n  /**\n   * Sort.\n   *\n   * See [docs].\n   *\n   * [docs]: https://x.org\n   */\n  sort = x: x;\n}";
+    let item = &nixdoc::extract::documented_items(src, None)[0];
+    let raw = &item.doc.raw_content;
+    for needle in ["Sort", "[docs]", "https://x.org"] {
+        let start = raw.rfind(needle).unwrap();
+        let span = item.source_span(src, Span::new(start, start + needle.len()));
+        assert_eq!(&src[span.start..span.end], needle);
+    }
+}
+
+#[test]
+#[cfg(feature = "lsp-types")]
+fn lsp_diagnostics() {
+    use nixdoc::lsp::to_lsp_diagnostics;
+    use nixdoc::{Diagnostic, Severity};
+
+    let mut set = DocSet::new();
+    set.add_source(SRC, None);
+    let item = &set.items()[0];
+    let raw = item.doc.raw_content.find("# Type").unwrap();
+    let found = Diagnostic::new(
+        "example-code",
+        Severity::Info,
+        "a finding",
+        Span::new(raw + 2, raw + 6),
+    );
+
+    let diagnostics = to_lsp_diagnostics(item, SRC, &[found]);
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(
+        diagnostic.range,
+        lsp_types::Range::new(
+            lsp_types::Position::new(6, 6),
+            lsp_types::Position::new(6, 10)
+        )
+    );
+    assert_eq!(
+        diagnostic.severity,
+        Some(lsp_types::DiagnosticSeverity::INFORMATION)
+    );
+    assert_eq!(
+        diagnostic.code,
+        Some(lsp_types::NumberOrString::String("example-code".into()))
+    );
+
+    let range: Range = diagnostic.range.into();
+    assert_eq!(
+        range.to_span(SRC).map(|s| &SRC[s.start..s.end]),
+        Some("Type")
+    );
+}
+
+fn outline(symbol: &Symbol, raw: &str, depth: usize, out: &mut String) {
+    let text = &raw[symbol.span.start..symbol.span.end];
+    let first_line = text.lines().next().unwrap_or_default();
+    out.push_str(&format!(
+        "{}{:?} {}: {first_line}\n",
+        "  ".repeat(depth),
+        symbol.kind,
+        symbol.name
+    ));
+    for child in &symbol.children {
+        outline(child, raw, depth + 1, out);
+    }
+}
+
+#[test]
+fn document_symbols() {
+    let src = SRC.replace(
+        "    # Type",
+        "    # Arguments\n\n    - [sep] The separator\n    - [list]\n\n    # Type",
+    );
+    let item = &nixdoc::extract::documented_items(&src, None)[0];
+
+    let mut out = String::new();
+    outline(&symbols(item), &item.doc.raw_content, 0, &mut out);
+    expect_test::expect![[r#"
+        Binding concatStringsSep: Concatenate strings with a separator.
+          Section Arguments: # Arguments
+            Argument sep: - [sep] The separator
+            Argument list: - [list]
+          Section Type: # Type
+          Section Examples: # Examples
+            Example Commas: ```nix title="Commas"
+            Example Example 2: ```nix
+    "#]]
+    .assert_eq(&out);
+
+    let examples = &symbols(item).children[2];
+    let last = &examples.children[1];
+    assert!(item.doc.raw_content[last.span.start..last.span.end].ends_with("[ ]\n```"));
+    assert_eq!(examples.span.end, item.doc.raw_content.len());
+}
+
+#[test]
+fn code_actions_for_missing_structure() {
+    use nixdoc::extract::documented_items;
+    use nixdoc::format::apply_edits;
+    use nixdoc::lsp::code_actions;
+
+    let src = "{\n  /**\n    Fold a list.\n\n    Type: foldl :: (b -> a -> b) -> b -> [a] -> b\n\n    # Arguments\n\n    - [op] The operator\n  */\n  foldl = op: nul: list: nul;\n}";
+    let item = &documented_items(src, None)[0];
+    let actions = code_actions(item, src);
+    let titles: Vec<_> = actions.iter().map(|a| a.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "Convert inline type to `# Type`",
+            "Document missing argument `nul`",
+            "Document missing argument `list`",
+        ]
+    );
+
+    expect_test::expect![[r#"
+        {
+          /**
+            Fold a list.
+
+            # Arguments
+
+            - [op] The operator
+
+            # Type
+
+            ```
+            foldl :: (b -> a -> b) -> b -> [a] -> b
+            ```
+          */
+          foldl = op: nul: list: nul;
+        }"#]]
+    .assert_eq(&apply_edits(src, &[actions[0].edit.clone()]));
+
+    let documented = apply_edits(src, &[actions[1].edit.clone()]);
+    assert!(documented.contains("    - [op] The operator\n    - [nul]\n  */"));
+}
+
+#[test]
+fn type_placeholder() {
+    use nixdoc::extract::documented_items;
+    use nixdoc::format::apply_edits;
+    use nixdoc::lsp::code_actions;
+
+    let src = "/** The identity. */\nid = x: x;";
+    let actions = code_actions(&documented_items(src, None)[0], src);
+    assert_eq!(actions[0].title, "Add `# Type` section");
+    assert_eq!(
+        apply_edits(src, &[actions[0].edit.clone()]),
+        "/**\n  The identity.\n\n  # Type\n\n  ```\n  id ::\n  ```\n*/\nid = x: x;"
+    );
+}
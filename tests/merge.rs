@@ -0,0 +1,118 @@
+use nixdoc::DocComment;
+use nixdoc::merge::{Conflict, Region};
+
+fn parse(s: &str) -> DocComment {
+    DocComment::parse(s).unwrap()
+}
+
+const BASE: &str = r#"/**
+  Apply a function to each element.
+
+  # Type
+
+  ```
+  map :: (a -> b) -> [a] -> [b]
+  ```
+
+  # Note
+
+  Lazy.
+
+  [docs]: https://example.org/map
+*/"#;
+
+#[test]
+fn one_sided_changes_merge_cleanly() {
+    // Generated update: new type signature, new section.
+    let theirs = parse(
+        r#"/**
+  Apply a function to each element.
+
+  # Type
+
+  ```
+  map :: (a -> b) -> List a -> List b
+  ```
+
+  # Note
+
+  Lazy.
+
+  # Since
+
+  1.0
+
+  [docs]: https://example.org/map
+*/"#,
+    );
+    // Hand edit: better description, no more note, a new link.
+    let ours = parse(
+        r#"/**
+  Apply a function to each element of a list.
+
+  # Type
+
+  ```
+  map :: (a -> b) -> [a] -> [b]
+  ```
+
+  [docs]: https://example.org/map
+  [nix]: https://nixos.org
+*/"#,
+    );
+
+    let merge = DocComment::merge3(&parse(BASE), &ours, &theirs);
+    assert!(merge.is_clean(), "{:?}", merge.conflicts);
+    expect_test::expect![[r#"
+        Apply a function to each element of a list.
+
+        # Type
+
+        ```
+        map :: (a -> b) -> List a -> List b
+        ```
+
+        # Since
+
+        1.0
+
+        [docs]: https://example.org/map
+        [nix]: https://nixos.org"#]]
+    .assert_eq(&merge.text);
+
+    let doc = merge.to_doc().unwrap();
+    assert_eq!(doc.since().unwrap().to_string(), "1.0");
+    assert_eq!(doc.link_definitions.len(), 2);
+}
+
+#[test]
+fn conflicting_sections_get_markers() {
+    let ours = parse(&BASE.replace("Lazy.", "Lazy in the list."));
+    let theirs = parse(&BASE.replace("Lazy.", "Strict."));
+
+    let merge = DocComment::merge3(&parse(BASE), &ours, &theirs);
+    assert_eq!(
+        merge.conflicts,
+        [Conflict {
+            region: Region::Section("Note".into()),
+            ours: Some("# Note\n\nLazy in the list.".into()),
+            theirs: Some("# Note\n\nStrict.".into()),
+        }]
+    );
+    assert!(merge.text.contains(
+        "<<<<<<< ours\n# Note\n\nLazy in the list.\n=======\n# Note\n\nStrict.\n>>>>>>> theirs"
+    ));
+    assert!(merge.to_doc().is_none());
+}
+
+#[test]
+fn removal_against_edit_conflicts() {
+    let ours = parse(&BASE.replace("\n  # Note\n\n  Lazy.\n", ""));
+    let theirs = parse(&BASE.replace("Lazy.", "Strict."));
+
+    let merge = DocComment::merge3(&parse(BASE), &ours, &theirs);
+    assert_eq!(merge.conflicts.len(), 1);
+    assert_eq!(merge.conflicts[0].ours, None);
+    assert_eq!(merge.conflicts[0].region.to_string(), "section `Note`");
+    assert!(merge.text.contains("<<<<<<< ours\n=======\n# Note"));
+}
@@ -0,0 +1,108 @@
+use nixdoc::migrate::{emit, to_rfc145};
+use nixdoc::{DocComment, ParseError, SectionKind};
+
+const FILTER_ATTRS: &str = r#"/* Filter an attribute set by removing all attributes for which the
+     given predicate return false.
+
+     Example:
+       filterAttrs (n: v: n == "foo") { foo = 1; bar = 2; }
+       => { foo = 1; }
+
+     Type:
+       filterAttrs :: (String -> Any -> Bool) -> AttrSet -> AttrSet
+  */"#;
+
+#[test]
+fn converts_labelled_blocks_to_sections() {
+    let doc = to_rfc145(FILTER_ATTRS).unwrap();
+    assert_eq!(
+        doc.description(),
+        "Filter an attribute set by removing all attributes for which the\ngiven predicate return false."
+    );
+    let kinds: Vec<_> = doc.sections.iter().map(|s| s.kind()).collect();
+    assert_eq!(kinds, [SectionKind::Type, SectionKind::Examples]);
+    assert_eq!(
+        doc.type_sig().as_deref(),
+        Some("filterAttrs :: (String -> Any -> Bool) -> AttrSet -> AttrSet\n")
+    );
+    let examples = doc.examples();
+    assert_eq!(examples.len(), 1);
+    assert_eq!(examples[0].language.as_deref(), Some("nix"));
+    assert_eq!(
+        examples[0].code,
+        "filterAttrs (n: v: n == \"foo\") { foo = 1; bar = 2; }\n=> { foo = 1; }\n"
+    );
+    assert!(doc.warnings.is_empty());
+}
+
+#[test]
+fn emitted_comment_round_trips() {
+    let doc = to_rfc145(FILTER_ATTRS).unwrap();
+    let text = emit(&doc, "  ");
+    assert_eq!(
+        text,
+        r#"/**
+    Filter an attribute set by removing all attributes for which the
+    given predicate return false.
+
+    # Type
+
+    ```
+    filterAttrs :: (String -> Any -> Bool) -> AttrSet -> AttrSet
+    ```
+
+    # Examples
+
+    ```nix
+    filterAttrs (n: v: n == "foo") { foo = 1; bar = 2; }
+    => { foo = 1; }
+    ```
+  */"#
+    );
+    assert_eq!(DocComment::parse(&text).unwrap(), doc);
+}
+
+#[test]
+fn moves_inline_signatures_and_keeps_prose() {
+    let doc = to_rfc145(
+        "/*\n  Merge two attribute sets shallowly.\n  mergeAttrs :: AttrSet -> AttrSet -> AttrSet\n\n  Example: mergeAttrs { a = 1; } { b = 2; }\n\n  Right side wins.\n*/",
+    )
+    .unwrap();
+    assert_eq!(
+        doc.description(),
+        "Merge two attribute sets shallowly.\n\nRight side wins."
+    );
+    assert_eq!(
+        doc.type_sig().as_deref(),
+        Some("mergeAttrs :: AttrSet -> AttrSet -> AttrSet\n")
+    );
+    assert_eq!(doc.examples()[0].code, "mergeAttrs { a = 1; } { b = 2; }\n");
+}
+
+#[test]
+fn combines_examples_and_ignores_labels_in_code() {
+    let doc = to_rfc145(
+        "/** Lists.\n\n    ```\n    Type: not a label\n    ```\n\n    Example:\n      a\n    Examples:\n      b\n*/",
+    )
+    .unwrap();
+    assert!(doc.description().contains("Type: not a label"));
+    assert_eq!(doc.type_sig(), None);
+    let codes: Vec<_> = doc.examples().into_iter().map(|e| e.code).collect();
+    assert_eq!(codes, ["a\n", "b\n"]);
+}
+
+#[test]
+fn migrates_labels_after_longer_closing_fence() {
+    let doc =
+        to_rfc145("/* Lists.\n\n    ```\n    x\n    ````\n\n    Example:\n      a\n*/").unwrap();
+    assert!(!doc.description().contains("Example:"));
+    let codes: Vec<_> = doc.examples().into_iter().map(|e| e.code).collect();
+    assert_eq!(codes, ["a\n"]);
+}
+
+#[test]
+fn rejects_non_comments() {
+    assert_eq!(to_rfc145("# comment"), Err(ParseError::NotDocComment));
+    assert_eq!(to_rfc145("/* open"), Err(ParseError::UnclosedComment));
+    assert_eq!(to_rfc145("/*\n  Type:\n*/"), Err(ParseError::EmptyComment));
+}
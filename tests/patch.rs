@@ -0,0 +1,111 @@
+use nixdoc::DocComment;
+use nixdoc::patch::{DocPatch, PatchError, PatchOp};
+
+const INPUT: &str = r#"/**
+  Apply a function to each element.
+
+  # Arguments
+
+  - [f] The function
+  - [list] The list,
+    evaluated lazily
+
+  # Note {#map-note}
+
+  Lazy.
+
+  See [docs].
+
+  [docs]: https://example.org/map
+*/"#;
+
+fn doc() -> DocComment {
+    DocComment::parse(INPUT).unwrap()
+}
+
+#[test]
+fn applies_edits_in_order() {
+    let patch = DocPatch::new()
+        .with(PatchOp::SetDescription(
+            "Apply a function to each element of a list.".into(),
+        ))
+        .with(PatchOp::RenameArgument {
+            from: "list".into(),
+            to: "xs".into(),
+        })
+        .with(PatchOp::SetSection {
+            heading: "note".into(),
+            content: "Strict in the spine.".into(),
+        })
+        .with(PatchOp::AppendExample {
+            code: "map (x: x * 2) [ 1 2 ]\n=> [ 2 4 ]".into(),
+            title: Some("Doubling".into()),
+        });
+
+    let patched = doc().apply(&patch).unwrap();
+    expect_test::expect![[r#"
+        Apply a function to each element of a list.
+
+        # Arguments
+
+        - [f] The function
+        - [xs] The list,
+          evaluated lazily
+
+        # Note {#map-note}
+
+        Strict in the spine.
+
+        # Examples
+
+        ```nix title="Doubling"
+        map (x: x * 2) [ 1 2 ]
+        => [ 2 4 ]
+        ```
+
+        [docs]: https://example.org/map"#]]
+    .assert_eq(&patched.raw_content);
+
+    assert_eq!(patched.arguments()[1].name, "xs");
+    assert_eq!(
+        patched.arguments()[1].description,
+        "The list, evaluated lazily"
+    );
+    assert_eq!(patched.examples()[0].title.as_deref(), Some("Doubling"));
+    assert!(patched.link_definition("docs").is_some());
+}
+
+#[test]
+fn appends_to_existing_examples_and_removes_sections() {
+    let patch = DocPatch::new()
+        .with(PatchOp::AppendExample {
+            code: "a".into(),
+            title: None,
+        })
+        .with(PatchOp::AppendExample {
+            code: "b".into(),
+            title: None,
+        })
+        .with(PatchOp::RemoveSection("NOTE".into()));
+
+    let patched = doc().apply(&patch).unwrap();
+    let headings: Vec<_> = patched.sections.iter().map(|s| &s.heading).collect();
+    assert_eq!(headings, ["Arguments", "Examples"]);
+    assert_eq!(patched.examples().len(), 2);
+}
+
+#[test]
+fn missing_targets_are_errors() {
+    let err = doc()
+        .apply(&DocPatch::new().with(PatchOp::RemoveSection("Type".into())))
+        .unwrap_err();
+    assert_eq!(err, PatchError::SectionNotFound("Type".into()));
+
+    let err = doc()
+        .apply(&DocPatch::new().with(PatchOp::RenameArgument {
+            from: "g".into(),
+            to: "h".into(),
+        }))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "no argument named `g`");
+}
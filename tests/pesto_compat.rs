@@ -1,50 +1,10 @@
-use std::fs;
-use std::path::{Path, PathBuf};
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
 
-use expect_test::expect;
-use nixdoc::{DocComment, ParseError, WarningKind};
-
-fn collect_nix_files(dir: &Path) -> Vec<PathBuf> {
-    let mut out = Vec::new();
-    let Ok(entries) = fs::read_dir(dir) else {
-        return out;
-    };
-    let mut entries: Vec<_> = entries.flatten().collect();
-    entries.sort_by_key(|e| e.path());
-    for entry in entries {
-        let path = entry.path();
-        if path.is_dir() {
-            out.extend(collect_nix_files(&path));
-        } else if path.extension().and_then(|e| e.to_str()) == Some("nix") {
-            out.push(path);
-        }
-    }
-    out
-}
+use std::path::PathBuf;
 
-fn extract_doc_comments(src: &str) -> Vec<String> {
-    let mut out = Vec::new();
-    let bytes = src.as_bytes();
-    let len = bytes.len();
-    let mut i = 0;
-    while i + 2 < len {
-        if bytes[i] == b'/' && bytes[i + 1] == b'*' && bytes[i + 2] == b'*' {
-            let start = i;
-            i += 3;
-            while i + 1 < len {
-                if bytes[i] == b'*' && bytes[i + 1] == b'/' {
-                    i += 2;
-                    out.push(src[start..i].to_string());
-                    break;
-                }
-                i += 1;
-            }
-        } else {
-            i += 1;
-        }
-    }
-    out
-}
+use expect_test::expect;
+use nixdoc::{DocSet, ParseError, WarningKind};
 
 #[test]
 fn pesto_test_data() {
@@ -56,63 +16,40 @@ fn pesto_test_data() {
         assets.display()
     );
 
-    let files = collect_nix_files(&assets);
-    assert!(!files.is_empty(), "no .nix files found under assets/");
+    let set = DocSet::from_paths(&[&assets]).expect("read fixtures");
+    assert!(!set.files().is_empty(), "no .nix files found under assets/");
 
-    let mut total = 0usize;
-    let mut ok = 0usize;
-    let mut empty = 0usize;
-    let mut warn_unknown = 0usize;
-    let mut warn_empty_section = 0usize;
-    let mut unclosed: Vec<(PathBuf, String)> = Vec::new();
+    for item in &set {
+        let file = item.file.as_deref().unwrap().display();
+        let doc = &item.doc;
+        assert!(!doc.raw_content.is_empty(), "{file}: empty raw_content");
+        if !doc.description().is_empty() {
+            assert!(
+                doc.title().is_some(),
+                "{file}: non-empty description but no title"
+            );
+        }
+        for s in &doc.sections {
+            assert!(!s.heading.is_empty(), "{file}: section with empty heading");
+        }
+    }
 
-    for file in &files {
-        let src = fs::read_to_string(file).expect("read file");
-        for raw in extract_doc_comments(&src) {
-            total += 1;
-            match DocComment::parse(&raw) {
-                Ok(doc) => {
-                    ok += 1;
-                    assert!(
-                        !doc.raw_content.is_empty(),
-                        "{}: empty raw_content",
-                        file.display()
-                    );
-                    if !doc.description().is_empty() {
-                        assert!(
-                            doc.title().is_some(),
-                            "{}: non-empty description but no title",
-                            file.display()
-                        );
-                    }
-                    for s in &doc.sections {
-                        assert!(
-                            !s.heading.is_empty(),
-                            "{}: section with empty heading",
-                            file.display()
-                        );
-                    }
-                    for w in &doc.warnings {
-                        match w.kind {
-                            WarningKind::UnknownSection => warn_unknown += 1,
-                            WarningKind::EmptySection => warn_empty_section += 1,
-                        }
-                    }
-                }
-                Err(ParseError::EmptyComment) => empty += 1,
-                Err(ParseError::NotDocComment) => {}
-                Err(ParseError::UnclosedComment) => {
-                    unclosed.push((file.clone(), raw));
-                }
-            }
+    let mut empty = 0usize;
+    let mut unclosed = Vec::new();
+    for failure in set.failures() {
+        match failure.error {
+            ParseError::EmptyComment => empty += 1,
+            ParseError::NotDocComment => {}
+            ParseError::UnclosedComment => unclosed.push(failure),
+            _ => unreachable!("warnings are not denied"),
         }
     }
 
-    for (path, raw) in &unclosed {
+    for failure in &unclosed {
         eprintln!(
-            "UNCLOSED {} - {:?}",
-            path.display(),
-            &raw[..raw.len().min(80)]
+            "UNCLOSED {}:{}",
+            failure.file.as_deref().unwrap().display(),
+            failure.line
         );
     }
 
@@ -125,21 +62,46 @@ fn pesto_test_data() {
     // Snapshot corpus-wide statistics. If these numbers change, the diff makes
     // it obvious whether we regressed (counts go up unexpectedly) or improved
     // (warning counts drop after a parser fix).
+    let stats = set.stats();
+    let warnings = |kind| stats.warnings.get(&kind).copied().unwrap_or(0);
     let stats = format!(
         "files: {}\ncomments: {}\nok: {}\nempty: {}\nwarn_unknown_section: {}\nwarn_empty_section: {}",
-        files.len(),
-        total,
-        ok,
+        stats.files,
+        stats.comments,
+        stats.parsed,
         empty,
-        warn_unknown,
-        warn_empty_section,
+        warnings(WarningKind::UnknownSection),
+        warnings(WarningKind::EmptySection),
     );
     expect![[r#"
         files: 180
-        comments: 402
-        ok: 402
+        comments: 399
+        ok: 399
         empty: 0
         warn_unknown_section: 6
         warn_empty_section: 10"#]]
     .assert_eq(&stats);
 }
+
+#[test]
+fn pesto_section_histogram() {
+    let assets = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures");
+    let set = DocSet::from_paths(&[&assets]).expect("read fixtures");
+
+    let histogram: Vec<String> = set
+        .section_histogram()
+        .iter()
+        .map(|(kind, count)| format!("{kind:?}: {count}"))
+        .collect();
+    expect![[r##"
+        Type: 204
+        Arguments: 285
+        Example: 213
+        Unknown("a list of definitions that weren't matched by any option"): 1
+        Unknown("a recursive set of options along with their final values"): 1
+        Unknown("all modules imported by the module for key1"): 1
+        Unknown("all modules imported by the module for key1-1"): 1
+        Unknown("all modules of the main module list"): 1
+        Unknown("result: str(\"#! /bin/sh \\n\\ntext a\\ntext b\\ntext c\\ntext c\",[])"): 1"##]]
+    .assert_eq(&histogram.join("\n"));
+}
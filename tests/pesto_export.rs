@@ -0,0 +1,141 @@
+#![cfg(feature = "serde")]
+
+use std::path::{Path, PathBuf};
+
+use expect_test::expect;
+use nixdoc::DocSet;
+use nixdoc::pesto::{self, PestoOptions};
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures")
+}
+
+/// Read `name` from the fixtures, recording it by its relative path so the
+/// snapshot does not depend on the checkout location.
+fn fixture_set(name: &str) -> DocSet {
+    let src = std::fs::read_to_string(fixtures().join(name)).unwrap();
+    let mut set = DocSet::new();
+    set.add_source(&src, Some(&Path::new("lib").join(name)));
+    set
+}
+
+#[test]
+fn asserts_snapshot() {
+    let json = pesto::to_json(&fixture_set("asserts.nix"), &PestoOptions::default());
+    expect![[r#"
+        [
+          {
+            "path": [
+              "lib",
+              "asserts",
+              "assertMsg"
+            ],
+            "aliases": [],
+            "docs": {
+              "attr": {
+                "position": {
+                  "file": "lib/asserts.nix",
+                  "line": 5,
+                  "column": 3
+                },
+                "content": "Throw if pred is false, else return pred.\nIntended to be used to augment asserts with helpful error messages.\n\n# Example\n\n```nix\nassertMsg false \"nope\"\nstderr> error: nope\nassert assertMsg (\"foo\" == \"bar\") \"foo is not bar, silly\"; \"\"\nstderr> error: foo is not bar, silly\n```\n\n# Type\n\n```\nassertMsg :: Bool -> String -> Bool\n```\n\n# Arguments\n\n- [pred] Predicate that needs to succeed, otherwise `msg` is thrown\n- [msg] Message to throw in case `pred` fails"
+              },
+              "lambda": {
+                "isPrimop": false,
+                "name": "assertMsg",
+                "position": null,
+                "args": null,
+                "experimental": null,
+                "arity": null,
+                "content": null,
+                "countApplied": null
+              }
+            }
+          },
+          {
+            "path": [
+              "lib",
+              "asserts",
+              "assertOneOf"
+            ],
+            "aliases": [],
+            "docs": {
+              "attr": {
+                "position": {
+                  "file": "lib/asserts.nix",
+                  "line": 38,
+                  "column": 3
+                },
+                "content": "Specialized `assertMsg` for checking if `val` is one of the elements\nof the list `xs`. Useful for checking enums.\n\n# Example\n\n```nix\nlet sslLibrary = \"libressl\";\nin assertOneOf \"sslLibrary\" sslLibrary [ \"openssl\" \"bearssl\" ]\nstderr> error: sslLibrary must be one of [\nstderr>   \"openssl\"\nstderr>   \"bearssl\"\nstderr> ], but is: \"libressl\"\n```\n\n# Type\n\n```\nassertOneOf :: String -> ComparableVal -> List ComparableVal -> Bool\n```\n\n# Arguments\n\n- [name] The name of the variable the user entered `val` into, for inclusion in the error message\n- [val] The value of what the user provided, to be compared against the values in `xs`\n- [xs] The list of valid values"
+              },
+              "lambda": {
+                "isPrimop": false,
+                "name": "assertOneOf",
+                "position": null,
+                "args": null,
+                "experimental": null,
+                "arity": null,
+                "content": null,
+                "countApplied": null
+              }
+            }
+          }
+        ]"#]]
+    .assert_eq(&json);
+}
+
+#[test]
+fn prefix_option() {
+    let options = PestoOptions {
+        prefix: vec!["pkgs".to_string(), "lib".to_string()],
+        ..PestoOptions::default()
+    };
+    let docs = pesto::export(&fixture_set("asserts.nix"), &options);
+    assert_eq!(docs[0].path, ["pkgs", "lib", "asserts", "assertMsg"]);
+
+    let options = PestoOptions {
+        categories: false,
+        ..PestoOptions::default()
+    };
+    let docs = pesto::export(&fixture_set("asserts.nix"), &options);
+    assert_eq!(docs[0].path, ["lib", "assertMsg"]);
+}
+
+#[test]
+fn categories_follow_pesto() {
+    // The paths noogle lists for these functions.
+    let path = |file: &str, src: &str| {
+        let mut set = DocSet::new();
+        set.add_source(src, Some(file.as_ref()));
+        pesto::export(&set, &PestoOptions::default()).remove(0).path
+    };
+    let src = "{\n  /** Doc. */\n  f = x: x;\n}";
+    assert_eq!(path("lib/strings.nix", src), ["lib", "strings", "f"]);
+    assert_eq!(
+        path("lib/systems/default.nix", src),
+        ["lib", "systems", "f"]
+    );
+    assert_eq!(path("lib/default.nix", src), ["lib", "f"]);
+}
+
+#[test]
+fn whole_corpus() {
+    let set = DocSet::from_paths(&[fixtures()]).expect("read fixtures");
+    let docs = pesto::export(&set, &PestoOptions::default());
+    let named = set.iter().filter(|item| item.name.is_some()).count();
+    assert_eq!(docs.len(), named);
+
+    for doc in &docs {
+        assert_eq!(doc.path[0], "lib");
+        assert!(doc.path.len() > 2, "{:?}", doc.path);
+        let position = doc.docs.attr.position.as_ref().unwrap();
+        assert!(position.line > 0 && position.column > 0);
+        let lambda = doc.docs.lambda.as_ref().unwrap();
+        assert_eq!(lambda.name.as_ref(), doc.path.last());
+    }
+
+    // Round-trips through the JSON consumers read.
+    let json = pesto::to_json(&set, &PestoOptions::default());
+    let parsed: Vec<pesto::PestoDoc> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, docs);
+}
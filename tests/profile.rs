@@ -0,0 +1,151 @@
+use nixdoc::lint::Rule;
+use nixdoc::*;
+
+const NIXPKGS_STYLE: &str = "/**\n  Concatenate strings.\n\n  # Inputs\n\n  `list`\n  : The strings.\n\n  # Type\n\n  ```\n  concatStrings :: [string] -> string\n  ```\n\n  # Since\n\n  24.05\n*/";
+
+#[test]
+fn default_options_use_the_nixpkgs_profile() {
+    assert_eq!(
+        ParserOptions::default().profile,
+        SpecProfile::NixpkgsCurrent
+    );
+    assert_eq!(
+        SpecProfile::NixpkgsCurrent.options(),
+        ParserOptions::default()
+    );
+}
+
+#[test]
+fn rfc145_2023_does_not_recognize_later_sections() {
+    let doc = SpecProfile::Rfc145_2023.parse(NIXPKGS_STYLE).unwrap();
+    let violations = SpecProfile::Rfc145_2023.violations(&doc);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].code, Rule::UnknownSection.code());
+    assert!(violations[0].message.contains("Since"));
+    // The section is still parsed.
+    assert!(doc.since().is_some());
+
+    let doc = SpecProfile::NixpkgsCurrent.parse(NIXPKGS_STYLE).unwrap();
+    assert!(doc.warnings.is_empty());
+}
+
+#[test]
+fn rfc145_2023_requires_unindented_headings() {
+    let input = "/**\n  Id.\n\n   # Type\n\n  ```\n  id :: a -> a\n  ```\n*/";
+    assert!(
+        SpecProfile::NixpkgsCurrent
+            .parse(input)
+            .unwrap()
+            .type_sig()
+            .is_some()
+    );
+    assert!(
+        SpecProfile::Rfc145_2023
+            .parse(input)
+            .unwrap()
+            .sections
+            .is_empty()
+    );
+}
+
+#[test]
+fn rfc145_2023_reports_heading_style() {
+    let input = "/**\n  Id.\n\n  # Type:\n\n  ```\n  id :: a -> a\n  ```\n*/";
+    let doc = SpecProfile::Rfc145_2023.parse(input).unwrap();
+    let codes: Vec<_> = SpecProfile::Rfc145_2023
+        .violations(&doc)
+        .into_iter()
+        .map(|d| d.code)
+        .collect();
+    assert_eq!(codes, [Rule::HeadingStyle.code()]);
+    assert!(SpecProfile::NixpkgsCurrent.violations(&doc).is_empty());
+}
+
+#[test]
+fn permissive_accepts_legacy_comments_without_violations() {
+    let input = "/*\n  Id.\n\n  # Type\n\n      id :: a -> a\n*/";
+    assert!(SpecProfile::NixpkgsCurrent.parse(input).is_err());
+
+    let doc = SpecProfile::Permissive.parse(input).unwrap();
+    assert_eq!(doc.type_sig().as_deref(), Some("id :: a -> a\n"));
+    assert!(doc.is_legacy());
+    assert!(SpecProfile::Permissive.violations(&doc).is_empty());
+}
+
+#[test]
+fn profile_options_can_be_adjusted() {
+    let options = SpecProfile::Rfc145_2023
+        .options()
+        .with_max_heading_indent(3);
+    assert_eq!(options.profile, SpecProfile::Rfc145_2023);
+    assert_eq!(options.max_heading_indent, 3);
+}
+
+#[test]
+fn names_round_trip() {
+    for profile in SpecProfile::ALL {
+        assert_eq!(SpecProfile::parse_name(profile.name()), Some(profile));
+        assert_eq!(profile.to_string(), profile.name());
+    }
+}
+
+#[test]
+fn strict_options_deny_sections_outside_rfc145() {
+    let error = DocComment::parse_spanned(NIXPKGS_STYLE, &ParserOptions::strict()).unwrap_err();
+    let ParseError::DeniedWarning(warning) = &error.error else {
+        panic!("expected a denied warning, got {:?}", error.error);
+    };
+    assert_eq!(warning.kind, WarningKind::UnknownSection);
+    assert!(
+        error
+            .error
+            .to_string()
+            .starts_with("unknown-section warning denied: ")
+    );
+    assert_eq!(error.span, Span::new(0, NIXPKGS_STYLE.len()));
+
+    let rfc145 = "/**\n  Id.\n\n# Type\n\n```\nid :: a -> a\n```\n*/";
+    assert!(DocComment::parse_with(rfc145, &ParserOptions::strict()).is_ok());
+}
+
+#[test]
+fn denied_warnings_are_errors_of_the_outcome() {
+    let outcome = DocComment::parse_outcome(NIXPKGS_STYLE, &ParserOptions::strict());
+    assert!(outcome.doc.is_some());
+    assert!(outcome.warnings.is_empty());
+    assert!(matches!(
+        outcome.errors[..],
+        [SpannedError {
+            error: ParseError::DeniedWarning(_),
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn lenient_options_recover_unclosed_comments() {
+    let input = "/**\n  Id.\n\n  # Since\n\n  1.0\n";
+    assert_eq!(DocComment::parse(input), Err(ParseError::UnclosedComment));
+
+    let doc = DocComment::parse_with(input, &ParserOptions::lenient()).unwrap();
+    assert_eq!(doc.since().unwrap().to_string(), "1.0");
+    assert_eq!(doc.warnings[0].kind, WarningKind::UnclosedComment);
+
+    let outcome = DocComment::parse_outcome(input, &ParserOptions::lenient());
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.warnings[0].kind, WarningKind::UnclosedComment);
+}
+
+#[test]
+fn lenient_options_accept_deeply_indented_headings() {
+    let input = "/*\n  Id.\n\n          # Since\n\n  1.0\n*/";
+    assert!(
+        DocComment::parse_with(input, &SpecProfile::Permissive.options())
+            .unwrap()
+            .since()
+            .is_none()
+    );
+    let doc = DocComment::parse_with(input, &ParserOptions::lenient()).unwrap();
+    assert_eq!(doc.since().unwrap().to_string(), "1.0");
+    assert!(doc.is_legacy());
+}
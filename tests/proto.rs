@@ -0,0 +1,79 @@
+// All tests in this file require `--features prost`.
+#![cfg(feature = "prost")]
+
+use nixdoc::DocSet;
+use nixdoc::proto::{self, DecodeError, SCHEMA, decode_item, decode_set, encode_item, encode_set};
+use prost::Message;
+
+#[test]
+fn roundtrips_a_full_comment() {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /**\n    Map, see [docs].\n\n    # Arguments {#map-args}\n\n    - [f] Function\n\n    # Bogus\n\n    [docs]: https://example.org \"Docs\"\n  */\n  lists.map = f: xs: xs;\n}",
+        Some("lib/lists.nix".as_ref()),
+    );
+    let item = &set.items()[0];
+    assert!(!item.doc.warnings.is_empty());
+    assert!(!item.doc.link_definitions.is_empty());
+
+    assert_eq!(&decode_item(&encode_item(item)).unwrap(), item);
+    assert_eq!(decode_set(&encode_set(&set)).unwrap(), set.items());
+
+    let message = proto::DocumentedItem::decode(encode_item(item).as_slice()).unwrap();
+    let doc = message.doc.unwrap();
+    assert_eq!(doc.sections[0].kind, "arguments");
+    assert_eq!(doc.sections[0].explicit_id.as_deref(), Some("map-args"));
+    assert!(
+        doc.warnings
+            .iter()
+            .any(|w| w.kind() == proto::WarningKind::UnknownSection)
+    );
+}
+
+#[test]
+fn rejects_incomplete_messages() {
+    let empty = proto::DocumentedItem::default().encode_to_vec();
+    assert!(matches!(
+        decode_item(&empty),
+        Err(DecodeError::MissingField("span"))
+    ));
+    assert!(matches!(
+        decode_item(&[0xff, 0xff]),
+        Err(DecodeError::Invalid(_))
+    ));
+
+    let unknown = proto::DocumentedItem {
+        span: Some(proto::Span { start: 0, end: 1 }),
+        doc: Some(proto::DocComment {
+            warnings: vec![proto::ParseWarning {
+                kind: 42,
+                message: String::new(),
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    assert!(matches!(
+        decode_item(&unknown.encode_to_vec()),
+        Err(DecodeError::UnknownWarningKind(42))
+    ));
+}
+
+#[test]
+fn schema_lists_every_message() {
+    for message in [
+        "Span",
+        "Section",
+        "LinkDefinition",
+        "ParseWarning",
+        "DocComment",
+        "DocumentedItem",
+        "DocSet",
+    ] {
+        assert!(
+            SCHEMA.contains(&format!("message {message} {{")),
+            "{message}"
+        );
+    }
+    assert!(SCHEMA.contains("package nixdoc.v1;"));
+}
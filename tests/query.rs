@@ -0,0 +1,134 @@
+use nixdoc::DocComment;
+use nixdoc::query::Value;
+
+const INPUT: &str = r#"/**
+  Concatenate strings with a separator.
+
+  # Arguments
+
+  - [sep] The separator
+  - [list] The strings
+
+  # Examples
+
+  ```nix title="Commas"
+  concatStringsSep ", " [ "a" "b" ]
+  => "a, b"
+  ```
+
+  ```nix
+  concatStringsSep "" [ ]
+  => ""
+  ```
+
+  # Tags
+
+  strings
+*/"#;
+
+fn doc() -> DocComment {
+    DocComment::parse(INPUT).unwrap()
+}
+
+fn strings(values: Vec<Value>) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn fields_and_indices() {
+    let doc = doc();
+    assert_eq!(
+        strings(doc.query("title").unwrap()),
+        ["Concatenate strings with a separator."]
+    );
+    assert_eq!(
+        strings(doc.query("sections[kind=arguments].items[0].name").unwrap()),
+        ["sep"]
+    );
+    assert_eq!(strings(doc.query("arguments[-1].name").unwrap()), ["list"]);
+    assert_eq!(
+        strings(doc.query("examples[0].attributes.title").unwrap()),
+        ["Commas"]
+    );
+    assert_eq!(strings(doc.query("tags[0]").unwrap()), ["strings"]);
+}
+
+#[test]
+fn lists_map_over_their_elements() {
+    let doc = doc();
+    assert_eq!(
+        strings(doc.query("sections.heading").unwrap()),
+        ["Arguments", "Examples", "Tags"]
+    );
+    assert_eq!(
+        strings(
+            doc.query("sections[kind=examples].items[*].expected")
+                .unwrap()
+        ),
+        ["\"a, b\"", "\"\""]
+    );
+    assert_eq!(
+        strings(doc.query("examples[title=\"commas\"].input").unwrap()),
+        ["concatStringsSep \", \" [ \"a\" \"b\" ]"]
+    );
+}
+
+#[test]
+fn missing_fields_select_nothing() {
+    let doc = doc();
+    assert!(doc.query("nope").unwrap().is_empty());
+    assert!(doc.query("arguments[5].name").unwrap().is_empty());
+    assert!(doc.query("title[0]").unwrap().is_empty());
+    assert_eq!(doc.query("since").unwrap(), [Value::Null]);
+    assert_eq!(doc.query("deprecated").unwrap(), [Value::Bool(false)]);
+}
+
+#[test]
+fn whole_values_format_as_json() {
+    let doc = doc();
+    let argument = &doc.query("arguments[0]").unwrap()[0];
+    assert_eq!(
+        argument.to_json(),
+        r#"{"default":null,"description":"The separator","name":"sep","type_sig":null}"#
+    );
+    assert_eq!(argument.get("name").and_then(Value::as_str), Some("sep"));
+}
+
+#[test]
+fn malformed_paths() {
+    let doc = doc();
+    for (path, position) in [
+        ("", 0),
+        ("sections[", 8),
+        ("sections[x]", 9),
+        ("a..b", 2),
+        ("a b", 1),
+    ] {
+        let error = doc.query(path).unwrap_err();
+        assert_eq!(error.position, position, "{path}: {error}");
+    }
+}
+
+#[test]
+fn json_pointers() {
+    let doc = doc();
+    assert_eq!(
+        doc.pointer("/sections/0/heading").unwrap().as_str(),
+        Some("Arguments")
+    );
+    assert_eq!(
+        doc.pointer("/sections/2/content").unwrap().as_str(),
+        Some("strings")
+    );
+    assert_eq!(doc.pointer("/sections/0/explicit_id"), Some(Value::Null));
+    assert!(doc.pointer("").unwrap().get("raw_content").is_some());
+    for missing in [
+        "sections",
+        "/sections/3",
+        "/sections/00",
+        "/sections/-",
+        "/nope",
+    ] {
+        assert_eq!(doc.pointer(missing), None, "{missing}");
+    }
+}
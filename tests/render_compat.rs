@@ -0,0 +1,350 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use expect_test::expect;
+use nixdoc::render::RenderOptions;
+use nixdoc::render::compat::{CompatOptions, render};
+use nixdoc::{DocSet, ParserOptions};
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures")
+}
+
+fn options(category: &str, description: &str) -> CompatOptions {
+    CompatOptions {
+        category: category.to_string(),
+        description: description.to_string(),
+        ..CompatOptions::default()
+    }
+}
+
+/// The reference output built by `nix build .#goldens`, see
+/// `nix/goldens.nix`, at the path in `NIXDOC_GOLDENS`.
+fn goldens() -> PathBuf {
+    std::env::var_os("NIXDOC_GOLDENS")
+        .expect("NIXDOC_GOLDENS should point at the output of `nix build .#goldens`")
+        .into()
+}
+
+/// A set that reads plain `/* */` comments, which get the structured
+/// layout rather than being copied as written.
+fn legacy_set(src: &str) -> DocSet {
+    let mut set = DocSet::new().with_options(ParserOptions::new().with_legacy_comments(true));
+    set.add_source(src, None);
+    set
+}
+
+#[test]
+fn asserts_fixture() {
+    let mut set = DocSet::new();
+    set.add_file(&fixtures().join("asserts.nix")).unwrap();
+    let mut options = options("asserts", "Assert functions");
+    options.locations.insert(
+        "lib.asserts.assertMsg".to_string(),
+        "[lib/asserts.nix:31](https://github.com/NixOS/nixpkgs/blob/master/lib/asserts.nix#L31) in `<nixpkgs/lib/asserts.nix>`".to_string(),
+    );
+
+    expect![[r##"
+        # Assert functions {#sec-functions-library-asserts}
+
+        ## `lib.asserts.assertMsg` {#function-library-lib.asserts.assertMsg}
+
+        Throw if pred is false, else return pred.
+        Intended to be used to augment asserts with helpful error messages.
+
+        ### Example
+
+        ```nix
+        assertMsg false "nope"
+        stderr> error: nope
+        assert assertMsg ("foo" == "bar") "foo is not bar, silly"; ""
+        stderr> error: foo is not bar, silly
+        ```
+
+        ### Type
+
+        ```
+        assertMsg :: Bool -> String -> Bool
+        ```
+
+        ### Arguments
+
+        - [pred] Predicate that needs to succeed, otherwise `msg` is thrown
+        - [msg] Message to throw in case `pred` fails
+
+        Located at [lib/asserts.nix:31](https://github.com/NixOS/nixpkgs/blob/master/lib/asserts.nix#L31) in `<nixpkgs/lib/asserts.nix>`.
+
+        ## `lib.asserts.assertOneOf` {#function-library-lib.asserts.assertOneOf}
+
+        Specialized `assertMsg` for checking if `val` is one of the elements
+        of the list `xs`. Useful for checking enums.
+
+        ### Example
+
+        ```nix
+        let sslLibrary = "libressl";
+        in assertOneOf "sslLibrary" sslLibrary [ "openssl" "bearssl" ]
+        stderr> error: sslLibrary must be one of [
+        stderr>   "openssl"
+        stderr>   "bearssl"
+        stderr> ], but is: "libressl"
+        ```
+
+        ### Type
+
+        ```
+        assertOneOf :: String -> ComparableVal -> List ComparableVal -> Bool
+        ```
+
+        ### Arguments
+
+        - [name] The name of the variable the user entered `val` into, for inclusion in the error message
+        - [val] The value of what the user provided, to be compared against the values in `xs`
+        - [xs] The list of valid values
+
+    "##]]
+    .assert_eq(&render(&set, &options));
+}
+
+#[test]
+fn file_comment_and_multiline_type() {
+    let src = r#"/**
+  Functions on lists.
+
+  # Overview
+
+  Start here.
+*/
+{
+  /*
+    Fold.
+
+    # Type
+
+    ```
+    foldr :: (a -> b -> b)
+          -> b -> [a] -> b
+    ```
+
+    # Examples
+
+    ## Sum
+
+    ```nix
+    foldr (a: b: a + b) 0 [ 1 2 ]
+    ```
+
+    ```nix
+    foldr (a: b: [ a ] ++ b) [ ] [ 1 ]
+    ```
+
+    # Note
+
+    Lazy in the accumulator.
+  */
+  foldr = op: nul: list: nul;
+}"#;
+    let set = legacy_set(src);
+
+    expect![[r#"
+        # List manipulation functions {#sec-functions-library-lists}
+
+        Functions on lists.
+
+        ## Overview
+
+        Start here.
+
+        ## `lib.lists.foldr` {#function-library-lib.lists.foldr}
+
+        Fold.
+
+        **Type**:
+        ```
+        foldr :: (a -> b -> b)
+              -> b -> [a] -> b
+        ```
+
+        ### Note
+
+        Lazy in the accumulator.
+
+        ::: {.example #function-library-example-lib.lists.foldr}
+        # Sum
+
+        ```nix
+        foldr (a: b: a + b) 0 [ 1 2 ]
+        ```
+        :::
+
+        ::: {.example #function-library-example-lib.lists.foldr-2}
+        # Sum
+
+        ```nix
+        foldr (a: b: [ a ] ++ b) [ ] [ 1 ]
+        ```
+        :::
+
+    "#]]
+    .assert_eq(&render(
+        &set,
+        &options("lists", "List manipulation functions"),
+    ));
+}
+
+#[test]
+fn every_fixture_renders_every_binding() {
+    let set = DocSet::from_paths(&[fixtures()]).unwrap();
+    for file in set.files() {
+        let mut single = DocSet::new();
+        single.add_file(file).unwrap();
+        let category = file.file_stem().unwrap().to_string_lossy();
+        let options = options(&category, "Functions");
+        let out = render(&single, &options);
+
+        for name in single.iter().filter_map(|item| item.name.as_deref()) {
+            let heading = format!("## `{}` {{#function-library-", options.identifier(name));
+            assert!(out.contains(&heading), "{}: missing {name}", file.display());
+        }
+    }
+}
+
+#[test]
+fn explicit_ids_replace_generated_anchors() {
+    let set = legacy_set(
+        "{\n  /*\n    f.\n\n    # Note {#f-note}\n\n    Careful.\n\n    # Example\n\n    ## Basic {#ex-f-basic}\n\n    ```nix\n    f\n    ```\n  */\n  f = 1;\n}",
+    );
+    let out = render(&set, &options("misc", "Misc"));
+    assert!(out.contains("\n### Note {#f-note}\n"), "{out}");
+    assert!(
+        out.contains("\n::: {.example #ex-f-basic}\n# Basic\n"),
+        "{out}"
+    );
+}
+
+#[test]
+fn references_are_linked_through_the_resolver() {
+    use nixdoc::render::PageLinks;
+    use nixdoc::xref::SymbolTable;
+
+    let set = legacy_set(
+        "{\n  /*\n    Like [`lib.lists.map`], see [`unknown`].\n\n    # Arguments\n\n    - [f] Passed to [`map`]\n  */\n  imap = f: 1;\n}",
+    );
+
+    let mut pages = PageLinks::new();
+    pages.insert("imap", "lib/lists/imap.html", "imap");
+    pages.insert("map", "lib/lists/map.html", "map");
+    let mut options = options("lists", "Lists");
+    options.render = RenderOptions::new()
+        .with_symbols(["imap", "map"].into_iter().collect::<SymbolTable>())
+        .with_link_resolver(pages);
+
+    let out = render(&set, &options);
+    assert!(
+        out.contains("Like [`lib.lists.map`](map.html#map), see [`unknown`]."),
+        "{out}"
+    );
+    assert!(out.contains(": Passed to [`map`](map.html#map)"), "{out}");
+}
+
+#[test]
+fn link_definitions_follow_the_entry() {
+    let set = legacy_set(
+        "{\n  /*\n    Implements [the RFC][rfc].\n\n    # Note\n\n    [rfc]: https://github.com/NixOS/rfcs/pull/145\n\n    See [rfc].\n  */\n  f = 1;\n}",
+    );
+    let out = render(&set, &options("misc", "Misc"));
+    assert!(
+        out.ends_with(
+            "### Note\n\nSee [rfc].\n\n[rfc]: https://github.com/NixOS/rfcs/pull/145\n\n"
+        ),
+        "{out}"
+    );
+}
+
+#[test]
+fn internal_items_can_be_hidden() {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /** Public. */\n  shown = 1;\n\n  /**\n    Helper. @internal\n  */\n  hidden = 2;\n}",
+        None,
+    );
+
+    let out = render(&set, &options("misc", "Misc"));
+    assert!(out.contains("`lib.misc.hidden`"));
+
+    let mut hiding = options("misc", "Misc");
+    hiding.render = RenderOptions::new().with_hide_internal(true);
+    let out = render(&set, &hiding);
+    assert!(out.contains("`lib.misc.shown`"));
+    assert!(!out.contains("hidden"), "{out}");
+}
+
+/// `lib/strings.nix` renders exactly as in the nixpkgs manual of the same
+/// revision. The manual's title and `Located at` lines come from its build
+/// rather than from the file, so they are taken from its output.
+#[test]
+#[ignore = "needs the output of `nix build .#goldens` in NIXDOC_GOLDENS"]
+fn strings_match_the_nixpkgs_manual() {
+    let dir = goldens().join("nixpkgs");
+    let revision = fs::read_to_string(dir.join("REVISION")).unwrap();
+    let expected = fs::read_to_string(dir.join("strings.md")).unwrap();
+
+    let title = expected
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("# "));
+    let (description, _) = title.and_then(|t| t.rsplit_once(" {#")).unwrap();
+    let mut options = options("strings", description);
+    let mut identifier = None;
+    for line in expected.lines() {
+        if let Some(heading) = line.strip_prefix("## `") {
+            identifier = heading.split_once('`').map(|(id, _)| id.to_string());
+        } else if let (Some(id), Some(location)) = (
+            &identifier,
+            line.strip_prefix("Located at ")
+                .and_then(|l| l.strip_suffix('.')),
+        ) {
+            options.locations.insert(id.clone(), location.to_string());
+        }
+    }
+
+    let mut set = DocSet::new().with_options(ParserOptions::new().with_legacy_comments(true));
+    set.add_file(&dir.join("strings.nix")).unwrap();
+    assert_eq!(
+        render(&set, &options),
+        expected,
+        "strings.md differs from the nixpkgs manual at {}",
+        revision.trim()
+    );
+}
+
+/// Every file of the fixture corpus renders exactly as the original nixdoc
+/// renders it, given the same category and description.
+#[test]
+#[ignore = "needs the output of `nix build .#goldens` in NIXDOC_GOLDENS"]
+fn corpus_matches_upstream_nixdoc() {
+    let dir = goldens().join("upstream");
+    let version = fs::read_to_string(dir.join("VERSION")).unwrap();
+    let mut compared = 0;
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension() != Some("md".as_ref()) {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let mut set = DocSet::new().with_options(ParserOptions::new().with_legacy_comments(true));
+        set.add_file(&fixtures().join(format!("{name}.nix")))
+            .unwrap();
+        assert_eq!(
+            render(&set, &options(name, name)),
+            fs::read_to_string(&path).unwrap(),
+            "{name}.md differs from the output of nixdoc {}",
+            version.trim()
+        );
+        compared += 1;
+    }
+    assert!(compared > 0, "no reference output in {}", dir.display());
+}
@@ -0,0 +1,79 @@
+// All tests in this file require `--features html`.
+#![cfg(feature = "html")]
+
+use nixdoc::DocComment;
+use nixdoc::render::html::{HtmlOptions, render};
+
+fn doc() -> DocComment {
+    DocComment::parse(
+        "/**
+  Map a function over a `list`.
+
+  # Example {#map-basic}
+
+  ```nix
+  # Double every element.
+  let xs = [ 1 2 ]; in map (x: x * 2) xs ++ [ ./a.nix <nixpkgs> \"s${toString 1}\" ]
+  => [ 2 4 ]
+  ```
+
+  # Example
+
+  ```sh
+  echo <hi>
+  ```
+
+  # Warning
+
+  Strict.
+
+  # Meta
+
+  stability: stable
+*/",
+    )
+    .unwrap()
+}
+
+#[test]
+fn sections_have_unique_anchors() {
+    let html = render(&doc(), &HtmlOptions::new().with_heading_level(3));
+    assert!(html.starts_with("<p>Map a function over a <code>list</code>.</p>\n"));
+    assert!(html.contains("<h3 id=\"map-basic\"><a href=\"#map-basic\">Example</a></h3>"));
+    assert!(html.contains("<h3 id=\"example\"><a href=\"#example\">Example</a></h3>"));
+    assert!(!html.contains("stability"));
+
+    let html = render(&doc(), &HtmlOptions::new().with_id_prefix("map-"));
+    assert!(html.contains("<h2 id=\"map-example\">"));
+}
+
+#[test]
+fn nix_blocks_are_highlighted() {
+    let html = render(&doc(), &HtmlOptions::new());
+    assert!(html.contains(
+        "<pre><code class=\"language-nix\"><span class=\"nix-comment\"># Double every element.</span>\n\
+         <span class=\"nix-keyword\">let</span> xs = [ <span class=\"nix-number\">1</span> "
+    ));
+    assert!(html.contains(
+        "[ <span class=\"nix-path\">./a.nix</span> <span class=\"nix-path\">&lt;nixpkgs&gt;</span> \
+         <span class=\"nix-string\">&quot;s${toString 1}&quot;</span> ]\n\
+         <span class=\"nix-result\">=&gt;</span> [ "
+    ));
+    // Other languages are escaped as they are.
+    assert!(html.contains("<pre><code class=\"language-sh\">echo &lt;hi&gt;\n</code></pre>"));
+}
+
+#[test]
+fn highlighting_can_be_disabled() {
+    let html = render(&doc(), &HtmlOptions::new().with_highlight(false));
+    assert!(!html.contains("nix-keyword"));
+    assert!(html.contains("<pre><code class=\"language-nix\"># Double every element.\n"));
+}
+
+#[test]
+fn callouts_become_admonitions() {
+    let html = doc().to_html(&HtmlOptions::new());
+    assert!(html.ends_with(
+        "<div class=\"admonition warning\">\n<p class=\"admonition-title\">Warning</p>\n<p>Strict.</p>\n</div>\n"
+    ));
+}
@@ -0,0 +1,107 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use expect_test::expect;
+use nixdoc::DocComment;
+use nixdoc::render::markdown::render;
+
+#[test]
+fn sections_are_reordered_and_callouts_become_admonitions() {
+    let doc = DocComment::parse(
+        "/**
+  Concatenate a list of strings
+  with a separator.
+  See [upstream][up].
+
+  # Examples {#concat-examples}
+
+  ```nix
+  concatSep \",\" [ \"a\" \"b\" ]
+  => \"a,b\"
+  ```
+
+  # Caution
+
+  Strict in every element.
+
+  # Custom
+
+  Kept last.
+
+  # Inputs
+
+  `sep`
+
+  : The separator.
+
+
+
+  # Deprecated
+
+  # Type
+
+  ```
+  concatSep :: string -> [string] -> string
+  ```
+
+  [up]: https://example.org
+*/",
+    )
+    .unwrap();
+    expect![[r#"
+        Concatenate a list of strings
+        with a separator.
+        See [upstream][up].
+
+        ::: {.warning}
+        **Deprecated.**
+        :::
+
+        # Type
+
+        ```
+        concatSep :: string -> [string] -> string
+        ```
+
+        # Inputs
+
+        `sep`
+
+        : The separator.
+
+        # Examples {#concat-examples}
+
+        ```nix
+        concatSep "," [ "a" "b" ]
+        => "a,b"
+        ```
+
+        ::: {.caution}
+        Strict in every element.
+        :::
+
+        # Custom
+
+        Kept last.
+
+        [up]: https://example.org
+    "#]]
+    .assert_eq(&doc.to_markdown());
+}
+
+#[test]
+fn rendering_is_stable() {
+    let doc = DocComment::parse(
+        "/**\n  Id.\n\n  # Arguments\n\n  - [x] A value\n\n  # Type\n\n  ```\n  id :: a -> a\n  ```\n*/",
+    )
+    .unwrap();
+    let markdown = render(&doc);
+    let reparsed = DocComment::parse(&format!("/**\n{markdown}*/")).unwrap();
+    assert_eq!(render(&reparsed), markdown);
+}
+
+#[test]
+fn empty_description_starts_with_the_first_section() {
+    let doc = DocComment::parse("/**\n  # Note\n\n  Only a note.\n*/").unwrap();
+    assert_eq!(render(&doc), "::: {.note}\nOnly a note.\n:::\n");
+}
@@ -0,0 +1,167 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::search::{FieldBoosts, FindOptions, SearchIndex, tokenize};
+use nixdoc::{DocComment, DocSet};
+
+const LIB: &str = r#"{
+  /**
+    Apply a function to each attribute value.
+
+    # Arguments
+
+    - [f] Function receiving the name and value
+  */
+  mapAttrs = f: set: set;
+
+  /**
+    Keep attributes matching a predicate.
+
+    Similar to `mapAttrs`, but drops values.
+  */
+  filterAttrs = pred: set: set;
+
+  /** Reverse a list. */
+  reverseList = xs: xs;
+}"#;
+
+fn set() -> DocSet {
+    let mut set = DocSet::new();
+    set.add_source(LIB, None);
+    set
+}
+
+#[test]
+fn tokenize_splits_camel_case() {
+    assert_eq!(
+        tokenize("mapAttrs, a toJSON!"),
+        vec!["mapattrs", "map", "attrs", "tojson", "to", "json"]
+    );
+    assert!(tokenize("a b c").is_empty());
+}
+
+#[test]
+fn identifier_outranks_body() {
+    let index = set().search_index();
+    let hits = index.search("mapAttrs");
+    let ids: Vec<_> = hits
+        .iter()
+        .map(|h| h.document.identifier.as_deref().unwrap())
+        .collect();
+    assert_eq!(ids[0], "mapAttrs");
+    assert!(ids.contains(&"filterAttrs"));
+    assert!(hits[0].score > hits[1].score);
+}
+
+#[test]
+fn title_outranks_body() {
+    let index = set().search_index();
+    let hits = index.search("function");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].document.identifier.as_deref(), Some("mapAttrs"));
+    // "function" appears once in the title and once in the arguments.
+    assert_eq!(hits[0].score, 3 + 1);
+}
+
+#[test]
+fn custom_boosts() {
+    let boosts = FieldBoosts {
+        identifier: 1,
+        title: 1,
+        body: 1,
+    };
+    let index = SearchIndex::build(&set(), &boosts);
+    assert_eq!(index.search("reverse")[0].score, 2);
+}
+
+#[test]
+fn no_hits() {
+    assert!(set().search_index().search("nonexistent").is_empty());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn json_is_compact() {
+    let json = set().search_index().to_json();
+    assert!(!json.contains('\n'));
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["documents"][2]["identifier"], "reverseList");
+    assert_eq!(value["terms"]["reverse"][0][0], 2);
+}
+
+#[test]
+#[cfg(feature = "tantivy")]
+fn tantivy_backend() {
+    use nixdoc::search::tantivy;
+
+    let index = tantivy::build(&set(), None).unwrap();
+    let hits = tantivy::search(&index, "predicate", &FieldBoosts::default(), 10).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].0.as_deref(), Some("filterAttrs"));
+}
+
+#[test]
+fn find_within_a_comment() {
+    let doc = DocComment::parse(
+        "/**\n  Über sets: ÜBER-SETS and subsets.\n\n  # Example\n\n  ```nix\n  sets.über { }\n  ```\n*/",
+    )
+    .unwrap();
+
+    let matches = doc.find("über");
+    let found: Vec<_> = matches
+        .iter()
+        .map(|m| &doc.raw_content[m.span.start..m.span.end])
+        .collect();
+    assert_eq!(found, ["Über", "ÜBER", "über"]);
+    assert_eq!(matches[0].section, None);
+    assert_eq!(matches[2].section.as_deref(), Some("Example"));
+    assert_eq!(matches[2].snippet, "sets.über { }");
+
+    let whole = FindOptions::new().with_whole_word(true);
+    let spans: Vec<_> = doc
+        .find_with("sets", &whole)
+        .iter()
+        .map(|m| m.span)
+        .collect();
+    assert_eq!(spans.len(), 3);
+    assert_eq!(doc.find("subsets").len(), 1);
+    assert!(doc.find_with("subset", &whole).is_empty());
+
+    let exact = FindOptions::new().with_case_sensitive(true);
+    assert_eq!(doc.find_with("ÜBER", &exact).len(), 1);
+    assert!(doc.find("").is_empty());
+}
+
+#[test]
+fn keywords_rank_identifiers_then_frequent_prose() {
+    let doc = DocComment::parse(
+        "/**\n  Merge overlays, later overlays winning. Each overlay\n  gets `final` and `prev`; see [`lib.fix`].\n\n  ```nix\n  composeExtensions ignored\n  ```\n\n  # Arguments\n\n  - [overlays] The list\n\n  # See Also\n\n  - `lib.extends`\n*/",
+    )
+    .unwrap();
+    assert_eq!(
+        doc.keywords(),
+        [
+            "overlays",
+            "lib.fix",
+            "lib.extends",
+            "final",
+            "prev",
+            "merge",
+            "later",
+            "winning",
+            "overlay",
+            "gets",
+        ]
+    );
+    let nested =
+        DocComment::parse("/**\n  Quoting.\n\n  ````md\n  ```\n  hidden words\n  ````\n*/")
+            .unwrap();
+    assert_eq!(nested.keywords(), ["quoting"]);
+    assert!(nixdoc::search::STOPWORDS.contains(&"the"));
+    assert!(
+        DocComment::parse("/**\n  # Type\n\n  a\n*/")
+            .unwrap()
+            .keywords()
+            .is_empty()
+    );
+}
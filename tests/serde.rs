@@ -53,6 +53,7 @@ fn json_minimal() {
           "raw_content": "The identity function.",
           "description": "The identity function.",
           "sections": [],
+          "link_definitions": [],
           "warnings": []
         }"#]]
     .assert_eq(&json(&doc));
@@ -72,13 +73,16 @@ fn json_with_sections() {
           "sections": [
             {
               "heading": "Type",
-              "content": "```\nf :: Int -> Int\n```"
+              "content": "```\nf :: Int -> Int\n```",
+              "explicit_id": null
             },
             {
               "heading": "Arguments",
-              "content": "- [x] Input"
+              "content": "- [x] Input",
+              "explicit_id": null
             }
           ],
+          "link_definitions": [],
           "warnings": []
         }"#]]
     .assert_eq(&json(&doc));
@@ -96,9 +100,11 @@ fn json_with_unknown_section_warning() {
           "sections": [
             {
               "heading": "See Also",
-              "content": "something"
+              "content": "something",
+              "explicit_id": null
             }
           ],
+          "link_definitions": [],
           "warnings": [
             {
               "kind": "UnknownSection",
@@ -203,3 +209,133 @@ fn section_kind_unknown_roundtrip() {
     let back: SectionKind = serde_json::from_str(&serialized).unwrap();
     assert_eq!(original, back);
 }
+
+#[test]
+#[cfg(feature = "serde")]
+fn pointer_matches_serialized_form() {
+    let doc = parse(
+        "/**\n  Desc.\n\n  # Note {#careful}\n\n  Careful.\n\n  # Bogus\n\n  x\n\n  [home]: https://example.org \"Home\"\n*/",
+    );
+    let serialized = serde_json::to_value(&doc).unwrap();
+    for pointer in [
+        "",
+        "/description",
+        "/sections/0",
+        "/sections/1/explicit_id",
+        "/link_definitions/0/title",
+        "/warnings",
+    ] {
+        let ours: serde_json::Value =
+            serde_json::from_str(&doc.pointer(pointer).unwrap().to_json()).unwrap();
+        assert_eq!(Some(&ours), serialized.pointer(pointer), "{pointer}");
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn patch_from_json() {
+    use nixdoc::patch::{DocPatch, PatchOp};
+
+    let patch: DocPatch = serde_json::from_str(
+        r#"{"ops": [
+            {"RenameArgument": {"from": "x", "to": "value"}},
+            {"RemoveSection": "Note"}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        patch.ops[0],
+        PatchOp::RenameArgument {
+            from: "x".to_string(),
+            to: "value".to_string(),
+        }
+    );
+
+    let doc =
+        parse("/**\n  Id.\n\n  # Arguments\n\n  - [x] Anything\n\n  # Note\n\n  Trivial.\n*/");
+    let patched = doc.apply(&patch).unwrap();
+    assert_eq!(patched.arguments()[0].name, "value");
+    assert!(patched.notes().is_empty());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn json_view() {
+    use expect_test::expect;
+    let doc = parse(
+        "/**\n  Identity.\n\n  # Type\n\n  ```\n  id :: a -> a\n  ```\n\n  # Arguments\n\n  - [x] Anything\n*/",
+    );
+    expect![[r#"
+        {
+          "title": "Identity.",
+          "summary": "Identity.",
+          "type_sig": "id :: a -> a\n",
+          "arguments": [
+            {
+              "name": "x",
+              "description": "Anything",
+              "type_sig": null,
+              "default": null
+            }
+          ],
+          "examples": [],
+          "notes": [],
+          "warnings": [],
+          "deprecation": null,
+          "see_also": []
+        }"#]]
+    .assert_eq(&json(&doc.view()));
+    let back: nixdoc::DocView = serde_json::from_str(&json(&doc.view())).unwrap();
+    assert_eq!(back, doc.view());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn json_item_view_has_provenance() {
+    use nixdoc::extract::documented_items;
+
+    let src = "{\n  /** Identity. */\n  trivial.id = x: x;\n}";
+    let item = &documented_items(src, Some("lib/trivial.nix".as_ref()))[0];
+    let value: serde_json::Value = serde_json::to_value(item.view()).unwrap();
+    expect_test::expect![[r#"
+        {
+          "attr_path": "trivial.id",
+          "byte_range": {
+            "end": 20,
+            "start": 4
+          },
+          "file": "lib/trivial.nix",
+          "line": 2
+        }"#]]
+    .assert_eq(&json(&value["provenance"]));
+    let back: nixdoc::DocView = serde_json::from_value(value).unwrap();
+    assert_eq!(back, item.view());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn json_lines_roundtrip_and_errors() {
+    use nixdoc::DocSet;
+    use nixdoc::jsonl::{JsonLinesError, JsonLinesReader, JsonLinesWriter};
+
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /**\n    Map.\n\n    # Example\n\n    ```nix\n    map f [ ]\n    ```\n  */\n  map = f: xs: xs;\n  /** Id. */\n  id = x: x;\n}",
+        Some("lib.nix".as_ref()),
+    );
+    let mut writer = JsonLinesWriter::new(Vec::new());
+    writer.write_all(&set).unwrap();
+    let text = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(text.lines().count(), 2);
+    assert!(text.lines().all(|line| line.starts_with("{\"name\":")));
+
+    let input = format!("\n{text}\nnot json\n");
+    let results: Vec<_> = JsonLinesReader::new(input.as_bytes()).collect();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap(), &set.items()[0]);
+    assert_eq!(results[1].as_ref().unwrap().name.as_deref(), Some("id"));
+    match &results[2] {
+        Err(JsonLinesError::Json { line, .. }) => assert_eq!(*line, 5),
+        other => panic!("expected a JSON error, got {other:?}"),
+    }
+}
@@ -0,0 +1,74 @@
+// All tests in this file require `--features serve`.
+#![cfg(feature = "serve")]
+
+use nixdoc::DocSet;
+use nixdoc::render::serve::{DocServer, SearchResult, Symbol};
+use nixdoc::render::site::SiteOptions;
+
+fn server() -> DocServer {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /** Map over a list. */\n  map = f: xs: xs;\n\n  /** Filter a list. */\n  filter = f: xs: xs;\n}",
+        Some("lib/lists.nix".as_ref()),
+    );
+    set.add_source(
+        "{\n  /** Map over an attribute set, see [`map`]. */\n  mapAttrs = f: s: s;\n}",
+        Some("lib/attr sets.nix".as_ref()),
+    );
+    DocServer::new(set, &SiteOptions::new().with_root("lib"))
+}
+
+#[test]
+fn serves_site_files() {
+    let server = server();
+    let index = server.respond("/");
+    assert_eq!(index.status, 200);
+    assert_eq!(index.content_type, "text/html; charset=utf-8");
+    assert!(index.body.contains("lists.html"));
+
+    let page = server.respond("/attr%20sets.html");
+    assert_eq!(page.status, 200);
+    assert!(page.body.contains("Map over an attribute set"));
+
+    assert_eq!(
+        server.respond("/search.js").content_type,
+        "text/javascript; charset=utf-8"
+    );
+    assert_eq!(server.respond("/missing.html").status, 404);
+}
+
+#[test]
+fn search_endpoint() {
+    let server = server();
+    let response = server.respond("/search?q=map+list&limit=2");
+    assert_eq!(response.status, 200);
+    let results: Vec<SearchResult> = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].identifier.as_deref(), Some("map"));
+    assert_eq!(results[0].url, "/lists.html#map");
+    assert!(results[0].score >= results[1].score);
+
+    let response = server.respond("/search?q=nothing%20here");
+    assert_eq!(response.body, "[]");
+}
+
+#[test]
+fn symbol_endpoint() {
+    let server = server();
+    let response = server.respond("/api/symbol/mapAttrs");
+    assert_eq!(response.content_type, "application/json");
+    let symbols: Vec<Symbol> = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].url, "/attr sets.html#mapAttrs");
+    assert_eq!(symbols[0].line, 2);
+    assert_eq!(symbols[0].doc.see_also, ["map".to_string()]);
+
+    // Qualified names resolve like references do.
+    let response = server.respond("/api/symbol/lib.filter");
+    let symbols: Vec<Symbol> = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(symbols[0].identifier.as_deref(), Some("filter"));
+
+    let missing = server.respond("/api/symbol/nope");
+    assert_eq!(missing.status, 404);
+    assert_eq!(missing.body, "not found: /api/symbol/nope\n");
+}
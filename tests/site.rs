@@ -0,0 +1,142 @@
+// All tests in this file require `--features site`.
+#![cfg(feature = "site")]
+
+use std::path::Path;
+
+use nixdoc::DocSet;
+use nixdoc::render::RenderOptions;
+use nixdoc::render::site::{Site, SiteOptions};
+
+const STRINGS: &str = r#"{
+  /**
+    Concatenate a list of strings.
+
+    # Examples
+
+    ```nix
+    concatStrings [ "a" "b" ]
+    ```
+  */
+  concatStrings = builtins.concatStringsSep "";
+
+  /**
+    Map and concatenate, see [`concatStrings`] and [`lib.lists.map`].
+
+    # Meta
+
+    category: strings
+  */
+  concatMapStrings = f: list: concatStrings (map f list);
+}"#;
+
+const LISTS: &str =
+    "{\n  /** Apply `f` to each element of a list & keep its order. */\n  map = f: xs: xs;\n}";
+
+fn set() -> DocSet {
+    let mut set = DocSet::new();
+    set.add_source(STRINGS, Some("/src/lib/strings.nix".as_ref()));
+    set.add_source(LISTS, Some("/src/lib/lists/default.nix".as_ref()));
+    set.add_source("/** Loose. */\nloose = 1;", None);
+    set
+}
+
+#[test]
+fn pages_follow_the_file_layout() {
+    let site = Site::build(&set(), &SiteOptions::new().with_root("/src/lib"));
+    let paths: Vec<_> = site.files.keys().map(|p| p.to_str().unwrap()).collect();
+    assert_eq!(
+        paths,
+        [
+            "index.html",
+            "input.html",
+            "lists/default.html",
+            "search-index.js",
+            "search.js",
+            "strings.html",
+            "style.css",
+        ]
+    );
+
+    let index = site.page("index.html").unwrap();
+    assert!(index.contains("<li><a href=\"strings.html\">strings.html</a> (2)</li>"));
+    assert!(index.contains("<title>Documentation - Documentation</title>"));
+
+    let lists = site.page("lists/default.html").unwrap();
+    assert!(lists.contains("href=\"../style.css\""));
+    assert!(lists.contains("<a href=\"../index.html\">"));
+
+    let without_root = Site::build(&set(), &SiteOptions::new());
+    assert!(without_root.page("src/lib/strings.html").is_some());
+}
+
+#[test]
+fn items_have_anchors_and_linked_references() {
+    let site = Site::build(&set(), &SiteOptions::new().with_root("/src/lib"));
+    let strings = site.page("strings.html").unwrap();
+    assert!(strings.contains(
+        "<section id=\"concatStrings\">\n<h2><a href=\"#concatStrings\"><code>concatStrings</code></a></h2>"
+    ));
+    assert!(strings.contains("<h3 id=\"concatStrings-examples\">Examples</h3>"));
+    assert!(strings.contains("<pre><code class=\"language-nix\">concatStrings [ \"a\" \"b\" ]"));
+    assert!(strings.contains("<a href=\"#concatStrings\"><code>concatStrings</code></a>"));
+    assert!(strings.contains("<a href=\"lists/default.html#map\"><code>lib.lists.map</code></a>"));
+    // `# Meta` is for tools.
+    assert!(!strings.contains("category"));
+
+    let lists = site.page("lists/default.html").unwrap();
+    assert!(lists.contains("<code>f</code> to each element of a list &amp; keep its order."));
+}
+
+#[test]
+fn items_can_show_provenance() {
+    let options = SiteOptions::new().with_root("/src/lib");
+    let hidden = Site::build(&set(), &options);
+    assert!(!hidden.page("strings.html").unwrap().contains("Defined in"));
+
+    let options = options.with_render(RenderOptions::new().with_show_provenance(true));
+    let site = Site::build(&set(), &options);
+    assert!(site.page("strings.html").unwrap().contains(
+        "<p class=\"provenance\">Defined in <code>/src/lib/strings.nix:13 (concatMapStrings)</code></p>"
+    ));
+    assert!(
+        site.page("input.html")
+            .unwrap()
+            .contains("Defined in <code>&lt;input&gt;:1 (loose)</code>")
+    );
+}
+
+#[test]
+fn search_index_maps_documents_to_pages() {
+    let site = Site::build(&set(), &SiteOptions::new().with_root("/src/lib"));
+    let script = site.page("search-index.js").unwrap();
+    assert!(script.starts_with("window.NIXDOC_INDEX = {\"documents\":"));
+    assert!(script.contains(
+        "window.NIXDOC_URLS = [\"strings.html#concatStrings\",\"strings.html#concatMapStrings\",\"lists/default.html#map\",\"input.html#loose\"];"
+    ));
+    assert!(site.page("search.js").unwrap().contains("NIXDOC_URLS[doc]"));
+}
+
+#[test]
+fn writes_files() {
+    let dir = std::env::temp_dir().join(format!("nixdoc-site-{}", std::process::id()));
+    let site = Site::build(&set(), &SiteOptions::new().with_root("/src/lib"));
+    site.write(&dir).unwrap();
+    let page = std::fs::read_to_string(dir.join(Path::new("lists/default.html"))).unwrap();
+    assert_eq!(page, site.page("lists/default.html").unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn duplicate_names_get_unique_anchors() {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /**\n    First.\n\n    # Note\n\n    a\n  */\n  f = 1;\n  /**\n    Second.\n\n    # Note\n\n    b\n  */\n  f = 2;\n}",
+        Some("lib.nix".as_ref()),
+    );
+    let site = Site::build(&set, &SiteOptions::new());
+    assert_eq!(site.urls, ["lib.html#f", "lib.html#f-2"]);
+    let page = site.page("lib.html").unwrap();
+    assert!(page.contains("<section id=\"f-2\">\n<h2><a href=\"#f-2\"><code>f</code></a></h2>"));
+    assert!(page.contains("<h3 id=\"f-note\">Note</h3>"));
+    assert!(page.contains("<h3 id=\"f-2-note\">Note</h3>"));
+}
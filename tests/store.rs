@@ -0,0 +1,132 @@
+// All tests in this file require `--features sqlite`.
+#![cfg(feature = "sqlite")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use nixdoc::store::{self, StoreError, StoredHit};
+use nixdoc::{DocSet, ParseError, ParserOptions};
+
+const LISTS: &str = r#"{
+  /**
+    Apply `f` to each element, see [`filter`].
+
+    # Type
+
+    ```
+    map :: (a -> b) -> [a] -> [b]
+    ```
+
+    # Notez
+
+    [docs]: https://nixos.org "Nix"
+  */
+  map = f: xs: xs;
+
+  /** Keep the elements satisfying `pred`. */
+  filter = pred: xs: xs;
+
+  /** */
+  broken = 1;
+}"#;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("nixdoc-store-{name}-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn roundtrips_items_failures_and_warnings() {
+    let dir = temp_dir("roundtrip");
+    let source = dir.join("latin1.nix");
+    fs::write(&source, b"{\n  /** Caf\xe9. */\n  cafe = 1;\n}").unwrap();
+
+    let mut set = DocSet::new();
+    set.add_source(LISTS, Some("lib/lists.nix".as_ref()));
+    set.add_file_lossy(&source).unwrap();
+    assert!(!set.failures().is_empty());
+    assert!(!set.file_warnings().is_empty());
+    assert!(set.iter().any(|item| !item.doc.warnings.is_empty()));
+
+    let db = dir.join("docs.sqlite");
+    set.save(&db).unwrap();
+    assert_eq!(DocSet::open(&db).unwrap(), set);
+
+    // Saving again replaces the database rather than adding to it.
+    set.save(&db).unwrap();
+    assert_eq!(DocSet::open(&db).unwrap().len(), set.len());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn roundtrips_denied_warnings() {
+    let dir = temp_dir("denied");
+    let mut set = DocSet::new().with_options(ParserOptions::strict());
+    set.add_source(LISTS, Some("lib/lists.nix".as_ref()));
+    assert!(
+        set.failures()
+            .iter()
+            .any(|failure| matches!(failure.error, ParseError::DeniedWarning(_)))
+    );
+
+    let db = dir.join("docs.sqlite");
+    set.save(&db).unwrap();
+    // Parser options are not stored, only what was parsed with them.
+    assert_eq!(DocSet::open(&db).unwrap().failures(), set.failures());
+
+    let (error, kind): (String, String) = rusqlite::Connection::open(&db)
+        .unwrap()
+        .query_row(
+            "SELECT error, warning_kind FROM failures WHERE warning_kind IS NOT NULL ORDER BY rowid",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(error, "denied-warning");
+    assert_eq!(kind, "empty-section");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn searches_the_full_text_index() {
+    let dir = temp_dir("search");
+    let db = dir.join("docs.sqlite");
+    let mut set = DocSet::new();
+    set.add_source(LISTS, Some("lib/lists.nix".as_ref()));
+    set.save(&db).unwrap();
+
+    let hits = store::search(&db, "satisfying", 10).unwrap();
+    assert_eq!(
+        hits[0],
+        StoredHit {
+            index: 1,
+            identifier: Some("filter".to_string()),
+            file: Some(PathBuf::from("lib/lists.nix")),
+            line: 17,
+        }
+    );
+    assert_eq!(hits.len(), 1);
+    assert_eq!(store::search(&db, "each satisfying", 10).unwrap().len(), 2);
+    assert_eq!(store::search(&db, "map", 10).unwrap()[0].index, 0);
+    assert_eq!(store::search(&db, "\"NEAR(", 10).unwrap(), Vec::new());
+    assert!(store::search(&db, "", 10).unwrap().is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_other_databases() {
+    let dir = temp_dir("version");
+    let db = dir.join("other.sqlite");
+    rusqlite::Connection::open(&db)
+        .unwrap()
+        .execute_batch("CREATE TABLE t (x INTEGER);")
+        .unwrap();
+
+    assert!(matches!(DocSet::open(&db), Err(StoreError::Version(0))));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,77 @@
+// All tests in this file require `--features rnix`.
+#![cfg(feature = "rnix")]
+
+use nixdoc::syntax::{check_examples, check_set};
+use nixdoc::{DocComment, DocSet, Severity};
+
+fn diagnostics(input: &str) -> Vec<nixdoc::Diagnostic> {
+    check_examples(&DocComment::parse(input).unwrap())
+}
+
+#[test]
+fn valid_examples_pass() {
+    let input = "/**\n  f.\n\n  # Examples\n\n  ```nix\n  builtins.map (x: x + 1) [ 1 2 ]\n  => [ 2 3 ]\n  ```\n\n  ```nix\n  let a = 1; in { inherit a; }\n  ```\n*/";
+    assert!(diagnostics(input).is_empty());
+}
+
+#[test]
+fn results_are_not_checked() {
+    let input = "/**\n  f.\n\n  # Example\n\n  ```nix\n  f\n  => <LAMBDA>\n  ```\n*/";
+    assert!(diagnostics(input).is_empty());
+}
+
+#[test]
+fn invalid_example_is_reported() {
+    let input = "/**\n  f.\n\n  # Example\n\n  ```nix\n  f { a = 1; b = ...; }\n  ```\n*/";
+    let diagnostics = diagnostics(input);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "invalid-nix");
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert!(
+        diagnostics[0]
+            .message
+            .starts_with("example is not valid Nix: ")
+    );
+}
+
+#[test]
+fn span_covers_the_code_block() {
+    let input = "/**\n  f.\n\n  # Example\n\n  ```nix\n  f (\n  ```\n*/";
+    let doc = DocComment::parse(input).unwrap();
+    let diagnostics = check_examples(&doc);
+    let span = diagnostics[0].span;
+    assert_eq!(&doc.raw_content[span.start..span.end], "```nix\nf (\n```");
+}
+
+#[test]
+fn other_languages_and_sections_are_skipped() {
+    let input = "/**\n  f.\n\n  ```nix\n  not ( nix\n  ```\n\n  # Example\n\n  ```bash\n  echo (\n  ```\n\n  ```\n  f (\n  ```\n*/";
+    assert!(diagnostics(input).is_empty());
+}
+
+#[test]
+fn setup_blocks_are_checked_as_bindings() {
+    let valid = "/**\n  f.\n\n  # Example\n\n  ```nix setup\n  xs = [ 1 2 ];\n  ```\n\n  ```nix\n  f xs\n  ```\n*/";
+    assert!(diagnostics(valid).is_empty());
+
+    let invalid = "/**\n  f.\n\n  # Example\n\n  ```nix setup\n  xs = [ 1 2 ]\n  ```\n*/";
+    assert_eq!(diagnostics(invalid).len(), 1);
+}
+
+#[test]
+fn repl_transcripts_check_each_input() {
+    let input = "/**\n  f.\n\n  # Example\n\n  ```nix\n  nix-repl> f 1\n  2\n\n  nix-repl> f (\n  error: syntax error\n  ```\n*/";
+    assert_eq!(diagnostics(input).len(), 1);
+}
+
+#[test]
+fn check_set_reports_items() {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /**\n    Good.\n\n    # Example\n\n    ```nix\n    good 1\n    ```\n  */\n  good = x: x;\n\n  /**\n    Bad.\n\n    # Example\n\n    ```nix\n    bad [\n    ```\n  */\n  bad = x: x;\n}",
+        None,
+    );
+    let found = check_set(&set);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0.name.as_deref(), Some("bad"));
+}
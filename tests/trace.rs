@@ -0,0 +1,76 @@
+// All tests in this file require `--features trace`.
+#![cfg(feature = "trace")]
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use nixdoc::DocComment;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Records each event as its `field=value` pairs.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        self.0.push_str(&format!("{}={value:?}", field.name()));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn explains_why_a_heading_is_not_a_section() {
+    let recorder = Recorder::default();
+    let input = "/**\n  f.\n\n  ```\n  # Example\n  ```\n\n  # Type\n*/";
+    let doc =
+        tracing::subscriber::with_default(recorder.clone(), || DocComment::parse(input).unwrap());
+    assert_eq!(doc.sections.len(), 1);
+
+    let events = recorder.0.lock().unwrap();
+    assert!(
+        events.contains(
+            &"message=not a section heading line=4 text=# Example reason=inside a code block"
+                .to_string()
+        )
+    );
+    assert!(events.contains(&"message=section heading line=7 heading=Type".to_string()));
+    assert!(
+        events
+            .iter()
+            .any(|e| e.starts_with("message=code block opened line=3"))
+    );
+}
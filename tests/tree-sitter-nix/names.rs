@@ -0,0 +1,41 @@
+//! The tree-sitter backend names items as the rnix backend does.
+
+use nixdoc::extract::{DocumentedItem, attach, ts::documented_items};
+
+const SRC: &str = r#"let
+  /** Private. */
+  helper = x: x;
+in
+rec {
+  /** First. */
+  first = 1;
+
+  /** Second. */
+  second = helper 2;
+
+  /** A path. */
+  a.b = 3;
+
+  # Not documentation.
+  /** After a comment. */
+  last = 4;
+}
+"#;
+
+fn names(items: Vec<DocumentedItem>) -> Vec<Option<String>> {
+    items.into_iter().map(|item| item.name).collect()
+}
+
+#[test]
+fn names_match_the_rnix_backend() {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_nix::LANGUAGE.into())
+        .unwrap();
+    let tree = parser.parse(SRC, None).unwrap();
+
+    let ts = names(documented_items(&tree, SRC, None));
+    let expected = ["helper", "first", "second", "a.b", "last"].map(|name| Some(name.to_string()));
+    assert_eq!(ts, expected);
+    assert_eq!(ts, names(attach(SRC, None)));
+}
@@ -0,0 +1,52 @@
+// All tests in this file require `--features ts`.
+#![cfg(feature = "ts")]
+
+//! There is no tree-sitter-nix grammar among the dev-dependencies, so these
+//! tests use the JavaScript grammar, whose block comments are `comment`
+//! nodes too. Its declarations are not `binding`s, so items are unnamed;
+//! `tests/tree-sitter-nix` checks the names with the Nix grammar.
+
+use nixdoc::ParserOptions;
+use nixdoc::extract::ts::{documented_items, parse_tree};
+
+const SRC: &str = "/** Identity. */\nconst id = (x) => x;\n\n/* Plain. */\nfunction f() {\n  /**\n   Nested.\n\n   # Type\n\n   ```\n   g :: a\n   ```\n  */\n  return 1;\n}\n/** */\n";
+
+fn tree() -> tree_sitter::Tree {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_javascript::LANGUAGE.into())
+        .unwrap();
+    parser.parse(SRC, None).unwrap()
+}
+
+#[test]
+fn finds_doc_comments_in_the_tree() {
+    let items = documented_items(&tree(), SRC, Some("a.js".as_ref()));
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].doc.title(), Some("Identity."));
+    assert_eq!((items[0].line, items[0].column), (1, 1));
+    assert_eq!(items[0].name, None);
+    assert_eq!(items[1].line, 6);
+    assert_eq!(items[1].doc.type_sig().as_deref(), Some("g :: a\n"));
+    assert_eq!(items[1].file.as_deref(), Some("a.js".as_ref()));
+
+    let span = items[1].span;
+    assert!(SRC[span.start..span.end].starts_with("/**\n   Nested."));
+    assert!(SRC[span.start..span.end].ends_with("*/"));
+}
+
+#[test]
+fn keeps_failures() {
+    let results = parse_tree(&tree(), SRC, None, &ParserOptions::default());
+    assert_eq!(results.len(), 3);
+    let failure = results[2].as_ref().unwrap_err();
+    assert_eq!(failure.line, 17);
+}
+
+#[test]
+fn legacy_comments_with_options() {
+    let options = ParserOptions::new().with_legacy_comments(true);
+    let results = parse_tree(&tree(), SRC, None, &options);
+    assert_eq!(results.len(), 4);
+    assert!(results[1].as_ref().unwrap().doc.is_legacy());
+}
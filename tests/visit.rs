@@ -0,0 +1,77 @@
+use nixdoc::links::LinkDefinition;
+use nixdoc::visit::DocVisitor;
+use nixdoc::{Argument, DocComment, Example, ParseWarning, Section};
+
+#[derive(Default)]
+struct Trace(Vec<String>);
+
+impl DocVisitor for Trace {
+    fn visit_description(&mut self, description: &str) {
+        self.0.push(format!("description {description}"));
+    }
+
+    fn visit_section(&mut self, section: &Section) {
+        self.0.push(format!("section {}", section.heading));
+    }
+
+    fn visit_argument(&mut self, section: &Section, argument: &Argument) {
+        self.0
+            .push(format!("argument {} in {}", argument.name, section.heading));
+    }
+
+    fn visit_example(&mut self, section: &Section, example: &Example) {
+        self.0
+            .push(format!("example {} in {}", example.input, section.heading));
+    }
+
+    fn visit_link_definition(&mut self, definition: &LinkDefinition) {
+        self.0.push(format!("link {}", definition.label));
+    }
+
+    fn visit_warning(&mut self, warning: &ParseWarning) {
+        self.0.push(format!("warning {:?}", warning.kind));
+    }
+}
+
+#[test]
+fn walk_visits_in_document_order() {
+    let doc = DocComment::parse(
+        "/**\n  Map [values].\n\n  # Args\n\n  - [f] Function\n  - [set] Set\n\n  # Example\n\n  ```nix\n  f 1\n  => 2\n  ```\n\n  ```nix\n  f 2\n  ```\n\n  # Bogus\n\n  Text.\n\n  [values]: https://example.org\n*/",
+    )
+    .unwrap();
+
+    let mut trace = Trace::default();
+    doc.walk(&mut trace);
+    assert_eq!(
+        trace.0,
+        [
+            "description Map [values].",
+            "section Args",
+            "argument f in Args",
+            "argument set in Args",
+            "section Example",
+            "example f 1 in Example",
+            "example f 2 in Example",
+            "section Bogus",
+            "link values",
+            "warning UnknownSection",
+        ]
+    );
+}
+
+#[test]
+fn default_methods_do_nothing() {
+    struct Sections(usize);
+    impl DocVisitor for Sections {
+        fn visit_section(&mut self, _section: &Section) {
+            self.0 += 1;
+        }
+    }
+
+    let doc =
+        DocComment::parse("/**\n  A.\n\n  # Note\n\n  B.\n\n  # Type\n\n  ```\n  int\n  ```\n*/")
+            .unwrap();
+    let mut sections = Sections(0);
+    doc.walk(&mut sections);
+    assert_eq!(sections.0, 2);
+}
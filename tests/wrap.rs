@@ -0,0 +1,85 @@
+use nixdoc::wrap::{fill, wrap};
+
+#[test]
+fn fill_keeps_code_and_links_whole() {
+    assert_eq!(
+        fill(
+            "Use ``a `b` c`` or [a  long\nlink][ref] or <https://example.org/x>.",
+            8
+        ),
+        [
+            "Use",
+            "``a `b` c``",
+            "or",
+            "[a long link][ref]",
+            "or",
+            "<https://example.org/x>."
+        ]
+    );
+    assert_eq!(fill("one two three", 80), ["one two three"]);
+    assert!(fill("  \n ", 10).is_empty());
+    // An unclosed code span is plain text.
+    assert_eq!(fill("a `b c", 3), ["a", "`b", "c"]);
+}
+
+#[test]
+fn wrap_blocks() {
+    let text = "\
+# A heading that is far too long to fit
+
+Some text that goes on
+and on.
+
+1. First item that wraps
+   over lines.
+2. Second
+  - nested item here
+
+> Quoted text that wraps
+> around.
+
+| a | table row that is long |
+
+    indented code that is long
+
+Line with a hard break\\
+next.
+";
+    assert_eq!(
+        wrap(text, 16),
+        "\
+# A heading that is far too long to fit
+
+Some text that
+goes on and on.
+
+1. First item
+   that wraps
+   over lines.
+2. Second
+  - nested item
+    here
+
+> Quoted text
+> that wraps
+> around.
+
+| a | table row that is long |
+
+    indented code that is long
+
+Line with a hard
+break\\
+next.
+"
+    );
+}
+
+#[test]
+fn wrap_after_longer_closing_fence() {
+    let text = "Text.\n\n```nix\nlet x = 1;\n````\n\nA very long paragraph that needs wrapping.";
+    assert_eq!(
+        wrap(text, 20),
+        "Text.\n\n```nix\nlet x = 1;\n````\n\nA very long\nparagraph that needs\nwrapping."
+    );
+}
@@ -0,0 +1,204 @@
+// All tests in this file require the `std` feature.
+#![cfg(feature = "std")]
+
+use nixdoc::xref::SymbolTable;
+use nixdoc::{DocComment, DocSet};
+
+const LIB: &str = r#"{
+  /**
+    Apply `f` to each element, like [`lib.lists.imap0`] without the index.
+
+    # See Also
+
+    - `lib.lists.imap0`
+    - `lib.attrsets.mapAttrs`
+    - `builtins.map`
+  */
+  lists.map = f: xs: xs;
+
+  /**
+    Map with an index.
+
+    Falls back to [`map`] when the index is unused.
+  */
+  lists.imap0 = f: xs: xs;
+
+  /**
+    Map over attribute values.
+
+    ```nix
+    [`notAReference`]
+    ```
+  */
+  attrsets.mapAttrs = f: set: set;
+
+  /** Unreferenced and referencing nothing. */
+  trivial.id = x: x;
+}"#;
+
+fn graph() -> nixdoc::xref::XrefGraph {
+    let mut set = DocSet::new();
+    set.add_source(LIB, None);
+    set.xref_graph()
+}
+
+#[test]
+fn edges_resolve_attribute_paths() {
+    let graph = graph();
+    assert_eq!(
+        graph.references("lists.map").collect::<Vec<_>>(),
+        ["attrsets.mapAttrs", "lists.imap0"]
+    );
+    assert_eq!(
+        graph.references("lists.imap0").collect::<Vec<_>>(),
+        ["lists.map"]
+    );
+    assert_eq!(graph.references("attrsets.mapAttrs").count(), 0);
+    assert_eq!(graph.references("unknown").count(), 0);
+}
+
+#[test]
+fn referenced_by() {
+    let graph = graph();
+    assert_eq!(
+        graph.referenced_by("attrsets.mapAttrs").collect::<Vec<_>>(),
+        ["lists.map"]
+    );
+}
+
+#[test]
+fn dangling_references() {
+    let graph = graph();
+    assert_eq!(graph.dangling.len(), 1);
+    assert_eq!(
+        graph.dangling["lists.map"].iter().collect::<Vec<_>>(),
+        ["builtins.map"]
+    );
+}
+
+#[test]
+fn cycles_and_orphans() {
+    let graph = graph();
+    assert_eq!(graph.cycles(), vec![vec!["lists.imap0", "lists.map"]]);
+    assert_eq!(graph.orphans(), ["trivial.id"]);
+}
+
+#[test]
+fn ambiguous_bare_names_dangle() {
+    let mut set = DocSet::new();
+    set.add_source(
+        "{\n  /** See [`map`]. */\n  f = 1;\n  /** a */\n  a.map = 1;\n  /** b */\n  b.map = 1;\n}",
+        None,
+    );
+    let graph = set.xref_graph();
+    assert_eq!(graph.references("f").count(), 0);
+    assert!(graph.dangling["f"].contains("map"));
+}
+
+#[test]
+fn resolve_references_with_spans() {
+    let doc = DocComment::parse(
+        "/**\n  See [`lib.attrsets.mapAttrs`] and [`missing`].\n\n  ```nix\n  [`inCode`]\n  ```\n\n  Also `notBracketed` and [`two words`].\n*/",
+    )
+    .unwrap();
+    let symbols: SymbolTable = ["attrsets.mapAttrs", "inCode", "notBracketed"]
+        .into_iter()
+        .collect();
+    let refs = doc.resolve_references(&symbols);
+
+    assert_eq!(refs.resolved.len(), 1);
+    let resolved = &refs.resolved[0];
+    assert_eq!(resolved.target, "attrsets.mapAttrs");
+    assert_eq!(resolved.reference.text, "lib.attrsets.mapAttrs");
+    let span = resolved.reference.span;
+    assert_eq!(
+        &doc.raw_content[span.start..span.end],
+        "[`lib.attrsets.mapAttrs`]"
+    );
+
+    assert_eq!(refs.unresolved.len(), 1);
+    let span = refs.unresolved[0].span;
+    assert_eq!(&doc.raw_content[span.start..span.end], "[`missing`]");
+}
+
+#[test]
+fn references_in_longer_fences_are_code() {
+    let doc = DocComment::parse(
+        "/**\n  Quoting.\n\n  ````md\n  ```\n  See [`inCode`].\n  ````\n\n  See [`inProse`].\n*/",
+    )
+    .unwrap();
+    let texts: Vec<String> = doc.references().into_iter().map(|r| r.text).collect();
+    assert_eq!(texts, ["inProse"]);
+}
+
+#[test]
+fn symbol_table_from_set() {
+    let mut set = DocSet::new();
+    set.add_source(LIB, None);
+    let table = SymbolTable::from_set(&set);
+    assert_eq!(table.len(), 4);
+    assert!(table.contains("lists.imap0"));
+    assert_eq!(table.resolve("lib.lists.imap0"), Some("lists.imap0"));
+    assert_eq!(table.resolve("id"), Some("trivial.id"));
+    assert_eq!(table.resolve("builtins.map"), None);
+}
+
+#[test]
+fn references_carry_sections() {
+    let doc = DocComment::parse(
+        "/**\n  Uses [`lib.id`].\n\n  # Note\n\n  Not `aReference`, but [`lib.const`] is.\n\n  # See Also\n\n  - `lib.flip`\n  - `not an identifier`\n*/",
+    )
+    .unwrap();
+    let refs: Vec<_> = doc
+        .references()
+        .into_iter()
+        .map(|r| {
+            assert!(doc.raw_content[r.span.start..r.span.end].contains(&r.text));
+            (r.text, r.section)
+        })
+        .collect();
+    assert_eq!(
+        refs,
+        [
+            ("lib.id".to_string(), None),
+            ("lib.const".to_string(), Some("Note".to_string())),
+            ("lib.flip".to_string(), Some("See Also".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn dot_clusters_by_file() {
+    let mut set = DocSet::new();
+    set.add_source(LIB, Some("lib/default.nix".as_ref()));
+    set.add_source(
+        "{\n  /** Quote a \"string\", see [`lists.map`]. */\n  escape = s: s;\n}",
+        Some("lib/strings.nix".as_ref()),
+    );
+    let dot = set.to_dot();
+
+    assert!(dot.starts_with("digraph xref {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(
+        dot.contains(
+            "  subgraph cluster_0 {\n    label=\"lib/default.nix\";\n    \"lists.map\";\n"
+        )
+    );
+    assert!(dot.contains(
+        "  subgraph cluster_1 {\n    label=\"lib/strings.nix\";\n    \"escape\";\n  }\n"
+    ));
+    assert!(dot.contains("  \"escape\" -> \"lists.map\";\n"));
+    assert!(dot.contains("  \"lists.map\" -> \"lists.imap0\";\n"));
+    // Isolated items are dashed.
+    assert!(dot.contains("    \"trivial.id\" [style=dashed];\n"));
+    assert!(!dot.contains("\"lists.map\" [style=dashed]"));
+}
+
+#[test]
+fn dot_without_files_has_no_clusters() {
+    let mut set = DocSet::new();
+    set.add_source(LIB, None);
+    let dot = set.to_dot();
+    assert!(!dot.contains("subgraph"));
+    assert!(dot.contains("  \"lists.imap0\" -> \"lists.map\";\n"));
+}